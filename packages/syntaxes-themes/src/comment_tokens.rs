@@ -0,0 +1,44 @@
+use crate::{SyntaxReference, SyntaxSet};
+use syntect::parsing::Scope;
+
+/// The comment tokens of a language, taken from the `.tmPreferences` metadata
+/// of the bundled syntaxes
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct CommentTokens {
+    /// the prefix used for line comments, ie: `// ` in rust
+    pub line: Option<String>,
+    /// the start and end tokens for block comments, ie: `/*` and `*/` in rust
+    pub block: Option<(String, String)>,
+}
+
+impl CommentTokens {
+    /// lookup the comment tokens for the syntax matching this syntax token.
+    /// Returns None if the language has no comment tokens defined.
+    pub fn from_syntax_token(syntax_token: &str) -> Option<Self> {
+        let syntax_set: &SyntaxSet = &crate::SYNTAX_SET;
+        let syntax_ref = syntax_set.find_syntax_by_token(syntax_token)?;
+        Self::from_syntax(syntax_set, syntax_ref)
+    }
+
+    fn from_syntax(syntax_set: &SyntaxSet, syntax_ref: &SyntaxReference) -> Option<Self> {
+        let scope: [Scope; 1] = [syntax_ref.scope];
+        let metadata = syntax_set.metadata().metadata_for_scope(&scope);
+        // Note: CDATA values in the .tmPreferences are loaded as blank strings,
+        // so blank tokens are treated as not defined
+        let comment_tokens = Self {
+            line: metadata
+                .line_comment()
+                .filter(|line| !line.trim().is_empty())
+                .map(ToString::to_string),
+            block: metadata
+                .block_comment()
+                .filter(|(start, end)| !start.trim().is_empty() && !end.trim().is_empty())
+                .map(|(start, end)| (start.to_string(), end.to_string())),
+        };
+        if comment_tokens.line.is_some() || comment_tokens.block.is_some() {
+            Some(comment_tokens)
+        } else {
+            None
+        }
+    }
+}
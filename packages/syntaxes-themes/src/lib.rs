@@ -1,6 +1,7 @@
 #![deny(warnings)]
 use once_cell::sync::Lazy;
 use syntect::dumps;
+pub use comment_tokens::CommentTokens;
 pub use syntect::{
     easy::HighlightLines,
     highlighting::{Color, Style, Theme, ThemeSet},
@@ -8,10 +9,17 @@ pub use syntect::{
 };
 pub use text_highlighter::TextHighlighter;
 
+mod comment_tokens;
 mod text_highlighter;
 
-pub static SYNTAX_SET: Lazy<SyntaxSet> =
-    Lazy::new(|| dumps::from_binary(include_bytes!("../dump/syntaxes.packdump")));
+/// The syntax set together with its metadata, which is dumped separately
+pub static SYNTAX_SET: Lazy<SyntaxSet> = Lazy::new(|| {
+    let mut syntax_set: SyntaxSet = dumps::from_binary(include_bytes!("../dump/syntaxes.packdump"));
+    syntax_set.set_metadata(dumps::from_binary(include_bytes!(
+        "../dump/syntaxes.metadump"
+    )));
+    syntax_set
+});
 
 pub static THEME_SET: Lazy<ThemeSet> =
     Lazy::new(|| dumps::from_binary(include_bytes!("../dump/themes.themedump")));
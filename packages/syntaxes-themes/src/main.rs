@@ -19,10 +19,14 @@ fn main() -> io::Result<()> {
 
 fn dump_sublime(syntaxset: &SyntaxSet, themeset: &ThemeSet) -> io::Result<()> {
     let syntaxset_pack_path = "./dump/syntaxes.packdump";
+    let metadata_pack_path = "./dump/syntaxes.metadump";
     let themeset_pack_path = "./dump/themes.themedump";
     create_parent_dir(syntaxset_pack_path)?;
     create_parent_dir(themeset_pack_path)?;
     dumps::dump_to_file(&syntaxset, syntaxset_pack_path).expect("must dump to file");
+    // the metadata(comment tokens, indentation rules) from the .tmPreferences files
+    // is not included in the syntaxset dump, so it has to be dumped separately
+    dumps::dump_to_file(syntaxset.metadata(), metadata_pack_path).expect("must dump to file");
     dumps::dump_to_file(&themeset, themeset_pack_path).expect("must dump to file");
 
     Ok(())
@@ -1,5 +1,6 @@
 use ultron_web::{
-    sauron::prelude::*, web_editor, BaseOptions, Options, SelectionMode, WebEditor, COMPONENT_NAME,
+    sauron::dom::Window, sauron::prelude::*, web_editor, BaseOptions, Options, SelectionMode,
+    WebEditor, COMPONENT_NAME,
 };
 
 pub enum Msg {
@@ -44,6 +45,7 @@ impl Application<Msg> for App {
                     on_mouseup(|me| Msg::WebEditorMsg(web_editor::Msg::Mouseup(me))),
                 ])
             }),
+            Window::on_resize(|_width, _height| Msg::WebEditorMsg(web_editor::Msg::WindowResized)),
             Cmd::from(self.web_editor.init().localize(Msg::WebEditorMsg)),
         ])
     }
@@ -1,14 +1,63 @@
 pub use crate::Selection;
-use crate::{BaseOptions, SelectionMode, TextBuffer, TextEdit};
+use crate::{
+    completion, formatter, util, BaseOptions, BufferWords, ChangeDelta, Clipboard, CompletionItem,
+    CompletionKind, CompletionProvider, Decoration, DecorationId, Decorations, Diagnostic,
+    Diagnostics, Edit, Folding, FormatError, Formatted, Formatter, GoToTarget, InvalidGoToTarget,
+    LineChanges, Macro, Macros, Marks, MemoryClipboard, RegisterContent, Registers, SelectionMode,
+    Snippet, SnippetSession, Snippets, SoftWrap, TextBuffer, TextEdit, TextTransform,
+};
 use nalgebra::Point2;
+use std::cell::RefCell;
+use std::cmp::Reverse;
 use std::marker::PhantomData;
+use std::rc::Rc;
 use std::sync::Arc;
-pub use ultron_syntaxes_themes::{Style, TextHighlighter};
+pub use ultron_syntaxes_themes::{CommentTokens, Style, TextHighlighter};
 
 /// An editor with core functionality platform specific UI
 pub struct BaseEditor<XMSG> {
     options: BaseOptions,
     text_edit: TextEdit,
+    /// the syntax of the content, ie: rust, python, sql
+    syntax_token: Option<String>,
+    /// the comment tokens of the language currently used in the editor
+    comment_tokens: Option<CommentTokens>,
+    /// the fold ranges of the content and which of them are folded
+    folding: Folding,
+    /// the visual rows of the long lines when soft wrap is enabled
+    soft_wrap: SoftWrap,
+    /// the named marks and bookmarks dropped in the document
+    marks: Marks,
+    /// the gutter icons, line backgrounds and range styles added by the host application
+    decorations: Decorations,
+    /// the problems reported by the linters and compilers
+    diagnostics: Diagnostics,
+    /// the selections besides the selection of the text edit,
+    /// they follow their text as it is edited
+    secondary_selections: Vec<Selection>,
+    /// the kill ring and the named registers of the cut and copied texts
+    registers: Registers,
+    /// the location and content of the last yank, which is replaced with `Command::YankPop`
+    last_yank: Option<(Point2<usize>, RegisterContent)>,
+    /// the clipboard used in copy, cut and paste, shared with the clones of this editor
+    clipboard: Rc<RefCell<dyn Clipboard>>,
+    /// the recorded macros and the macro being recorded
+    macros: Macros,
+    /// suggest the completions at the cursor, shared with the clones of this editor
+    completion_providers: Vec<Rc<dyn CompletionProvider>>,
+    /// formats the content in `Command::Format`, shared with the clones of this editor
+    formatter: Option<Rc<dyn Formatter>>,
+    /// the snippets of each syntax, suggested in the completions
+    snippets: Snippets,
+    /// the tab stops of the snippet being filled in
+    snippet_session: Option<SnippetSession>,
+    /// the changes of the lines from the base text shown in the gutter
+    line_changes: Option<LineChanges>,
+    /// the changes of the content made by the commands, collected only when tracked
+    change_log: Option<Vec<ChangeDelta>>,
+    /// the changes of the content not yet taken with `take_changes`,
+    /// collected by every command once the changes are tracked
+    change_feed: Option<Vec<ChangeDelta>>,
     /// Other components can listen to the an event.
     /// When the content of the text editor changes, the change listener will be emitted
     #[cfg(feature = "callback")]
@@ -37,6 +86,25 @@ impl<XMSG> Default for BaseEditor<XMSG> {
         Self {
             options: BaseOptions::default(),
             text_edit: TextEdit::default(),
+            syntax_token: None,
+            comment_tokens: None,
+            folding: Folding::default(),
+            soft_wrap: SoftWrap::default(),
+            marks: Marks::default(),
+            decorations: Decorations::default(),
+            diagnostics: Diagnostics::default(),
+            secondary_selections: vec![],
+            registers: Registers::default(),
+            last_yank: None,
+            clipboard: Rc::new(RefCell::new(MemoryClipboard::default())),
+            macros: Macros::default(),
+            completion_providers: vec![Rc::new(BufferWords::new())],
+            formatter: None,
+            snippets: Snippets::default(),
+            snippet_session: None,
+            line_changes: None,
+            change_log: None,
+            change_feed: None,
             #[cfg(feature = "callback")]
             change_listeners: vec![],
             #[cfg(feature = "callback")]
@@ -51,6 +119,25 @@ impl<XMSG> Clone for BaseEditor<XMSG> {
         Self {
             options: self.options.clone(),
             text_edit: self.text_edit.clone(),
+            syntax_token: self.syntax_token.clone(),
+            comment_tokens: self.comment_tokens.clone(),
+            folding: self.folding.clone(),
+            soft_wrap: self.soft_wrap.clone(),
+            marks: self.marks.clone(),
+            decorations: self.decorations.clone(),
+            diagnostics: self.diagnostics.clone(),
+            secondary_selections: self.secondary_selections.clone(),
+            registers: self.registers.clone(),
+            last_yank: self.last_yank.clone(),
+            clipboard: Rc::clone(&self.clipboard),
+            macros: self.macros.clone(),
+            completion_providers: self.completion_providers.clone(),
+            formatter: self.formatter.clone(),
+            snippets: self.snippets.clone(),
+            snippet_session: self.snippet_session.clone(),
+            line_changes: self.line_changes.clone(),
+            change_log: self.change_log.clone(),
+            change_feed: self.change_feed.clone(),
             #[cfg(feature = "callback")]
             change_listeners: self.change_listeners.clone(),
             #[cfg(feature = "callback")]
@@ -60,7 +147,8 @@ impl<XMSG> Clone for BaseEditor<XMSG> {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Command {
     IndentForward,
     IndentBackward,
@@ -76,17 +164,126 @@ pub enum Command {
     InsertChar(char),
     ReplaceChar(char),
     InsertText(String),
+    /// replace the text from start up to but not including the end location, undone in one step.
+    /// The cursor is put right after the inserted text
+    ReplaceText(Point2<usize>, Point2<usize>, String),
+    /// replace the word before the cursor with the text of the completion,
+    /// or apply its edit, undone in one step. A snippet completion is expanded
+    AcceptCompletion(CompletionItem),
+    /// expand the snippet at the cursor and select its first tab stop,
+    /// ie: `for ${1:item} in ${2:items} {\n\t$0\n}`
+    InsertSnippet(String),
+    /// select the next tab stop of the snippet, the snippet is done at the final tab stop
+    NextTabStop,
+    PrevTabStop,
+    /// stop moving between the tab stops of the snippet
+    ExitSnippet,
+    /// put back the lines of the base text in place of the changed lines at the cursor
+    RevertHunk,
+    /// format the content with the formatter, undone in one step
+    Format,
+    /// format the selected lines, or the cursor line, with the formatter
+    FormatSelection,
+    /// change the content into this text with the minimal edits, undone in one step.
+    /// Unlike `SetContent` the cursor, the marks and the undo history are kept
+    ReplaceContent(String),
+    /// apply the edits, located in the content before any of them is applied, undone in one step.
+    /// The cursor follows the text it is in
+    ApplyEdits(Vec<Edit>),
     PasteTextBlock(String),
     MergeText(String),
-    /// set a new content to the editor, resetting to a new history for undo/redo
+    /// set a new content to the editor, resetting to a new history for undo/redo.
+    /// The folds, marks, decorations, diagnostics and secondary selections are cleared
     SetContent(String),
     Undo,
     Redo,
     BumpHistory,
     SetSelection(Point2<i32>, Point2<i32>),
+    /// add a selection besides the current selection, to edit in multiple places at once
+    AddSelection(Point2<i32>, Point2<i32>),
     SelectAll,
     ClearSelection,
     SetPosition(Point2<i32>),
+    /// comment or uncomment the selected lines using the comment tokens of the current syntax
+    ToggleComment,
+    /// change the case, whitespaces or line order of the selected text or the word under the cursor
+    Transform(TextTransform),
+    /// fold the inner most unfolded range containing the cursor
+    Fold,
+    /// unfold the folded ranges containing the cursor
+    Unfold,
+    /// toggle the fold range starting at the cursor line
+    ToggleFold,
+    /// toggle the fold range starting at this line
+    ToggleFoldAt(usize),
+    FoldAll,
+    UnfoldAll,
+    /// fold the ranges which are nested deeper than this level
+    FoldToLevel(usize),
+    /// set the named mark at the cursor
+    SetMark(String),
+    /// move the cursor to the named mark
+    JumpToMark(String),
+    RemoveMark(String),
+    /// add or remove a bookmark on the cursor line
+    ToggleBookmark,
+    /// move the cursor to the next bookmark, wrapping around to the first one
+    NextBookmark,
+    /// move the cursor to the previous bookmark, wrapping around to the last one
+    PrevBookmark,
+    /// remove all the named marks and bookmarks
+    ClearMarks,
+    /// move the cursor to the next diagnostic, wrapping around to the first one
+    NextDiagnostic,
+    /// move the cursor to the previous diagnostic, wrapping around to the last one
+    PrevDiagnostic,
+    /// apply the fix of the diagnostic at the cursor
+    ApplyFix,
+    /// copy the selected text into the clipboard and the kill ring
+    Copy,
+    /// cut the selected text into the clipboard and the kill ring
+    Cut,
+    /// insert the content of the clipboard at the cursor, replacing the selected text
+    Paste,
+    /// insert the text read from a system clipboard, replacing the selected text.
+    /// The text is pasted as a block if it is the same block last written into the clipboard
+    PasteText(String),
+    /// remove the selected text without putting it into the clipboard
+    DeleteSelection,
+    /// insert the most recent text in the kill ring at the cursor
+    Yank,
+    /// replace the text inserted by the last yank with the next older text in the kill ring
+    YankPop,
+    CopyToRegister(char),
+    CutToRegister(char),
+    /// insert the text of the named register at the cursor
+    PasteFromRegister(char),
+    /// start recording the commands into the named macro slot, or only as the last macro
+    StartRecording(Option<String>),
+    StopRecording,
+    /// play back the named macro, or the last recorded macro, this many times
+    PlayMacro(Option<String>, usize),
+}
+
+impl Command {
+    /// the commands which control the macro recording and are not recorded themselves
+    pub fn is_macro_command(&self) -> bool {
+        matches!(
+            self,
+            Command::StartRecording(_) | Command::StopRecording | Command::PlayMacro(_, _)
+        )
+    }
+}
+
+/// The state of the editor after processing a batch of commands
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CommandResponse {
+    pub cursor: Point2<usize>,
+    pub selection: Selection,
+    pub is_content_changed: bool,
+    /// the changes of the content made by the commands, in the order they are applied
+    pub changes: Vec<ChangeDelta>,
 }
 
 pub struct Callback<IN, OUT> {
@@ -122,10 +319,30 @@ impl<IN, OUT> Callback<IN, OUT> {
 impl<XMSG> BaseEditor<XMSG> {
     pub fn from_str(options: &BaseOptions, content: &str) -> Self {
         let text_edit = TextEdit::new_from_str(content);
+        let folding = Folding::new(options.fold_strategy.clone(), text_edit.text_buffer());
 
         BaseEditor {
             options: options.clone(),
             text_edit,
+            syntax_token: None,
+            comment_tokens: None,
+            folding,
+            soft_wrap: SoftWrap::default(),
+            marks: Marks::default(),
+            decorations: Decorations::default(),
+            diagnostics: Diagnostics::default(),
+            secondary_selections: vec![],
+            registers: Registers::default(),
+            last_yank: None,
+            clipboard: Rc::new(RefCell::new(MemoryClipboard::default())),
+            macros: Macros::default(),
+            completion_providers: vec![Rc::new(BufferWords::new())],
+            formatter: None,
+            snippets: Snippets::default(),
+            snippet_session: None,
+            line_changes: None,
+            change_log: None,
+            change_feed: None,
             #[cfg(feature = "callback")]
             change_listeners: vec![],
             #[cfg(feature = "callback")]
@@ -138,14 +355,401 @@ impl<XMSG> BaseEditor<XMSG> {
         self.text_edit.text_buffer()
     }
 
+    /// set the syntax token of the content, ie: rust, python, sql.
+    /// This is used to determine the comment tokens used in `Command::ToggleComment`
+    /// and the snippets which are suggested
+    pub fn set_syntax_token(&mut self, syntax_token: &str) {
+        self.syntax_token = Some(syntax_token.to_string());
+        self.comment_tokens = CommentTokens::from_syntax_token(syntax_token);
+    }
+
+    pub fn syntax_token(&self) -> Option<&str> {
+        self.syntax_token.as_deref()
+    }
+
+    pub fn comment_tokens(&self) -> Option<&CommentTokens> {
+        self.comment_tokens.as_ref()
+    }
+
+    pub fn folding(&self) -> &Folding {
+        &self.folding
+    }
+
+    pub fn marks(&self) -> &Marks {
+        &self.marks
+    }
+
+    /// access the marks, ie: to restore the marks saved by the host application
+    pub fn marks_mut(&mut self) -> &mut Marks {
+        &mut self.marks
+    }
+
+    pub fn decorations(&self) -> &Decorations {
+        &self.decorations
+    }
+
+    pub fn decorations_mut(&mut self) -> &mut Decorations {
+        &mut self.decorations
+    }
+
+    /// decorate a line or a range of text, the decoration follows the text as it is edited
+    pub fn add_decoration(&mut self, decoration: Decoration) -> DecorationId {
+        self.decorations.add(decoration)
+    }
+
+    pub fn remove_decoration(&mut self, id: DecorationId) -> Option<Decoration> {
+        self.decorations.remove(id)
+    }
+
+    pub fn diagnostics(&self) -> &Diagnostics {
+        &self.diagnostics
+    }
+
+    /// replace the diagnostics, ie: with the new results of a linter.
+    /// The diagnostics follow the text as it is edited
+    pub fn set_diagnostics(&mut self, diagnostics: impl IntoIterator<Item = Diagnostic>) {
+        self.diagnostics.set(diagnostics);
+    }
+
+    pub fn clear_diagnostics(&mut self) {
+        self.diagnostics.clear();
+    }
+
+    /// start collecting the changes of the content made by all the commands,
+    /// ie: to keep the copy of a language server in sync.
+    /// The changes collected so far are dropped
+    pub fn track_changes(&mut self) {
+        self.change_feed = Some(vec![]);
+    }
+
+    /// the changes of the content since the last call, in the order they are applied.
+    /// Empty when the changes are not tracked
+    pub fn take_changes(&mut self) -> Vec<ChangeDelta> {
+        self.change_feed
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+
+    /// add a provider of the completions, the words of the content are suggested by default
+    pub fn add_completion_provider(&mut self, provider: impl CompletionProvider + 'static) {
+        self.completion_providers.push(Rc::new(provider));
+    }
+
+    pub fn clear_completion_providers(&mut self) {
+        self.completion_providers.clear();
+    }
+
+    /// a provider of the completions triggers the completion when this character is typed
+    pub fn is_completion_trigger(&self, ch: char) -> bool {
+        self.completion_providers
+            .iter()
+            .any(|provider| provider.trigger_characters().contains(&ch))
+    }
+
+    /// the start of the word before the cursor and the part of it typed so far
+    pub fn completion_prefix(&self) -> (Point2<usize>, String) {
+        completion::word_before(self.text_buffer(), self.get_position())
+    }
+
+    /// the completions of all the providers which match the word before the cursor,
+    /// the best matches first
+    pub fn completions(&self, trigger: Option<char>) -> Vec<CompletionItem> {
+        let cursor = self.get_position();
+        let mut labels = std::collections::BTreeSet::new();
+        let items: Vec<CompletionItem> = self
+            .completion_providers
+            .iter()
+            .flat_map(|provider| provider.complete(self.text_buffer(), cursor, trigger))
+            .filter(|item| labels.insert(item.label.clone()))
+            .collect();
+        let (_, typed) = self.completion_prefix();
+        let snippets = self
+            .syntax_token
+            .as_deref()
+            .map(|syntax_token| self.snippets.for_syntax(syntax_token))
+            .unwrap_or_default()
+            .iter()
+            .map(|snippet| {
+                let item = CompletionItem::snippet(&snippet.prefix, &snippet.body);
+                match snippet.description.as_ref() {
+                    Some(description) => item.with_detail(description),
+                    None => item,
+                }
+            });
+        let items: Vec<CompletionItem> = items.into_iter().chain(snippets).collect();
+        completion::filter_completions(&items, &typed)
+    }
+
+    /// use this formatter in `Command::Format`, ie: one which runs rustfmt
+    pub fn set_formatter(&mut self, formatter: impl Formatter + 'static) {
+        self.formatter = Some(Rc::new(formatter));
+    }
+
+    pub fn clear_formatter(&mut self) {
+        self.formatter = None;
+    }
+
+    pub fn has_formatter(&self) -> bool {
+        self.formatter.is_some()
+    }
+
+    /// format the content, or the lines from the start to the end(exclusive) of `range`,
+    /// returns true if the content is changed
+    pub fn format(
+        &mut self,
+        range: Option<(Point2<usize>, Point2<usize>)>,
+    ) -> Result<bool, FormatError> {
+        let Some(formatter) = self.formatter.clone() else {
+            return Err(FormatError("no formatter is set".to_string()));
+        };
+        let command = match formatter.format(self.text_buffer(), range)? {
+            Formatted::Text(text) => Command::ReplaceContent(text),
+            Formatted::Edits(edits) => Command::ApplyEdits(edits),
+        };
+        Ok(self.process_command(command))
+    }
+
+    /// show the changes of the lines from this base text, ie: the last saved or committed text
+    pub fn set_base_text(&mut self, base: &str) {
+        self.line_changes = Some(LineChanges::new(base, self.text_buffer()));
+    }
+
+    pub fn clear_base_text(&mut self) {
+        self.line_changes = None;
+    }
+
+    /// the changes of the lines from the base text, None when there is no base text
+    pub fn line_changes(&self) -> Option<&LineChanges> {
+        self.line_changes.as_ref()
+    }
+
+    pub fn snippets(&self) -> &Snippets {
+        &self.snippets
+    }
+
+    pub fn snippets_mut(&mut self) -> &mut Snippets {
+        &mut self.snippets
+    }
+
+    /// add a snippet for the syntax, it is suggested in the completions when its prefix is typed
+    pub fn register_snippet(
+        &mut self,
+        syntax_token: &str,
+        prefix: &str,
+        body: &str,
+        description: Option<&str>,
+    ) {
+        self.snippets
+            .register(syntax_token, prefix, body, description.map(String::from));
+    }
+
+    /// the tab stops of the snippet being filled in
+    pub fn snippet_session(&self) -> Option<&SnippetSession> {
+        self.snippet_session.as_ref()
+    }
+
+    /// the choices of the current tab stop of the snippet,
+    /// as completions which replace its placeholder
+    pub fn snippet_choices(&self) -> Vec<CompletionItem> {
+        let Some(stop) = self
+            .snippet_session
+            .as_ref()
+            .map(|session| session.current_stop())
+        else {
+            return vec![];
+        };
+        let (start, end) = stop.placeholder();
+        stop.choices
+            .iter()
+            .map(|choice| {
+                CompletionItem::new(choice, CompletionKind::Text)
+                    .with_edit(Edit::new(start, end, choice))
+            })
+            .collect()
+    }
+
+    pub fn registers(&self) -> &Registers {
+        &self.registers
+    }
+
+    /// access the registers, ie: to put texts from the host application
+    pub fn registers_mut(&mut self) -> &mut Registers {
+        &mut self.registers
+    }
+
+    /// use this clipboard in copy, cut and paste, ie: a system clipboard of the platform
+    pub fn set_clipboard(&mut self, clipboard: impl Clipboard + 'static) {
+        self.clipboard = Rc::new(RefCell::new(clipboard));
+    }
+
+    /// the content of the clipboard
+    pub fn clipboard_content(&self) -> Option<RegisterContent> {
+        self.clipboard.borrow().read()
+    }
+
+    pub fn macros(&self) -> &Macros {
+        &self.macros
+    }
+
+    /// access the macros, ie: to restore the macros saved by the host application
+    pub fn macros_mut(&mut self) -> &mut Macros {
+        &mut self.macros
+    }
+
+    pub fn soft_wrap(&self) -> &SoftWrap {
+        &self.soft_wrap
+    }
+
+    /// wrap the lines longer than this column into multiple visual rows,
+    /// None disables the soft wrap
+    pub fn set_wrap_column(&mut self, wrap_column: Option<usize>) {
+        if self.soft_wrap.wrap_column() != wrap_column {
+            self.soft_wrap
+                .set_wrap_column(wrap_column, self.text_edit.text_buffer());
+        }
+    }
+
+    /// the first visual row where this line is displayed,
+    /// taking the folded lines and the soft wrapped lines into account
+    pub fn line_to_row(&self, line: usize) -> usize {
+        if self.soft_wrap.is_enabled() {
+            self.position_to_visual(Point2::new(0, line)).y
+        } else {
+            self.folding.line_to_row(line)
+        }
+    }
+
+    /// the line displayed at this visual row,
+    /// taking the folded lines and the soft wrapped lines into account
+    pub fn row_to_line(&self, row: usize) -> usize {
+        if self.soft_wrap.is_enabled() {
+            self.visual_to_position(Point2::new(0, row)).y
+        } else {
+            self.folding.row_to_line(row)
+        }
+    }
+
+    /// the total number of visual rows of the content
+    pub fn total_rows(&self) -> usize {
+        self.line_to_row(self.total_lines())
+    }
+
+    /// the visual column and row where this location is displayed
+    pub fn position_to_visual(&self, loc: Point2<usize>) -> Point2<usize> {
+        let line = self.folding.visible_line_at(loc.y);
+        if !self.soft_wrap.is_enabled() {
+            return Point2::new(loc.x, self.folding.line_to_row(line));
+        }
+        let total_lines = self.total_lines();
+        let hidden_rows: usize = self
+            .folding
+            .hidden_lines()
+            .iter()
+            .filter(|(_, end)| *end < line)
+            .map(|(start, end)| {
+                self.soft_wrap.rows_before(end + 1) - self.soft_wrap.rows_before(*start)
+            })
+            .sum();
+        let row = self.soft_wrap.rows_before(line.min(total_lines)) - hidden_rows;
+        if line >= total_lines {
+            // lines past the content are not wrapped
+            return Point2::new(loc.x, row + line - total_lines);
+        }
+        if line != loc.y {
+            // hidden lines are displayed at the start of the line they are folded into
+            return Point2::new(0, row);
+        }
+        let row_in_line = self.soft_wrap.row_in_line(line, loc.x);
+        let row_start = self.soft_wrap.row_starts(line)[row_in_line];
+        Point2::new(loc.x - row_start, row + row_in_line)
+    }
+
+    /// the location in the text buffer which is displayed at this visual column and row
+    pub fn visual_to_position(&self, visual: Point2<usize>) -> Point2<usize> {
+        if !self.soft_wrap.is_enabled() {
+            return Point2::new(visual.x, self.folding.row_to_line(visual.y));
+        }
+        let total_lines = self.total_lines();
+        // the visual rows of the visible lines in between the folded lines
+        let mut row = 0;
+        let mut visible_start = 0;
+        for (hidden_start, hidden_end) in self
+            .folding
+            .hidden_lines()
+            .iter()
+            .copied()
+            .chain([(total_lines, total_lines)])
+        {
+            let rows_before_visible = self.soft_wrap.rows_before(visible_start);
+            let rows = self.soft_wrap.rows_before(hidden_start) - rows_before_visible;
+            if visual.y < row + rows {
+                let wrapped_row = rows_before_visible + visual.y - row;
+                let y = self.soft_wrap.line_at_row(wrapped_row);
+                let starts = self.soft_wrap.row_starts(y);
+                let row_in_line = wrapped_row - self.soft_wrap.rows_before(y);
+                let row_start = starts[row_in_line];
+                let x = match starts.get(row_in_line + 1) {
+                    // the cursor can not go past the end of a row which continues to the next row
+                    Some(next_start) => (row_start + visual.x).min(next_start.saturating_sub(1)),
+                    None => row_start + visual.x,
+                };
+                return Point2::new(x, y);
+            }
+            row += rows;
+            visible_start = hidden_end + 1;
+        }
+        Point2::new(visual.x, total_lines + visual.y - row)
+    }
+
+    /// move the cursor to the location displayed at this visual column and row
+    fn move_to_visual(&mut self, visual: Point2<usize>) {
+        let loc = self.visual_to_position(visual);
+        if self.options.use_virtual_edit {
+            self.text_edit.command_set_position(loc);
+        } else {
+            self.text_edit.command_set_position_clamped(loc);
+        }
+    }
+
     pub fn set_selection(&mut self, start: Point2<i32>, end: Point2<i32>) {
         self.text_edit.set_selection(start, end);
     }
 
+    pub fn selection_mode(&self) -> SelectionMode {
+        self.options.selection_mode
+    }
+
+    /// select the text in linear or block mode, ie: when entering the vim visual block mode
+    pub fn set_selection_mode(&mut self, selection_mode: SelectionMode) {
+        self.options.selection_mode = selection_mode;
+    }
+
     pub fn selection(&self) -> &Selection {
         self.text_edit.selection()
     }
 
+    /// the selections added besides the selection, see `Command::AddSelection`
+    pub fn secondary_selections(&self) -> &[Selection] {
+        &self.secondary_selections
+    }
+
+    /// the selection followed by the secondary selections
+    pub fn selections(&self) -> Vec<Selection> {
+        std::iter::once(self.selection())
+            .filter(|selection| selection.start.is_some() && selection.end.is_some())
+            .chain(self.secondary_selections.iter())
+            .cloned()
+            .collect()
+    }
+
+    pub fn add_selection(&mut self, start: Point2<i32>, end: Point2<i32>) {
+        self.secondary_selections.push(Selection {
+            start: Some(start),
+            end: Some(end),
+        });
+    }
+
     pub fn selected_text(&self) -> Option<String> {
         match self.options.selection_mode {
             SelectionMode::Linear => self.text_edit.selected_text_in_linear_mode(),
@@ -160,15 +764,46 @@ impl<XMSG> BaseEditor<XMSG> {
         }
     }
 
+    /// both ends of the selection are set
+    pub fn has_selection(&self) -> bool {
+        let selection = self.selection();
+        selection.start.is_some() && selection.end.is_some()
+    }
+
+    /// Group the edits of the succeeding commands, so they are undone and redone in one step
+    pub fn begin_group(&mut self) {
+        self.text_edit.begin_group();
+    }
+
+    /// end the group started in `begin_group`
+    pub fn end_group(&mut self) {
+        self.text_edit.end_group();
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.text_edit.can_undo()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.text_edit.can_redo()
+    }
+
     pub fn is_selected(&self, loc: Point2<i32>) -> bool {
-        match self.options.selection_mode {
+        let is_selected = match self.options.selection_mode {
             SelectionMode::Linear => self.text_edit.is_selected_in_linear_mode(loc),
             SelectionMode::Block => self.text_edit.is_selected_in_block_mode(loc),
-        }
+        };
+        is_selected
+            || self
+                .secondary_selections
+                .iter()
+                .any(|selection| selection.contains_in_linear_mode(loc))
     }
 
+    /// clear the selection and the secondary selections
     pub fn clear_selection(&mut self) {
-        self.text_edit.clear_selection()
+        self.text_edit.clear_selection();
+        self.secondary_selections.clear();
     }
 
     pub fn set_selection_start(&mut self, start: Point2<i32>) {
@@ -194,6 +829,29 @@ impl<XMSG> BaseEditor<XMSG> {
     pub fn total_lines(&self) -> usize {
         self.text_edit.total_lines()
     }
+
+    /// move the cursor to the location, ie: `12`, `12:5`, `+3` or `-3`,
+    /// and return the new cursor position
+    pub fn go_to(&mut self, location: &str) -> Result<Point2<usize>, InvalidGoToTarget> {
+        let target: GoToTarget = location.parse()?;
+        Ok(self.go_to_target(target))
+    }
+
+    /// move the cursor to the line and the column which starts at 1,
+    /// ie: for the host applications linking to a location in the content
+    pub fn go_to_line(&mut self, line: usize, column: Option<usize>) -> Point2<usize> {
+        self.go_to_target(GoToTarget::Line { line, column })
+    }
+
+    /// move the cursor with `Command::SetPosition`, the location is clamped to the content
+    pub fn go_to_target(&mut self, target: GoToTarget) -> Point2<usize> {
+        let loc = target.resolve(self.get_position(), self.total_lines());
+        self.process_command(Command::SetPosition(Point2::new(
+            loc.x as i32,
+            loc.y as i32,
+        )));
+        self.get_position()
+    }
 }
 
 impl<XMSG> BaseEditor<XMSG> {
@@ -216,8 +874,298 @@ impl<XMSG> BaseEditor<XMSG> {
         vec![]
     }
 
+    /// process the commands and return the resulting cursor, selection and the changes of the content
+    pub fn process_commands_with_response(
+        &mut self,
+        commands: impl IntoIterator<Item = Command>,
+    ) -> CommandResponse {
+        self.change_log = Some(vec![]);
+        let mut is_content_changed = false;
+        for command in commands {
+            is_content_changed |= self.process_command(command);
+        }
+        CommandResponse {
+            cursor: self.get_position(),
+            selection: self.selection().clone(),
+            is_content_changed,
+            changes: self.change_log.take().unwrap_or_default(),
+        }
+    }
+
+    /// process a json array of commands and return the `CommandResponse` as json,
+    /// ie: `[{"InsertText": "hello"}, "BreakLine"]`
+    #[cfg(feature = "serde")]
+    pub fn process_json_commands(&mut self, json: &str) -> Result<String, serde_json::Error> {
+        let commands: Vec<Command> = serde_json::from_str(json)?;
+        let response = self.process_commands_with_response(commands);
+        serde_json::to_string(&response)
+    }
+
     /// process the supplied command to text_edit
     pub fn process_command(&mut self, command: Command) -> bool {
+        // only a yank right after a yank can be replaced with yank pop
+        if !matches!(command, Command::Yank | Command::YankPop) {
+            self.last_yank = None;
+        }
+        self.macros.record(&command);
+        // the edits in a snippet are undone together with the edits of their mirrors
+        let is_snippet_group = self.snippet_session.is_some()
+            && !matches!(
+                command,
+                Command::Undo | Command::Redo | Command::SetContent(_) | Command::BumpHistory
+            );
+        if is_snippet_group {
+            self.text_edit.begin_group();
+        }
+        let mut is_content_changed = self.replace_snippet_placeholder(&command);
+        // the selected placeholder is deleted instead of the character next to it
+        if !(is_content_changed && matches!(command, Command::DeleteBack | Command::DeleteForward))
+        {
+            is_content_changed |= self.apply_command(command);
+        }
+        self.apply_changes();
+        if is_content_changed && self.sync_snippet_mirrors() {
+            self.apply_changes();
+        }
+        if is_snippet_group {
+            self.text_edit.end_group();
+        }
+        if is_content_changed {
+            for provider in self.completion_providers.iter() {
+                provider.content_changed();
+            }
+            self.folding
+                .update_changed_lines(self.text_edit.text_buffer());
+            self.soft_wrap
+                .update_changed_lines(self.text_edit.text_buffer());
+            if let Some(line_changes) = self.line_changes.as_mut() {
+                line_changes.refresh(self.text_edit.text_buffer());
+            }
+        }
+        is_content_changed
+    }
+
+    /// move the locations which follow the text with the changes of the content
+    fn apply_changes(&mut self) {
+        for action in self.text_edit.take_changes() {
+            self.folding.apply_action(&action);
+            self.soft_wrap.apply_action(&action);
+            self.marks.apply_action(&action);
+            self.decorations.apply_action(&action);
+            self.diagnostics.apply_action(&action);
+            for selection in self.secondary_selections.iter_mut() {
+                let shift = |loc: Point2<i32>| {
+                    let loc = action.shift_point(Point2::new(loc.x as usize, loc.y as usize));
+                    Point2::new(loc.x as i32, loc.y as i32)
+                };
+                selection.start = selection.start.map(shift);
+                selection.end = selection.end.map(shift);
+            }
+            if let Some(session) = self.snippet_session.as_mut() {
+                session.apply_action(&action);
+            }
+            if let Some(line_changes) = self.line_changes.as_mut() {
+                line_changes.apply_action(&action);
+            }
+            for changes in [self.change_log.as_mut(), self.change_feed.as_mut()]
+                .into_iter()
+                .flatten()
+            {
+                ChangeDelta::push_action(changes, &action);
+            }
+        }
+    }
+
+    /// put back the lines of the base text in place of the hunk at the cursor line
+    fn revert_hunk(&mut self) -> bool {
+        let Some(line_changes) = self.line_changes.as_ref() else {
+            return false;
+        };
+        let Some(hunk) = line_changes.hunk_at(self.get_position().y) else {
+            return false;
+        };
+        let Some(edit) = line_changes.revert_edit(&hunk, self.text_buffer()) else {
+            return false;
+        };
+        let is_changed = self.apply_command(Command::ApplyEdits(vec![edit]));
+        self.apply_changes();
+        if let Some(line_changes) = self.line_changes.as_mut() {
+            line_changes.set_unchanged(hunk.new_lines.start, hunk.old_lines);
+        }
+        is_changed
+    }
+
+    /// apply the result of the formatter, the formatting errors are logged
+    fn apply_formatter(&mut self, range: Option<(Point2<usize>, Point2<usize>)>) -> bool {
+        let Some(formatter) = self.formatter.clone() else {
+            return false;
+        };
+        match formatter.format(self.text_buffer(), range) {
+            Ok(Formatted::Text(text)) => self.apply_command(Command::ReplaceContent(text)),
+            Ok(Formatted::Edits(edits)) => self.apply_command(Command::ApplyEdits(edits)),
+            Err(e) => {
+                log::warn!("{e}");
+                false
+            }
+        }
+    }
+
+    /// expand the snippet at the cursor, indented like the cursor line,
+    /// and select its first tab stop
+    fn insert_snippet(&mut self, body: &str) -> bool {
+        let cursor = self.get_position();
+        let line = self
+            .text_buffer()
+            .lines()
+            .get(cursor.y)
+            .cloned()
+            .unwrap_or_default();
+        let word = self
+            .text_buffer()
+            .word_range_at(cursor)
+            .map(|(start, end)| self.text_buffer().get_text(start, end))
+            .unwrap_or_default();
+        let variables = |name: &str| match name {
+            "TM_CURRENT_LINE" => Some(line.clone()),
+            "TM_CURRENT_WORD" => Some(word.clone()),
+            "TM_LINE_INDEX" => Some(cursor.y.to_string()),
+            "TM_LINE_NUMBER" => Some((cursor.y + 1).to_string()),
+            "TM_SELECTED_TEXT" => Some(String::new()),
+            _ => None,
+        };
+        let Ok(snippet) = Snippet::parse_with_variables(body, variables) else {
+            return false;
+        };
+        let indent: String = line.chars().take_while(|ch| ch.is_whitespace()).collect();
+        let snippet = snippet.indented(&indent);
+        self.snippet_session = None;
+        self.text_edit.clear_selection();
+        self.text_edit.begin_group();
+        self.text_edit.insert_text_at(cursor, snippet.text());
+        self.text_edit.end_group();
+        // the tab stops are located in the content with the snippet inserted
+        self.apply_changes();
+        let stops = snippet.stops_at(cursor);
+        if stops.len() > 1 {
+            self.snippet_session = Some(SnippetSession::new(stops));
+            self.select_tab_stop();
+        } else {
+            self.text_edit
+                .command_set_position(stops[0].placeholder().0);
+        }
+        true
+    }
+
+    /// select the next or previous tab stop, the snippet is done at the final tab stop
+    fn move_to_tab_stop(&mut self, forward: bool) {
+        let Some(session) = self.snippet_session.as_mut() else {
+            return;
+        };
+        if session.advance(forward) {
+            self.select_tab_stop();
+        } else {
+            let (start, _) = session.current_stop().placeholder();
+            self.snippet_session = None;
+            self.text_edit.clear_selection();
+            self.text_edit.command_set_position(start);
+        }
+    }
+
+    /// select the placeholder of the current tab stop, so it is replaced once something is typed
+    fn select_tab_stop(&mut self) {
+        let Some(session) = self.snippet_session.as_mut() else {
+            return;
+        };
+        let (start, end) = session.current_stop().placeholder();
+        session.is_placeholder_selected = start != end;
+        self.text_edit.clear_selection();
+        self.text_edit.command_set_position(end);
+        if start != end {
+            let last = self.last_char_before(end);
+            self.text_edit.command_set_selection(
+                Point2::new(start.x as i32, start.y as i32),
+                Point2::new(last.x as i32, last.y as i32),
+            );
+        }
+    }
+
+    /// the location of the character right before this location,
+    /// which is the end of the previous line at the start of a line
+    fn last_char_before(&self, loc: Point2<usize>) -> Point2<usize> {
+        if loc.x == 0 {
+            let y = loc.y.saturating_sub(1);
+            return Point2::new(self.text_buffer().line_width(y), y);
+        }
+        let line = self
+            .text_buffer()
+            .chars()
+            .get(loc.y)
+            .map(Vec::as_slice)
+            .unwrap_or(&[]);
+        let mut x = 0;
+        for ch in line {
+            if x + ch.width >= loc.x {
+                break;
+            }
+            x += ch.width;
+        }
+        Point2::new(x, loc.y)
+    }
+
+    /// delete the selected placeholder of the snippet when text is typed over it,
+    /// returns true if it is deleted
+    fn replace_snippet_placeholder(&mut self, command: &Command) -> bool {
+        let Some(session) = self.snippet_session.as_mut() else {
+            return false;
+        };
+        if !std::mem::take(&mut session.is_placeholder_selected) {
+            return false;
+        }
+        let is_typing = matches!(
+            command,
+            Command::InsertChar(_)
+                | Command::InsertText(_)
+                | Command::BreakLine
+                | Command::DeleteBack
+                | Command::DeleteForward
+                | Command::Paste
+                | Command::PasteText(_)
+                | Command::Yank
+        );
+        if !is_typing {
+            return false;
+        }
+        let (start, end) = session.current_stop().placeholder();
+        self.text_edit.clear_selection();
+        self.text_edit.begin_group();
+        self.text_edit.delete_text(start, end);
+        self.text_edit.end_group();
+        self.text_edit.command_set_position(start);
+        true
+    }
+
+    /// copy the text of the placeholder of the current tab stop into its mirrors,
+    /// returns true if a mirror is changed
+    fn sync_snippet_mirrors(&mut self) -> bool {
+        let Some(stop) = self
+            .snippet_session
+            .as_ref()
+            .map(|session| session.current_stop())
+        else {
+            return false;
+        };
+        let (start, end) = stop.placeholder();
+        let text = self.text_buffer().get_text(start, end);
+        let edits: Vec<Edit> = stop.ranges[1..]
+            .iter()
+            .filter(|(start, end)| self.text_buffer().get_text(*start, *end) != text)
+            .map(|(start, end)| Edit::new(*start, *end, &text))
+            .collect();
+        !edits.is_empty() && self.apply_command(Command::ApplyEdits(edits))
+    }
+
+    /// apply the command and return true if the content is changed
+    fn apply_command(&mut self, command: Command) -> bool {
         match command {
             Command::IndentForward => {
                 let indent = "    ";
@@ -281,16 +1229,111 @@ impl<XMSG> BaseEditor<XMSG> {
                 self.text_edit.command_insert_text(&text);
                 true
             }
+            Command::ReplaceText(start, end, text) => {
+                self.text_edit.begin_group();
+                let end = self.text_edit.replace_text(start, end, &text);
+                self.text_edit.end_group();
+                self.text_edit.command_set_position(end);
+                true
+            }
+            Command::AcceptCompletion(item) => {
+                let cursor = self.get_position();
+                let (start, end, text) = match item.edit {
+                    // the edit is extended over the characters typed after it is requested
+                    Some(edit) if edit.end.y == cursor.y && edit.end.x < cursor.x => {
+                        (edit.start, cursor, edit.text)
+                    }
+                    Some(edit) => (edit.start, edit.end, edit.text),
+                    None => (self.completion_prefix().0, cursor, item.insert_text),
+                };
+                if item.is_snippet {
+                    self.text_edit.begin_group();
+                    self.text_edit.delete_text(start, end);
+                    self.text_edit.end_group();
+                    self.text_edit.command_set_position(start);
+                    self.insert_snippet(&text)
+                } else {
+                    self.apply_command(Command::ReplaceText(start, end, text))
+                }
+            }
+            Command::InsertSnippet(body) => self.insert_snippet(&body),
+            Command::NextTabStop => {
+                self.move_to_tab_stop(true);
+                false
+            }
+            Command::PrevTabStop => {
+                self.move_to_tab_stop(false);
+                false
+            }
+            Command::ExitSnippet => {
+                self.snippet_session = None;
+                false
+            }
+            Command::RevertHunk => self.revert_hunk(),
+            Command::Format => self.apply_formatter(None),
+            Command::FormatSelection => {
+                let (start, end) = self
+                    .text_edit
+                    .selection_normalized_casted()
+                    .unwrap_or((self.get_position(), self.get_position()));
+                self.apply_formatter(Some((Point2::new(0, start.y), Point2::new(0, end.y + 1))))
+            }
+            Command::ReplaceContent(text) => {
+                let edits = formatter::diff_edits(&self.get_content(), &text);
+                self.apply_command(Command::ApplyEdits(edits))
+            }
+            Command::ApplyEdits(edits) => {
+                if edits.is_empty() {
+                    return false;
+                }
+                // applied from the last to the first, so the locations of the edits
+                // before it are not moved. The edits at the same location are inserted in order
+                let mut edits: Vec<(usize, Edit)> = edits.into_iter().enumerate().collect();
+                edits.sort_by_key(|(i, edit)| Reverse((edit.start.y, edit.start.x, *i)));
+                let cursor = edits.iter().fold(self.get_position(), |cursor, (_, edit)| {
+                    edit.shift_point(cursor)
+                });
+                self.text_edit.begin_group();
+                for (_, edit) in edits {
+                    self.text_edit
+                        .replace_text(edit.start, edit.end, &edit.text);
+                }
+                self.text_edit.end_group();
+                self.text_edit.command_set_position(cursor);
+                true
+            }
             Command::SetContent(content) => {
+                if self.change_log.is_some() || self.change_feed.is_some() {
+                    let delta = ChangeDelta::new(Point2::origin(), self.get_content(), &*content);
+                    for changes in [self.change_log.as_mut(), self.change_feed.as_mut()]
+                        .into_iter()
+                        .flatten()
+                    {
+                        changes.push(delta.clone());
+                    }
+                }
                 self.text_edit = TextEdit::new_from_str(&content);
+                self.folding.unfold_all();
+                self.folding.update(self.text_edit.text_buffer());
+                self.soft_wrap.update(self.text_edit.text_buffer());
+                self.marks.clear();
+                self.decorations.clear();
+                self.diagnostics.clear();
+                self.secondary_selections.clear();
+                self.snippet_session = None;
+                if let Some(line_changes) = self.line_changes.as_mut() {
+                    line_changes.rebuild(self.text_edit.text_buffer());
+                }
                 true
             }
             Command::Undo => {
                 self.text_edit.command_undo();
+                self.snippet_session = None;
                 true
             }
             Command::Redo => {
                 self.text_edit.command_redo();
+                self.snippet_session = None;
                 true
             }
             Command::BumpHistory => {
@@ -305,27 +1348,347 @@ impl<XMSG> BaseEditor<XMSG> {
                 self.text_edit.command_select_all();
                 false
             }
+            Command::AddSelection(start, end) => {
+                self.add_selection(start, end);
+                false
+            }
             Command::ClearSelection => {
-                self.text_edit.clear_selection();
+                self.clear_selection();
                 false
             }
             Command::SetPosition(pos) => {
                 self.command_set_position(pos);
                 false
             }
+            Command::ToggleComment => {
+                if let Some(comment_tokens) = self.comment_tokens.as_ref() {
+                    self.text_edit.command_toggle_comment(comment_tokens);
+                    true
+                } else {
+                    false
+                }
+            }
+            Command::Transform(transform) => self
+                .text_edit
+                .command_transform(transform, self.options.selection_mode),
+            Command::Fold => {
+                let line = self.get_position().y;
+                self.folding.fold(line);
+                self.reveal_cursor();
+                false
+            }
+            Command::Unfold => {
+                let line = self.get_position().y;
+                self.folding.unfold(line);
+                false
+            }
+            Command::ToggleFold => {
+                let line = self.get_position().y;
+                self.folding.toggle(line);
+                self.reveal_cursor();
+                false
+            }
+            Command::ToggleFoldAt(line) => {
+                self.folding.toggle(line);
+                self.reveal_cursor();
+                false
+            }
+            Command::FoldAll => {
+                self.folding.fold_all();
+                self.reveal_cursor();
+                false
+            }
+            Command::UnfoldAll => {
+                self.folding.unfold_all();
+                false
+            }
+            Command::FoldToLevel(level) => {
+                self.folding.fold_to_level(level);
+                self.reveal_cursor();
+                false
+            }
+            Command::SetMark(name) => {
+                self.marks.set_mark(&name, self.get_position());
+                false
+            }
+            Command::JumpToMark(name) => {
+                if let Some(loc) = self.marks.mark(&name) {
+                    self.jump_to(loc);
+                }
+                false
+            }
+            Command::RemoveMark(name) => {
+                self.marks.remove_mark(&name);
+                false
+            }
+            Command::ToggleBookmark => {
+                self.marks.toggle_bookmark(self.get_position());
+                false
+            }
+            Command::NextBookmark => {
+                if let Some(loc) = self.marks.next_bookmark(self.get_position()) {
+                    self.jump_to(loc);
+                }
+                false
+            }
+            Command::PrevBookmark => {
+                if let Some(loc) = self.marks.prev_bookmark(self.get_position()) {
+                    self.jump_to(loc);
+                }
+                false
+            }
+            Command::ClearMarks => {
+                self.marks.clear();
+                false
+            }
+            Command::NextDiagnostic => {
+                if let Some(diagnostic) = self.diagnostics.next_diagnostic(self.get_position()) {
+                    self.jump_to(diagnostic.start);
+                }
+                false
+            }
+            Command::PrevDiagnostic => {
+                if let Some(diagnostic) = self.diagnostics.prev_diagnostic(self.get_position()) {
+                    self.jump_to(diagnostic.start);
+                }
+                false
+            }
+            Command::ApplyFix => {
+                let fix = self
+                    .diagnostics
+                    .at(self.get_position())
+                    .find_map(|diagnostic| diagnostic.fix);
+                match fix {
+                    Some(fix) => {
+                        self.apply_command(Command::ReplaceText(fix.start, fix.end, fix.text))
+                    }
+                    None => false,
+                }
+            }
+            Command::Copy => {
+                if let Some(content) = self.selected_content() {
+                    self.clipboard.borrow_mut().write(content.clone());
+                    self.registers.kill(content);
+                }
+                false
+            }
+            Command::Cut => {
+                if let Some(content) = self.cut_selection() {
+                    self.clipboard.borrow_mut().write(content.clone());
+                    self.registers.kill(content);
+                    true
+                } else {
+                    false
+                }
+            }
+            Command::Paste => {
+                let content = self.clipboard.borrow().read();
+                if let Some(content) = content {
+                    self.paste_content(content);
+                    true
+                } else {
+                    false
+                }
+            }
+            Command::PasteText(text) => {
+                let content = match self.clipboard.borrow().read() {
+                    // keep the selection mode of the text copied from this editor
+                    Some(content) if content.text == text => content,
+                    _ => RegisterContent::linear(text),
+                };
+                self.paste_content(content);
+                true
+            }
+            Command::DeleteSelection => {
+                self.text_edit.begin_group();
+                let is_deleted = self.cut_selection().is_some();
+                self.text_edit.end_group();
+                is_deleted
+            }
+            Command::Yank => {
+                if let Some(content) = self.registers.yank().cloned() {
+                    let cursor = self.get_position();
+                    self.insert_content(content.clone());
+                    self.last_yank = Some((cursor, content));
+                    true
+                } else {
+                    false
+                }
+            }
+            Command::YankPop => {
+                if let Some((loc, last_content)) = self.last_yank.take() {
+                    if let Some(content) = self.registers.yank_pop().cloned() {
+                        self.text_edit.begin_group();
+                        self.remove_content(loc, &last_content);
+                        self.text_edit.command_set_position(loc);
+                        self.insert_content(content.clone());
+                        self.text_edit.end_group();
+                        self.last_yank = Some((loc, content));
+                        return true;
+                    }
+                }
+                false
+            }
+            Command::CopyToRegister(name) => {
+                if let Some(content) = self.selected_content() {
+                    self.registers.set(name, content);
+                }
+                false
+            }
+            Command::CutToRegister(name) => {
+                if let Some(content) = self.cut_selection() {
+                    self.registers.set(name, content);
+                    true
+                } else {
+                    false
+                }
+            }
+            Command::PasteFromRegister(name) => {
+                if let Some(content) = self.registers.get(name).cloned() {
+                    self.insert_content(content);
+                    true
+                } else {
+                    false
+                }
+            }
+            Command::StartRecording(name) => {
+                self.macros.start_recording(name);
+                false
+            }
+            Command::StopRecording => {
+                self.macros.stop_recording();
+                false
+            }
+            Command::PlayMacro(name, count) => {
+                if let Some(recorded) = self.macros.get(name.as_deref()).cloned() {
+                    self.play_macro(&recorded, count)
+                } else {
+                    false
+                }
+            }
+        }
+    }
+
+    /// play back the commands of the macro this many times, undone in one step
+    fn play_macro(&mut self, recorded: &Macro, count: usize) -> bool {
+        self.text_edit.begin_group();
+        let mut is_content_changed = false;
+        for _ in 0..count {
+            for command in recorded.commands.iter() {
+                is_content_changed |= self.process_command(command.clone());
+            }
+        }
+        self.text_edit.end_group();
+        is_content_changed
+    }
+
+    /// the selected text and the selection mode it is selected with
+    fn selected_content(&self) -> Option<RegisterContent> {
+        self.selected_text()
+            .map(|text| RegisterContent::new(text, self.options.selection_mode))
+    }
+
+    /// cut the selected text, clear the selection and put the cursor where the selection starts
+    fn cut_selection(&mut self) -> Option<RegisterContent> {
+        let start = match self.options.selection_mode {
+            SelectionMode::Linear => self.text_edit.selection_reorder_casted(),
+            SelectionMode::Block => self.text_edit.selection_normalized_casted(),
+        }
+        .map(|(start, _end)| start)?;
+        let text = self.cut_selected_text()?;
+        self.text_edit.clear_selection();
+        self.text_edit.command_set_position(start);
+        Some(RegisterContent::new(text, self.options.selection_mode))
+    }
+
+    /// insert the register content at the cursor, a block content is inserted as a block
+    fn insert_content(&mut self, content: RegisterContent) {
+        let cursor = self.get_position();
+        match content.mode {
+            SelectionMode::Linear => self.text_edit.command_insert_text(&content.text),
+            SelectionMode::Block => {
+                self.text_edit.begin_group();
+                self.text_edit.insert_text_block(cursor, &content.text);
+                self.text_edit.end_group();
+            }
+        }
+    }
+
+    /// insert the content at the cursor replacing the selected text, undone in one step
+    fn paste_content(&mut self, content: RegisterContent) {
+        self.text_edit.begin_group();
+        self.cut_selection();
+        self.insert_content(content);
+        self.text_edit.end_group();
+    }
+
+    /// remove the register content which was inserted at this location
+    fn remove_content(&mut self, loc: Point2<usize>, content: &RegisterContent) {
+        match content.mode {
+            SelectionMode::Linear => {
+                let end = util::text_end(loc, &content.text);
+                self.text_edit.delete_text(loc, end);
+            }
+            SelectionMode::Block => self.text_edit.delete_text_block(loc, &content.text),
+        }
+    }
+
+    /// move the cursor to this location, unfolding the ranges hiding it
+    fn jump_to(&mut self, loc: Point2<usize>) {
+        self.command_set_position(Point2::new(loc.x as i32, loc.y as i32));
+    }
+
+    /// move the cursor to the line the hidden cursor line is folded into
+    fn reveal_cursor(&mut self) {
+        let cursor = self.get_position();
+        let line = self.folding.visible_line_at(cursor.y);
+        if line != cursor.y {
+            let x = cursor.x.min(self.text_buffer().line_width(line));
+            self.text_edit.command_set_position(Point2::new(x, line));
         }
     }
 
     fn command_move_up(&mut self) {
+        if self.soft_wrap.is_enabled() {
+            // move by visual row, which could still be in the same line
+            let visual = self.position_to_visual(self.get_position());
+            if visual.y > 0 {
+                self.move_to_visual(Point2::new(visual.x, visual.y - 1));
+            }
+            return;
+        }
         if self.options.use_virtual_edit {
             self.text_edit.command_move_up();
         } else {
             self.text_edit.command_move_up_clamped();
         }
+        // moving up into a folded range lands on the line it is folded into
+        self.reveal_cursor();
     }
 
     fn command_move_down(&mut self) {
-        if self.options.use_virtual_edit {
+        if self.soft_wrap.is_enabled() {
+            let visual = self.position_to_visual(self.get_position());
+            if visual.y + 1 < self.total_rows() || self.options.use_virtual_edit {
+                self.move_to_visual(Point2::new(visual.x, visual.y + 1));
+            }
+            return;
+        }
+        let cursor = self.get_position();
+        let next_line = self.folding.next_visible_line(cursor.y);
+        if next_line > cursor.y + 1 {
+            // skip over the lines hidden in a folded range
+            if next_line < self.total_lines() || self.options.use_virtual_edit {
+                let line_width = self.text_buffer().line_width(next_line);
+                let x = if self.options.use_virtual_edit {
+                    cursor.x
+                } else {
+                    cursor.x.min(line_width)
+                };
+                self.text_edit
+                    .command_set_position(Point2::new(x, next_line));
+            }
+        } else if self.options.use_virtual_edit {
             self.text_edit.command_move_down();
         } else {
             self.text_edit.command_move_down_clamped();
@@ -346,6 +1709,10 @@ impl<XMSG> BaseEditor<XMSG> {
 
     fn command_set_position(&mut self, loc: Point2<i32>) {
         let cursor = Point2::new(loc.x as usize, loc.y as usize);
+        // the folded ranges hiding the new cursor position are unfolded
+        if self.folding.is_hidden(cursor.y) {
+            self.folding.unfold(cursor.y);
+        }
         if self.options.use_virtual_edit {
             self.text_edit.command_set_position(cursor);
         } else {
@@ -1,4 +1,4 @@
-use crate::SelectionMode;
+use crate::{FoldStrategy, SelectionMode};
 
 #[derive(Clone, Debug)]
 pub struct BaseOptions {
@@ -7,6 +7,8 @@ pub struct BaseOptions {
     pub selection_mode: SelectionMode,
     /// allow the click outside of the bounds of the text content editor
     pub use_virtual_edit: bool,
+    /// how the fold ranges of the content are computed
+    pub fold_strategy: FoldStrategy,
 }
 
 impl Default for BaseOptions {
@@ -14,6 +16,7 @@ impl Default for BaseOptions {
         Self {
             selection_mode: SelectionMode::Linear,
             use_virtual_edit: false,
+            fold_strategy: FoldStrategy::default(),
         }
     }
 }
@@ -0,0 +1,88 @@
+use crate::{text_edit::Action, util};
+use nalgebra::Point2;
+use unicode_width::UnicodeWidthChar;
+
+/// A change of the content: the `deleted` text at `start` is replaced with the `inserted` text.
+/// The changes are listed in the order they are applied,
+/// the location of each change is relative to the content after the previous changes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChangeDelta {
+    pub start: Point2<usize>,
+    pub deleted: String,
+    pub inserted: String,
+}
+
+impl ChangeDelta {
+    pub fn new(
+        start: Point2<usize>,
+        deleted: impl Into<String>,
+        inserted: impl Into<String>,
+    ) -> Self {
+        Self {
+            start,
+            deleted: deleted.into(),
+            inserted: inserted.into(),
+        }
+    }
+
+    /// the location right after the inserted text
+    pub fn inserted_end(&self) -> Point2<usize> {
+        util::text_end(self.start, &self.inserted)
+    }
+
+    /// add the character level action to the changes,
+    /// merged into the last change if it continues where the last change ends
+    pub(crate) fn push_action(deltas: &mut Vec<Self>, action: &Action) {
+        if let Some(last) = deltas.last_mut() {
+            if last.merge(action) {
+                return;
+            }
+        }
+        deltas.push(Self::from_action(action));
+    }
+
+    fn from_action(action: &Action) -> Self {
+        match *action {
+            Action::Insert(loc, ch) => Self::new(loc, "", ch),
+            Action::Delete(loc, ch) => Self::new(loc, ch, ""),
+            Action::Replace(loc, old_ch, ch) => Self::new(loc, old_ch, ch),
+            Action::BreakLine(loc) => Self::new(loc, "", '\n'),
+            // the location of the join is at the end of the line
+            Action::JoinLine(loc) => Self::new(loc, '\n', ""),
+        }
+    }
+
+    /// extend this change with the action if it continues where this change ends,
+    /// returns false if the action can not be merged
+    fn merge(&mut self, action: &Action) -> bool {
+        let end = self.inserted_end();
+        let is_deleting = self.inserted.is_empty();
+        match *action {
+            Action::Insert(loc, ch) if loc == end => self.inserted.push(ch),
+            Action::BreakLine(loc) if loc == end => self.inserted.push('\n'),
+            Action::Replace(loc, old_ch, ch) if loc == end => {
+                self.deleted.push(old_ch);
+                self.inserted.push(ch);
+            }
+            // deleting forward
+            Action::Delete(loc, ch) if is_deleting && loc == self.start => self.deleted.push(ch),
+            Action::JoinLine(loc) if is_deleting && loc == self.start => self.deleted.push('\n'),
+            // deleting backward
+            Action::Delete(loc, ch)
+                if is_deleting
+                    && loc.y == self.start.y
+                    && loc.x + ch.width().unwrap_or(0) == self.start.x =>
+            {
+                self.deleted.insert(0, ch);
+                self.start = loc;
+            }
+            Action::JoinLine(loc) if is_deleting && self.start == Point2::new(0, loc.y + 1) => {
+                self.deleted.insert(0, '\n');
+                self.start = loc;
+            }
+            _ => return false,
+        }
+        true
+    }
+}
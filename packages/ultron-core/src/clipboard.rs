@@ -0,0 +1,115 @@
+use crate::RegisterContent;
+use std::io::{self, Write};
+
+/// A clipboard where the cut and copied texts are written into and pasted from
+///
+/// The system clipboards only hold plain text,
+/// so the backends keep the selection mode of the last written content,
+/// which lets a block copied in the editor be pasted back as a block.
+pub trait Clipboard {
+    /// put the content into the clipboard
+    fn write(&mut self, content: RegisterContent);
+
+    /// the content of the clipboard,
+    /// None if it is empty or the backend can not be read synchronously
+    fn read(&self) -> Option<RegisterContent>;
+}
+
+/// A clipboard which only lives in memory, used in headless editors and tests
+#[derive(Clone, Debug, Default)]
+pub struct MemoryClipboard {
+    content: Option<RegisterContent>,
+}
+
+impl MemoryClipboard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Clipboard for MemoryClipboard {
+    fn write(&mut self, content: RegisterContent) {
+        self.content = Some(content);
+    }
+
+    fn read(&self) -> Option<RegisterContent> {
+        self.content.clone()
+    }
+}
+
+/// A clipboard for the editors running in a terminal.
+/// The written text is sent to the terminal clipboard with the OSC 52 escape sequence.
+///
+/// Most terminals don't allow the clipboard to be read back,
+/// so the last written content is also kept in memory.
+#[derive(Debug)]
+pub struct Osc52Clipboard<W: Write> {
+    writer: W,
+    content: Option<RegisterContent>,
+}
+
+impl Osc52Clipboard<io::Stdout> {
+    /// write the escape sequences into the standard output
+    pub fn stdout() -> Self {
+        Self::new(io::stdout())
+    }
+}
+
+impl<W: Write> Osc52Clipboard<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            content: None,
+        }
+    }
+
+    /// the writer where the escape sequences are written into
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// the escape sequence which sets the terminal clipboard to this text
+    pub fn escape_sequence(text: &str) -> String {
+        format!("\x1b]52;c;{}\x07", base64_encode(text.as_bytes()))
+    }
+}
+
+impl<W: Write> Clipboard for Osc52Clipboard<W> {
+    fn write(&mut self, content: RegisterContent) {
+        let sequence = Self::escape_sequence(&content.text);
+        if let Err(e) = self
+            .writer
+            .write_all(sequence.as_bytes())
+            .and_then(|_| self.writer.flush())
+        {
+            log::error!("unable to write to the terminal clipboard: {e}");
+        }
+        self.content = Some(content);
+    }
+
+    fn read(&self) -> Option<RegisterContent> {
+        self.content.clone()
+    }
+}
+
+/// encode the bytes into standard base64 with padding
+fn base64_encode(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
@@ -0,0 +1,348 @@
+use crate::{Binding, Command, TextTransform};
+
+/// An editor command or a host action which can be discovered and run by its id,
+/// ie: from a command palette
+#[derive(Clone, Debug, PartialEq)]
+pub struct CommandEntry {
+    /// the unique id of the command, ie: `editor.undo`
+    pub id: String,
+    /// the human readable title, ie: `Undo`
+    pub title: String,
+    /// the group the command is listed in, ie: `Edit`
+    pub category: String,
+    /// what is run when the command is chosen
+    pub binding: Binding,
+}
+
+impl CommandEntry {
+    pub fn new(
+        id: impl Into<String>,
+        title: impl Into<String>,
+        category: impl Into<String>,
+        binding: impl Into<Binding>,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            title: title.into(),
+            category: category.into(),
+            binding: binding.into(),
+        }
+    }
+
+    /// a host action, the name of the action is passed to the host when the command is run
+    pub fn action(
+        id: impl Into<String>,
+        title: impl Into<String>,
+        category: impl Into<String>,
+        action: impl Into<String>,
+    ) -> Self {
+        Self::new(id, title, category, Binding::Action(action.into()))
+    }
+
+    /// the category and the title, ie: `Edit: Undo`
+    pub fn label(&self) -> String {
+        if self.category.is_empty() {
+            self.title.clone()
+        } else {
+            format!("{}: {}", self.category, self.title)
+        }
+    }
+}
+
+/// A match of the fuzzy search
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FuzzyMatch {
+    /// higher is better
+    pub score: i64,
+    /// the indices of the matched characters in the text, used to highlight them
+    pub indices: Vec<usize>,
+}
+
+/// The commands of the editor and the host application, listed in the order they are registered
+#[derive(Clone, Debug, Default)]
+pub struct CommandRegistry {
+    entries: Vec<CommandEntry>,
+}
+
+impl CommandRegistry {
+    /// an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// the commands of the editor
+    pub fn standard() -> Self {
+        let mut registry = Self::new();
+        let commands = [
+            ("editor.undo", "Undo", "Edit", Command::Undo),
+            ("editor.redo", "Redo", "Edit", Command::Redo),
+            ("editor.cut", "Cut", "Edit", Command::Cut),
+            ("editor.copy", "Copy", "Edit", Command::Copy),
+            ("editor.paste", "Paste", "Edit", Command::Paste),
+            (
+                "editor.delete",
+                "Delete Selection",
+                "Edit",
+                Command::DeleteSelection,
+            ),
+            ("editor.yank", "Yank", "Edit", Command::Yank),
+            ("editor.yank_pop", "Yank Pop", "Edit", Command::YankPop),
+            (
+                "editor.toggle_comment",
+                "Toggle Comment",
+                "Edit",
+                Command::ToggleComment,
+            ),
+            ("editor.indent", "Indent", "Edit", Command::IndentForward),
+            ("editor.outdent", "Outdent", "Edit", Command::IndentBackward),
+            (
+                "editor.select_all",
+                "Select All",
+                "Selection",
+                Command::SelectAll,
+            ),
+            (
+                "editor.clear_selection",
+                "Clear Selection",
+                "Selection",
+                Command::ClearSelection,
+            ),
+            ("editor.fold", "Fold", "Folding", Command::Fold),
+            ("editor.unfold", "Unfold", "Folding", Command::Unfold),
+            (
+                "editor.toggle_fold",
+                "Toggle Fold",
+                "Folding",
+                Command::ToggleFold,
+            ),
+            ("editor.fold_all", "Fold All", "Folding", Command::FoldAll),
+            (
+                "editor.unfold_all",
+                "Unfold All",
+                "Folding",
+                Command::UnfoldAll,
+            ),
+            (
+                "editor.toggle_bookmark",
+                "Toggle Bookmark",
+                "Bookmarks",
+                Command::ToggleBookmark,
+            ),
+            (
+                "editor.next_bookmark",
+                "Next Bookmark",
+                "Bookmarks",
+                Command::NextBookmark,
+            ),
+            (
+                "editor.prev_bookmark",
+                "Previous Bookmark",
+                "Bookmarks",
+                Command::PrevBookmark,
+            ),
+            (
+                "editor.clear_marks",
+                "Clear Marks",
+                "Bookmarks",
+                Command::ClearMarks,
+            ),
+            (
+                "editor.next_diagnostic",
+                "Next Problem",
+                "Diagnostics",
+                Command::NextDiagnostic,
+            ),
+            (
+                "editor.prev_diagnostic",
+                "Previous Problem",
+                "Diagnostics",
+                Command::PrevDiagnostic,
+            ),
+            (
+                "editor.apply_fix",
+                "Apply Fix",
+                "Diagnostics",
+                Command::ApplyFix,
+            ),
+            (
+                "editor.format",
+                "Format Document",
+                "Formatting",
+                Command::Format,
+            ),
+            (
+                "editor.format_selection",
+                "Format Selection",
+                "Formatting",
+                Command::FormatSelection,
+            ),
+            (
+                "editor.revert_hunk",
+                "Revert Change",
+                "Changes",
+                Command::RevertHunk,
+            ),
+            (
+                "editor.start_recording",
+                "Start Recording Macro",
+                "Macros",
+                Command::StartRecording(None),
+            ),
+            (
+                "editor.stop_recording",
+                "Stop Recording Macro",
+                "Macros",
+                Command::StopRecording,
+            ),
+            (
+                "editor.play_macro",
+                "Play Last Macro",
+                "Macros",
+                Command::PlayMacro(None, 1),
+            ),
+        ];
+        for (id, title, category, command) in commands {
+            registry.register(CommandEntry::new(id, title, category, command));
+        }
+        let transforms = [
+            ("uppercase", "Uppercase", TextTransform::Uppercase),
+            ("lowercase", "Lowercase", TextTransform::Lowercase),
+            ("title_case", "Title Case", TextTransform::TitleCase),
+            ("snake_case", "Snake Case", TextTransform::SnakeCase),
+            ("camel_case", "Camel Case", TextTransform::CamelCase),
+            ("kebab_case", "Kebab Case", TextTransform::KebabCase),
+            (
+                "screaming_snake_case",
+                "Screaming Snake Case",
+                TextTransform::ScreamingSnakeCase,
+            ),
+            (
+                "trim_trailing_whitespace",
+                "Trim Trailing Whitespace",
+                TextTransform::TrimTrailingWhitespace,
+            ),
+            (
+                "tabs_to_spaces",
+                "Tabs to Spaces",
+                TextTransform::TabsToSpaces,
+            ),
+            (
+                "spaces_to_tabs",
+                "Spaces to Tabs",
+                TextTransform::SpacesToTabs,
+            ),
+            (
+                "reverse_lines",
+                "Reverse Lines",
+                TextTransform::ReverseLines,
+            ),
+        ];
+        for (id, title, transform) in transforms {
+            registry.register(CommandEntry::new(
+                format!("transform.{id}"),
+                title,
+                "Transform",
+                Command::Transform(transform),
+            ));
+        }
+        registry
+    }
+
+    /// add the command, replacing the command with the same id
+    pub fn register(&mut self, entry: CommandEntry) {
+        match self.entries.iter_mut().find(|e| e.id == entry.id) {
+            Some(existing) => *existing = entry,
+            None => self.entries.push(entry),
+        }
+    }
+
+    pub fn unregister(&mut self, id: &str) -> Option<CommandEntry> {
+        let index = self.entries.iter().position(|entry| entry.id == id)?;
+        Some(self.entries.remove(index))
+    }
+
+    pub fn get(&self, id: &str) -> Option<&CommandEntry> {
+        self.entries.iter().find(|entry| entry.id == id)
+    }
+
+    /// the commands in the order they are registered
+    pub fn entries(&self) -> &[CommandEntry] {
+        &self.entries
+    }
+
+    /// the commands whose label fuzzy matches the query, the best match first.
+    /// All the commands are returned when the query is empty
+    pub fn search(&self, query: &str) -> Vec<(&CommandEntry, FuzzyMatch)> {
+        let mut found: Vec<(&CommandEntry, FuzzyMatch)> = self
+            .entries
+            .iter()
+            .filter_map(|entry| Some((entry, fuzzy_match(query, &entry.label())?)))
+            .collect();
+        // the stable sort keeps the registration order of the same scores
+        found.sort_by_key(|(_, m)| -m.score);
+        found
+    }
+}
+
+/// match the characters of the pattern in the same order in the text, ignoring the case
+/// and the whitespaces of the pattern.
+/// The consecutive matches and the matches at the start of the words score higher,
+/// the best scoring placement of the characters is returned
+pub fn fuzzy_match(pattern: &str, text: &str) -> Option<FuzzyMatch> {
+    let pattern: Vec<char> = pattern
+        .chars()
+        .filter(|ch| !ch.is_whitespace())
+        .map(lowercase)
+        .collect();
+    let text: Vec<char> = text.chars().collect();
+    if pattern.is_empty() {
+        return Some(FuzzyMatch {
+            score: 0,
+            indices: vec![],
+        });
+    }
+    let lower: Vec<char> = text.iter().copied().map(lowercase).collect();
+    // best[j][i] is the best score and the previous index of
+    // the pattern character `j` matched at the text index `i`
+    let mut best: Vec<Vec<Option<(i64, usize)>>> = vec![vec![None; text.len()]; pattern.len()];
+    for (j, p) in pattern.iter().enumerate() {
+        for i in (0..text.len()).filter(|&i| lower[i] == *p) {
+            let bonus = if is_word_start(&text, i) { 9 } else { 1 };
+            best[j][i] = if j == 0 {
+                // the characters skipped before the first match
+                Some((bonus - i.min(3) as i64, i))
+            } else {
+                (0..i)
+                    .filter_map(|k| {
+                        let (score, _) = best[j - 1][k]?;
+                        let gap = i - k - 1;
+                        let joined = if gap == 0 { 5 } else { -(gap.min(3) as i64) };
+                        Some((score + bonus + joined, k))
+                    })
+                    .max_by_key(|(score, k)| (*score, std::cmp::Reverse(*k)))
+            };
+        }
+    }
+    let last = pattern.len() - 1;
+    let (end, score) = (0..text.len())
+        .filter_map(|i| Some((i, best[last][i]?.0)))
+        .max_by_key(|(i, score)| (*score, std::cmp::Reverse(*i)))?;
+    let mut indices = vec![end];
+    for j in (1..=last).rev() {
+        let (_, prev) = best[j][indices[0]].expect("must have a previous match");
+        indices.insert(0, prev);
+    }
+    Some(FuzzyMatch { score, indices })
+}
+
+fn lowercase(ch: char) -> char {
+    ch.to_lowercase().next().unwrap_or(ch)
+}
+
+/// the character is at the start of a word, ie: `S` in `Select All` or in `selectAll`
+fn is_word_start(text: &[char], i: usize) -> bool {
+    match i.checked_sub(1).map(|prev| text[prev]) {
+        None => true,
+        Some(prev) => !prev.is_alphanumeric() || (prev.is_lowercase() && text[i].is_uppercase()),
+    }
+}
@@ -0,0 +1,207 @@
+use crate::{fuzzy_match, Edit, TextBuffer};
+use nalgebra::Point2;
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+
+/// What a completion item is, ie: shown as an icon in the completion list
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CompletionKind {
+    /// a word found in the content
+    Text,
+    Keyword,
+    Function,
+    Method,
+    Variable,
+    Field,
+    Type,
+    Module,
+    Constant,
+    Snippet,
+}
+
+impl CompletionKind {
+    /// the lowercase name, ie: `function`, also used as the class of the item
+    pub fn name(&self) -> &'static str {
+        match self {
+            CompletionKind::Text => "text",
+            CompletionKind::Keyword => "keyword",
+            CompletionKind::Function => "function",
+            CompletionKind::Method => "method",
+            CompletionKind::Variable => "variable",
+            CompletionKind::Field => "field",
+            CompletionKind::Type => "type",
+            CompletionKind::Module => "module",
+            CompletionKind::Constant => "constant",
+            CompletionKind::Snippet => "snippet",
+        }
+    }
+}
+
+/// A suggestion to complete the text at the cursor
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CompletionItem {
+    /// the text shown in the completion list and matched with the typed text
+    pub label: String,
+    pub kind: CompletionKind,
+    /// replaces the word before the cursor when there is no edit
+    pub insert_text: String,
+    /// replaces a specific range of the content instead of the word before the cursor
+    pub edit: Option<Edit>,
+    /// more information shown besides the label, ie: the signature of a function
+    pub detail: Option<String>,
+    /// the inserted text is a snippet with tab stops, ie: `fn ${1:name}() {\n\t$0\n}`
+    pub is_snippet: bool,
+}
+
+impl CompletionItem {
+    /// an item which inserts its label
+    pub fn new(label: impl Into<String>, kind: CompletionKind) -> Self {
+        let label = label.into();
+        Self {
+            insert_text: label.clone(),
+            label,
+            kind,
+            edit: None,
+            detail: None,
+            is_snippet: false,
+        }
+    }
+
+    /// an item which expands the snippet
+    pub fn snippet(label: impl Into<String>, body: impl Into<String>) -> Self {
+        Self {
+            insert_text: body.into(),
+            is_snippet: true,
+            ..Self::new(label, CompletionKind::Snippet)
+        }
+    }
+
+    pub fn with_insert_text(mut self, insert_text: impl Into<String>) -> Self {
+        self.insert_text = insert_text.into();
+        self
+    }
+
+    pub fn with_edit(mut self, edit: Edit) -> Self {
+        self.edit = Some(edit);
+        self
+    }
+
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+}
+
+/// Suggests the completions at the cursor, ie: the words of the content or the symbols
+/// known to a language server
+pub trait CompletionProvider {
+    /// the completions at the cursor, `trigger` is the typed character which triggers
+    /// the completion, None when the completion is requested explicitly.
+    /// The items are filtered with the word before the cursor afterwards
+    fn complete(
+        &self,
+        buffer: &TextBuffer,
+        cursor: Point2<usize>,
+        trigger: Option<char>,
+    ) -> Vec<CompletionItem>;
+
+    /// the characters which trigger the completion when typed, ie: `.`
+    fn trigger_characters(&self) -> &[char] {
+        &[]
+    }
+
+    /// the content of the editor has changed, ie: to drop the results kept so far
+    fn content_changed(&self) {}
+}
+
+/// Suggests the words already in the content.
+/// The words are collected once and kept until the content changes
+#[derive(Clone, Debug, Default)]
+pub struct BufferWords {
+    words: RefCell<Option<Vec<String>>>,
+}
+
+impl BufferWords {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// the distinct words of the content in the order they first appear
+    fn collect_words(buffer: &TextBuffer) -> Vec<String> {
+        let mut seen = BTreeSet::new();
+        buffer
+            .lines()
+            .iter()
+            .flat_map(|line| line.split(|ch: char| !is_word_char(ch)))
+            .filter(|word| word.chars().count() > 1)
+            .map(String::from)
+            .filter(|word| seen.insert(word.clone()))
+            .collect()
+    }
+}
+
+impl CompletionProvider for BufferWords {
+    fn complete(
+        &self,
+        buffer: &TextBuffer,
+        cursor: Point2<usize>,
+        _trigger: Option<char>,
+    ) -> Vec<CompletionItem> {
+        let (_, typed) = word_before(buffer, cursor);
+        self.words
+            .borrow_mut()
+            .get_or_insert_with(|| Self::collect_words(buffer))
+            .iter()
+            .filter(|word| **word != typed)
+            .map(|word| CompletionItem::new(word, CompletionKind::Text))
+            .collect()
+    }
+
+    fn content_changed(&self) {
+        self.words.take();
+    }
+}
+
+/// the characters of the words which are completed
+pub fn is_word_char(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '_'
+}
+
+/// the start of the word before the cursor and the part of it typed so far
+pub fn word_before(buffer: &TextBuffer, cursor: Point2<usize>) -> (Point2<usize>, String) {
+    let line = buffer
+        .chars()
+        .get(cursor.y)
+        .map(Vec::as_slice)
+        .unwrap_or(&[]);
+    let mut width = 0;
+    let before: Vec<_> = line
+        .iter()
+        .take_while(|ch| {
+            width += ch.width;
+            width <= cursor.x
+        })
+        .collect();
+    let typed_len = before
+        .iter()
+        .rev()
+        .take_while(|ch| is_word_char(ch.ch))
+        .count();
+    let typed = &before[before.len() - typed_len..];
+    let typed_width: usize = typed.iter().map(|ch| ch.width).sum();
+    let before_width: usize = before.iter().map(|ch| ch.width).sum();
+    let start = Point2::new(before_width - typed_width, cursor.y);
+    (start, typed.iter().map(|ch| ch.ch).collect())
+}
+
+/// the items whose label matches the typed text, the best matches first
+pub fn filter_completions(items: &[CompletionItem], typed: &str) -> Vec<CompletionItem> {
+    let mut matches: Vec<(i64, &CompletionItem)> = items
+        .iter()
+        .filter_map(|item| Some((fuzzy_match(typed, &item.label)?.score, item)))
+        .collect();
+    matches.sort_by_key(|(score, _)| -score);
+    matches.into_iter().map(|(_, item)| item.clone()).collect()
+}
@@ -0,0 +1,189 @@
+use crate::text_edit::Action;
+use nalgebra::Point2;
+use std::collections::BTreeMap;
+
+/// Identifies a decoration added to the editor, used to remove it
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DecorationId(usize);
+
+/// How a decoration is displayed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DecorationKind {
+    /// an icon in the gutter of the line, ie: `●` for a breakpoint
+    Gutter(String),
+    /// the background of the whole line
+    Line,
+    /// the text in the range, ie: a squiggle under an error or a highlight
+    Range,
+}
+
+/// A decoration of a line or a range of text, drawn with its class.
+/// The locations are in display columns and follow the text as it is edited
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Decoration {
+    pub kind: DecorationKind,
+    pub start: Point2<usize>,
+    /// the end of the range, excluded.
+    /// The gutter and line decorations start and end on their line
+    pub end: Point2<usize>,
+    /// the class of the decoration, ie: `breakpoint`, `error`, `highlight`
+    pub class: String,
+    /// the text shown when hovering the decoration, ie: an error message
+    pub title: Option<String>,
+}
+
+impl Decoration {
+    /// an icon in the gutter of the line
+    pub fn gutter(line: usize, icon: impl Into<String>, class: impl Into<String>) -> Self {
+        Self::new(DecorationKind::Gutter(icon.into()), line, line, class)
+    }
+
+    /// the background of the whole line
+    pub fn line(line: usize, class: impl Into<String>) -> Self {
+        Self::new(DecorationKind::Line, line, line, class)
+    }
+
+    /// the text from `start` until `end`
+    pub fn range(start: Point2<usize>, end: Point2<usize>, class: impl Into<String>) -> Self {
+        let (start, end) = if (end.y, end.x) < (start.y, start.x) {
+            (end, start)
+        } else {
+            (start, end)
+        };
+        Self {
+            kind: DecorationKind::Range,
+            start,
+            end,
+            class: class.into(),
+            title: None,
+        }
+    }
+
+    fn new(kind: DecorationKind, start: usize, end: usize, class: impl Into<String>) -> Self {
+        Self {
+            kind,
+            start: Point2::new(0, start),
+            end: Point2::new(0, end),
+            class: class.into(),
+            title: None,
+        }
+    }
+
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// the line of a gutter or line decoration, the first line of a range decoration
+    pub fn line_index(&self) -> usize {
+        self.start.y
+    }
+
+    /// the columns of the range decoration in this line,
+    /// the end is None when the range continues to the next line
+    pub fn columns_in_line(&self, line: usize) -> Option<(usize, Option<usize>)> {
+        if self.kind != DecorationKind::Range || line < self.start.y || line > self.end.y {
+            return None;
+        }
+        let start = if line == self.start.y {
+            self.start.x
+        } else {
+            0
+        };
+        let end = (line == self.end.y).then_some(self.end.x);
+        Some((start, end))
+    }
+}
+
+/// The decorations of the lines and ranges of text, added by the host application
+#[derive(Clone, Debug, Default)]
+pub struct Decorations {
+    next_id: usize,
+    decorations: BTreeMap<DecorationId, Decoration>,
+}
+
+impl Decorations {
+    pub fn add(&mut self, decoration: Decoration) -> DecorationId {
+        let id = DecorationId(self.next_id);
+        self.next_id += 1;
+        self.decorations.insert(id, decoration);
+        id
+    }
+
+    pub fn remove(&mut self, id: DecorationId) -> Option<Decoration> {
+        self.decorations.remove(&id)
+    }
+
+    pub fn get(&self, id: DecorationId) -> Option<&Decoration> {
+        self.decorations.get(&id)
+    }
+
+    /// the decorations in the order they are added
+    pub fn iter(&self) -> impl Iterator<Item = (DecorationId, &Decoration)> {
+        self.decorations
+            .iter()
+            .map(|(id, decoration)| (*id, decoration))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.decorations.is_empty()
+    }
+
+    /// the gutter icons of this line and their decoration
+    pub fn gutter_icons(&self, line: usize) -> impl Iterator<Item = (&str, &Decoration)> {
+        self.decorations
+            .values()
+            .filter_map(move |decoration| match &decoration.kind {
+                DecorationKind::Gutter(icon) if decoration.line_index() == line => {
+                    Some((icon.as_str(), decoration))
+                }
+                _ => None,
+            })
+    }
+
+    pub fn has_gutter_icons(&self) -> bool {
+        self.decorations
+            .values()
+            .any(|decoration| matches!(decoration.kind, DecorationKind::Gutter(_)))
+    }
+
+    /// the line decorations of this line
+    pub fn line_decorations(&self, line: usize) -> impl Iterator<Item = &Decoration> {
+        self.decorations.values().filter(move |decoration| {
+            decoration.kind == DecorationKind::Line && decoration.line_index() == line
+        })
+    }
+
+    /// the range decorations which cover a part of this line
+    pub fn range_decorations(&self, line: usize) -> impl Iterator<Item = &Decoration> {
+        self.decorations
+            .values()
+            .filter(move |decoration| decoration.columns_in_line(line).is_some())
+    }
+
+    /// remove the decorations with this class, ie: to replace all the errors at once
+    pub fn remove_class(&mut self, class: &str) {
+        self.decorations
+            .retain(|_, decoration| decoration.class != class);
+    }
+
+    pub fn clear(&mut self) {
+        self.decorations.clear();
+    }
+
+    /// move the decorations along with the text changed by this action
+    pub(crate) fn apply_action(&mut self, action: &Action) {
+        for decoration in self.decorations.values_mut() {
+            if decoration.kind == DecorationKind::Range {
+                decoration.start = action.shift_point(decoration.start);
+                decoration.end = action.shift_point(decoration.end);
+            } else {
+                // anchored at the start of the line, so it moves down only when
+                // the line is broken at its start
+                let line = action.shift_point(Point2::new(0, decoration.start.y)).y;
+                decoration.start = Point2::new(0, line);
+                decoration.end = Point2::new(0, line);
+            }
+        }
+    }
+}
@@ -0,0 +1,270 @@
+use crate::text_edit::Action;
+use crate::{Decoration, DecorationId, Decorations};
+use nalgebra::Point2;
+use std::fmt;
+
+/// How severe a diagnostic is, the most severe first
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+    Hint,
+}
+
+impl Severity {
+    /// the lowercase name, ie: `error`, also used as the class of its decorations
+    pub fn name(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Info => "info",
+            Severity::Hint => "hint",
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// A replacement of the text which fixes the diagnostic, ie: a suggestion of a compiler
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Fix {
+    /// describes the fix, ie: `remove the unused import`
+    pub title: String,
+    pub start: Point2<usize>,
+    /// the end of the replaced text, excluded
+    pub end: Point2<usize>,
+    pub text: String,
+}
+
+impl Fix {
+    pub fn new(
+        title: impl Into<String>,
+        start: Point2<usize>,
+        end: Point2<usize>,
+        text: impl Into<String>,
+    ) -> Self {
+        Self {
+            title: title.into(),
+            start,
+            end,
+            text: text.into(),
+        }
+    }
+}
+
+/// A problem reported by a linter or a compiler on a range of the content.
+/// The locations are in display columns and follow the text as it is edited
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Diagnostic {
+    pub start: Point2<usize>,
+    /// the end of the range, excluded
+    pub end: Point2<usize>,
+    pub severity: Severity,
+    pub message: String,
+    /// the tool which reported it, ie: `rustc` or `clippy`
+    pub source: Option<String>,
+    pub fix: Option<Fix>,
+}
+
+impl Diagnostic {
+    pub fn new(
+        start: Point2<usize>,
+        end: Point2<usize>,
+        severity: Severity,
+        message: impl Into<String>,
+    ) -> Self {
+        Self {
+            start,
+            end,
+            severity,
+            message: message.into(),
+            source: None,
+            fix: None,
+        }
+    }
+
+    pub fn error(start: Point2<usize>, end: Point2<usize>, message: impl Into<String>) -> Self {
+        Self::new(start, end, Severity::Error, message)
+    }
+
+    pub fn warning(start: Point2<usize>, end: Point2<usize>, message: impl Into<String>) -> Self {
+        Self::new(start, end, Severity::Warning, message)
+    }
+
+    pub fn with_source(mut self, source: impl Into<String>) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+
+    pub fn with_fix(mut self, fix: Fix) -> Self {
+        self.fix = Some(fix);
+        self
+    }
+
+    /// the location is within the range, an empty range contains its start
+    pub fn contains(&self, loc: Point2<usize>) -> bool {
+        let loc = (loc.y, loc.x);
+        let start = (self.start.y, self.start.x);
+        let end = (self.end.y, self.end.x);
+        loc == start || (start <= loc && loc < end)
+    }
+
+    /// the message prefixed with its severity and source, ie: `error[rustc]: unused variable`
+    pub fn label(&self) -> String {
+        match &self.source {
+            Some(source) => format!("{}[{}]: {}", self.severity, source, self.message),
+            None => format!("{}: {}", self.severity, self.message),
+        }
+    }
+}
+
+/// The diagnostics of the content sorted by location.
+/// Their ranges are kept as range decorations with the class of their severity,
+/// so they follow the text as it is edited and are drawn like the other decorations
+#[derive(Clone, Debug, Default)]
+pub struct Diagnostics {
+    /// the ranges of the diagnostics
+    ranges: Decorations,
+    /// the ranges of the fixes, which are not drawn
+    fix_ranges: Decorations,
+    entries: Vec<Entry>,
+}
+
+/// A diagnostic without its range and the range of its fix, which are in the decorations
+#[derive(Clone, Debug)]
+struct Entry {
+    range: DecorationId,
+    severity: Severity,
+    message: String,
+    source: Option<String>,
+    fix: Option<(DecorationId, String, String)>,
+}
+
+impl Diagnostics {
+    /// replace all the diagnostics, ie: with the new results of a linter
+    pub fn set(&mut self, diagnostics: impl IntoIterator<Item = Diagnostic>) {
+        self.clear();
+        let mut diagnostics: Vec<Diagnostic> = diagnostics.into_iter().collect();
+        diagnostics.sort_by_key(|diagnostic| (diagnostic.start.y, diagnostic.start.x));
+        for diagnostic in diagnostics {
+            let range = self.ranges.add(Decoration::range(
+                diagnostic.start,
+                diagnostic.end,
+                diagnostic.severity.name(),
+            ));
+            let fix = diagnostic.fix.map(|fix| {
+                let fix_range = self
+                    .fix_ranges
+                    .add(Decoration::range(fix.start, fix.end, "fix"));
+                (fix_range, fix.title, fix.text)
+            });
+            self.entries.push(Entry {
+                range,
+                severity: diagnostic.severity,
+                message: diagnostic.message,
+                source: diagnostic.source,
+                fix,
+            });
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.ranges.clear();
+        self.fix_ranges.clear();
+        self.entries.clear();
+    }
+
+    /// the range decorations of the diagnostics, with the class of their severity
+    pub fn decorations(&self) -> &Decorations {
+        &self.ranges
+    }
+
+    /// the diagnostics sorted by location
+    pub fn all(&self) -> Vec<Diagnostic> {
+        self.iter().collect()
+    }
+
+    fn iter(&self) -> impl DoubleEndedIterator<Item = Diagnostic> + '_ {
+        self.entries.iter().map(|entry| self.diagnostic(entry))
+    }
+
+    fn diagnostic(&self, entry: &Entry) -> Diagnostic {
+        let range = self.ranges.get(entry.range).expect("must have a range");
+        let fix = entry.fix.as_ref().map(|(fix_range, title, text)| {
+            let fix_range = self.fix_ranges.get(*fix_range).expect("must have a range");
+            Fix::new(title, fix_range.start, fix_range.end, text)
+        });
+        Diagnostic {
+            start: range.start,
+            end: range.end,
+            severity: entry.severity,
+            message: entry.message.clone(),
+            source: entry.source.clone(),
+            fix,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn count(&self, severity: Severity) -> usize {
+        self.entries
+            .iter()
+            .filter(|entry| entry.severity == severity)
+            .count()
+    }
+
+    /// the diagnostics whose range contains this location
+    pub fn at(&self, loc: Point2<usize>) -> impl Iterator<Item = Diagnostic> + '_ {
+        self.iter()
+            .filter(move |diagnostic| diagnostic.contains(loc))
+    }
+
+    /// the diagnostics which cover a part of this line
+    pub fn on_line(&self, line: usize) -> impl Iterator<Item = Diagnostic> + '_ {
+        self.iter()
+            .filter(move |diagnostic| diagnostic.start.y <= line && line <= diagnostic.end.y)
+    }
+
+    /// the most severe diagnostic starting on this line, ie: shown in the gutter
+    pub fn severity_on_line(&self, line: usize) -> Option<Severity> {
+        self.entries
+            .iter()
+            .filter(|entry| self.ranges.get(entry.range).map(|range| range.start.y) == Some(line))
+            .map(|entry| entry.severity)
+            .min()
+    }
+
+    /// the first diagnostic after this location, wrapping around to the first diagnostic
+    pub fn next_diagnostic(&self, loc: Point2<usize>) -> Option<Diagnostic> {
+        let mut diagnostics = self.iter().peekable();
+        let first = diagnostics.peek().cloned();
+        diagnostics
+            .find(|diagnostic| (diagnostic.start.y, diagnostic.start.x) > (loc.y, loc.x))
+            .or(first)
+    }
+
+    /// the last diagnostic before this location, wrapping around to the last diagnostic
+    pub fn prev_diagnostic(&self, loc: Point2<usize>) -> Option<Diagnostic> {
+        let mut diagnostics = self.iter().rev().peekable();
+        let last = diagnostics.peek().cloned();
+        diagnostics
+            .find(|diagnostic| (diagnostic.start.y, diagnostic.start.x) < (loc.y, loc.x))
+            .or(last)
+    }
+
+    /// move the diagnostics and their fixes along with the text changed by this action
+    pub(crate) fn apply_action(&mut self, action: &Action) {
+        self.ranges.apply_action(action);
+        self.fix_ranges.apply_action(action);
+    }
+}
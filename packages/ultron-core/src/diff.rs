@@ -0,0 +1,543 @@
+use crate::{BaseEditor, Command, Edit, TextBuffer};
+use nalgebra::Point2;
+use std::collections::BTreeMap;
+use std::ops::Range;
+use unicode_width::UnicodeWidthChar;
+
+/// the most pairs of characters compared in a changed line,
+/// the longer lines are highlighted as a whole
+const MAX_LINE_CELLS: usize = 250_000;
+
+/// the most pairs of changed lines compared in a diff,
+/// the changed lines of larger contents are in one hunk
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// the start and end(exclusive) columns of the changed characters of a line
+type Columns = Vec<(usize, usize)>;
+
+/// How the lines of a hunk or a row are changed
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DiffKind {
+    Unchanged,
+    /// the lines are only in the new content
+    Added,
+    /// the lines are only in the old content
+    Removed,
+    /// the lines of the old content are replaced with the lines of the new content
+    Changed,
+}
+
+impl DiffKind {
+    /// the lowercase name, ie: `added`, also used as the class of the row
+    pub fn name(&self) -> &'static str {
+        match self {
+            DiffKind::Unchanged => "unchanged",
+            DiffKind::Added => "added",
+            DiffKind::Removed => "removed",
+            DiffKind::Changed => "changed",
+        }
+    }
+}
+
+/// The consecutive lines of the old content which are replaced with lines of the new content
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DiffHunk {
+    pub old_lines: Range<usize>,
+    pub new_lines: Range<usize>,
+}
+
+impl DiffHunk {
+    pub fn kind(&self) -> DiffKind {
+        match (self.old_lines.is_empty(), self.new_lines.is_empty()) {
+            (true, true) => DiffKind::Unchanged,
+            (true, false) => DiffKind::Added,
+            (false, true) => DiffKind::Removed,
+            (false, false) => DiffKind::Changed,
+        }
+    }
+}
+
+/// A row of the diff view, the lines are side by side or only one of them in the inline view
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DiffRow {
+    pub old_line: Option<usize>,
+    pub new_line: Option<usize>,
+    pub kind: DiffKind,
+    /// the index of the hunk the row is in
+    pub hunk: Option<usize>,
+}
+
+/// The line and character differences of an old and a new content
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Diff {
+    hunks: Vec<DiffHunk>,
+    old_len: usize,
+    new_len: usize,
+    /// the changed characters in the changed lines
+    old_highlights: BTreeMap<usize, Columns>,
+    new_highlights: BTreeMap<usize, Columns>,
+}
+
+impl Diff {
+    pub fn new(old: &TextBuffer, new: &TextBuffer) -> Self {
+        Self::from_lines(&old.lines(), &new.lines())
+    }
+
+    pub fn from_lines(old: &[String], new: &[String]) -> Self {
+        let mut diff = Self {
+            hunks: line_hunks(old, new)
+                .into_iter()
+                .map(|(old_lines, new_lines)| DiffHunk {
+                    old_lines,
+                    new_lines,
+                })
+                .collect(),
+            old_len: old.len(),
+            new_len: new.len(),
+            ..Default::default()
+        };
+        // the lines of a changed hunk are compared in pairs
+        for hunk in diff.hunks.iter() {
+            for (old_line, new_line) in hunk.old_lines.clone().zip(hunk.new_lines.clone()) {
+                let (old_columns, new_columns) = char_changes(&old[old_line], &new[new_line]);
+                diff.old_highlights.insert(old_line, old_columns);
+                diff.new_highlights.insert(new_line, new_columns);
+            }
+        }
+        diff
+    }
+
+    pub fn hunks(&self) -> &[DiffHunk] {
+        &self.hunks
+    }
+
+    /// the contents are the same
+    pub fn is_empty(&self) -> bool {
+        self.hunks.is_empty()
+    }
+
+    /// the columns of the changed characters in this line of the old content
+    pub fn old_highlights(&self, line: usize) -> &[(usize, usize)] {
+        self.old_highlights
+            .get(&line)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// the columns of the changed characters in this line of the new content
+    pub fn new_highlights(&self, line: usize) -> &[(usize, usize)] {
+        self.new_highlights
+            .get(&line)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// the rows with the old and new lines side by side,
+    /// the changed lines are paired and the added or removed lines have a blank side
+    pub fn side_by_side_rows(&self) -> Vec<DiffRow> {
+        self.rows(|hunk_index, hunk, rows| {
+            let (old_count, new_count) = (hunk.old_lines.len(), hunk.new_lines.len());
+            for i in 0..old_count.max(new_count) {
+                let old_line = (i < old_count).then_some(hunk.old_lines.start + i);
+                let new_line = (i < new_count).then_some(hunk.new_lines.start + i);
+                let kind = match (old_line, new_line) {
+                    (Some(_), Some(_)) => DiffKind::Changed,
+                    (Some(_), None) => DiffKind::Removed,
+                    _ => DiffKind::Added,
+                };
+                rows.push(DiffRow {
+                    old_line,
+                    new_line,
+                    kind,
+                    hunk: Some(hunk_index),
+                });
+            }
+        })
+    }
+
+    /// the rows of one column, the removed lines of a hunk are followed by its added lines
+    pub fn inline_rows(&self) -> Vec<DiffRow> {
+        self.rows(|hunk_index, hunk, rows| {
+            let removed = hunk.old_lines.clone().map(|line| DiffRow {
+                old_line: Some(line),
+                new_line: None,
+                kind: DiffKind::Removed,
+                hunk: Some(hunk_index),
+            });
+            let added = hunk.new_lines.clone().map(|line| DiffRow {
+                old_line: None,
+                new_line: Some(line),
+                kind: DiffKind::Added,
+                hunk: Some(hunk_index),
+            });
+            rows.extend(removed.chain(added));
+        })
+    }
+
+    /// the unchanged rows between the rows of the hunks
+    fn rows(&self, hunk_rows: impl Fn(usize, &DiffHunk, &mut Vec<DiffRow>)) -> Vec<DiffRow> {
+        let unchanged_rows = |old_lines: Range<usize>, new_start: usize| {
+            old_lines.enumerate().map(move |(i, old_line)| DiffRow {
+                old_line: Some(old_line),
+                new_line: Some(new_start + i),
+                kind: DiffKind::Unchanged,
+                hunk: None,
+            })
+        };
+        let mut rows = vec![];
+        let (mut old_line, mut new_line) = (0, 0);
+        for (i, hunk) in self.hunks.iter().enumerate() {
+            rows.extend(unchanged_rows(old_line..hunk.old_lines.start, new_line));
+            hunk_rows(i, hunk, &mut rows);
+            (old_line, new_line) = (hunk.old_lines.end, hunk.new_lines.end);
+        }
+        rows.extend(unchanged_rows(old_line..self.old_len, new_line));
+        rows
+    }
+
+    /// the hunk after the current hunk, wrapping around to the first hunk
+    pub fn next_hunk(&self, current: Option<usize>) -> Option<usize> {
+        if self.hunks.is_empty() {
+            return None;
+        }
+        Some(current.map(|i| (i + 1) % self.hunks.len()).unwrap_or(0))
+    }
+
+    /// the hunk before the current hunk, wrapping around to the last hunk
+    pub fn prev_hunk(&self, current: Option<usize>) -> Option<usize> {
+        if self.hunks.is_empty() {
+            return None;
+        }
+        let last = self.hunks.len() - 1;
+        Some(match current {
+            Some(0) | None => last,
+            Some(i) => i - 1,
+        })
+    }
+
+    /// the edit of the old content which replaces the old lines of the hunk with its new lines
+    pub fn accept_edit(&self, index: usize, old: &TextBuffer, new: &TextBuffer) -> Option<Edit> {
+        let hunk = self.hunks.get(index)?;
+        let new_lines = new.lines();
+        lines_edit(
+            &old.lines(),
+            hunk.old_lines.clone(),
+            &new_lines[hunk.new_lines.clone()],
+        )
+    }
+
+    /// the edit of the new content which replaces the new lines of the hunk with its old lines
+    pub fn revert_edit(&self, index: usize, old: &TextBuffer, new: &TextBuffer) -> Option<Edit> {
+        let hunk = self.hunks.get(index)?;
+        let old_lines = old.lines();
+        lines_edit(
+            &new.lines(),
+            hunk.new_lines.clone(),
+            &old_lines[hunk.old_lines.clone()],
+        )
+    }
+}
+
+/// What is done to the hunks of a `DiffEditor`
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DiffCommand {
+    /// move to the next hunk, wrapping around to the first one
+    NextHunk,
+    PrevHunk,
+    /// put the new lines of the hunk into the old content
+    AcceptHunk(usize),
+    /// put the old lines of the hunk back into the new content
+    RevertHunk(usize),
+    AcceptAll,
+    RevertAll,
+}
+
+/// An old and a new version of a content, ie: the saved code and a generated code to review.
+/// The hunks are accepted into the old content or reverted in the new content
+#[derive(Clone, Default)]
+pub struct DiffEditor {
+    old: BaseEditor<()>,
+    new: BaseEditor<()>,
+    diff: Diff,
+    /// the hunk which is navigated to
+    current: Option<usize>,
+}
+
+impl DiffEditor {
+    pub fn new(old: &TextBuffer, new: &TextBuffer) -> Self {
+        let mut diff_editor = Self::default();
+        diff_editor.set_contents(&old.to_string(), &new.to_string());
+        diff_editor
+    }
+
+    /// compare these contents, the undo history of the previous contents is reset
+    pub fn set_contents(&mut self, old: &str, new: &str) {
+        self.old
+            .process_command(Command::SetContent(old.to_string()));
+        self.new
+            .process_command(Command::SetContent(new.to_string()));
+        self.current = None;
+        self.update_diff();
+    }
+
+    pub fn old(&self) -> &BaseEditor<()> {
+        &self.old
+    }
+
+    pub fn new_editor(&self) -> &BaseEditor<()> {
+        &self.new
+    }
+
+    pub fn diff(&self) -> &Diff {
+        &self.diff
+    }
+
+    /// the hunk which is navigated to
+    pub fn current_hunk(&self) -> Option<usize> {
+        self.current
+    }
+
+    pub fn set_current_hunk(&mut self, current: Option<usize>) {
+        self.current = current.filter(|i| *i < self.diff.hunks().len());
+    }
+
+    /// process the command, returns true if either of the contents is changed
+    pub fn process_command(&mut self, command: DiffCommand) -> bool {
+        let is_content_changed = match command {
+            DiffCommand::NextHunk => {
+                self.current = self.diff.next_hunk(self.current);
+                false
+            }
+            DiffCommand::PrevHunk => {
+                self.current = self.diff.prev_hunk(self.current);
+                false
+            }
+            DiffCommand::AcceptHunk(index) => {
+                let edit =
+                    self.diff
+                        .accept_edit(index, self.old.text_buffer(), self.new.text_buffer());
+                self.current = Some(index);
+                edit.is_some_and(|edit| self.old.process_command(Command::ApplyEdits(vec![edit])))
+            }
+            DiffCommand::RevertHunk(index) => {
+                let edit =
+                    self.diff
+                        .revert_edit(index, self.old.text_buffer(), self.new.text_buffer());
+                self.current = Some(index);
+                edit.is_some_and(|edit| self.new.process_command(Command::ApplyEdits(vec![edit])))
+            }
+            DiffCommand::AcceptAll => {
+                let content = self.new.get_content();
+                self.old.process_command(Command::ReplaceContent(content))
+            }
+            DiffCommand::RevertAll => {
+                let content = self.old.get_content();
+                self.new.process_command(Command::ReplaceContent(content))
+            }
+        };
+        if is_content_changed {
+            self.update_diff();
+        }
+        is_content_changed
+    }
+
+    /// compare the contents again, the current hunk is kept at the same place
+    pub fn update_diff(&mut self) {
+        self.diff = Diff::new(self.old.text_buffer(), self.new.text_buffer());
+        let last = self.diff.hunks().len().checked_sub(1);
+        self.current = self.current.and_then(|i| Some(i.min(last?)));
+    }
+}
+
+/// the edit which replaces the lines in the range of the target lines with these lines
+pub(crate) fn lines_edit(target: &[String], range: Range<usize>, lines: &[String]) -> Option<Edit> {
+    let is_last = range.end >= target.len();
+    let (start, old, new) = match (range.is_empty(), lines.is_empty()) {
+        (true, true) => return None,
+        (false, false) => (
+            Point2::new(0, range.start),
+            target[range].join("\n"),
+            lines.join("\n"),
+        ),
+        // the lines are inserted before the line at the start of the range,
+        // or after the last line
+        (true, false) if !is_last => (
+            Point2::new(0, range.start),
+            String::new(),
+            lines.join("\n") + "\n",
+        ),
+        (true, false) => {
+            let last = target.len().saturating_sub(1);
+            let width = advance(Point2::new(0, 0), &chars(target.get(last))).x;
+            (
+                Point2::new(width, last),
+                String::new(),
+                format!("\n{}", lines.join("\n")),
+            )
+        }
+        // the line break after the removed lines is removed with them,
+        // or the line break before them when they are the last lines
+        (false, true) if !is_last => (
+            Point2::new(0, range.start),
+            target[range].join("\n") + "\n",
+            String::new(),
+        ),
+        (false, true) if range.start > 0 => {
+            let before = range.start - 1;
+            let width = advance(Point2::new(0, 0), &chars(target.get(before))).x;
+            (
+                Point2::new(width, before),
+                format!("\n{}", target[range].join("\n")),
+                String::new(),
+            )
+        }
+        (false, true) => (Point2::new(0, 0), target[range].join("\n"), String::new()),
+    };
+    hunk_edit(start, &old, &new)
+}
+
+fn chars(line: Option<&String>) -> Vec<char> {
+    line.map(|line| line.chars().collect()).unwrap_or_default()
+}
+
+/// the columns of the changed characters of the old and the new line
+fn char_changes(old: &str, new: &str) -> (Columns, Columns) {
+    let old_chars: Vec<char> = old.chars().collect();
+    let new_chars: Vec<char> = new.chars().collect();
+    let hunks = if old_chars.len() * new_chars.len() <= MAX_LINE_CELLS {
+        diff_hunks(&old_chars, &new_chars)
+    } else {
+        vec![((0, old_chars.len()), (0, new_chars.len()))]
+    };
+    let to_columns = |chars: &[char], ranges: Vec<(usize, usize)>| {
+        let columns: Vec<usize> = chars
+            .iter()
+            .scan(0, |x, ch| {
+                let column = *x;
+                *x += ch.width().unwrap_or(0);
+                Some(column)
+            })
+            .chain([advance(Point2::new(0, 0), chars).x])
+            .collect();
+        ranges
+            .into_iter()
+            .filter(|(start, end)| start < end)
+            .map(|(start, end)| (columns[start], columns[end]))
+            .collect()
+    };
+    let (old_ranges, new_ranges) = hunks.into_iter().unzip();
+    (
+        to_columns(&old_chars, old_ranges),
+        to_columns(&new_chars, new_ranges),
+    )
+}
+
+/// the ranges of the old lines replaced with the ranges of the new lines,
+/// the unchanged lines at the start and end are skipped before the rest is compared
+pub(crate) fn line_hunks<T: PartialEq>(old: &[T], new: &[T]) -> Vec<(Range<usize>, Range<usize>)> {
+    let prefix = old
+        .iter()
+        .zip(new)
+        .take_while(|(old, new)| old == new)
+        .count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(old, new)| old == new)
+        .count();
+    let old_changed = &old[prefix..old.len() - suffix];
+    let new_changed = &new[prefix..new.len() - suffix];
+    let hunks = if old_changed.len() * new_changed.len() <= MAX_DIFF_CELLS {
+        diff_hunks(old_changed, new_changed)
+    } else {
+        vec![((0, old_changed.len()), (0, new_changed.len()))]
+    };
+    hunks
+        .into_iter()
+        .map(|((old_start, old_end), (new_start, new_end))| {
+            (
+                prefix + old_start..prefix + old_end,
+                prefix + new_start..prefix + new_end,
+            )
+        })
+        .collect()
+}
+
+/// the ranges of the old items replaced with the ranges of the new items,
+/// from the longest common subsequence of the items, ie: the lines or the characters
+fn diff_hunks<T: PartialEq>(old: &[T], new: &[T]) -> Vec<((usize, usize), (usize, usize))> {
+    // lcs[i][j] is the length of the common subsequence of old[i..] and new[j..]
+    let mut lcs = vec![vec![0u32; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+    let mut hunks = vec![];
+    let (mut i, mut j) = (0, 0);
+    let (mut hunk_i, mut hunk_j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            if (hunk_i, hunk_j) != (i, j) {
+                hunks.push(((hunk_i, i), (hunk_j, j)));
+            }
+            i += 1;
+            j += 1;
+            (hunk_i, hunk_j) = (i, j);
+        } else if j < new.len() && (i == old.len() || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            j += 1;
+        } else {
+            i += 1;
+        }
+    }
+    if (hunk_i, hunk_j) != (i, j) {
+        hunks.push(((hunk_i, i), (hunk_j, j)));
+    }
+    hunks
+}
+
+/// the edit which replaces the old text at `start` with the new text,
+/// without the characters they have in common at their start and end
+pub(crate) fn hunk_edit(start: Point2<usize>, old: &str, new: &str) -> Option<Edit> {
+    let old_chars: Vec<char> = old.chars().collect();
+    let new_chars: Vec<char> = new.chars().collect();
+    let prefix = old_chars
+        .iter()
+        .zip(new_chars.iter())
+        .take_while(|(old, new)| old == new)
+        .count();
+    let suffix = old_chars[prefix..]
+        .iter()
+        .rev()
+        .zip(new_chars[prefix..].iter().rev())
+        .take_while(|(old, new)| old == new)
+        .count();
+    let removed = &old_chars[prefix..old_chars.len() - suffix];
+    let inserted = &new_chars[prefix..new_chars.len() - suffix];
+    if removed.is_empty() && inserted.is_empty() {
+        return None;
+    }
+    let start = advance(start, &old_chars[..prefix]);
+    let end = advance(start, removed);
+    Some(Edit::new(start, end, String::from_iter(inserted)))
+}
+
+/// the location after the characters written from `loc`
+fn advance(loc: Point2<usize>, chars: &[char]) -> Point2<usize> {
+    chars.iter().fold(loc, |loc, ch| {
+        if *ch == '\n' {
+            Point2::new(0, loc.y + 1)
+        } else {
+            Point2::new(loc.x + ch.width().unwrap_or(0), loc.y)
+        }
+    })
+}
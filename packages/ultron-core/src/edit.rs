@@ -0,0 +1,52 @@
+use crate::util;
+use nalgebra::Point2;
+
+/// A replacement of the text from `start` until `end` with `text`,
+/// ie: a result of a formatter or a rename from a language server.
+/// The locations are in display columns
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Edit {
+    pub start: Point2<usize>,
+    /// the end of the replaced text, excluded
+    pub end: Point2<usize>,
+    pub text: String,
+}
+
+impl Edit {
+    pub fn new(start: Point2<usize>, end: Point2<usize>, text: impl Into<String>) -> Self {
+        Self {
+            start,
+            end,
+            text: text.into(),
+        }
+    }
+
+    /// insert the text at this location
+    pub fn insert(loc: Point2<usize>, text: impl Into<String>) -> Self {
+        Self::new(loc, loc, text)
+    }
+
+    /// the location right after the inserted text
+    pub fn inserted_end(&self) -> Point2<usize> {
+        util::text_end(self.start, &self.text)
+    }
+
+    /// where the location is moved to once this edit is applied,
+    /// a location inside the replaced text is moved to the start of the edit
+    pub fn shift_point(&self, loc: Point2<usize>) -> Point2<usize> {
+        let at = |p: Point2<usize>| (p.y, p.x);
+        if at(loc) < at(self.start) || (at(loc) == at(self.start) && self.start != self.end) {
+            return loc;
+        }
+        if at(loc) < at(self.end) {
+            return self.start;
+        }
+        let inserted_end = self.inserted_end();
+        if loc.y == self.end.y {
+            Point2::new(inserted_end.x + loc.x - self.end.x, inserted_end.y)
+        } else {
+            Point2::new(loc.x, loc.y + inserted_end.y - self.end.y)
+        }
+    }
+}
@@ -0,0 +1,479 @@
+use crate::text_edit::Action;
+use crate::TextBuffer;
+use nalgebra::Point2;
+use std::collections::BTreeSet;
+
+/// How the fold ranges of the content are determined
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum FoldStrategy {
+    /// lines which are indented deeper than the line before them are folded into that line
+    #[default]
+    Indentation,
+    /// lines in between matching `{}`, `[]` and `()` are folded into the line of the opening
+    /// bracket, the line of the closing bracket stays visible
+    Brackets,
+    /// lines in between the lines containing the start and end marker,
+    /// ie: `// region` and `// endregion`
+    Markers { start: String, end: String },
+}
+
+/// A range of lines which can be folded into its first line
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FoldRange {
+    /// the line which stays visible when the range is folded
+    pub start: usize,
+    /// the last line of the range, inclusive
+    pub end: usize,
+    /// the nesting level of this range, the outer most range is 1
+    pub level: usize,
+}
+
+impl FoldRange {
+    /// returns true if the line is within this range, including the first line
+    pub fn contains(&self, line: usize) -> bool {
+        line >= self.start && line <= self.end
+    }
+
+    /// returns true if the line is hidden when this range is folded
+    pub fn hides(&self, line: usize) -> bool {
+        line > self.start && line <= self.end
+    }
+}
+
+/// What the fold ranges depend on in a line,
+/// only the changed lines are scanned again when the content changes
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+enum LineKey {
+    /// a blank line, or a line without brackets or markers
+    #[default]
+    Empty,
+    Indent(usize),
+    /// the brackets in the line and whether the line starts with the first of them
+    Brackets(Vec<char>, bool),
+    StartMarker,
+    EndMarker,
+}
+
+/// The fold ranges of the content and which of them are folded
+#[derive(Clone, Debug, Default)]
+pub struct Folding {
+    strategy: FoldStrategy,
+    /// the key of each line the ranges are computed from
+    lines: Vec<LineKey>,
+    /// the lines changed since the ranges were last updated
+    changed: BTreeSet<usize>,
+    /// lines are inserted or removed since the ranges were last updated
+    are_lines_moved: bool,
+    /// sorted by start line, and the outer ranges first when they start on the same line
+    ranges: Vec<FoldRange>,
+    /// the start line of the folded ranges
+    folded: BTreeSet<usize>,
+    /// the lines hidden in the folded ranges as non overlapping sorted inclusive ranges,
+    /// updated whenever the ranges or the folded ranges change
+    hidden: Vec<(usize, usize)>,
+}
+
+impl Folding {
+    pub fn new(strategy: FoldStrategy, text_buffer: &TextBuffer) -> Self {
+        let mut folding = Self {
+            strategy,
+            lines: vec![],
+            changed: BTreeSet::new(),
+            are_lines_moved: false,
+            ranges: vec![],
+            folded: BTreeSet::new(),
+            hidden: vec![],
+        };
+        folding.update(text_buffer);
+        folding
+    }
+
+    pub fn strategy(&self) -> &FoldStrategy {
+        &self.strategy
+    }
+
+    pub fn set_strategy(&mut self, strategy: FoldStrategy, text_buffer: &TextBuffer) {
+        self.strategy = strategy;
+        self.folded.clear();
+        self.update(text_buffer);
+    }
+
+    /// recompute the fold ranges of the text buffer,
+    /// folded ranges which still exist stay folded
+    pub fn update(&mut self, text_buffer: &TextBuffer) {
+        self.lines = (0..text_buffer.total_lines())
+            .map(|y| self.line_key(text_buffer, y))
+            .collect();
+        self.changed.clear();
+        self.are_lines_moved = false;
+        self.update_ranges();
+    }
+
+    /// scan only the lines changed since the last update, the fold ranges are recomputed
+    /// from the keys of the lines when lines are moved or the key of a changed line is different
+    pub(crate) fn update_changed_lines(&mut self, text_buffer: &TextBuffer) {
+        if self.lines.len() != text_buffer.total_lines() {
+            return self.update(text_buffer);
+        }
+        let mut is_changed = std::mem::take(&mut self.are_lines_moved);
+        for y in std::mem::take(&mut self.changed) {
+            let key = self.line_key(text_buffer, y);
+            if let Some(line) = self.lines.get_mut(y) {
+                if *line != key {
+                    *line = key;
+                    is_changed = true;
+                }
+            }
+        }
+        if is_changed {
+            self.update_ranges();
+        } else {
+            self.update_hidden();
+        }
+    }
+
+    /// move the folded ranges with their start line as lines are inserted or removed before it,
+    /// the ranges are recomputed in `update_changed_lines`
+    pub(crate) fn apply_action(&mut self, action: &Action) {
+        self.folded = self
+            .folded
+            .iter()
+            .map(|start| action.shift_point(Point2::new(0, *start)).y)
+            .collect();
+        self.are_lines_moved |= matches!(action, Action::BreakLine(_) | Action::JoinLine(_));
+        action.apply_to_lines(&mut self.lines, &mut self.changed);
+    }
+
+    fn update_ranges(&mut self) {
+        let mut ranges = match &self.strategy {
+            FoldStrategy::Indentation => indentation_ranges(&self.lines),
+            FoldStrategy::Brackets => bracket_ranges(&self.lines),
+            FoldStrategy::Markers { .. } => marker_ranges(&self.lines),
+        };
+        assign_levels(&mut ranges);
+        self.folded
+            .retain(|start| ranges.iter().any(|range| range.start == *start));
+        self.ranges = ranges;
+        self.update_hidden();
+    }
+
+    /// what the fold ranges of the strategy depend on in this line
+    fn line_key(&self, text_buffer: &TextBuffer, y: usize) -> LineKey {
+        let line = text_buffer.chars().get(y).map(Vec::as_slice).unwrap_or(&[]);
+        if line.iter().all(|ch| ch.ch.is_whitespace()) {
+            return LineKey::Empty;
+        }
+        match &self.strategy {
+            FoldStrategy::Indentation => LineKey::Indent(text_buffer.line_indent(y)),
+            FoldStrategy::Brackets => {
+                let brackets: Vec<char> = line
+                    .iter()
+                    .map(|ch| ch.ch)
+                    .filter(|ch| "{}[]()".contains(*ch))
+                    .collect();
+                let is_leading = line
+                    .iter()
+                    .find(|ch| !ch.ch.is_whitespace())
+                    .is_some_and(|ch| "{}[]()".contains(ch.ch));
+                if brackets.is_empty() {
+                    LineKey::Empty
+                } else {
+                    LineKey::Brackets(brackets, is_leading)
+                }
+            }
+            FoldStrategy::Markers { start, end } => {
+                let line = String::from_iter(line.iter().map(|ch| ch.ch));
+                // the end marker is checked first, since it can contain the start marker
+                // ie: `endregion` and `region`
+                if line.contains(end.as_str()) {
+                    LineKey::EndMarker
+                } else if line.contains(start.as_str()) {
+                    LineKey::StartMarker
+                } else {
+                    LineKey::Empty
+                }
+            }
+        }
+    }
+
+    pub fn ranges(&self) -> &[FoldRange] {
+        &self.ranges
+    }
+
+    /// the fold range which starts at this line
+    pub fn range_starting_at(&self, line: usize) -> Option<&FoldRange> {
+        self.ranges.iter().find(|range| range.start == line)
+    }
+
+    /// returns true if the range starting at this line is folded
+    pub fn is_folded(&self, start_line: usize) -> bool {
+        self.folded.contains(&start_line)
+    }
+
+    /// the ranges that contain this line, the inner most first
+    fn ranges_containing(&self, line: usize) -> impl Iterator<Item = &FoldRange> {
+        self.ranges
+            .iter()
+            .rev()
+            .filter(move |range| range.contains(line))
+    }
+
+    /// fold the inner most unfolded range which contains this line,
+    /// returns true if a range is folded
+    pub fn fold(&mut self, line: usize) -> bool {
+        let folded = &self.folded;
+        let start = self
+            .ranges_containing(line)
+            .find(|range| !folded.contains(&range.start))
+            .map(|range| range.start);
+        if let Some(start) = start {
+            self.folded.insert(start);
+            self.update_hidden();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// unfold all the folded ranges which contains this line,
+    /// returns true if any range is unfolded
+    pub fn unfold(&mut self, line: usize) -> bool {
+        let starts: Vec<usize> = self
+            .ranges_containing(line)
+            .map(|range| range.start)
+            .filter(|start| self.folded.contains(start))
+            .collect();
+        for start in starts.iter() {
+            self.folded.remove(start);
+        }
+        self.update_hidden();
+        !starts.is_empty()
+    }
+
+    /// unfold the range starting at this line if it is folded, otherwise fold the range
+    pub fn toggle(&mut self, line: usize) -> bool {
+        if self.folded.remove(&line) {
+            self.update_hidden();
+            true
+        } else {
+            self.fold(line)
+        }
+    }
+
+    pub fn fold_all(&mut self) {
+        self.folded = self.ranges.iter().map(|range| range.start).collect();
+        self.update_hidden();
+    }
+
+    pub fn unfold_all(&mut self) {
+        self.folded.clear();
+        self.update_hidden();
+    }
+
+    /// keep the ranges up to this nesting level unfolded and fold the ranges deeper than it.
+    /// Level 0 folds everything
+    pub fn fold_to_level(&mut self, level: usize) {
+        self.folded = self
+            .ranges
+            .iter()
+            .filter(|range| range.level > level)
+            .map(|range| range.start)
+            .collect();
+        self.update_hidden();
+    }
+
+    /// returns true if this line is hidden inside a folded range
+    pub fn is_hidden(&self, line: usize) -> bool {
+        self.hidden_lines()
+            .iter()
+            .any(|(start, end)| line >= *start && line <= *end)
+    }
+
+    fn folded_ranges(&self) -> impl Iterator<Item = &FoldRange> {
+        self.ranges
+            .iter()
+            .filter(|range| self.folded.contains(&range.start))
+    }
+
+    fn update_hidden(&mut self) {
+        let mut hidden: Vec<(usize, usize)> = vec![];
+        for range in self.folded_ranges() {
+            let (start, end) = (range.start + 1, range.end);
+            match hidden.last_mut() {
+                Some(last) if start <= last.1 + 1 => last.1 = last.1.max(end),
+                _ => hidden.push((start, end)),
+            }
+        }
+        self.hidden = hidden;
+    }
+
+    /// the hidden lines as non overlapping sorted inclusive ranges
+    pub(crate) fn hidden_lines(&self) -> &[(usize, usize)] {
+        &self.hidden
+    }
+
+    /// the first line of the folded range hiding this line,
+    /// or the line itself if it is not hidden
+    pub fn visible_line_at(&self, line: usize) -> usize {
+        self.hidden_lines()
+            .iter()
+            .find(|(start, end)| line >= *start && line <= *end)
+            .map(|(start, _)| start - 1)
+            .unwrap_or(line)
+    }
+
+    /// the first visible line after this line
+    pub fn next_visible_line(&self, line: usize) -> usize {
+        let next = line + 1;
+        self.hidden_lines()
+            .iter()
+            .find(|(start, end)| next >= *start && next <= *end)
+            .map(|(_, end)| end + 1)
+            .unwrap_or(next)
+    }
+
+    /// the visual row where this line is displayed, hidden lines are displayed
+    /// on the row of the range they are folded into
+    pub fn line_to_row(&self, line: usize) -> usize {
+        let line = self.visible_line_at(line);
+        let hidden_before: usize = self
+            .hidden_lines()
+            .iter()
+            .filter(|(_, end)| *end < line)
+            .map(|(start, end)| end - start + 1)
+            .sum();
+        line - hidden_before
+    }
+
+    /// the line displayed at this visual row
+    pub fn row_to_line(&self, row: usize) -> usize {
+        let mut line = row;
+        for &(start, end) in self.hidden_lines() {
+            if start <= line {
+                line += end - start + 1;
+            } else {
+                break;
+            }
+        }
+        line
+    }
+}
+
+/// set the nesting level of the ranges and sort them by start line, outer ranges first
+fn assign_levels(ranges: &mut [FoldRange]) {
+    ranges.sort_by(|a, b| a.start.cmp(&b.start).then(b.end.cmp(&a.end)));
+    let mut ends: Vec<usize> = vec![];
+    for range in ranges.iter_mut() {
+        while let Some(end) = ends.last() {
+            if *end < range.start {
+                ends.pop();
+            } else {
+                break;
+            }
+        }
+        range.level = ends.len() + 1;
+        ends.push(range.end);
+    }
+}
+
+fn indentation_ranges(lines: &[LineKey]) -> Vec<FoldRange> {
+    let mut ranges = vec![];
+    // the start line and indentation of the lines which could still have deeper lines after them
+    let mut stack: Vec<(usize, usize)> = vec![];
+    let mut last_non_blank = 0;
+    for (y, key) in lines.iter().enumerate() {
+        let LineKey::Indent(indent) = *key else {
+            continue;
+        };
+        while let Some((start, start_indent)) = stack.last().copied() {
+            if indent > start_indent {
+                break;
+            }
+            stack.pop();
+            if last_non_blank > start {
+                ranges.push(FoldRange {
+                    start,
+                    end: last_non_blank,
+                    level: 0,
+                });
+            }
+        }
+        stack.push((y, indent));
+        last_non_blank = y;
+    }
+    for (start, _) in stack {
+        if last_non_blank > start {
+            ranges.push(FoldRange {
+                start,
+                end: last_non_blank,
+                level: 0,
+            });
+        }
+    }
+    ranges
+}
+
+fn bracket_ranges(lines: &[LineKey]) -> Vec<FoldRange> {
+    let mut ranges: Vec<FoldRange> = vec![];
+    let mut stack: Vec<(char, usize)> = vec![];
+    for (y, key) in lines.iter().enumerate() {
+        let LineKey::Brackets(brackets, is_leading) = key else {
+            continue;
+        };
+        for (i, bracket) in brackets.iter().enumerate() {
+            let opening = match bracket {
+                '{' | '[' | '(' => {
+                    stack.push((*bracket, y));
+                    continue;
+                }
+                '}' => '{',
+                ']' => '[',
+                ')' => '(',
+                _ => continue,
+            };
+            if let Some(pos) = stack.iter().rposition(|(open, _)| *open == opening) {
+                let start = stack[pos].1;
+                stack.truncate(pos);
+                // keep the line of the closing bracket visible if it starts with it
+                let end = if *is_leading && i == 0 {
+                    y.saturating_sub(1)
+                } else {
+                    y
+                };
+                if end > start {
+                    // only the outer most range is used when ranges start in the same line
+                    match ranges.iter_mut().find(|range| range.start == start) {
+                        Some(range) => range.end = range.end.max(end),
+                        None => ranges.push(FoldRange {
+                            start,
+                            end,
+                            level: 0,
+                        }),
+                    }
+                }
+            }
+        }
+    }
+    ranges
+}
+
+fn marker_ranges(lines: &[LineKey]) -> Vec<FoldRange> {
+    let mut ranges = vec![];
+    let mut stack: Vec<usize> = vec![];
+    for (y, key) in lines.iter().enumerate() {
+        match key {
+            LineKey::EndMarker => {
+                if let Some(start) = stack.pop() {
+                    ranges.push(FoldRange {
+                        start,
+                        end: y,
+                        level: 0,
+                    });
+                }
+            }
+            LineKey::StartMarker => stack.push(y),
+            _ => (),
+        }
+    }
+    ranges
+}
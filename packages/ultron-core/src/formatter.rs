@@ -0,0 +1,61 @@
+use crate::diff::{hunk_edit, line_hunks};
+use crate::{Edit, TextBuffer};
+use nalgebra::Point2;
+use std::fmt;
+
+/// The result of a formatter
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Formatted {
+    /// the new text of the whole content, it is applied as the minimal edits from the old content
+    Text(String),
+    /// the edits located in the content before any of them is applied
+    Edits(Vec<Edit>),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FormatError(pub String);
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unable to format: {}", self.0)
+    }
+}
+
+impl std::error::Error for FormatError {}
+
+/// Formats the content, ie: by running rustfmt or prettier
+pub trait Formatter {
+    /// format the content, or only the lines from the start to the end(exclusive) of `range`
+    fn format(
+        &self,
+        buffer: &TextBuffer,
+        range: Option<(Point2<usize>, Point2<usize>)>,
+    ) -> Result<Formatted, FormatError>;
+}
+
+/// the edits which change the old content into the new content,
+/// only the changed lines are edited and the unchanged characters at
+/// the start and end of the changes are kept
+pub fn diff_edits(old: &str, new: &str) -> Vec<Edit> {
+    let old_lines = split_lines(old);
+    let new_lines = split_lines(new);
+    line_hunks(&old_lines, &new_lines)
+        .into_iter()
+        .filter_map(|(old_range, new_range)| {
+            hunk_edit(
+                Point2::new(0, old_range.start),
+                &old_lines[old_range].concat(),
+                &new_lines[new_range].concat(),
+            )
+        })
+        .collect()
+}
+
+/// the lines with their line break, the last line has none
+fn split_lines(content: &str) -> Vec<&str> {
+    content
+        .split_inclusive('\n')
+        // the empty last line after the last line break
+        .chain((content.is_empty() || content.ends_with('\n')).then_some(""))
+        .collect()
+}
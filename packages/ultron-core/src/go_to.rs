@@ -0,0 +1,68 @@
+use nalgebra::Point2;
+use std::fmt;
+use std::str::FromStr;
+
+/// A location typed in a go to line prompt or linked to by the host application,
+/// ie: `12`, `12:5`, `+3` or `-3`.
+///
+/// The lines and columns start at 1, the relative lines are counted from the cursor.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GoToTarget {
+    Line {
+        line: usize,
+        column: Option<usize>,
+    },
+    /// the number of lines below the cursor, or above when negative
+    Relative(isize),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvalidGoToTarget(pub String);
+
+impl fmt::Display for InvalidGoToTarget {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid location: {}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidGoToTarget {}
+
+impl FromStr for GoToTarget {
+    type Err = InvalidGoToTarget;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let invalid = || InvalidGoToTarget(s.to_string());
+        let relative = |lines: &str| lines.parse::<isize>().ok().filter(|n| *n >= 0);
+        if let Some(lines) = s.strip_prefix('+') {
+            let lines = relative(lines).ok_or_else(invalid)?;
+            return Ok(GoToTarget::Relative(lines));
+        }
+        if let Some(lines) = s.strip_prefix('-') {
+            let lines = relative(lines).ok_or_else(invalid)?;
+            return Ok(GoToTarget::Relative(-lines));
+        }
+        let (line, column) = match s.split_once(':') {
+            Some((line, column)) => (line, Some(column.parse().map_err(|_| invalid())?)),
+            None => (s, None),
+        };
+        let line = line.parse().map_err(|_| invalid())?;
+        Ok(GoToTarget::Line { line, column })
+    }
+}
+
+impl GoToTarget {
+    /// the position of the cursor, starting at 0, clamped to the lines of the content.
+    /// The cursor is moved to the start of the line when no column is specified
+    pub fn resolve(&self, cursor: Point2<usize>, total_lines: usize) -> Point2<usize> {
+        let last_line = total_lines.saturating_sub(1);
+        let (line, column) = match *self {
+            GoToTarget::Line { line, column } => (
+                line.saturating_sub(1),
+                column.map(|column| column.saturating_sub(1)),
+            ),
+            GoToTarget::Relative(lines) => (cursor.y.saturating_add_signed(lines), None),
+        };
+        Point2::new(column.unwrap_or(0), line.min(last_line))
+    }
+}
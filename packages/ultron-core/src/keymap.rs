@@ -0,0 +1,812 @@
+use crate::{Command, TextTransform};
+pub use emacs::Emacs;
+pub use kakoune::{Kakoune, KakouneMode};
+use nalgebra::Point2;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::iter::Peekable;
+use std::str::{Chars, FromStr};
+pub use vim::{Vim, VimMode};
+
+mod emacs;
+mod kakoune;
+mod text;
+mod vim;
+
+/// The modifier keys held down while a key is pressed
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Modifiers {
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+    /// the command key on mac, the windows key on the others
+    pub meta: bool,
+}
+
+impl Modifiers {
+    pub const NONE: Self = Self {
+        ctrl: false,
+        alt: false,
+        shift: false,
+        meta: false,
+    };
+
+    pub fn ctrl() -> Self {
+        Self {
+            ctrl: true,
+            ..Self::NONE
+        }
+    }
+
+    pub fn alt() -> Self {
+        Self {
+            alt: true,
+            ..Self::NONE
+        }
+    }
+
+    pub fn shift() -> Self {
+        Self {
+            shift: true,
+            ..Self::NONE
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::NONE
+    }
+}
+
+/// A key on the keyboard, independent of the platform
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Key {
+    Char(char),
+    Enter,
+    Tab,
+    Backspace,
+    Delete,
+    Escape,
+    Insert,
+    Up,
+    Down,
+    Left,
+    Right,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    /// the function keys F1 to F24
+    F(u8),
+}
+
+impl Key {
+    /// the key from its name, ie: `Enter`, `ArrowUp`, `F2`, `Space`, `Equal` or a single character.
+    /// The names of the browser keyboard events are also recognized.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let mut chars = name.chars();
+        if let (Some(ch), None) = (chars.next(), chars.next()) {
+            return Some(Key::Char(ch));
+        }
+        let key = match name.to_lowercase().as_str() {
+            "enter" | "return" => Key::Enter,
+            "tab" => Key::Tab,
+            "backspace" => Key::Backspace,
+            "delete" | "del" => Key::Delete,
+            "escape" | "esc" => Key::Escape,
+            "insert" | "ins" => Key::Insert,
+            "up" | "arrowup" => Key::Up,
+            "down" | "arrowdown" => Key::Down,
+            "left" | "arrowleft" => Key::Left,
+            "right" | "arrowright" => Key::Right,
+            "home" => Key::Home,
+            "end" => Key::End,
+            "pageup" | "pgup" => Key::PageUp,
+            "pagedown" | "pgdn" => Key::PageDown,
+            "space" | "spc" => Key::Char(' '),
+            "equal" => Key::Char('='),
+            "plus" => Key::Char('+'),
+            "minus" => Key::Char('-'),
+            name => {
+                let n: u8 = name.strip_prefix('f')?.parse().ok()?;
+                if (1..=24).contains(&n) {
+                    Key::F(n)
+                } else {
+                    return None;
+                }
+            }
+        };
+        Some(key)
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Key::Char(' ') => write!(f, "Space"),
+            Key::Char(ch) => write!(f, "{ch}"),
+            Key::F(n) => write!(f, "F{n}"),
+            key => write!(f, "{key:?}"),
+        }
+    }
+}
+
+/// A key press translated from the platform, ie: a keyboard event of the browser
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeyEvent {
+    pub key: Key,
+    pub modifiers: Modifiers,
+}
+
+impl KeyEvent {
+    pub fn new(key: Key, modifiers: Modifiers) -> Self {
+        Self { key, modifiers }
+    }
+
+    /// the character typed with this key, None if it is pressed with ctrl, alt or meta.
+    /// Ctrl+Alt is AltGr on windows, which types the characters such as `@` or `€`
+    pub fn typed_char(&self) -> Option<char> {
+        let Modifiers {
+            ctrl, alt, meta, ..
+        } = self.modifiers;
+        let is_alt_gr = ctrl && alt;
+        match self.key {
+            Key::Char(ch) if !meta && (is_alt_gr || (!ctrl && !alt)) => Some(ch),
+            _ => None,
+        }
+    }
+
+    pub fn chord(&self) -> KeyChord {
+        KeyChord::new(self.key, self.modifiers)
+    }
+}
+
+/// A key and its modifiers as used in the key bindings, ie: `Ctrl+Shift+p`, `C-x`, `M-f`.
+///
+/// The shift of a character key is part of the character itself,
+/// so `Ctrl+Shift+z` is the same as `Ctrl+Z`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct KeyChord {
+    pub key: Key,
+    pub modifiers: Modifiers,
+}
+
+impl KeyChord {
+    pub fn new(key: Key, modifiers: Modifiers) -> Self {
+        let mut modifiers = modifiers;
+        let key = match key {
+            Key::Char(ch) if modifiers.shift => {
+                modifiers.shift = false;
+                Key::Char(ch.to_ascii_uppercase())
+            }
+            key => key,
+        };
+        Self { key, modifiers }
+    }
+
+    /// parse the chords of a key sequence separated by spaces, ie: `Ctrl+k Ctrl+c`
+    pub fn parse_sequence(keys: &str) -> Result<Vec<Self>, KeymapError> {
+        let chords = keys
+            .split_whitespace()
+            .map(KeyChord::from_str)
+            .collect::<Result<Vec<_>, _>>()?;
+        if chords.is_empty() {
+            Err(KeymapError::InvalidKey(keys.to_string()))
+        } else {
+            Ok(chords)
+        }
+    }
+}
+
+impl FromStr for KeyChord {
+    type Err = KeymapError;
+
+    /// the modifiers are separated with `+` or `-`, ie: `Ctrl+Alt+Delete`, `C-M-f`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut modifiers = Modifiers::default();
+        let mut rest = s.trim();
+        while let Some(sep) = rest
+            .find(['+', '-'])
+            .filter(|&sep| sep > 0 && sep + 1 < rest.len())
+        {
+            match rest[..sep].to_lowercase().as_str() {
+                "ctrl" | "control" | "c" => modifiers.ctrl = true,
+                "alt" | "option" | "m" => modifiers.alt = true,
+                "shift" | "s" => modifiers.shift = true,
+                "meta" | "cmd" | "super" => modifiers.meta = true,
+                _ => break,
+            }
+            rest = &rest[sep + 1..];
+        }
+        let key = Key::from_name(rest).ok_or_else(|| KeymapError::InvalidKey(s.to_string()))?;
+        Ok(KeyChord::new(key, modifiers))
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.modifiers.ctrl {
+            write!(f, "Ctrl+")?;
+        }
+        if self.modifiers.alt {
+            write!(f, "Alt+")?;
+        }
+        if self.modifiers.shift {
+            write!(f, "Shift+")?;
+        }
+        if self.modifiers.meta {
+            write!(f, "Meta+")?;
+        }
+        write!(f, "{}", self.key)
+    }
+}
+
+/// What a key sequence is bound to
+#[derive(Clone, Debug, PartialEq)]
+pub enum Binding {
+    Command(Command),
+    /// an action of the host application identified by its name, ie: `save`
+    Action(String),
+}
+
+impl From<Command> for Binding {
+    fn from(command: Command) -> Self {
+        Binding::Command(command)
+    }
+}
+
+impl FromStr for Binding {
+    type Err = KeymapError;
+
+    /// a host action is prefixed with `@`, ie: `@save`, anything else is a `Command`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().strip_prefix('@') {
+            Some(action) => Ok(Binding::Action(action.trim().to_string())),
+            None => Ok(Binding::Command(s.parse()?)),
+        }
+    }
+}
+
+/// A state of the editor in which the bindings of that context take precedence
+/// over the other bindings, ie: `Tab` moves to the next tab stop while a snippet is filled in
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum KeyContext {
+    /// the tab stops of a snippet are being filled in
+    Snippet,
+}
+
+impl KeyContext {
+    /// the context as it is written in the config, ie: `snippet`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "snippet" => Some(KeyContext::Snippet),
+            _ => None,
+        }
+    }
+}
+
+/// The result of passing a key press to the keymap
+#[derive(Clone, Debug, PartialEq)]
+pub enum KeyResult {
+    /// the key sequence is bound to this
+    Bound(Binding),
+    /// the keys are the start of a longer key sequence, waiting for the next key
+    Pending,
+    /// the key sequence is not bound to anything,
+    /// the characters of the keys can be inserted with `KeyEvent::typed_char`
+    Unbound(Vec<KeyEvent>),
+}
+
+/// What an editing profile did with a key press
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct KeyOutcome {
+    pub is_content_changed: bool,
+    /// the host actions triggered by the key, ie: `write` from the vim `:w` command
+    pub actions: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum KeymapError {
+    InvalidKey(String),
+    InvalidCommand(String),
+    /// the line number, starting at 1, and the error in that line of the config
+    InvalidConfig(usize, String),
+}
+
+impl fmt::Display for KeymapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            KeymapError::InvalidKey(key) => write!(f, "invalid key: {key}"),
+            KeymapError::InvalidCommand(command) => write!(f, "invalid command: {command}"),
+            KeymapError::InvalidConfig(line, error) => write!(f, "line {line}: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for KeymapError {}
+
+/// Maps the key chords and multi-key sequences to commands or host actions
+#[derive(Clone, Debug, Default)]
+pub struct Keymap {
+    bindings: BTreeMap<Vec<KeyChord>, Binding>,
+    /// the bindings which only apply while their context is active
+    context_bindings: BTreeMap<KeyContext, BTreeMap<Vec<KeyChord>, Binding>>,
+    active_contexts: BTreeSet<KeyContext>,
+    /// the keys pressed so far of a key sequence
+    pending: Vec<KeyEvent>,
+}
+
+impl Keymap {
+    /// an empty keymap
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// the default key bindings of the editor
+    pub fn standard() -> Self {
+        Self::from_config(STANDARD_CONFIG).expect("the standard keymap must be valid")
+    }
+
+    /// a keymap with the bindings of the config, see `load_config`
+    pub fn from_config(config: &str) -> Result<Self, KeymapError> {
+        let mut keymap = Self::new();
+        keymap.load_config(config)?;
+        Ok(keymap)
+    }
+
+    /// add the bindings in the config, overriding the existing bindings of the same keys.
+    ///
+    /// Each line binds a key sequence to a command or a host action prefixed with `@`,
+    /// `none` removes the binding. Lines starting with `#` are comments.
+    /// The character keys are case sensitive, `Ctrl+Z` is the same as `Ctrl+Shift+z`.
+    /// A binding followed by `when` and a context only applies while that context is active.
+    /// ```text
+    /// # comment or uncomment the selected lines
+    /// Ctrl+/ = ToggleComment
+    /// Ctrl+k Ctrl+u = Transform(Uppercase)
+    /// Ctrl+s = @save
+    /// Ctrl+r = none
+    /// Tab when snippet = NextTabStop
+    /// ```
+    pub fn load_config(&mut self, config: &str) -> Result<(), KeymapError> {
+        for (i, line) in config.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid = |error: String| KeymapError::InvalidConfig(i + 1, error);
+            // the `=` key is written as `Equal`, so the first `=` separates the keys from the binding
+            let (keys, binding) = line
+                .split_once('=')
+                .ok_or_else(|| invalid(format!("expecting `keys = command`: {line}")))?;
+            let (keys, context) = match keys.split_once(" when ") {
+                Some((keys, context)) => {
+                    let context = KeyContext::from_name(context.trim())
+                        .ok_or_else(|| invalid(format!("invalid context: {}", context.trim())))?;
+                    (keys, Some(context))
+                }
+                None => (keys, None),
+            };
+            let chords = KeyChord::parse_sequence(keys).map_err(|e| invalid(e.to_string()))?;
+            let bindings = match context {
+                Some(context) => self.context_bindings.entry(context).or_default(),
+                None => &mut self.bindings,
+            };
+            match binding.trim() {
+                "none" => {
+                    bindings.remove(&chords);
+                }
+                binding => {
+                    let binding = binding
+                        .parse()
+                        .map_err(|e: KeymapError| invalid(e.to_string()))?;
+                    bindings.insert(chords, binding);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// bind the key sequence, ie: `Ctrl+k Ctrl+c`
+    pub fn bind(&mut self, keys: &str, binding: impl Into<Binding>) -> Result<(), KeymapError> {
+        let chords = KeyChord::parse_sequence(keys)?;
+        self.bind_chords(chords, binding);
+        Ok(())
+    }
+
+    pub fn bind_chords(&mut self, chords: Vec<KeyChord>, binding: impl Into<Binding>) {
+        self.bindings.insert(chords, binding.into());
+    }
+
+    /// remove the binding of the key sequence
+    pub fn unbind(&mut self, keys: &str) -> Result<Option<Binding>, KeymapError> {
+        let chords = KeyChord::parse_sequence(keys)?;
+        Ok(self.bindings.remove(&chords))
+    }
+
+    pub fn get(&self, chords: &[KeyChord]) -> Option<&Binding> {
+        self.bindings.get(chords)
+    }
+
+    /// the binding of the key sequence which only applies in this context
+    pub fn get_in(&self, context: KeyContext, chords: &[KeyChord]) -> Option<&Binding> {
+        self.context_bindings.get(&context)?.get(chords)
+    }
+
+    /// activate or deactivate the bindings of the context, ie: when a snippet is inserted
+    pub fn set_context(&mut self, context: KeyContext, is_active: bool) {
+        if is_active {
+            self.active_contexts.insert(context);
+        } else {
+            self.active_contexts.remove(&context);
+        }
+    }
+
+    /// the binding of the key sequence in the active contexts, if any
+    pub fn context_binding(&self, chords: &[KeyChord]) -> Option<&Binding> {
+        self.active_contexts
+            .iter()
+            .find_map(|context| self.get_in(*context, chords))
+    }
+
+    /// the bindings of the active contexts, followed by the other bindings
+    fn active_bindings(&self) -> impl Iterator<Item = &BTreeMap<Vec<KeyChord>, Binding>> {
+        self.active_contexts
+            .iter()
+            .filter_map(|context| self.context_bindings.get(context))
+            .chain([&self.bindings])
+    }
+
+    fn lookup(&self, chords: &[KeyChord]) -> Option<&Binding> {
+        self.active_bindings()
+            .find_map(|bindings| bindings.get(chords))
+    }
+
+    /// the key sequences and what they are bound to, sorted by the keys
+    pub fn bindings(&self) -> impl Iterator<Item = (&[KeyChord], &Binding)> {
+        self.bindings
+            .iter()
+            .map(|(chords, binding)| (chords.as_slice(), binding))
+    }
+
+    /// the first key sequence bound to this
+    pub fn keys_for(&self, binding: &Binding) -> Option<&[KeyChord]> {
+        self.bindings()
+            .find(|(_chords, b)| *b == binding)
+            .map(|(chords, _)| chords)
+    }
+
+    /// the keys pressed so far are the start of a longer key sequence
+    pub fn is_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// discard the keys pressed so far of a key sequence
+    pub fn cancel_pending(&mut self) {
+        self.pending.clear();
+    }
+
+    /// find what the key press, along with the previous pending keys, is bound to.
+    /// The bindings of the active contexts are looked up first.
+    /// A non character key pressed with shift falls back to its binding without shift,
+    /// ie: `Shift+Enter` breaks the line when only `Enter` is bound
+    pub fn process_key(&mut self, event: KeyEvent) -> KeyResult {
+        self.pending.push(event);
+        let chords: Vec<KeyChord> = self.pending.iter().map(KeyEvent::chord).collect();
+        if let Some(binding) = self.lookup(&chords).cloned() {
+            self.pending.clear();
+            return KeyResult::Bound(binding);
+        }
+        let is_prefix = self.active_bindings().any(|bindings| {
+            bindings
+                .range(chords.clone()..)
+                .next()
+                .is_some_and(|(keys, _)| keys.starts_with(&chords))
+        });
+        if is_prefix {
+            return KeyResult::Pending;
+        }
+        let unshifted = without_shift(&chords).and_then(|chords| self.lookup(&chords).cloned());
+        if let Some(binding) = unshifted {
+            self.pending.clear();
+            return KeyResult::Bound(binding);
+        }
+        KeyResult::Unbound(std::mem::take(&mut self.pending))
+    }
+}
+
+/// the chords with the shift removed from the last chord, if it is a non character key
+fn without_shift(chords: &[KeyChord]) -> Option<Vec<KeyChord>> {
+    let (last, rest) = chords.split_last()?;
+    if !last.modifiers.shift || matches!(last.key, Key::Char(_)) {
+        return None;
+    }
+    let modifiers = Modifiers {
+        shift: false,
+        ..last.modifiers
+    };
+    Some(
+        rest.iter()
+            .copied()
+            .chain([KeyChord::new(last.key, modifiers)])
+            .collect(),
+    )
+}
+
+/// the default key bindings of the editor
+const STANDARD_CONFIG: &str = r#"
+Ctrl+c = Copy
+Ctrl+x = Cut
+Ctrl+v = Paste
+Ctrl+z = Undo
+Ctrl+Shift+z = Redo
+Ctrl+r = Redo
+Ctrl+a = SelectAll
+# emacs style yank and yank pop from the kill ring
+Ctrl+y = Yank
+Alt+y = YankPop
+Ctrl+/ = ToggleComment
+# ctrl+shift+[ and ctrl+shift+]
+Ctrl+{ = Fold
+Ctrl+} = Unfold
+Tab = IndentForward
+Enter = BreakLine
+Backspace = DeleteBack
+Delete = DeleteForward
+Up = MoveUp
+Down = MoveDown
+Left = MoveLeft
+Right = MoveRight
+Home = MoveLeftStart
+End = MoveRightEnd
+Ctrl+F2 = ToggleBookmark
+Shift+F2 = PrevBookmark
+F2 = NextBookmark
+F8 = NextDiagnostic
+Shift+F8 = PrevDiagnostic
+Ctrl+. = ApplyFix
+Ctrl+Shift+p = @command-palette
+Ctrl+g = @go-to-line
+# while the tab stops of a snippet are filled in
+Tab when snippet = NextTabStop
+Shift+Tab when snippet = PrevTabStop
+Escape when snippet = ExitSnippet
+"#;
+
+impl FromStr for TextTransform {
+    type Err = KeymapError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let transform = match s.trim() {
+            "Uppercase" => TextTransform::Uppercase,
+            "Lowercase" => TextTransform::Lowercase,
+            "TitleCase" => TextTransform::TitleCase,
+            "SnakeCase" => TextTransform::SnakeCase,
+            "CamelCase" => TextTransform::CamelCase,
+            "KebabCase" => TextTransform::KebabCase,
+            "ScreamingSnakeCase" => TextTransform::ScreamingSnakeCase,
+            "TrimTrailingWhitespace" => TextTransform::TrimTrailingWhitespace,
+            "TabsToSpaces" => TextTransform::TabsToSpaces,
+            "SpacesToTabs" => TextTransform::SpacesToTabs,
+            "ReverseLines" => TextTransform::ReverseLines,
+            _ => return Err(KeymapError::InvalidCommand(s.to_string())),
+        };
+        Ok(transform)
+    }
+}
+
+impl FromStr for Command {
+    type Err = KeymapError;
+
+    /// parse the command written the same way as its variant, ie: `Undo`, `InsertText("hello")`,
+    /// `InsertChar('x')`, `SetPosition(0, 10)`, `SetSelection((0, 0), (5, 0))`,
+    /// `Transform(Uppercase)`. An empty `Option` is written as `None` or left out
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let invalid = || KeymapError::InvalidCommand(s.to_string());
+        let (name, args) = match s.find('(') {
+            Some(open) if s.ends_with(')') => {
+                let args = parse_args(&mut s[open + 1..].chars().peekable(), Some(')'))
+                    .ok_or_else(invalid)?;
+                (s[..open].trim(), args)
+            }
+            _ => (s, vec![]),
+        };
+        let text = |arg: &Arg| match arg {
+            Arg::Text(text) => Ok(text.clone()),
+            _ => Err(invalid()),
+        };
+        let optional_text = |arg: &Arg| match arg {
+            Arg::Word(word) if word == "None" => Ok(None),
+            arg => text(arg).map(Some),
+        };
+        let ch = |arg: &Arg| {
+            let text = text(arg)?;
+            let mut chars = text.chars();
+            match (chars.next(), chars.next()) {
+                (Some(ch), None) => Ok(ch),
+                _ => Err(invalid()),
+            }
+        };
+        let number = |arg: &Arg| match arg {
+            Arg::Number(n) => usize::try_from(*n).map_err(|_| invalid()),
+            _ => Err(invalid()),
+        };
+        let coordinate = |arg: &Arg| match arg {
+            Arg::Number(n) => i32::try_from(*n).map_err(|_| invalid()),
+            _ => Err(invalid()),
+        };
+        let point = |x: &Arg, y: &Arg| Ok(Point2::new(coordinate(x)?, coordinate(y)?));
+        let location = |x: &Arg, y: &Arg| Ok(Point2::new(number(x)?, number(y)?));
+        let transform = |arg: &Arg| match arg {
+            Arg::Word(word) => word.parse(),
+            _ => Err(invalid()),
+        };
+        let command = match (name, args.as_slice()) {
+            ("IndentForward", []) => Command::IndentForward,
+            ("IndentBackward", []) => Command::IndentBackward,
+            ("BreakLine", []) => Command::BreakLine,
+            ("DeleteBack", []) => Command::DeleteBack,
+            ("DeleteForward", []) => Command::DeleteForward,
+            ("MoveUp", []) => Command::MoveUp,
+            ("MoveDown", []) => Command::MoveDown,
+            ("MoveLeft", []) => Command::MoveLeft,
+            ("MoveLeftStart", []) => Command::MoveLeftStart,
+            ("MoveRight", []) => Command::MoveRight,
+            ("MoveRightEnd", []) => Command::MoveRightEnd,
+            ("InsertChar", [c]) => Command::InsertChar(ch(c)?),
+            ("ReplaceChar", [c]) => Command::ReplaceChar(ch(c)?),
+            ("InsertText", [t]) => Command::InsertText(text(t)?),
+            ("ReplaceText", [x1, y1, x2, y2, t]) => {
+                Command::ReplaceText(location(x1, y1)?, location(x2, y2)?, text(t)?)
+            }
+            ("InsertSnippet", [t]) => Command::InsertSnippet(text(t)?),
+            ("NextTabStop", []) => Command::NextTabStop,
+            ("PrevTabStop", []) => Command::PrevTabStop,
+            ("ExitSnippet", []) => Command::ExitSnippet,
+            ("RevertHunk", []) => Command::RevertHunk,
+            ("Format", []) => Command::Format,
+            ("FormatSelection", []) => Command::FormatSelection,
+            ("ReplaceContent", [t]) => Command::ReplaceContent(text(t)?),
+            ("PasteTextBlock", [t]) => Command::PasteTextBlock(text(t)?),
+            ("MergeText", [t]) => Command::MergeText(text(t)?),
+            ("SetContent", [t]) => Command::SetContent(text(t)?),
+            ("Undo", []) => Command::Undo,
+            ("Redo", []) => Command::Redo,
+            ("BumpHistory", []) => Command::BumpHistory,
+            ("SetSelection", [x1, y1, x2, y2]) => {
+                Command::SetSelection(point(x1, y1)?, point(x2, y2)?)
+            }
+            ("AddSelection", [x1, y1, x2, y2]) => {
+                Command::AddSelection(point(x1, y1)?, point(x2, y2)?)
+            }
+            ("SelectAll", []) => Command::SelectAll,
+            ("ClearSelection", []) => Command::ClearSelection,
+            ("SetPosition", [x, y]) => Command::SetPosition(point(x, y)?),
+            ("ToggleComment", []) => Command::ToggleComment,
+            ("Transform", [t]) => Command::Transform(transform(t)?),
+            ("Fold", []) => Command::Fold,
+            ("Unfold", []) => Command::Unfold,
+            ("ToggleFold", []) => Command::ToggleFold,
+            ("ToggleFoldAt", [n]) => Command::ToggleFoldAt(number(n)?),
+            ("FoldAll", []) => Command::FoldAll,
+            ("UnfoldAll", []) => Command::UnfoldAll,
+            ("FoldToLevel", [n]) => Command::FoldToLevel(number(n)?),
+            ("SetMark", [t]) => Command::SetMark(text(t)?),
+            ("JumpToMark", [t]) => Command::JumpToMark(text(t)?),
+            ("RemoveMark", [t]) => Command::RemoveMark(text(t)?),
+            ("ToggleBookmark", []) => Command::ToggleBookmark,
+            ("NextBookmark", []) => Command::NextBookmark,
+            ("PrevBookmark", []) => Command::PrevBookmark,
+            ("ClearMarks", []) => Command::ClearMarks,
+            ("NextDiagnostic", []) => Command::NextDiagnostic,
+            ("PrevDiagnostic", []) => Command::PrevDiagnostic,
+            ("ApplyFix", []) => Command::ApplyFix,
+            ("Copy", []) => Command::Copy,
+            ("Cut", []) => Command::Cut,
+            ("Paste", []) => Command::Paste,
+            ("PasteText", [t]) => Command::PasteText(text(t)?),
+            ("DeleteSelection", []) => Command::DeleteSelection,
+            ("Yank", []) => Command::Yank,
+            ("YankPop", []) => Command::YankPop,
+            ("CopyToRegister", [c]) => Command::CopyToRegister(ch(c)?),
+            ("CutToRegister", [c]) => Command::CutToRegister(ch(c)?),
+            ("PasteFromRegister", [c]) => Command::PasteFromRegister(ch(c)?),
+            ("StartRecording", []) => Command::StartRecording(None),
+            ("StartRecording", [t]) => Command::StartRecording(optional_text(t)?),
+            ("StopRecording", []) => Command::StopRecording,
+            ("PlayMacro", []) => Command::PlayMacro(None, 1),
+            ("PlayMacro", [t]) => Command::PlayMacro(optional_text(t)?, 1),
+            ("PlayMacro", [t, n]) => Command::PlayMacro(optional_text(t)?, number(n)?),
+            _ => return Err(invalid()),
+        };
+        Ok(command)
+    }
+}
+
+/// An argument of a command in the config,
+/// the points `(x, y)` are flattened into their coordinates
+#[derive(Clone, Debug, PartialEq)]
+enum Arg {
+    /// a quoted text or character, ie: `"hello"` or `'x'`
+    Text(String),
+    Number(i64),
+    /// an unquoted word, ie: `Uppercase` or `None`
+    Word(String),
+}
+
+/// parse the arguments separated by commas up to the closing character
+fn parse_args(chars: &mut Peekable<Chars>, close: Option<char>) -> Option<Vec<Arg>> {
+    let mut args = vec![];
+    loop {
+        skip_whitespace(chars);
+        if chars.peek().copied() == close {
+            chars.next();
+            break;
+        }
+        args.extend(parse_arg(chars)?);
+        skip_whitespace(chars);
+        match chars.next() {
+            Some(',') => (),
+            ch if ch == close => break,
+            _ => return None,
+        }
+    }
+    // nothing is allowed after the closing character
+    if chars.next().is_some() {
+        return None;
+    }
+    Some(args)
+}
+
+/// parse one argument, a point is parsed into its coordinates
+fn parse_arg(chars: &mut Peekable<Chars>) -> Option<Vec<Arg>> {
+    match chars.peek()? {
+        &quote @ ('"' | '\'') => {
+            chars.next();
+            let mut text = String::new();
+            loop {
+                match chars.next()? {
+                    ch if ch == quote => break,
+                    '\\' => text.push(chars.next()?),
+                    ch => text.push(ch),
+                }
+            }
+            Some(vec![Arg::Text(text)])
+        }
+        '(' => {
+            chars.next();
+            let mut inner = String::new();
+            let mut depth = 1;
+            for ch in chars.by_ref() {
+                match ch {
+                    '(' => depth += 1,
+                    ')' if depth == 1 => break,
+                    ')' => depth -= 1,
+                    _ => (),
+                }
+                inner.push(ch);
+            }
+            parse_args(&mut inner.chars().peekable(), None)
+        }
+        _ => {
+            let mut token = String::new();
+            while let Some(&ch) = chars.peek() {
+                if ch == ',' || ch == ')' {
+                    break;
+                }
+                token.push(ch);
+                chars.next();
+            }
+            let token = token.trim();
+            let arg = match token.parse::<i64>() {
+                Ok(number) => Arg::Number(number),
+                Err(_) => Arg::Word(token.to_string()),
+            };
+            Some(vec![arg])
+        }
+    }
+}
+
+fn skip_whitespace(chars: &mut Peekable<Chars>) {
+    while chars.next_if(|ch| ch.is_whitespace()).is_some() {}
+}
@@ -0,0 +1,566 @@
+use super::text::{char_class, is_before, ordered, Ctx, Text};
+use crate::{
+    BaseEditor, Binding, Command, Key, KeyEvent, KeyOutcome, KeyResult, Keymap, Marks,
+    RegisterContent,
+};
+use nalgebra::Point2;
+
+/// the name of the mark in the marks of the editor
+const MARK: &str = "emacs-mark";
+
+/// The emacs editing profile, where the mark and the point make the region,
+/// the killed texts go into the kill ring of the editor.
+///
+/// The key bindings can be changed with the `keymap_mut`, the emacs commands are bound
+/// as actions, ie: `C-k = @kill-line`, the other actions are passed to the host application.
+#[derive(Clone, Debug)]
+pub struct Emacs {
+    keymap: Keymap,
+    /// the region between the mark and the point is highlighted and is extended by the motions
+    is_mark_active: bool,
+    /// the last key killed a text, so the next kill is appended to it
+    is_last_kill: bool,
+    search: Option<Search>,
+    /// the query of the last incremental search, searched again with `C-s C-s`
+    last_query: String,
+    message: Option<String>,
+}
+
+/// An incremental search in progress
+#[derive(Clone, Debug)]
+struct Search {
+    query: String,
+    is_forward: bool,
+    /// the cursor when the search started, where it goes back to when the search is cancelled
+    origin: Point2<usize>,
+    /// the start of the current match
+    found: Option<Point2<usize>>,
+    /// the query is not found
+    is_failing: bool,
+}
+
+impl Default for Emacs {
+    fn default() -> Self {
+        Self {
+            keymap: Keymap::from_config(EMACS_CONFIG).expect("the emacs keymap must be valid"),
+            is_mark_active: false,
+            is_last_kill: false,
+            search: None,
+            last_query: String::new(),
+            message: None,
+        }
+    }
+}
+
+impl Emacs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn keymap(&self) -> &Keymap {
+        &self.keymap
+    }
+
+    pub fn keymap_mut(&mut self) -> &mut Keymap {
+        &mut self.keymap
+    }
+
+    /// the mark, where x is the column in the editor
+    pub fn mark<XMSG>(&self, editor: &BaseEditor<XMSG>) -> Option<Point2<usize>> {
+        let text = Text {
+            lines: editor.text_buffer().chars(),
+        };
+        mark_index(text, editor.marks()).map(|mark| text.location(mark))
+    }
+
+    pub fn is_mark_active(&self) -> bool {
+        self.is_mark_active
+    }
+
+    /// an incremental search is in progress
+    pub fn is_searching(&self) -> bool {
+        self.search.is_some()
+    }
+
+    /// the message of the last command, ie: `Mark set`
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+
+    /// the text shown in the status line: the incremental search or the message
+    pub fn status(&self) -> String {
+        match &self.search {
+            Some(search) => {
+                let failing = if search.is_failing { "Failing " } else { "" };
+                let backward = if search.is_forward { "" } else { " backward" };
+                format!("{failing}I-search{backward}: {}", search.query)
+            }
+            None => self.message.clone().unwrap_or_default(),
+        }
+    }
+
+    /// stop the search and deactivate the mark, ie: when switching to another profile
+    pub fn reset<XMSG>(&mut self, editor: &mut BaseEditor<XMSG>) {
+        self.keymap.cancel_pending();
+        self.search = None;
+        self.is_last_kill = false;
+        self.message = None;
+        if self.is_mark_active {
+            self.is_mark_active = false;
+            editor.clear_selection();
+        }
+    }
+
+    /// process the key press on the editor
+    pub fn process_key<XMSG>(
+        &mut self,
+        editor: &mut BaseEditor<XMSG>,
+        event: KeyEvent,
+    ) -> KeyOutcome {
+        let mut ctx = Ctx::new(editor);
+        self.process(&mut ctx, event);
+        ctx.outcome
+    }
+
+    fn process<XMSG>(&mut self, ctx: &mut Ctx<XMSG>, event: KeyEvent) {
+        if self.search.is_some() && self.search_key(ctx, event) {
+            return;
+        }
+        if event.key == Key::Char('g') && event.modifiers.ctrl {
+            self.keyboard_quit(ctx);
+            return;
+        }
+        let is_last_kill = std::mem::take(&mut self.is_last_kill);
+        match self.keymap.process_key(event) {
+            KeyResult::Pending => self.is_last_kill = is_last_kill,
+            KeyResult::Bound(Binding::Command(command)) => self.command(ctx, command),
+            KeyResult::Bound(Binding::Action(action)) => self.action(ctx, &action, is_last_kill),
+            KeyResult::Unbound(events) => match events.as_slice() {
+                [event] => {
+                    if let Some(ch) = event.typed_char() {
+                        self.deactivate_mark(ctx);
+                        self.command(ctx, Command::InsertChar(ch));
+                    }
+                }
+                _ => {
+                    let keys: Vec<String> = events
+                        .iter()
+                        .map(|event| event.chord().to_string())
+                        .collect();
+                    self.message = Some(format!("{} is undefined", keys.join(" ")));
+                }
+            },
+        }
+    }
+
+    /// the motions extend the active region, the edits deactivate the mark
+    fn command<XMSG>(&mut self, ctx: &mut Ctx<XMSG>, command: Command) {
+        self.message = None;
+        if ctx.editor.process_command(command) {
+            ctx.outcome.is_content_changed = true;
+            self.deactivate_mark(ctx);
+        } else {
+            self.show_region(ctx);
+        }
+    }
+
+    fn action<XMSG>(&mut self, ctx: &mut Ctx<XMSG>, action: &str, is_last_kill: bool) {
+        self.message = None;
+        match action {
+            "forward-word" => {
+                let target = forward_word(ctx.text(), ctx.cursor());
+                self.move_to(ctx, target);
+            }
+            "backward-word" => {
+                let target = backward_word(ctx.text(), ctx.cursor());
+                self.move_to(ctx, target);
+            }
+            "beginning-of-buffer" => self.move_to(ctx, Point2::new(0, 0)),
+            "end-of-buffer" => {
+                let text = ctx.text();
+                let last = text.last_line();
+                let target = Point2::new(text.line_len(last), last);
+                self.move_to(ctx, target);
+            }
+            "kill-line" => self.kill_line(ctx, is_last_kill),
+            "kill-region" => self.kill_region(ctx, is_last_kill),
+            "copy-region" => {
+                if let Some((start, end)) = self.region(ctx) {
+                    let text = ctx.text().text(start, end);
+                    ctx.editor
+                        .registers_mut()
+                        .kill(RegisterContent::linear(text));
+                }
+                self.deactivate_mark(ctx);
+            }
+            "set-mark" => {
+                set_mark(ctx, ctx.cursor());
+                self.is_mark_active = true;
+                self.message = Some("Mark set".to_string());
+                self.show_region(ctx);
+            }
+            "exchange-point-and-mark" => match mark_index(ctx.text(), ctx.editor.marks()) {
+                Some(mark) => {
+                    set_mark(ctx, ctx.cursor());
+                    self.is_mark_active = true;
+                    ctx.set_cursor(mark);
+                    self.show_region(ctx);
+                }
+                None => self.message = Some("No mark set in this buffer".to_string()),
+            },
+            "isearch-forward" | "isearch-backward" => {
+                self.deactivate_mark(ctx);
+                self.search = Some(Search {
+                    query: String::new(),
+                    is_forward: action == "isearch-forward",
+                    origin: ctx.cursor(),
+                    found: None,
+                    is_failing: false,
+                });
+            }
+            _ => ctx.outcome.actions.push(action.to_string()),
+        }
+    }
+
+    fn move_to<XMSG>(&mut self, ctx: &mut Ctx<XMSG>, target: Point2<usize>) {
+        ctx.set_cursor(target);
+        self.show_region(ctx);
+    }
+
+    /// kill up to the end of the line, or the line break when only whitespaces are left
+    fn kill_line<XMSG>(&mut self, ctx: &mut Ctx<XMSG>, is_last_kill: bool) {
+        let cursor = ctx.cursor();
+        let text = ctx.text();
+        let line_end = Point2::new(text.line_len(cursor.y), cursor.y);
+        let rest = text.text(cursor, line_end);
+        let end = if rest.trim().is_empty() {
+            match text.next(line_end) {
+                Some(next) => next,
+                None if rest.is_empty() => {
+                    self.message = Some("End of buffer".to_string());
+                    return;
+                }
+                None => line_end,
+            }
+        } else {
+            line_end
+        };
+        self.kill(ctx, cursor, end, is_last_kill);
+    }
+
+    fn kill_region<XMSG>(&mut self, ctx: &mut Ctx<XMSG>, is_last_kill: bool) {
+        match self.region(ctx) {
+            Some((start, end)) => self.kill(ctx, start, end, is_last_kill),
+            None if ctx.editor.marks().mark(MARK).is_none() => {
+                self.message = Some("The mark is not set now, so there is no region".to_string())
+            }
+            None => (),
+        }
+    }
+
+    /// remove the text into the kill ring, appending it to the previous kill of the last key
+    fn kill<XMSG>(
+        &mut self,
+        ctx: &mut Ctx<XMSG>,
+        start: Point2<usize>,
+        end: Point2<usize>,
+        is_last_kill: bool,
+    ) {
+        let killed = ctx.text().text(start, end);
+        let registers = ctx.editor.registers_mut();
+        if is_last_kill {
+            registers.kill_append(&killed);
+        } else {
+            registers.kill(RegisterContent::linear(killed));
+        }
+        self.deactivate_mark(ctx);
+        ctx.replace(start, end, "");
+        self.is_last_kill = true;
+    }
+
+    /// the text between the mark and the point, None if it is empty
+    fn region<XMSG>(&self, ctx: &Ctx<XMSG>) -> Option<(Point2<usize>, Point2<usize>)> {
+        let mark = mark_index(ctx.text(), ctx.editor.marks())?;
+        let (start, end) = ordered(mark, ctx.cursor());
+        is_before(start, end).then_some((start, end))
+    }
+
+    /// highlight the active region with the selection of the editor
+    fn show_region<XMSG>(&self, ctx: &mut Ctx<XMSG>) {
+        if !self.is_mark_active {
+            return;
+        }
+        match self.region(ctx) {
+            Some((start, end)) => select(ctx, start, end),
+            None => ctx.editor.clear_selection(),
+        }
+    }
+
+    fn deactivate_mark<XMSG>(&mut self, ctx: &mut Ctx<XMSG>) {
+        if self.is_mark_active {
+            self.is_mark_active = false;
+            ctx.editor.clear_selection();
+        }
+    }
+
+    /// `C-g` cancels the key sequence being typed and deactivates the mark
+    fn keyboard_quit<XMSG>(&mut self, ctx: &mut Ctx<XMSG>) {
+        self.keymap.cancel_pending();
+        self.is_last_kill = false;
+        self.deactivate_mark(ctx);
+        self.message = Some("Quit".to_string());
+    }
+
+    /// process the key in the incremental search, returns false if the key ends the search
+    /// and is to be processed as a normal key
+    fn search_key<XMSG>(&mut self, ctx: &mut Ctx<XMSG>, event: KeyEvent) -> bool {
+        let Some(search) = self.search.as_mut() else {
+            return false;
+        };
+        let is_ctrl = event.modifiers.ctrl && !event.modifiers.alt;
+        match event.key {
+            Key::Char('s') | Key::Char('r') if is_ctrl => {
+                search.is_forward = event.key == Key::Char('s');
+                if search.query.is_empty() {
+                    search.query = self.last_query.clone();
+                    let origin = search.origin;
+                    self.search_from(ctx, origin);
+                } else {
+                    self.search_next(ctx);
+                }
+            }
+            Key::Char('g') if is_ctrl => {
+                let origin = search.origin;
+                self.search = None;
+                ctx.editor.clear_selection();
+                ctx.set_cursor(origin);
+            }
+            Key::Backspace => {
+                search.query.pop();
+                let origin = search.origin;
+                self.search_from(ctx, origin);
+            }
+            Key::Enter | Key::Escape => self.end_search(ctx),
+            _ => match event.typed_char() {
+                Some(ch) => {
+                    search.query.push(ch);
+                    let from = search.found.unwrap_or(search.origin);
+                    self.search_from(ctx, from);
+                }
+                None => {
+                    self.end_search(ctx);
+                    return false;
+                }
+            },
+        }
+        true
+    }
+
+    /// find the next match after the current one, in the direction of the search
+    fn search_next<XMSG>(&mut self, ctx: &mut Ctx<XMSG>) {
+        let Some(search) = &self.search else {
+            return;
+        };
+        let text = ctx.text();
+        let current = search.found.unwrap_or(search.origin);
+        let from = if search.is_forward {
+            Point2::new(current.x + 1, current.y)
+        } else {
+            let last = text.last_line();
+            text.prev(current)
+                .unwrap_or(Point2::new(text.line_len(last), last))
+        };
+        self.search_from(ctx, from);
+    }
+
+    /// find the query starting at this location, wrapping around the end of the content.
+    /// The cursor is moved to the end of the match in a forward search, to the start otherwise
+    fn search_from<XMSG>(&mut self, ctx: &mut Ctx<XMSG>, from: Point2<usize>) {
+        let Some(search) = self.search.as_mut() else {
+            return;
+        };
+        let query: Vec<char> = search.query.chars().collect();
+        if query.is_empty() {
+            search.found = None;
+            search.is_failing = false;
+            ctx.editor.clear_selection();
+            ctx.set_cursor(search.origin);
+            return;
+        }
+        let text = ctx.text();
+        match find(text, &query, clamp(text, from), search.is_forward) {
+            Some(start) => {
+                let end = Point2::new(start.x + query.len(), start.y);
+                search.found = Some(start);
+                search.is_failing = false;
+                let cursor = if search.is_forward { end } else { start };
+                ctx.set_cursor(cursor);
+                select(ctx, start, end);
+            }
+            None => search.is_failing = true,
+        }
+    }
+
+    /// leave the cursor at the match and set the mark where the search started
+    fn end_search<XMSG>(&mut self, ctx: &mut Ctx<XMSG>) {
+        if let Some(search) = self.search.take() {
+            ctx.editor.clear_selection();
+            if !search.query.is_empty() {
+                self.last_query = search.query;
+            }
+            if search.found.is_some() {
+                set_mark(ctx, search.origin);
+                self.message = Some("Mark saved where search started".to_string());
+            }
+        }
+    }
+}
+
+/// the default key bindings of the emacs profile
+const EMACS_CONFIG: &str = r#"
+C-f = MoveRight
+C-b = MoveLeft
+C-n = MoveDown
+C-p = MoveUp
+C-a = MoveLeftStart
+C-e = MoveRightEnd
+M-f = @forward-word
+M-b = @backward-word
+M-< = @beginning-of-buffer
+M-> = @end-of-buffer
+C-d = DeleteForward
+C-k = @kill-line
+C-w = @kill-region
+M-w = @copy-region
+C-y = Yank
+M-y = YankPop
+C-Space = @set-mark
+C-x C-x = @exchange-point-and-mark
+C-s = @isearch-forward
+C-r = @isearch-backward
+C-/ = Undo
+C-_ = Undo
+C-x u = Undo
+C-x C-s = @save
+M-x = @command-palette
+M-g g = @go-to-line
+M-g M-g = @go-to-line
+Tab = IndentForward
+Enter = BreakLine
+Backspace = DeleteBack
+Delete = DeleteForward
+Up = MoveUp
+Down = MoveDown
+Left = MoveLeft
+Right = MoveRight
+Home = MoveLeftStart
+End = MoveRightEnd
+"#;
+
+/// the location within the content
+/// the mark is kept in the marks of the editor, so it follows the edits
+fn set_mark<XMSG>(ctx: &mut Ctx<XMSG>, pos: Point2<usize>) {
+    let loc = ctx.text().location(pos);
+    ctx.editor.marks_mut().set_mark(MARK, loc);
+}
+
+/// the mark, where x is the index of the character in the line
+fn mark_index(text: Text, marks: &Marks) -> Option<Point2<usize>> {
+    let mark = marks.mark(MARK)?;
+    let y = mark.y.min(text.last_line());
+    Some(clamp(text, Point2::new(text.index_at_column(y, mark.x), y)))
+}
+
+fn clamp(text: Text, pos: Point2<usize>) -> Point2<usize> {
+    let y = pos.y.min(text.last_line());
+    Point2::new(pos.x.min(text.line_len(y)), y)
+}
+
+/// select the text from start up to but not including the end location
+fn select<XMSG>(ctx: &mut Ctx<XMSG>, start: Point2<usize>, end: Point2<usize>) {
+    let text = ctx.text();
+    match text.prev(end).filter(|_| is_before(start, end)) {
+        Some(last) => {
+            let start = text.location(start);
+            let last = text.location(last);
+            ctx.editor.set_selection(
+                Point2::new(start.x as i32, start.y as i32),
+                Point2::new(last.x as i32, last.y as i32),
+            );
+        }
+        None => ctx.editor.clear_selection(),
+    }
+}
+
+/// the end of the next word
+fn forward_word(text: Text, from: Point2<usize>) -> Point2<usize> {
+    let is_word = |pos| char_class(text.char_at(pos), false) == 1;
+    let mut pos = from;
+    while !is_word(pos) {
+        match text.next(pos) {
+            Some(next) => pos = next,
+            None => return pos,
+        }
+    }
+    while is_word(pos) {
+        match text.next(pos) {
+            Some(next) => pos = next,
+            None => return pos,
+        }
+    }
+    pos
+}
+
+/// the start of the previous word
+fn backward_word(text: Text, from: Point2<usize>) -> Point2<usize> {
+    let is_word = |pos| char_class(text.char_at(pos), false) == 1;
+    let mut pos = from;
+    while let Some(prev) = text.prev(pos).filter(|prev| !is_word(*prev)) {
+        pos = prev;
+    }
+    while let Some(prev) = text.prev(pos).filter(|prev| is_word(*prev)) {
+        pos = prev;
+    }
+    pos
+}
+
+/// the start of the first match of the query at or after the location in a forward search,
+/// at or before the location otherwise, wrapping around.
+/// The search ignores the case if the query is all in lowercase
+fn find(
+    text: Text,
+    query: &[char],
+    from: Point2<usize>,
+    is_forward: bool,
+) -> Option<Point2<usize>> {
+    let is_ignore_case = !query.iter().any(|ch| ch.is_uppercase());
+    let fold = |ch: char| {
+        if is_ignore_case {
+            ch.to_lowercase().next().unwrap_or(ch)
+        } else {
+            ch
+        }
+    };
+    let query: Vec<char> = query.iter().map(|ch| fold(*ch)).collect();
+    let total = text.total_lines();
+    // the line of the location is visited again at the end for the wrapped around part
+    (0..=total).find_map(|i| {
+        let y = if is_forward {
+            (from.y + i) % total
+        } else {
+            (from.y + total - i % total) % total
+        };
+        let line: Vec<char> = text.line(y).chars().map(fold).collect();
+        let last = line.len().checked_sub(query.len())?;
+        let is_match = |x: &usize| line[*x..].starts_with(&query);
+        let x = match (is_forward, i) {
+            (true, 0) => (from.x..=last).find(is_match),
+            (true, i) if i == total => (0..from.x.min(last + 1)).find(is_match),
+            (true, _) => (0..=last).find(is_match),
+            (false, 0) => (0..=from.x.min(last)).rev().find(is_match),
+            (false, i) if i == total => (from.x + 1..=last).rev().find(is_match),
+            (false, _) => (0..=last).rev().find(is_match),
+        }?;
+        Some(Point2::new(x, y))
+    })
+}
@@ -0,0 +1,873 @@
+use super::text::{char_class, is_before, next_word, ordered, prev_word, word_end, Ctx, Text};
+use crate::{BaseEditor, Command, Key, KeyEvent, KeyOutcome, RegisterContent};
+use nalgebra::Point2;
+use regex::Regex;
+use std::fmt;
+
+/// the text inserted with the tab key in insert mode
+const INDENT: &str = "    ";
+
+/// The modes of the kakoune editing profile
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum KakouneMode {
+    /// the motions select the text, the operators act on the selections
+    #[default]
+    Normal,
+    /// the typed characters are inserted at every selection
+    Insert,
+    /// a regex is typed to select, split, keep or remove the selections
+    Prompt,
+}
+
+impl fmt::Display for KakouneMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            KakouneMode::Normal => write!(f, "NORMAL"),
+            KakouneMode::Insert => write!(f, "INSERT"),
+            KakouneMode::Prompt => write!(f, "PROMPT"),
+        }
+    }
+}
+
+/// The selection first editing profile in the style of kakoune and helix,
+/// where the motions select the text and then the operators act on every selection.
+///
+/// The first selection of the editor is the main selection,
+/// the other selections are the secondary selections of the editor.
+#[derive(Clone, Debug, Default)]
+pub struct Kakoune {
+    mode: KakouneMode,
+    /// what the regex typed in the prompt is used for
+    prompt: Option<Prompt>,
+    input: String,
+    /// the key waiting for a character, ie: `r` replaces the selected characters
+    pending: Option<char>,
+    /// the insert cursors in insert mode sorted top to bottom,
+    /// where x is the index of the character in the line
+    cursors: Vec<Point2<usize>>,
+    /// the index of the main cursor in the insert cursors
+    main_cursor: usize,
+    /// the texts of the last yanked selections
+    yanked: Vec<String>,
+    /// the undo group of a change which continues in insert mode is still open
+    is_group_open: bool,
+    message: Option<String>,
+}
+
+/// What the regex typed in the prompt is used for
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Prompt {
+    /// select the matches inside the selections, `s`
+    Select,
+    /// split the selections on the matches, `S`
+    Split,
+    /// keep the selections which have a match, `alt-k`
+    Keep,
+    /// remove the selections which have a match, `alt-K`
+    Remove,
+}
+
+impl Prompt {
+    fn label(&self) -> &'static str {
+        match self {
+            Prompt::Select => "select",
+            Prompt::Split => "split",
+            Prompt::Keep => "keep matching",
+            Prompt::Remove => "keep not matching",
+        }
+    }
+}
+
+/// A selection where x is the index of the character in the line, both ends are included
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Range {
+    anchor: Point2<usize>,
+    /// the moving end of the selection, where the cursor is
+    head: Point2<usize>,
+}
+
+impl Range {
+    fn new(anchor: Point2<usize>, head: Point2<usize>) -> Self {
+        Self { anchor, head }
+    }
+
+    fn point(pos: Point2<usize>) -> Self {
+        Self::new(pos, pos)
+    }
+
+    fn start(&self) -> Point2<usize> {
+        ordered(self.anchor, self.head).0
+    }
+
+    fn end(&self) -> Point2<usize> {
+        ordered(self.anchor, self.head).1
+    }
+
+    /// the location right after the end of the selection
+    fn end_exclusive(&self, text: Text) -> Point2<usize> {
+        let end = self.end();
+        text.next(end)
+            .unwrap_or(Point2::new((end.x + 1).min(text.line_len(end.y)), end.y))
+    }
+
+    fn content(&self, text: Text) -> String {
+        text.text(self.start(), self.end_exclusive(text))
+    }
+}
+
+impl Kakoune {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn mode(&self) -> KakouneMode {
+        self.mode
+    }
+
+    /// the message of the last command, ie: an invalid regex
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+
+    /// the text shown next to the mode in the status line:
+    /// the regex being typed, the pending key or the message of the last command
+    pub fn status(&self) -> String {
+        match (self.prompt, self.pending, &self.message) {
+            (Some(prompt), _, _) => format!("{}: {}", prompt.label(), self.input),
+            (None, Some(key), _) => key.to_string(),
+            (None, None, Some(message)) => message.clone(),
+            (None, None, None) => String::new(),
+        }
+    }
+
+    /// go back to the normal mode with only the main selection,
+    /// ie: when switching to another profile
+    pub fn reset<XMSG>(&mut self, editor: &mut BaseEditor<XMSG>) {
+        if self.is_group_open {
+            editor.end_group();
+            self.is_group_open = false;
+        }
+        self.mode = KakouneMode::Normal;
+        self.prompt = None;
+        self.input.clear();
+        self.pending = None;
+        self.cursors.clear();
+        self.message = None;
+        editor.clear_selection();
+    }
+
+    /// process the key press on the editor
+    pub fn process_key<XMSG>(
+        &mut self,
+        editor: &mut BaseEditor<XMSG>,
+        event: KeyEvent,
+    ) -> KeyOutcome {
+        let mut ctx = Ctx::new(editor);
+        match self.mode {
+            KakouneMode::Normal => self.normal_key(&mut ctx, event),
+            KakouneMode::Insert => self.insert_key(&mut ctx, event),
+            KakouneMode::Prompt => self.prompt_key(&mut ctx, event),
+        }
+        ctx.outcome
+    }
+
+    fn normal_key<XMSG>(&mut self, ctx: &mut Ctx<XMSG>, event: KeyEvent) {
+        self.message = None;
+        if let Some(key) = self.pending.take() {
+            if let (Some(ch), 'r') = (event.typed_char(), key) {
+                self.replace_chars(ctx, ch);
+            }
+            return;
+        }
+        let key = match event.key {
+            Key::Left => 'h',
+            Key::Down => 'j',
+            Key::Up => 'k',
+            Key::Right => 'l',
+            Key::Char(ch) if !event.modifiers.ctrl && !event.modifiers.meta => ch,
+            _ => return,
+        };
+        if event.modifiers.alt {
+            match key {
+                'k' => self.start_prompt(Prompt::Keep),
+                'K' => self.start_prompt(Prompt::Remove),
+                ';' => self.map_ranges(ctx, |_text, range| Range::new(range.head, range.anchor)),
+                _ => (),
+            }
+            return;
+        }
+        match key {
+            'h' | 'j' | 'k' | 'l' | 'H' | 'J' | 'K' | 'L' => {
+                let is_extend = key.is_uppercase();
+                let direction = key.to_ascii_lowercase();
+                self.map_ranges(ctx, |text, range| {
+                    let head = move_head(text, range.head, direction);
+                    extend_or_select(range, head, head, is_extend)
+                });
+            }
+            'w' | 'W' => self.map_ranges(ctx, |text, range| {
+                let from = word_anchor(text, range.head, true);
+                let target = next_word(text, from, false);
+                let head = text
+                    .prev(target)
+                    .filter(|head| !is_before(*head, from))
+                    .unwrap_or(from);
+                extend_or_select(range, from, head, key == 'W')
+            }),
+            'b' | 'B' => self.map_ranges(ctx, |text, range| {
+                let from = word_anchor(text, range.head, false);
+                let head = prev_word(text, range.head, false);
+                extend_or_select(range, from, head, key == 'B')
+            }),
+            'e' | 'E' => self.map_ranges(ctx, |text, range| {
+                let from = word_anchor(text, range.head, true);
+                let head = word_end(text, range.head, false);
+                extend_or_select(range, from, head, key == 'E')
+            }),
+            'x' => self.map_ranges(ctx, |text, range| {
+                let (start, end) = (range.start(), range.end());
+                let is_whole_lines = start.x == 0 && end.x == line_end(text, end.y);
+                let y = if is_whole_lines && end.y < text.last_line() {
+                    end.y + 1
+                } else {
+                    end.y
+                };
+                Range::new(Point2::new(0, start.y), Point2::new(line_end(text, y), y))
+            }),
+            '%' => {
+                let text = ctx.text();
+                let last = text.last_line();
+                let range = Range::new(Point2::new(0, 0), Point2::new(line_end(text, last), last));
+                set_ranges(ctx, vec![range]);
+            }
+            ';' => self.map_ranges(ctx, |_text, range| Range::point(range.head)),
+            ',' => {
+                let main = ranges(ctx)[0];
+                set_ranges(ctx, vec![main]);
+            }
+            'C' => self.copy_selection_below(ctx),
+            'y' => {
+                self.yank(ctx);
+                self.message = Some(format!("yanked {} selections", self.yanked.len()));
+            }
+            'd' => {
+                self.yank(ctx);
+                self.delete(ctx);
+            }
+            'c' => {
+                self.yank(ctx);
+                ctx.editor.begin_group();
+                self.is_group_open = true;
+                let starts = self.delete(ctx);
+                self.start_insert(ctx, starts);
+            }
+            'p' | 'P' => self.paste(ctx, key == 'P'),
+            'R' => self.replace_with_yanked(ctx),
+            '~' | '`' => {
+                let is_upper = key == '~';
+                self.edit_ranges(ctx, |text, range| {
+                    let content = range.content(text);
+                    if is_upper {
+                        content.to_uppercase()
+                    } else {
+                        content.to_lowercase()
+                    }
+                });
+            }
+            'r' => self.pending = Some('r'),
+            'i' | 'a' => {
+                let (sorted, main) = sorted_ranges(ctx);
+                let text = ctx.text();
+                let cursors = sorted
+                    .iter()
+                    .map(|range| match key {
+                        'i' => range.start(),
+                        _ => range.end_exclusive(text),
+                    })
+                    .collect();
+                ctx.editor.begin_group();
+                self.is_group_open = true;
+                self.main_cursor = main;
+                self.start_insert(ctx, cursors);
+            }
+            'o' | 'O' => self.open_lines(ctx, key == 'O'),
+            'u' | 'U' => {
+                ctx.command(if key == 'u' {
+                    Command::Undo
+                } else {
+                    Command::Redo
+                });
+                let cursor = ctx.cursor();
+                set_ranges(ctx, vec![Range::point(clamp(ctx.text(), cursor))]);
+            }
+            's' => self.start_prompt(Prompt::Select),
+            'S' => self.start_prompt(Prompt::Split),
+            _ => (),
+        }
+    }
+
+    fn insert_key<XMSG>(&mut self, ctx: &mut Ctx<XMSG>, event: KeyEvent) {
+        let text = ctx.text();
+        let spans: Vec<(Point2<usize>, Point2<usize>, String)> = match event.key {
+            Key::Escape => {
+                self.stop_insert(ctx);
+                return;
+            }
+            Key::Enter => self.cursors.iter().map(|c| (*c, *c, "\n".into())).collect(),
+            Key::Tab => self
+                .cursors
+                .iter()
+                .map(|c| (*c, *c, INDENT.into()))
+                .collect(),
+            Key::Backspace => self
+                .cursors
+                .iter()
+                .map(|c| (text.prev(*c).unwrap_or(*c), *c, String::new()))
+                .collect(),
+            Key::Delete => self
+                .cursors
+                .iter()
+                .map(|c| (*c, text.next(*c).unwrap_or(*c), String::new()))
+                .collect(),
+            Key::Left | Key::Right => {
+                let is_left = event.key == Key::Left;
+                self.cursors = self
+                    .cursors
+                    .iter()
+                    .map(|c| {
+                        if is_left {
+                            Point2::new(c.x.saturating_sub(1), c.y)
+                        } else {
+                            Point2::new((c.x + 1).min(text.line_len(c.y)), c.y)
+                        }
+                    })
+                    .collect();
+                self.show_cursors(ctx);
+                return;
+            }
+            _ => match event.typed_char() {
+                Some(ch) => self
+                    .cursors
+                    .iter()
+                    .map(|c| (*c, *c, ch.to_string()))
+                    .collect(),
+                None => return,
+            },
+        };
+        let inserted = edit(ctx, spans);
+        self.cursors = inserted.into_iter().map(|(_start, end)| end).collect();
+        self.cursors.dedup();
+        self.main_cursor = self.main_cursor.min(self.cursors.len().saturating_sub(1));
+        self.show_cursors(ctx);
+    }
+
+    fn prompt_key<XMSG>(&mut self, ctx: &mut Ctx<XMSG>, event: KeyEvent) {
+        match event.key {
+            Key::Escape => self.stop_prompt(),
+            Key::Backspace => {
+                if self.input.pop().is_none() {
+                    self.stop_prompt();
+                }
+            }
+            Key::Enter => {
+                let prompt = self.prompt;
+                let input = std::mem::take(&mut self.input);
+                self.stop_prompt();
+                if let Some(prompt) = prompt {
+                    self.select_with_regex(ctx, prompt, &input);
+                }
+            }
+            _ => {
+                if let Some(ch) = event.typed_char() {
+                    self.input.push(ch);
+                }
+            }
+        }
+    }
+
+    fn start_prompt(&mut self, prompt: Prompt) {
+        self.mode = KakouneMode::Prompt;
+        self.prompt = Some(prompt);
+        self.input.clear();
+    }
+
+    fn stop_prompt(&mut self) {
+        self.mode = KakouneMode::Normal;
+        self.prompt = None;
+    }
+
+    /// change each selection, the selections which end up overlapping are merged
+    fn map_ranges<XMSG>(&mut self, ctx: &mut Ctx<XMSG>, f: impl Fn(Text, Range) -> Range) {
+        let text = ctx.text();
+        let ranges: Vec<Range> = ranges(ctx).into_iter().map(|r| f(text, r)).collect();
+        set_ranges(ctx, ranges);
+    }
+
+    /// replace the text of every selection, which then selects the new text
+    fn edit_ranges<XMSG>(&mut self, ctx: &mut Ctx<XMSG>, f: impl Fn(Text, Range) -> String) {
+        let (sorted, main) = sorted_ranges(ctx);
+        let text = ctx.text();
+        let spans = sorted
+            .iter()
+            .map(|range| (range.start(), range.end_exclusive(text), f(text, *range)))
+            .collect();
+        self.edit_and_select(ctx, spans, main);
+    }
+
+    /// do the edits in one undo group and select the inserted texts
+    fn edit_and_select<XMSG>(
+        &mut self,
+        ctx: &mut Ctx<XMSG>,
+        spans: Vec<(Point2<usize>, Point2<usize>, String)>,
+        main: usize,
+    ) {
+        ctx.editor.begin_group();
+        let inserted = edit(ctx, spans);
+        ctx.editor.end_group();
+        let text = ctx.text();
+        let mut ranges: Vec<Range> = inserted
+            .into_iter()
+            .map(|(start, end)| {
+                match text
+                    .prev(end)
+                    .filter(|last| is_before(start, end) && !is_before(*last, start))
+                {
+                    Some(last) => Range::new(start, last),
+                    None => Range::point(clamp(text, start)),
+                }
+            })
+            .collect();
+        let main = main.min(ranges.len().saturating_sub(1));
+        ranges.rotate_left(main);
+        set_ranges(ctx, ranges);
+    }
+
+    /// put the texts of the selections into the kill ring, joined by line breaks
+    fn yank<XMSG>(&mut self, ctx: &mut Ctx<XMSG>) {
+        let (sorted, _main) = sorted_ranges(ctx);
+        let text = ctx.text();
+        self.yanked = sorted.iter().map(|range| range.content(text)).collect();
+        let content = RegisterContent::linear(self.yanked.join("\n"));
+        ctx.editor.registers_mut().kill(content);
+    }
+
+    /// the text yanked from the selection at this index,
+    /// the last yanked text is used when there are less texts than the selections
+    fn yanked_text<XMSG>(&self, ctx: &Ctx<XMSG>, index: usize) -> String {
+        match self.yanked.get(index).or(self.yanked.last()) {
+            Some(text) => text.clone(),
+            None => ctx
+                .editor
+                .registers()
+                .kill_ring()
+                .next()
+                .map(|content| content.text.clone())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// delete the selected texts, returns where the selections were sorted top to bottom
+    fn delete<XMSG>(&mut self, ctx: &mut Ctx<XMSG>) -> Vec<Point2<usize>> {
+        let (sorted, main) = sorted_ranges(ctx);
+        let text = ctx.text();
+        let spans = sorted
+            .iter()
+            .map(|range| (range.start(), range.end_exclusive(text), String::new()))
+            .collect();
+        ctx.editor.begin_group();
+        let starts: Vec<Point2<usize>> = edit(ctx, spans)
+            .into_iter()
+            .map(|(start, _end)| start)
+            .collect();
+        ctx.editor.end_group();
+        let text = ctx.text();
+        let mut ranges: Vec<Range> = starts
+            .iter()
+            .map(|start| Range::point(clamp(text, *start)))
+            .collect();
+        let main = main.min(ranges.len().saturating_sub(1));
+        ranges.rotate_left(main);
+        self.main_cursor = main;
+        set_ranges(ctx, ranges);
+        starts
+    }
+
+    /// insert the yanked texts after or before the selections, selecting the inserted texts
+    fn paste<XMSG>(&mut self, ctx: &mut Ctx<XMSG>, is_before: bool) {
+        let (sorted, main) = sorted_ranges(ctx);
+        let text = ctx.text();
+        let spans = sorted
+            .iter()
+            .enumerate()
+            .map(|(i, range)| {
+                let loc = if is_before {
+                    range.start()
+                } else {
+                    range.end_exclusive(text)
+                };
+                (loc, loc, self.yanked_text(ctx, i))
+            })
+            .collect();
+        self.edit_and_select(ctx, spans, main);
+    }
+
+    fn replace_with_yanked<XMSG>(&mut self, ctx: &mut Ctx<XMSG>) {
+        let (sorted, main) = sorted_ranges(ctx);
+        let text = ctx.text();
+        let spans = sorted
+            .iter()
+            .enumerate()
+            .map(|(i, range)| {
+                let replacement = self.yanked_text(ctx, i);
+                (range.start(), range.end_exclusive(text), replacement)
+            })
+            .collect();
+        self.edit_and_select(ctx, spans, main);
+    }
+
+    /// replace every selected character with this character, the line breaks are kept
+    fn replace_chars<XMSG>(&mut self, ctx: &mut Ctx<XMSG>, ch: char) {
+        self.edit_ranges(ctx, |text, range| {
+            range
+                .content(text)
+                .chars()
+                .map(|c| if c == '\n' { c } else { ch })
+                .collect()
+        });
+    }
+
+    /// add a new line below or above each selection and insert there
+    fn open_lines<XMSG>(&mut self, ctx: &mut Ctx<XMSG>, is_above: bool) {
+        let (sorted, main) = sorted_ranges(ctx);
+        let text = ctx.text();
+        let spans = sorted
+            .iter()
+            .map(|range| {
+                let loc = if is_above {
+                    Point2::new(0, range.start().y)
+                } else {
+                    let y = range.end().y;
+                    Point2::new(text.line_len(y), y)
+                };
+                (loc, loc, "\n".to_string())
+            })
+            .collect();
+        ctx.editor.begin_group();
+        self.is_group_open = true;
+        let cursors = edit(ctx, spans)
+            .into_iter()
+            .map(|(start, end)| if is_above { start } else { end })
+            .collect();
+        self.main_cursor = main;
+        self.start_insert(ctx, cursors);
+    }
+
+    /// add a selection on the line below the last selection at the same columns
+    fn copy_selection_below<XMSG>(&mut self, ctx: &mut Ctx<XMSG>) {
+        let (sorted, _main) = sorted_ranges(ctx);
+        let text = ctx.text();
+        let Some(last) = sorted.last().copied() else {
+            return;
+        };
+        let height = last.end().y - last.start().y + 1;
+        let shift = |pos: Point2<usize>| {
+            let y = pos.y + height;
+            let column = text.location(pos).x;
+            (y <= text.last_line()).then(|| Point2::new(text.index_at_column(y, column), y))
+        };
+        if let (Some(anchor), Some(head)) = (shift(last.anchor), shift(last.head)) {
+            let mut ranges = ranges(ctx);
+            ranges.insert(0, Range::new(anchor, head));
+            set_ranges(ctx, ranges);
+        }
+    }
+
+    fn start_insert<XMSG>(&mut self, ctx: &mut Ctx<XMSG>, cursors: Vec<Point2<usize>>) {
+        self.mode = KakouneMode::Insert;
+        self.cursors = cursors;
+        self.show_cursors(ctx);
+    }
+
+    /// go back to the normal mode with the selections at the insert cursors
+    fn stop_insert<XMSG>(&mut self, ctx: &mut Ctx<XMSG>) {
+        self.mode = KakouneMode::Normal;
+        if self.is_group_open {
+            ctx.editor.end_group();
+            self.is_group_open = false;
+        }
+        let text = ctx.text();
+        let mut ranges: Vec<Range> = self
+            .cursors
+            .iter()
+            .map(|cursor| Range::point(clamp(text, *cursor)))
+            .collect();
+        let main = self.main_cursor.min(ranges.len().saturating_sub(1));
+        ranges.rotate_left(main);
+        self.cursors.clear();
+        set_ranges(ctx, ranges);
+    }
+
+    /// the main cursor is the cursor of the editor, the others are shown as selections
+    fn show_cursors<XMSG>(&mut self, ctx: &mut Ctx<XMSG>) {
+        let text = ctx.text();
+        let locations: Vec<Point2<i32>> = self
+            .cursors
+            .iter()
+            .map(|cursor| to_column(text, *cursor))
+            .collect();
+        ctx.command(Command::ClearSelection);
+        for (i, loc) in locations.iter().enumerate() {
+            if i != self.main_cursor {
+                ctx.command(Command::AddSelection(*loc, *loc));
+            }
+        }
+        if let Some(main) = self.cursors.get(self.main_cursor).copied() {
+            ctx.set_cursor(main);
+        }
+    }
+
+    /// select the matches, split on the matches, or keep or remove the matching selections
+    fn select_with_regex<XMSG>(&mut self, ctx: &mut Ctx<XMSG>, prompt: Prompt, pattern: &str) {
+        let regex = match Regex::new(pattern) {
+            Ok(regex) => regex,
+            Err(_) => {
+                self.message = Some(format!("invalid regex: {pattern}"));
+                return;
+            }
+        };
+        let text = ctx.text();
+        let mut selected = vec![];
+        for range in ranges(ctx) {
+            let start = range.start();
+            let content = range.content(text);
+            // the char location of the byte offset in the content
+            let location = |offset: usize| advance(text, start, content[..offset].chars().count());
+            match prompt {
+                Prompt::Select => {
+                    for m in regex.find_iter(&content).filter(|m| m.start() < m.end()) {
+                        let last = content[..m.end()].chars().count() - 1;
+                        selected.push(Range::new(location(m.start()), advance(text, start, last)));
+                    }
+                }
+                Prompt::Split => {
+                    let mut offset = 0;
+                    let ends = regex
+                        .find_iter(&content)
+                        .map(|m| (m.start(), m.end()))
+                        .chain([(content.len(), content.len())]);
+                    for (match_start, match_end) in ends {
+                        if match_start > offset {
+                            let last = content[..match_start].chars().count() - 1;
+                            selected.push(Range::new(location(offset), advance(text, start, last)));
+                        }
+                        offset = match_end;
+                    }
+                }
+                Prompt::Keep | Prompt::Remove => {
+                    if regex.is_match(&content) == (prompt == Prompt::Keep) {
+                        selected.push(range);
+                    }
+                }
+            }
+        }
+        if selected.is_empty() {
+            self.message = Some("no selections remaining".to_string());
+        } else {
+            set_ranges(ctx, selected);
+        }
+    }
+}
+
+/// the selections of the editor, the main selection first.
+/// The main selection is at the cursor when the editor has no selection
+fn ranges<XMSG>(ctx: &Ctx<XMSG>) -> Vec<Range> {
+    let text = ctx.text();
+    let to_index = |loc: Point2<i32>| {
+        let y = (loc.y.max(0) as usize).min(text.last_line());
+        Point2::new(text.index_at_column(y, loc.x.max(0) as usize), y)
+    };
+    let selection = ctx.editor.selection();
+    let main = match (selection.start, selection.end) {
+        (Some(start), Some(end)) => Range::new(to_index(start), to_index(end)),
+        _ => Range::point(clamp(text, ctx.cursor())),
+    };
+    let secondary = ctx
+        .editor
+        .secondary_selections()
+        .iter()
+        .filter_map(|selection| {
+            Some(Range::new(
+                to_index(selection.start?),
+                to_index(selection.end?),
+            ))
+        });
+    std::iter::once(main).chain(secondary).collect()
+}
+
+/// the selections sorted top to bottom with the overlapping selections merged,
+/// and the index of the main selection
+fn sorted_ranges<XMSG>(ctx: &Ctx<XMSG>) -> (Vec<Range>, usize) {
+    let mut sorted: Vec<(Range, bool)> = ranges(ctx)
+        .into_iter()
+        .enumerate()
+        .map(|(i, range)| (range, i == 0))
+        .collect();
+    sorted.sort_by_key(|(range, _)| (range.start().y, range.start().x));
+    let mut merged: Vec<(Range, bool)> = vec![];
+    for (range, is_main) in sorted {
+        match merged.last_mut() {
+            Some((last, is_last_main)) if !is_before(last.end(), range.start()) => {
+                let (_, end) = ordered(last.end(), range.end());
+                *last = Range::new(last.start(), end);
+                *is_last_main |= is_main;
+            }
+            _ => merged.push((range, is_main)),
+        }
+    }
+    let main = merged.iter().position(|(_, is_main)| *is_main).unwrap_or(0);
+    (merged.into_iter().map(|(range, _)| range).collect(), main)
+}
+
+/// set the selections of the editor, the first one is the main selection
+fn set_ranges<XMSG>(ctx: &mut Ctx<XMSG>, ranges: Vec<Range>) {
+    let Some(main) = ranges.first().copied() else {
+        return;
+    };
+    let text = ctx.text();
+    let locations: Vec<(Point2<i32>, Point2<i32>)> = ranges
+        .iter()
+        .map(|range| (to_column(text, range.anchor), to_column(text, range.head)))
+        .collect();
+    ctx.command(Command::ClearSelection);
+    for (i, (anchor, head)) in locations.into_iter().enumerate() {
+        if i == 0 {
+            ctx.command(Command::SetSelection(anchor, head));
+        } else {
+            ctx.command(Command::AddSelection(anchor, head));
+        }
+    }
+    ctx.set_cursor(main.head);
+    let (sorted, main) = sorted_ranges(ctx);
+    if sorted.len() < ranges.len() {
+        // some selections are merged
+        let mut merged = sorted;
+        merged.rotate_left(main);
+        set_ranges(ctx, merged);
+    }
+}
+
+/// replace the texts of the spans sorted top to bottom, in one pass from the top.
+/// Returns the start and the end of each inserted text
+fn edit<XMSG>(
+    ctx: &mut Ctx<XMSG>,
+    spans: Vec<(Point2<usize>, Point2<usize>, String)>,
+) -> Vec<(Point2<usize>, Point2<usize>)> {
+    // the spans after an edit are shifted by the edits before it
+    let mut edits: Vec<(Point2<usize>, Point2<usize>)> = vec![];
+    let mut inserted = vec![];
+    for (start, end, replacement) in spans {
+        let (start, end) = edits
+            .iter()
+            .fold((start, end), |(start, end), (old_end, new_end)| {
+                (
+                    shift(start, *old_end, *new_end),
+                    shift(end, *old_end, *new_end),
+                )
+            });
+        ctx.replace(start, end, &replacement);
+        let new_end = text_end(start, &replacement);
+        edits.push((end, new_end));
+        inserted.push((start, new_end));
+    }
+    inserted
+}
+
+/// move the location after an edit which ended at `old_end`, and now ends at `new_end`
+fn shift(pos: Point2<usize>, old_end: Point2<usize>, new_end: Point2<usize>) -> Point2<usize> {
+    if is_before(pos, old_end) {
+        pos
+    } else if pos.y == old_end.y {
+        Point2::new(new_end.x + pos.x - old_end.x, new_end.y)
+    } else {
+        Point2::new(pos.x, pos.y - old_end.y + new_end.y)
+    }
+}
+
+/// the location right after the text inserted at the start
+fn text_end(start: Point2<usize>, text: &str) -> Point2<usize> {
+    match text.rsplit_once('\n') {
+        Some((_, last_line)) => Point2::new(
+            last_line.chars().count(),
+            start.y + text.matches('\n').count(),
+        ),
+        None => Point2::new(start.x + text.chars().count(), start.y),
+    }
+}
+
+/// the location n characters after the start
+fn advance(text: Text, start: Point2<usize>, n: usize) -> Point2<usize> {
+    (0..n).fold(start, |pos, _| text.next(pos).unwrap_or(pos))
+}
+
+fn to_column(text: Text, pos: Point2<usize>) -> Point2<i32> {
+    let loc = text.location(pos);
+    Point2::new(loc.x as i32, loc.y as i32)
+}
+
+/// the last location a selection can be in the line, which is the line break except in the last line
+fn line_end(text: Text, y: usize) -> usize {
+    if y < text.last_line() {
+        text.line_len(y)
+    } else {
+        text.line_len(y).saturating_sub(1)
+    }
+}
+
+fn clamp(text: Text, pos: Point2<usize>) -> Point2<usize> {
+    let y = pos.y.min(text.last_line());
+    Point2::new(pos.x.min(line_end(text, y)), y)
+}
+
+fn move_head(text: Text, head: Point2<usize>, direction: char) -> Point2<usize> {
+    match direction {
+        'h' => Point2::new(head.x.saturating_sub(1), head.y),
+        'l' => clamp(text, Point2::new(head.x + 1, head.y)),
+        _ => {
+            let y = if direction == 'k' {
+                head.y.saturating_sub(1)
+            } else {
+                (head.y + 1).min(text.last_line())
+            };
+            let column = text.location(head).x;
+            clamp(text, Point2::new(text.index_at_column(y, column), y))
+        }
+    }
+}
+
+/// the new selection from the anchor to the head, or the selection extended to the head
+fn extend_or_select(
+    range: Range,
+    anchor: Point2<usize>,
+    head: Point2<usize>,
+    is_extend: bool,
+) -> Range {
+    if is_extend {
+        Range::new(range.anchor, head)
+    } else {
+        Range::new(anchor, head)
+    }
+}
+
+/// where a word selection starts, the character after or before the cursor
+/// when the cursor is at the edge of a word
+fn word_anchor(text: Text, head: Point2<usize>, is_forward: bool) -> Point2<usize> {
+    let class = |pos| char_class(text.char_at(pos), false);
+    let neighbor = if is_forward {
+        text.next(head)
+    } else {
+        text.prev(head)
+    };
+    neighbor
+        .filter(|neighbor| class(*neighbor) != class(head))
+        .unwrap_or(head)
+}
@@ -0,0 +1,302 @@
+use crate::{BaseEditor, Ch, Command, KeyOutcome};
+use nalgebra::Point2;
+
+/// the editor the keys are processed on, and what the keys did
+pub(super) struct Ctx<'a, XMSG> {
+    pub(super) editor: &'a mut BaseEditor<XMSG>,
+    pub(super) outcome: KeyOutcome,
+}
+
+impl<'a, XMSG> Ctx<'a, XMSG> {
+    pub(super) fn new(editor: &'a mut BaseEditor<XMSG>) -> Self {
+        Self {
+            editor,
+            outcome: KeyOutcome::default(),
+        }
+    }
+
+    pub(super) fn command(&mut self, command: Command) {
+        self.outcome.is_content_changed |= self.editor.process_command(command);
+    }
+
+    pub(super) fn text(&self) -> Text<'_> {
+        Text {
+            lines: self.editor.text_buffer().chars(),
+        }
+    }
+
+    /// the cursor, where x is the index of the character in the line
+    pub(super) fn cursor(&self) -> Point2<usize> {
+        let loc = self.editor.get_position();
+        let text = self.text();
+        let y = loc.y.min(text.last_line());
+        Point2::new(text.index_at_column(y, loc.x), y)
+    }
+
+    pub(super) fn set_cursor(&mut self, pos: Point2<usize>) {
+        let loc = self.text().location(pos);
+        self.command(Command::SetPosition(Point2::new(
+            loc.x as i32,
+            loc.y as i32,
+        )));
+    }
+
+    /// keep the cursor on a character, it can only be past the end of the line in insert mode
+    pub(super) fn clamp_cursor(&mut self) {
+        let cursor = self.cursor();
+        let last = self.text().line_len(cursor.y).saturating_sub(1);
+        if cursor.x > last {
+            self.set_cursor(Point2::new(last, cursor.y));
+        }
+    }
+
+    pub(super) fn replace(&mut self, start: Point2<usize>, end: Point2<usize>, text: &str) {
+        let content = self.text();
+        let start = content.location(start);
+        let end = content.location(end);
+        self.command(Command::ReplaceText(start, end, text.to_string()));
+    }
+}
+
+/// The lines of the content where a location is the index of the character in the line,
+/// the location at the length of the line is the line break
+#[derive(Clone, Copy)]
+pub(super) struct Text<'a> {
+    pub(super) lines: &'a [Vec<Ch>],
+}
+
+impl Text<'_> {
+    pub(super) fn total_lines(&self) -> usize {
+        self.lines.len().max(1)
+    }
+
+    pub(super) fn last_line(&self) -> usize {
+        self.total_lines() - 1
+    }
+
+    pub(super) fn line_len(&self, y: usize) -> usize {
+        self.lines.get(y).map_or(0, Vec::len)
+    }
+
+    pub(super) fn line(&self, y: usize) -> String {
+        self.lines
+            .get(y)
+            .map(|line| line.iter().map(|ch| ch.ch).collect())
+            .unwrap_or_default()
+    }
+
+    /// the character at this location, the line break is `\n`
+    pub(super) fn char_at(&self, pos: Point2<usize>) -> Option<char> {
+        let line = self.lines.get(pos.y)?;
+        match line.get(pos.x) {
+            Some(ch) => Some(ch.ch),
+            None if pos.x == line.len() && pos.y + 1 < self.lines.len() => Some('\n'),
+            None => None,
+        }
+    }
+
+    pub(super) fn next(&self, pos: Point2<usize>) -> Option<Point2<usize>> {
+        if pos.x < self.line_len(pos.y) {
+            Some(Point2::new(pos.x + 1, pos.y))
+        } else if pos.y + 1 < self.lines.len() {
+            Some(Point2::new(0, pos.y + 1))
+        } else {
+            None
+        }
+    }
+
+    pub(super) fn prev(&self, pos: Point2<usize>) -> Option<Point2<usize>> {
+        if pos.x > 0 {
+            Some(Point2::new(pos.x - 1, pos.y))
+        } else if pos.y > 0 {
+            Some(Point2::new(self.line_len(pos.y - 1), pos.y - 1))
+        } else {
+            None
+        }
+    }
+
+    /// the text from start up to but not including the end location
+    pub(super) fn text(&self, start: Point2<usize>, end: Point2<usize>) -> String {
+        let mut text = String::new();
+        let mut pos = start;
+        while is_before(pos, end) {
+            match self.char_at(pos) {
+                Some(ch) => text.push(ch),
+                None => break,
+            }
+            match self.next(pos) {
+                Some(next) => pos = next,
+                None => break,
+            }
+        }
+        text
+    }
+
+    /// the location in the editor, where x is the column considering the character widths
+    pub(super) fn location(&self, pos: Point2<usize>) -> Point2<usize> {
+        let x = match self.lines.get(pos.y) {
+            Some(line) => {
+                line.iter().take(pos.x).map(|ch| ch.width).sum::<usize>()
+                    + pos.x.saturating_sub(line.len())
+            }
+            None => pos.x,
+        };
+        Point2::new(x, pos.y)
+    }
+
+    /// the index of the character at this column
+    pub(super) fn index_at_column(&self, y: usize, column: usize) -> usize {
+        let Some(line) = self.lines.get(y) else {
+            return 0;
+        };
+        let mut width = 0;
+        for (i, ch) in line.iter().enumerate() {
+            if width >= column {
+                return i;
+            }
+            width += ch.width;
+        }
+        line.len()
+    }
+
+    pub(super) fn indent(&self, y: usize) -> String {
+        self.line(y)
+            .chars()
+            .take_while(|ch| ch.is_whitespace())
+            .collect()
+    }
+
+    /// the index of the first non blank character of the line
+    pub(super) fn indent_len(&self, y: usize) -> usize {
+        let len = self.indent(y).chars().count();
+        len.min(self.line_len(y).saturating_sub(1))
+    }
+
+    /// the text of the lines including the line breaks
+    pub(super) fn lines_text(&self, top: usize, bottom: usize) -> String {
+        (top..=bottom)
+            .map(|y| format!("{}\n", self.line(y)))
+            .collect()
+    }
+
+    /// the range of the text to remove the lines, including a line break
+    pub(super) fn lines_span(&self, top: usize, bottom: usize) -> (Point2<usize>, Point2<usize>) {
+        if bottom + 1 < self.lines.len() {
+            (Point2::new(0, top), Point2::new(0, bottom + 1))
+        } else if top > 0 {
+            (
+                Point2::new(self.line_len(top - 1), top - 1),
+                Point2::new(self.line_len(bottom), bottom),
+            )
+        } else {
+            (
+                Point2::new(0, top),
+                Point2::new(self.line_len(bottom), bottom),
+            )
+        }
+    }
+}
+
+/// the location `a` is before `b` in the text
+pub(super) fn is_before(a: Point2<usize>, b: Point2<usize>) -> bool {
+    (a.y, a.x) < (b.y, b.x)
+}
+
+pub(super) fn ordered(a: Point2<usize>, b: Point2<usize>) -> (Point2<usize>, Point2<usize>) {
+    if !is_before(b, a) {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+/// 0 for whitespace, 1 for the word characters and 2 for the punctuations.
+/// The WORD, when `big` is set, is separated only by whitespaces
+pub(super) fn char_class(ch: Option<char>, big: bool) -> u8 {
+    match ch {
+        None => 0,
+        Some(ch) if ch.is_whitespace() => 0,
+        Some(_) if big => 1,
+        Some(ch) if ch.is_alphanumeric() || ch == '_' => 1,
+        Some(_) => 2,
+    }
+}
+
+/// the start of the next word, an empty line is also a word
+pub(super) fn next_word(text: Text, from: Point2<usize>, big: bool) -> Point2<usize> {
+    let class = |pos| char_class(text.char_at(pos), big);
+    let start_class = class(from);
+    let mut pos = from;
+    if start_class != 0 {
+        while class(pos) == start_class {
+            match text.next(pos) {
+                Some(next) => pos = next,
+                None => return pos,
+            }
+        }
+    }
+    while class(pos) == 0 {
+        if pos != from && pos.x == 0 && text.line_len(pos.y) == 0 {
+            break;
+        }
+        match text.next(pos) {
+            Some(next) => pos = next,
+            None => return pos,
+        }
+    }
+    pos
+}
+
+/// the start of the word before the cursor, or of the word the cursor is in
+pub(super) fn prev_word(text: Text, from: Point2<usize>, big: bool) -> Point2<usize> {
+    let class = |pos| char_class(text.char_at(pos), big);
+    let Some(mut pos) = text.prev(from) else {
+        return from;
+    };
+    while class(pos) == 0 {
+        if pos.x == 0 && text.line_len(pos.y) == 0 {
+            return pos;
+        }
+        match text.prev(pos) {
+            Some(prev) => pos = prev,
+            None => return pos,
+        }
+    }
+    let word_class = class(pos);
+    while let Some(prev) = text.prev(pos) {
+        if class(prev) != word_class {
+            break;
+        }
+        pos = prev;
+    }
+    pos
+}
+
+/// the last character of the word after the cursor, or of the word the cursor is in
+pub(super) fn word_end(text: Text, from: Point2<usize>, big: bool) -> Point2<usize> {
+    let class = |pos| char_class(text.char_at(pos), big);
+    let Some(mut pos) = text.next(from) else {
+        return from;
+    };
+    while class(pos) == 0 {
+        match text.next(pos) {
+            Some(next) => pos = next,
+            None => return pos,
+        }
+    }
+    end_of_word(text, pos, big)
+}
+
+/// the last character of the word the location is in
+pub(super) fn end_of_word(text: Text, from: Point2<usize>, big: bool) -> Point2<usize> {
+    let class = |pos| char_class(text.char_at(pos), big);
+    let word_class = class(from);
+    let mut pos = from;
+    while let Some(next) = text.next(pos) {
+        if class(next) != word_class {
+            break;
+        }
+        pos = next;
+    }
+    pos
+}
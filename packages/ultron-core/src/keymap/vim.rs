@@ -0,0 +1,1523 @@
+use super::text::{
+    char_class, end_of_word, is_before, next_word, ordered, prev_word, word_end, Ctx, Text,
+};
+use crate::{BaseEditor, Command, Key, KeyEvent, KeyOutcome, RegisterContent, SelectionMode};
+use nalgebra::Point2;
+use regex::RegexBuilder;
+use std::fmt;
+
+/// the number of spaces added or removed with `>` and `<`
+const SHIFT_WIDTH: usize = 4;
+
+/// The modes of the vim editing profile
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VimMode {
+    #[default]
+    Normal,
+    Insert,
+    Visual,
+    VisualLine,
+    /// selecting a rectangular block of text
+    VisualBlock,
+    /// typing an ex command after `:`
+    CommandLine,
+}
+
+impl VimMode {
+    pub fn is_visual(&self) -> bool {
+        matches!(
+            self,
+            VimMode::Visual | VimMode::VisualLine | VimMode::VisualBlock
+        )
+    }
+}
+
+impl fmt::Display for VimMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            VimMode::Normal => "NORMAL",
+            VimMode::Insert => "INSERT",
+            VimMode::Visual => "VISUAL",
+            VimMode::VisualLine => "VISUAL LINE",
+            VimMode::VisualBlock => "VISUAL BLOCK",
+            VimMode::CommandLine => "COMMAND",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// A vim style modal editing profile, the key presses are processed into commands of the editor.
+///
+/// Supported are the operators `d`, `c`, `y`, `>` and `<` with motions, counts and text objects,
+/// the visual modes, `.` repeat, the named registers and the ex commands `:w`, `:q`, `:<line>`
+/// and `:s/pattern/replacement/flags` where the pattern uses the syntax of the `regex` crate.
+#[derive(Clone, Debug, Default)]
+pub struct Vim {
+    mode: VimMode,
+    /// the keys typed so far of an incomplete command, ie: `2d`
+    pending: Vec<char>,
+    /// the key events of the change being typed, including the keys typed in insert mode
+    change_keys: Vec<KeyEvent>,
+    /// the key events of the last change, replayed with `.`
+    last_change: Vec<KeyEvent>,
+    /// the keys typed in insert mode are part of the change being recorded
+    is_recording_insert: bool,
+    /// replaying the last change, where the keys are not recorded again
+    is_repeating: bool,
+    /// the undo group of a change which continues in insert mode is still open
+    is_group_open: bool,
+    /// the other end of the visual selection, the cursor is the moving end
+    visual_start: Point2<usize>,
+    /// the first and last line of the last visual selection, used in the `'<,'>` range
+    visual_lines: (usize, usize),
+    /// the selection mode of the editor before entering the visual block mode
+    selection_mode: Option<SelectionMode>,
+    /// the ex command typed after `:`
+    command_line: String,
+    /// the error of the last ex command
+    message: Option<String>,
+}
+
+impl Vim {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn mode(&self) -> VimMode {
+        self.mode
+    }
+
+    /// the keys typed so far of an incomplete command, ie: `"a2d`
+    pub fn pending_keys(&self) -> String {
+        self.pending.iter().collect()
+    }
+
+    /// the ex command being typed, None if not in the command line mode
+    pub fn command_line(&self) -> Option<&str> {
+        (self.mode == VimMode::CommandLine).then_some(self.command_line.as_str())
+    }
+
+    /// the error of the last ex command
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+
+    /// the text shown next to the mode in the status line:
+    /// the ex command being typed, the pending keys or the error of the last ex command
+    pub fn status(&self) -> String {
+        match (self.mode, &self.message) {
+            (VimMode::CommandLine, _) => format!(":{}", self.command_line),
+            (_, Some(message)) if self.pending.is_empty() => message.clone(),
+            _ => self.pending_keys(),
+        }
+    }
+
+    /// go back to the normal mode, ie: when switching to another profile
+    pub fn reset<XMSG>(&mut self, editor: &mut BaseEditor<XMSG>) {
+        let mut ctx = Ctx::new(editor);
+        if self.is_group_open {
+            ctx.editor.end_group();
+            self.is_group_open = false;
+        }
+        if self.mode.is_visual() {
+            self.exit_visual(&mut ctx);
+        }
+        self.mode = VimMode::Normal;
+        self.pending.clear();
+        self.change_keys.clear();
+        self.is_recording_insert = false;
+    }
+
+    /// process the key press on the editor
+    pub fn process_key<XMSG>(
+        &mut self,
+        editor: &mut BaseEditor<XMSG>,
+        event: KeyEvent,
+    ) -> KeyOutcome {
+        let mut ctx = Ctx::new(editor);
+        self.process(&mut ctx, event);
+        ctx.outcome
+    }
+
+    fn process<XMSG>(&mut self, ctx: &mut Ctx<XMSG>, event: KeyEvent) {
+        match self.mode {
+            VimMode::Insert => self.insert_key(ctx, event),
+            VimMode::CommandLine => self.command_line_key(ctx, event),
+            _ => self.normal_key(ctx, event),
+        }
+    }
+
+    fn insert_key<XMSG>(&mut self, ctx: &mut Ctx<XMSG>, event: KeyEvent) {
+        if self.is_recording_insert && !self.is_repeating {
+            self.change_keys.push(event);
+        }
+        let command = match event.key {
+            Key::Escape => {
+                self.stop_insert(ctx);
+                return;
+            }
+            Key::Enter => Command::BreakLine,
+            Key::Backspace => Command::DeleteBack,
+            Key::Delete => Command::DeleteForward,
+            Key::Tab => Command::IndentForward,
+            Key::Left => Command::MoveLeft,
+            Key::Right => Command::MoveRight,
+            Key::Up => Command::MoveUp,
+            Key::Down => Command::MoveDown,
+            Key::Home => Command::MoveLeftStart,
+            Key::End => Command::MoveRightEnd,
+            _ => match event.typed_char() {
+                Some(ch) => Command::InsertChar(ch),
+                None => return,
+            },
+        };
+        ctx.command(command);
+    }
+
+    /// go back to the normal mode, the cursor moves back onto the last inserted character
+    fn stop_insert<XMSG>(&mut self, ctx: &mut Ctx<XMSG>) {
+        self.mode = VimMode::Normal;
+        if self.is_group_open {
+            ctx.editor.end_group();
+            self.is_group_open = false;
+        }
+        if self.is_recording_insert {
+            self.last_change = std::mem::take(&mut self.change_keys);
+            self.is_recording_insert = false;
+        }
+        let cursor = ctx.cursor();
+        if cursor.x > 0 {
+            ctx.set_cursor(Point2::new(cursor.x - 1, cursor.y));
+        }
+    }
+
+    fn command_line_key<XMSG>(&mut self, ctx: &mut Ctx<XMSG>, event: KeyEvent) {
+        match event.key {
+            Key::Escape => self.mode = VimMode::Normal,
+            Key::Enter => {
+                self.mode = VimMode::Normal;
+                let command_line = std::mem::take(&mut self.command_line);
+                self.execute_ex(ctx, &command_line);
+                ctx.clamp_cursor();
+            }
+            Key::Backspace => {
+                if self.command_line.pop().is_none() {
+                    self.mode = VimMode::Normal;
+                }
+            }
+            _ => {
+                if let Some(ch) = event.typed_char() {
+                    self.command_line.push(ch);
+                }
+            }
+        }
+    }
+
+    fn normal_key<XMSG>(&mut self, ctx: &mut Ctx<XMSG>, event: KeyEvent) {
+        if event.key == Key::Escape {
+            self.pending.clear();
+            self.change_keys.clear();
+            if self.mode.is_visual() {
+                self.exit_visual(ctx);
+            }
+            return;
+        }
+        if event.modifiers.ctrl && self.pending.is_empty() {
+            match event.key {
+                Key::Char('r') => ctx.command(Command::Redo),
+                Key::Char('v') => self.enter_visual(ctx, VimMode::VisualBlock),
+                _ => (),
+            }
+            ctx.clamp_cursor();
+            return;
+        }
+        let Some(key) = normal_char(event) else {
+            return;
+        };
+        self.message = None;
+        self.pending.push(key);
+        self.change_keys.push(event);
+        match parse_command(&self.pending, self.mode.is_visual()) {
+            Parse::Incomplete => (),
+            Parse::Invalid => {
+                self.pending.clear();
+                self.change_keys.clear();
+            }
+            Parse::Done(command) => {
+                self.pending.clear();
+                let keys = std::mem::take(&mut self.change_keys);
+                self.execute(ctx, command, keys);
+            }
+        }
+    }
+
+    /// execute the command, a change is undone in one step and can be repeated with `.`
+    fn execute<XMSG>(&mut self, ctx: &mut Ctx<XMSG>, command: NormalCommand, keys: Vec<KeyEvent>) {
+        let is_visual = self.mode.is_visual();
+        let is_change = !is_visual && command.is_change();
+        let is_grouped = is_change || is_visual;
+        if is_grouped {
+            ctx.editor.begin_group();
+        }
+        if is_visual {
+            self.execute_visual(ctx, command);
+        } else {
+            self.execute_normal(ctx, command);
+        }
+        if is_grouped && self.mode == VimMode::Insert {
+            // the insert mode continues the same change until escape is pressed
+            self.is_group_open = true;
+            if is_change && !self.is_repeating {
+                self.change_keys = keys;
+                self.is_recording_insert = true;
+            }
+        } else {
+            if is_grouped {
+                ctx.editor.end_group();
+            }
+            if is_change && !self.is_repeating {
+                self.last_change = keys;
+            }
+        }
+        if self.mode != VimMode::Insert {
+            ctx.clamp_cursor();
+        }
+    }
+
+    fn execute_normal<XMSG>(&mut self, ctx: &mut Ctx<XMSG>, command: NormalCommand) {
+        let NormalCommand {
+            register,
+            count,
+            action,
+        } = command;
+        let n = count.unwrap_or(1);
+        let line = count.map(|n| n.saturating_sub(1));
+        let cursor = ctx.cursor();
+        match action {
+            Action::Move(motion) => {
+                if let Some(target) = motion_target(ctx.text(), cursor, motion, n, line) {
+                    ctx.set_cursor(target);
+                }
+            }
+            Action::Operate(operator, target) => {
+                if let Some(range) = operator_range(ctx.text(), cursor, operator, target, n, line) {
+                    self.apply_operator(ctx, operator, register, range, 1);
+                }
+            }
+            Action::ReplaceChar(ch) => {
+                let text = ctx.text();
+                if cursor.x + n <= text.line_len(cursor.y) {
+                    let end = Point2::new(cursor.x + n, cursor.y);
+                    ctx.replace(cursor, end, &ch.to_string().repeat(n));
+                    ctx.set_cursor(Point2::new(end.x - 1, end.y));
+                }
+            }
+            Action::Edit(key) => self.edit(ctx, key, register, n),
+            Action::Select(_) => (),
+        }
+    }
+
+    /// the commands of a single key
+    fn edit<XMSG>(&mut self, ctx: &mut Ctx<XMSG>, key: char, register: Option<char>, n: usize) {
+        let cursor = ctx.cursor();
+        let text = ctx.text();
+        let line_len = text.line_len(cursor.y);
+        match key {
+            'i' => self.mode = VimMode::Insert,
+            'a' => {
+                ctx.set_cursor(Point2::new((cursor.x + 1).min(line_len), cursor.y));
+                self.mode = VimMode::Insert;
+            }
+            'I' => {
+                let x = text.indent_len(cursor.y);
+                ctx.set_cursor(Point2::new(x, cursor.y));
+                self.mode = VimMode::Insert;
+            }
+            'A' => {
+                ctx.set_cursor(Point2::new(line_len, cursor.y));
+                self.mode = VimMode::Insert;
+            }
+            'o' => {
+                let indent = text.indent(cursor.y);
+                let end = Point2::new(line_len, cursor.y);
+                ctx.replace(end, end, &format!("\n{indent}"));
+                self.mode = VimMode::Insert;
+            }
+            'O' => {
+                let indent = text.indent(cursor.y);
+                let start = Point2::new(0, cursor.y);
+                ctx.replace(start, start, &format!("{indent}\n"));
+                ctx.set_cursor(Point2::new(indent.chars().count(), cursor.y));
+                self.mode = VimMode::Insert;
+            }
+            'p' | 'P' => paste(ctx, register, n, key == 'P'),
+            'J' => join_lines(ctx, cursor.y, n.max(2) - 1),
+            '~' => {
+                let end = Point2::new((cursor.x + n).min(line_len), cursor.y);
+                change_case(ctx, &[(cursor, end)], '~');
+                ctx.set_cursor(end);
+            }
+            'u' => {
+                for _ in 0..n {
+                    ctx.command(Command::Undo);
+                }
+            }
+            'v' => self.enter_visual(ctx, VimMode::Visual),
+            'V' => self.enter_visual(ctx, VimMode::VisualLine),
+            ':' => {
+                self.mode = VimMode::CommandLine;
+                self.command_line.clear();
+            }
+            '.' => self.repeat(ctx, n),
+            _ => (),
+        }
+    }
+
+    /// replay the keys of the last change, undone in one step
+    fn repeat<XMSG>(&mut self, ctx: &mut Ctx<XMSG>, n: usize) {
+        if self.is_repeating || self.last_change.is_empty() {
+            return;
+        }
+        let keys = self.last_change.clone();
+        self.is_repeating = true;
+        ctx.editor.begin_group();
+        for _ in 0..n {
+            for key in keys.iter() {
+                self.process(ctx, *key);
+            }
+        }
+        ctx.editor.end_group();
+        self.is_repeating = false;
+    }
+
+    fn execute_visual<XMSG>(&mut self, ctx: &mut Ctx<XMSG>, command: NormalCommand) {
+        let NormalCommand {
+            register,
+            count,
+            action,
+        } = command;
+        let n = count.unwrap_or(1);
+        let line = count.map(|n| n.saturating_sub(1));
+        let cursor = ctx.cursor();
+        match action {
+            Action::Move(motion) => {
+                if let Some(target) = motion_target(ctx.text(), cursor, motion, n, line) {
+                    ctx.set_cursor(target);
+                }
+            }
+            Action::Select(object) => {
+                let text = ctx.text();
+                if let Some((start, end)) = object.range(text, cursor) {
+                    let last = text
+                        .prev(end)
+                        .filter(|&last| !is_before(last, start))
+                        .unwrap_or(start);
+                    self.visual_start = start;
+                    ctx.set_cursor(last);
+                }
+            }
+            Action::Operate(operator, _) => {
+                let range = self.visual_range(ctx);
+                self.exit_visual(ctx);
+                self.apply_operator(ctx, operator, register, range, n);
+            }
+            Action::Edit(key) => match key {
+                'o' | 'O' => {
+                    ctx.set_cursor(self.visual_start);
+                    self.visual_start = cursor;
+                }
+                '~' | 'u' | 'U' => {
+                    let range = self.visual_range(ctx);
+                    self.exit_visual(ctx);
+                    let spans = range.spans(ctx.text());
+                    change_case(ctx, &spans, key);
+                    if let Some((start, _)) = spans.first() {
+                        ctx.set_cursor(*start);
+                    }
+                }
+                'J' => {
+                    let (top, bottom) = ordered_lines(self.visual_start.y, cursor.y);
+                    self.exit_visual(ctx);
+                    join_lines(ctx, top, (bottom - top).max(1));
+                }
+                'p' | 'P' => {
+                    let content = register_content(ctx, register);
+                    let range = self.visual_range(ctx);
+                    self.exit_visual(ctx);
+                    if let Some(content) = content {
+                        let spans = range.spans(ctx.text());
+                        for (start, end) in spans.iter().rev() {
+                            ctx.replace(*start, *end, "");
+                        }
+                        if let Some((start, _)) = spans.first() {
+                            ctx.replace(*start, *start, &content.text);
+                        }
+                    }
+                }
+                'v' => self.enter_visual(ctx, VimMode::Visual),
+                'V' => self.enter_visual(ctx, VimMode::VisualLine),
+                ':' => {
+                    self.exit_visual(ctx);
+                    self.mode = VimMode::CommandLine;
+                    self.command_line = "'<,'>".to_string();
+                }
+                _ => (),
+            },
+            Action::ReplaceChar(_) => (),
+        }
+        if self.mode.is_visual() {
+            ctx.clamp_cursor();
+            self.update_selection(ctx);
+        }
+    }
+
+    fn enter_visual<XMSG>(&mut self, ctx: &mut Ctx<XMSG>, mode: VimMode) {
+        if self.mode == mode {
+            self.exit_visual(ctx);
+            return;
+        }
+        if !self.mode.is_visual() {
+            self.visual_start = ctx.cursor();
+        }
+        if mode == VimMode::VisualBlock {
+            self.selection_mode = Some(ctx.editor.selection_mode());
+            ctx.editor.set_selection_mode(SelectionMode::Block);
+        } else if let Some(selection_mode) = self.selection_mode.take() {
+            ctx.editor.set_selection_mode(selection_mode);
+        }
+        self.mode = mode;
+        self.update_selection(ctx);
+    }
+
+    fn exit_visual<XMSG>(&mut self, ctx: &mut Ctx<XMSG>) {
+        self.visual_lines = ordered_lines(self.visual_start.y, ctx.cursor().y);
+        self.mode = VimMode::Normal;
+        ctx.command(Command::ClearSelection);
+        if let Some(selection_mode) = self.selection_mode.take() {
+            ctx.editor.set_selection_mode(selection_mode);
+        }
+    }
+
+    /// show the visual selection in the editor
+    fn update_selection<XMSG>(&self, ctx: &mut Ctx<XMSG>) {
+        let text = ctx.text();
+        let cursor = ctx.cursor();
+        let (start, end) = match self.mode {
+            VimMode::VisualLine => {
+                let (top, bottom) = ordered_lines(self.visual_start.y, cursor.y);
+                let last = text.line_len(bottom).saturating_sub(1);
+                (Point2::new(0, top), Point2::new(last, bottom))
+            }
+            _ => (self.visual_start, cursor),
+        };
+        let start = text.location(start);
+        let end = text.location(end);
+        ctx.command(Command::SetSelection(
+            Point2::new(start.x as i32, start.y as i32),
+            Point2::new(end.x as i32, end.y as i32),
+        ));
+    }
+
+    /// the text selected in the visual mode
+    fn visual_range<XMSG>(&self, ctx: &Ctx<XMSG>) -> OpRange {
+        let text = ctx.text();
+        let cursor = ctx.cursor();
+        match self.mode {
+            VimMode::VisualLine => {
+                let (top, bottom) = ordered_lines(self.visual_start.y, cursor.y);
+                OpRange::Lines(top, bottom)
+            }
+            VimMode::VisualBlock => {
+                let start = text.location(self.visual_start);
+                let end = text.location(cursor);
+                let (top, bottom) = ordered_lines(start.y, end.y);
+                let (left, right) = ordered_lines(start.x, end.x);
+                OpRange::Block {
+                    top,
+                    bottom,
+                    left,
+                    right,
+                }
+            }
+            _ => {
+                let (start, end) = ordered(self.visual_start, cursor);
+                OpRange::Chars(start, text.next(end).unwrap_or(end))
+            }
+        }
+    }
+
+    fn apply_operator<XMSG>(
+        &mut self,
+        ctx: &mut Ctx<XMSG>,
+        operator: Operator,
+        register: Option<char>,
+        range: OpRange,
+        n: usize,
+    ) {
+        let text = ctx.text();
+        let content = range.content(text);
+        let spans = range.spans(text);
+        let (top, bottom) = range.lines();
+        match operator {
+            Operator::Yank => {
+                store(ctx, register, content);
+                if let Some((start, _)) = spans.first() {
+                    ctx.set_cursor(*start);
+                }
+            }
+            Operator::Delete | Operator::Change => {
+                store(ctx, register, content);
+                let is_change = operator == Operator::Change;
+                match range {
+                    OpRange::Lines(top, bottom) if is_change => {
+                        // the indentation of the first line is kept
+                        let text = ctx.text();
+                        let start = Point2::new(text.indent_len(top), top);
+                        let end = Point2::new(text.line_len(bottom), bottom);
+                        ctx.replace(start, end, "");
+                    }
+                    OpRange::Lines(top, bottom) => {
+                        let (start, end) = ctx.text().lines_span(top, bottom);
+                        ctx.replace(start, end, "");
+                        let text = ctx.text();
+                        let y = top.min(text.last_line());
+                        ctx.set_cursor(Point2::new(text.indent_len(y), y));
+                    }
+                    _ => {
+                        for (start, end) in spans.iter().rev() {
+                            ctx.replace(*start, *end, "");
+                        }
+                        if let Some((start, _)) = spans.first() {
+                            ctx.set_cursor(*start);
+                        }
+                    }
+                }
+                if is_change {
+                    self.mode = VimMode::Insert;
+                }
+            }
+            Operator::Indent | Operator::Outdent => {
+                shift_lines(ctx, top, bottom, n, operator == Operator::Indent);
+            }
+        }
+    }
+
+    /// execute the ex command typed after `:`
+    fn execute_ex<XMSG>(&mut self, ctx: &mut Ctx<XMSG>, command_line: &str) {
+        let command_line = command_line.trim();
+        let (range, command) = self.parse_range(ctx, command_line);
+        let write = || "write".to_string();
+        let quit = || "quit".to_string();
+        match command.trim() {
+            "" => {
+                if let Some((_, y)) = range {
+                    let x = ctx.text().indent_len(y);
+                    ctx.set_cursor(Point2::new(x, y));
+                }
+            }
+            "w" | "w!" | "write" => ctx.outcome.actions.push(write()),
+            "q" | "q!" | "quit" => ctx.outcome.actions.push(quit()),
+            "wq" | "wq!" | "x" => ctx.outcome.actions.extend([write(), quit()]),
+            command => match command.strip_prefix('s') {
+                Some(args) if args.starts_with(|c: char| !c.is_alphanumeric() && c != ' ') => {
+                    let cursor_line = ctx.cursor().y;
+                    let range = range.unwrap_or((cursor_line, cursor_line));
+                    self.substitute(ctx, range, args);
+                }
+                _ => self.message = Some(format!("not an editor command: {command_line}")),
+            },
+        }
+    }
+
+    /// the lines of the range at the start of an ex command, ie: `%`, `'<,'>`, `3,$`
+    /// and the rest of the command
+    fn parse_range<'a, XMSG>(
+        &self,
+        ctx: &Ctx<XMSG>,
+        command: &'a str,
+    ) -> (Option<(usize, usize)>, &'a str) {
+        let last_line = ctx.text().last_line();
+        if let Some(rest) = command.strip_prefix('%') {
+            return (Some((0, last_line)), rest);
+        }
+        let address = |s: &'a str| -> (Option<usize>, &'a str) {
+            if let Some(rest) = s.strip_prefix('.') {
+                (Some(ctx.cursor().y), rest)
+            } else if let Some(rest) = s.strip_prefix('$') {
+                (Some(last_line), rest)
+            } else if let Some(rest) = s.strip_prefix("'<") {
+                (Some(self.visual_lines.0), rest)
+            } else if let Some(rest) = s.strip_prefix("'>") {
+                (Some(self.visual_lines.1), rest)
+            } else {
+                let digits = s.chars().take_while(char::is_ascii_digit).count();
+                match s[..digits].parse::<usize>() {
+                    Ok(n) => (Some(n.saturating_sub(1).min(last_line)), &s[digits..]),
+                    Err(_) => (None, s),
+                }
+            }
+        };
+        match address(command) {
+            (Some(first), rest) => match rest.strip_prefix(',') {
+                Some(rest) => {
+                    let (last, rest) = address(rest);
+                    let (first, last) = ordered_lines(first, last.unwrap_or(first));
+                    (Some((first, last)), rest)
+                }
+                None => (Some((first, first)), rest),
+            },
+            (None, rest) => (None, rest),
+        }
+    }
+
+    /// `:s/pattern/replacement/flags` on the lines of the range, undone in one step.
+    /// The flag `g` replaces all the matches in the line, `i` ignores the case
+    fn substitute<XMSG>(&mut self, ctx: &mut Ctx<XMSG>, (top, bottom): (usize, usize), args: &str) {
+        let Some(delimiter) = args.chars().next() else {
+            return;
+        };
+        let parts = split_unescaped(&args[delimiter.len_utf8()..], delimiter);
+        let pattern = parts.first().cloned().unwrap_or_default();
+        let replacement = to_regex_replacement(parts.get(1).map_or("", String::as_str));
+        let flags = parts.get(2).map_or("", String::as_str);
+        let regex = match RegexBuilder::new(&pattern)
+            .case_insensitive(flags.contains('i'))
+            .build()
+        {
+            Ok(regex) => regex,
+            Err(e) => {
+                self.message = Some(e.to_string());
+                return;
+            }
+        };
+        let mut last_changed = None;
+        ctx.editor.begin_group();
+        for y in (top..=bottom).rev() {
+            let text = ctx.text();
+            let line = text.line(y);
+            let replaced = if flags.contains('g') {
+                regex.replace_all(&line, replacement.as_str())
+            } else {
+                regex.replace(&line, replacement.as_str())
+            };
+            if replaced != line {
+                let end = Point2::new(text.line_len(y), y);
+                ctx.replace(Point2::new(0, y), end, &replaced);
+                last_changed.get_or_insert(y);
+            }
+        }
+        ctx.editor.end_group();
+        match last_changed {
+            Some(y) => {
+                let x = ctx.text().indent_len(y);
+                ctx.set_cursor(Point2::new(x, y));
+            }
+            None => self.message = Some(format!("pattern not found: {pattern}")),
+        }
+    }
+}
+
+/// the text an operator is applied to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum OpRange {
+    /// from the start up to but not including the end location
+    Chars(Point2<usize>, Point2<usize>),
+    /// the first and last line
+    Lines(usize, usize),
+    /// the lines and the columns of a rectangular block, the right column is included
+    Block {
+        top: usize,
+        bottom: usize,
+        left: usize,
+        right: usize,
+    },
+}
+
+impl OpRange {
+    fn lines(&self) -> (usize, usize) {
+        match *self {
+            OpRange::Chars(start, end) if end.x == 0 && end.y > start.y => (start.y, end.y - 1),
+            OpRange::Chars(start, end) => (start.y, end.y),
+            OpRange::Lines(top, bottom) => (top, bottom),
+            OpRange::Block { top, bottom, .. } => (top, bottom),
+        }
+    }
+
+    /// the ranges of the characters, one for each line of a block
+    fn spans(&self, text: Text) -> Vec<(Point2<usize>, Point2<usize>)> {
+        match *self {
+            OpRange::Chars(start, end) => vec![(start, end)],
+            OpRange::Lines(top, bottom) => {
+                vec![(
+                    Point2::new(0, top),
+                    Point2::new(text.line_len(bottom), bottom),
+                )]
+            }
+            OpRange::Block {
+                top,
+                bottom,
+                left,
+                right,
+            } => (top..=bottom)
+                .map(|y| {
+                    let start = text.index_at_column(y, left);
+                    let end = text.index_at_column(y, right + 1);
+                    (Point2::new(start, y), Point2::new(end, y))
+                })
+                .collect(),
+        }
+    }
+
+    /// the text put into the register, the lines end with a line break
+    fn content(&self, text: Text) -> RegisterContent {
+        match *self {
+            OpRange::Chars(start, end) => RegisterContent::linear(text.text(start, end)),
+            OpRange::Lines(top, bottom) => RegisterContent::linear(text.lines_text(top, bottom)),
+            OpRange::Block { .. } => {
+                let lines: Vec<String> = self
+                    .spans(text)
+                    .into_iter()
+                    .map(|(start, end)| text.text(start, end))
+                    .collect();
+                RegisterContent::block(lines.join("\n"))
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Operator {
+    Delete,
+    Change,
+    Yank,
+    Indent,
+    Outdent,
+}
+
+impl Operator {
+    fn from_key(key: char) -> Option<Self> {
+        let operator = match key {
+            'd' => Operator::Delete,
+            'c' => Operator::Change,
+            'y' => Operator::Yank,
+            '>' => Operator::Indent,
+            '<' => Operator::Outdent,
+            _ => return None,
+        };
+        Some(operator)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Motion {
+    Left,
+    Right,
+    Up,
+    Down,
+    /// `w`, the flag is set for `W` where the words are only separated by whitespaces
+    NextWord(bool),
+    PrevWord(bool),
+    WordEnd(bool),
+    LineStart,
+    FirstNonBlank,
+    LineEnd,
+    /// `gg`, or the line of the count
+    FirstLine,
+    /// `G`, or the line of the count
+    LastLine,
+    FindChar(char),
+    TillChar(char),
+    FindCharBack(char),
+    TillCharBack(char),
+}
+
+impl Motion {
+    fn is_linewise(&self) -> bool {
+        matches!(
+            self,
+            Motion::Up | Motion::Down | Motion::FirstLine | Motion::LastLine
+        )
+    }
+
+    /// the character at the target is included when used with an operator
+    fn is_inclusive(&self) -> bool {
+        matches!(
+            self,
+            Motion::WordEnd(_) | Motion::FindChar(_) | Motion::TillChar(_)
+        )
+    }
+
+    fn parse(keys: &[char]) -> Parse<Self> {
+        let Some((&key, rest)) = keys.split_first() else {
+            return Parse::Incomplete;
+        };
+        let motion = match key {
+            'h' => Motion::Left,
+            'l' | ' ' => Motion::Right,
+            'k' => Motion::Up,
+            'j' => Motion::Down,
+            'w' => Motion::NextWord(false),
+            'W' => Motion::NextWord(true),
+            'b' => Motion::PrevWord(false),
+            'B' => Motion::PrevWord(true),
+            'e' => Motion::WordEnd(false),
+            'E' => Motion::WordEnd(true),
+            '0' => Motion::LineStart,
+            '^' => Motion::FirstNonBlank,
+            '$' => Motion::LineEnd,
+            'G' => Motion::LastLine,
+            'g' | 'f' | 't' | 'F' | 'T' => {
+                let Some(&arg) = rest.first() else {
+                    return Parse::Incomplete;
+                };
+                match key {
+                    'g' if arg == 'g' => Motion::FirstLine,
+                    'g' => return Parse::Invalid,
+                    'f' => Motion::FindChar(arg),
+                    't' => Motion::TillChar(arg),
+                    'F' => Motion::FindCharBack(arg),
+                    _ => Motion::TillCharBack(arg),
+                }
+            }
+            _ => return Parse::Invalid,
+        };
+        Parse::Done(motion)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct TextObject {
+    /// `i` selects only the inside, `a` includes the delimiters or the surrounding whitespace
+    inner: bool,
+    /// `w`, `W`, a quote or an opening bracket
+    kind: char,
+}
+
+impl TextObject {
+    fn new(inner: bool, key: char) -> Option<Self> {
+        let kind = match key {
+            'w' | 'W' | '"' | '\'' | '`' => key,
+            '(' | ')' | 'b' => '(',
+            '{' | '}' | 'B' => '{',
+            '[' | ']' => '[',
+            '<' | '>' => '<',
+            _ => return None,
+        };
+        Some(Self { inner, kind })
+    }
+
+    /// the range of the text object around the cursor, the end is excluded
+    fn range(&self, text: Text, cursor: Point2<usize>) -> Option<(Point2<usize>, Point2<usize>)> {
+        match self.kind {
+            'w' | 'W' => word_object(text, cursor, self.inner, self.kind == 'W'),
+            '"' | '\'' | '`' => quote_object(text, cursor, self.kind, self.inner),
+            '(' => bracket_object(text, cursor, '(', ')', self.inner),
+            '{' => bracket_object(text, cursor, '{', '}', self.inner),
+            '[' => bracket_object(text, cursor, '[', ']', self.inner),
+            _ => bracket_object(text, cursor, '<', '>', self.inner),
+        }
+    }
+}
+
+/// what the operator is applied to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Target {
+    Motion(Motion),
+    /// the doubled operator applies to the whole lines, ie: `dd`, `yy`
+    Line,
+    Object(TextObject),
+    /// the text selected in the visual mode
+    Selection,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Action {
+    Move(Motion),
+    Operate(Operator, Target),
+    /// `r` followed by the replacement character
+    ReplaceChar(char),
+    /// select the text object in the visual mode
+    Select(TextObject),
+    /// the commands of a single key, ie: `p`, `J`, `o`
+    Edit(char),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct NormalCommand {
+    register: Option<char>,
+    count: Option<usize>,
+    action: Action,
+}
+
+impl NormalCommand {
+    /// the command changes the content and can be repeated with `.`
+    fn is_change(&self) -> bool {
+        match self.action {
+            Action::Operate(operator, _) => operator != Operator::Yank,
+            Action::ReplaceChar(_) => true,
+            Action::Edit(key) => "pPJ~oOiaIA".contains(key),
+            Action::Move(_) | Action::Select(_) => false,
+        }
+    }
+}
+
+enum Parse<T> {
+    Done(T),
+    /// waiting for more keys
+    Incomplete,
+    Invalid,
+}
+
+/// parse the keys typed in the normal or visual mode,
+/// ie: `"a2dw`, `ci(`, `3j`, `fx`
+fn parse_command(keys: &[char], is_visual: bool) -> Parse<NormalCommand> {
+    let mut rest = keys;
+    let mut register = None;
+    if let Some(('"', after)) = rest.split_first().map(|(&key, after)| (key, after)) {
+        let Some((&name, after)) = after.split_first() else {
+            return Parse::Incomplete;
+        };
+        register = Some(name);
+        rest = after;
+    }
+    let (mut count, rest) = split_count(rest);
+    let Some((&key, after)) = rest.split_first() else {
+        return Parse::Incomplete;
+    };
+    let operate = |operator, target| Action::Operate(operator, target);
+    let action = match key {
+        'i' | 'a' if is_visual => match after.first() {
+            None => return Parse::Incomplete,
+            Some(&kind) => match TextObject::new(key == 'i', kind) {
+                Some(object) => Action::Select(object),
+                None => return Parse::Invalid,
+            },
+        },
+        'd' | 'x' | 'X' | 'D' if is_visual => operate(Operator::Delete, Target::Selection),
+        'c' | 's' | 'C' | 'S' if is_visual => operate(Operator::Change, Target::Selection),
+        'y' | 'Y' if is_visual => operate(Operator::Yank, Target::Selection),
+        '>' if is_visual => operate(Operator::Indent, Target::Selection),
+        '<' if is_visual => operate(Operator::Outdent, Target::Selection),
+        '~' | 'u' | 'U' | 'o' | 'O' | 'J' | 'p' | 'P' | 'v' | 'V' | ':' if is_visual => {
+            Action::Edit(key)
+        }
+        'x' => operate(Operator::Delete, Target::Motion(Motion::Right)),
+        'X' => operate(Operator::Delete, Target::Motion(Motion::Left)),
+        'D' => operate(Operator::Delete, Target::Motion(Motion::LineEnd)),
+        'C' => operate(Operator::Change, Target::Motion(Motion::LineEnd)),
+        's' => operate(Operator::Change, Target::Motion(Motion::Right)),
+        'S' => operate(Operator::Change, Target::Line),
+        'Y' => operate(Operator::Yank, Target::Line),
+        'r' => match after.first() {
+            Some(&ch) => Action::ReplaceChar(ch),
+            None => return Parse::Incomplete,
+        },
+        'p' | 'P' | 'J' | '~' | 'u' | 'o' | 'O' | 'i' | 'a' | 'I' | 'A' | 'v' | 'V' | ':' | '.' => {
+            Action::Edit(key)
+        }
+        _ if !is_visual && Operator::from_key(key).is_some() => {
+            let operator = Operator::from_key(key).expect("must be an operator");
+            let (motion_count, after) = split_count(after);
+            if count.is_some() || motion_count.is_some() {
+                count = Some(count.unwrap_or(1) * motion_count.unwrap_or(1));
+            }
+            let target = match after.split_first() {
+                None => return Parse::Incomplete,
+                Some((&next, _)) if next == key => Target::Line,
+                Some((&next, object)) if next == 'i' || next == 'a' => match object.first() {
+                    None => return Parse::Incomplete,
+                    Some(&kind) => match TextObject::new(next == 'i', kind) {
+                        Some(object) => Target::Object(object),
+                        None => return Parse::Invalid,
+                    },
+                },
+                Some(_) => match Motion::parse(after) {
+                    Parse::Done(motion) => Target::Motion(motion),
+                    Parse::Incomplete => return Parse::Incomplete,
+                    Parse::Invalid => return Parse::Invalid,
+                },
+            };
+            Action::Operate(operator, target)
+        }
+        _ => match Motion::parse(rest) {
+            Parse::Done(motion) => Action::Move(motion),
+            Parse::Incomplete => return Parse::Incomplete,
+            Parse::Invalid => return Parse::Invalid,
+        },
+    };
+    Parse::Done(NormalCommand {
+        register,
+        count,
+        action,
+    })
+}
+
+/// the count typed before a command, a leading `0` is the motion to the start of the line
+fn split_count(keys: &[char]) -> (Option<usize>, &[char]) {
+    let digits = keys
+        .iter()
+        .enumerate()
+        .take_while(|(i, key)| key.is_ascii_digit() && (*i > 0 || **key != '0'))
+        .count();
+    let count = keys[..digits].iter().collect::<String>().parse().ok();
+    (count, &keys[digits..])
+}
+
+/// the key typed in the normal mode, the arrow keys are the same as `h`, `j`, `k` and `l`
+fn normal_char(event: KeyEvent) -> Option<char> {
+    if let Some(ch) = event.typed_char() {
+        return Some(ch);
+    }
+    if !event.modifiers.is_empty() && !event.modifiers.shift {
+        return None;
+    }
+    let key = match event.key {
+        Key::Left | Key::Backspace => 'h',
+        Key::Right => 'l',
+        Key::Up => 'k',
+        Key::Down | Key::Enter => 'j',
+        Key::Home => '0',
+        Key::End => '$',
+        Key::Delete => 'x',
+        _ => return None,
+    };
+    Some(key)
+}
+
+fn ordered_lines(a: usize, b: usize) -> (usize, usize) {
+    (a.min(b), a.max(b))
+}
+
+/// the location the cursor moves to with the motion repeated `n` times,
+/// `line` is the line given with the count of `gg` and `G`
+fn motion_target(
+    text: Text,
+    from: Point2<usize>,
+    motion: Motion,
+    n: usize,
+    line: Option<usize>,
+) -> Option<Point2<usize>> {
+    let y = from.y;
+    let target = match motion {
+        Motion::Left => Point2::new(from.x.saturating_sub(n), y),
+        Motion::Right => Point2::new((from.x + n).min(text.line_len(y)), y),
+        // moving past the first or the last line fails, so `dj` on the last line deletes nothing
+        Motion::Up if y == 0 => return None,
+        Motion::Down if y >= text.last_line() => return None,
+        Motion::Up | Motion::Down => {
+            let target_y = if motion == Motion::Up {
+                y.saturating_sub(n)
+            } else {
+                (y + n).min(text.last_line())
+            };
+            let column = text.location(from).x;
+            Point2::new(text.index_at_column(target_y, column), target_y)
+        }
+        Motion::NextWord(big) => (0..n).fold(from, |pos, _| next_word(text, pos, big)),
+        Motion::PrevWord(big) => (0..n).fold(from, |pos, _| prev_word(text, pos, big)),
+        Motion::WordEnd(big) => (0..n).fold(from, |pos, _| word_end(text, pos, big)),
+        Motion::LineStart => Point2::new(0, y),
+        Motion::FirstNonBlank => Point2::new(text.indent_len(y), y),
+        Motion::LineEnd => {
+            let target_y = (y + n - 1).min(text.last_line());
+            Point2::new(text.line_len(target_y), target_y)
+        }
+        Motion::FirstLine | Motion::LastLine => {
+            let default = if motion == Motion::FirstLine {
+                0
+            } else {
+                text.last_line()
+            };
+            let target_y = line.unwrap_or(default).min(text.last_line());
+            Point2::new(text.indent_len(target_y), target_y)
+        }
+        Motion::FindChar(ch) => Point2::new(find_in_line(text, from, ch, n, true)?, y),
+        Motion::TillChar(ch) => Point2::new(find_in_line(text, from, ch, n, true)? - 1, y),
+        Motion::FindCharBack(ch) => Point2::new(find_in_line(text, from, ch, n, false)?, y),
+        Motion::TillCharBack(ch) => Point2::new(find_in_line(text, from, ch, n, false)? + 1, y),
+    };
+    Some(target)
+}
+
+/// the index of the n-th occurence of the character after or before the cursor in the line
+fn find_in_line(
+    text: Text,
+    from: Point2<usize>,
+    ch: char,
+    n: usize,
+    forward: bool,
+) -> Option<usize> {
+    let line = text.lines.get(from.y)?;
+    let mut x = from.x;
+    for _ in 0..n {
+        x = if forward {
+            (x + 1..line.len()).find(|&i| line[i].ch == ch)?
+        } else {
+            (0..x).rev().find(|&i| line[i].ch == ch)?
+        };
+    }
+    Some(x)
+}
+
+/// the text the operator with the motion, text object or the doubled operator is applied to
+fn operator_range(
+    text: Text,
+    cursor: Point2<usize>,
+    operator: Operator,
+    target: Target,
+    n: usize,
+    line: Option<usize>,
+) -> Option<OpRange> {
+    let motion = match target {
+        Target::Line => {
+            let bottom = (cursor.y + n - 1).min(text.last_line());
+            return Some(OpRange::Lines(cursor.y, bottom));
+        }
+        Target::Object(object) => {
+            let (start, end) = object.range(text, cursor)?;
+            return Some(OpRange::Chars(start, end));
+        }
+        Target::Selection => return None,
+        Target::Motion(motion) => motion,
+    };
+    // `cw` on a word changes up to the end of the word like `ce`
+    if let (Operator::Change, Motion::NextWord(big)) = (operator, motion) {
+        if char_class(text.char_at(cursor), big) != 0 {
+            let end = (1..n).fold(end_of_word(text, cursor, big), |pos, _| {
+                word_end(text, pos, big)
+            });
+            return Some(OpRange::Chars(cursor, text.next(end).unwrap_or(end)));
+        }
+    }
+    let target = motion_target(text, cursor, motion, n, line)?;
+    if motion.is_linewise() {
+        let (top, bottom) = ordered_lines(cursor.y, target.y);
+        return Some(OpRange::Lines(top, bottom));
+    }
+    let (start, mut end) = ordered(cursor, target);
+    if motion.is_inclusive() {
+        end = text.next(end).unwrap_or(end);
+    } else if end.y > start.y && (end.x == 0 || matches!(motion, Motion::NextWord(_))) {
+        // an exclusive motion into the next lines stops at the end of the previous line
+        let line_end = Point2::new(text.line_len(end.y - 1), end.y - 1);
+        if is_before(start, line_end) {
+            end = line_end;
+        }
+    }
+    Some(OpRange::Chars(start, end))
+}
+
+/// the word or the whitespaces at the cursor, `aw` also includes the whitespaces after the word
+fn word_object(
+    text: Text,
+    cursor: Point2<usize>,
+    inner: bool,
+    big: bool,
+) -> Option<(Point2<usize>, Point2<usize>)> {
+    let line = text.lines.get(cursor.y)?;
+    if line.is_empty() {
+        return None;
+    }
+    let class = |i: usize| char_class(Some(line[i].ch), big);
+    let x = cursor.x.min(line.len() - 1);
+    let word_class = class(x);
+    let mut start = x;
+    while start > 0 && class(start - 1) == word_class {
+        start -= 1;
+    }
+    let mut end = x + 1;
+    while end < line.len() && class(end) == word_class {
+        end += 1;
+    }
+    if !inner {
+        if word_class == 0 {
+            // the whitespaces and the word after it
+            if end < line.len() {
+                let next_class = class(end);
+                while end < line.len() && class(end) == next_class {
+                    end += 1;
+                }
+            }
+        } else if end < line.len() && class(end) == 0 {
+            while end < line.len() && class(end) == 0 {
+                end += 1;
+            }
+        } else {
+            while start > 0 && class(start - 1) == 0 {
+                start -= 1;
+            }
+        }
+    }
+    Some((Point2::new(start, cursor.y), Point2::new(end, cursor.y)))
+}
+
+/// the quoted text in the line, the cursor is in the quotes or before the quotes
+fn quote_object(
+    text: Text,
+    cursor: Point2<usize>,
+    quote: char,
+    inner: bool,
+) -> Option<(Point2<usize>, Point2<usize>)> {
+    let line = text.lines.get(cursor.y)?;
+    let quotes: Vec<usize> = (0..line.len())
+        .filter(|&i| line[i].ch == quote && (i == 0 || line[i - 1].ch != '\\'))
+        .collect();
+    let (open, close) = quotes
+        .chunks_exact(2)
+        .map(|pair| (pair[0], pair[1]))
+        .find(|&(_, close)| close >= cursor.x)?;
+    let (start, end) = if inner {
+        (open + 1, close)
+    } else {
+        let mut end = close + 1;
+        while end < line.len() && line[end].ch.is_whitespace() {
+            end += 1;
+        }
+        (open, end)
+    };
+    Some((Point2::new(start, cursor.y), Point2::new(end, cursor.y)))
+}
+
+/// the text in the inner most brackets around the cursor
+fn bracket_object(
+    text: Text,
+    cursor: Point2<usize>,
+    open: char,
+    close: char,
+    inner: bool,
+) -> Option<(Point2<usize>, Point2<usize>)> {
+    let mut depth = 0;
+    let mut pos = cursor;
+    let open_pos = loop {
+        match text.char_at(pos) {
+            Some(ch) if ch == close && pos != cursor => depth += 1,
+            Some(ch) if ch == open => {
+                if depth == 0 {
+                    break pos;
+                }
+                depth -= 1;
+            }
+            _ => (),
+        }
+        pos = text.prev(pos)?;
+    };
+    let mut depth = 0;
+    let mut pos = text.next(open_pos)?;
+    let close_pos = loop {
+        match text.char_at(pos) {
+            Some(ch) if ch == open => depth += 1,
+            Some(ch) if ch == close => {
+                if depth == 0 {
+                    break pos;
+                }
+                depth -= 1;
+            }
+            _ => (),
+        }
+        pos = text.next(pos)?;
+    };
+    if inner {
+        Some((text.next(open_pos)?, close_pos))
+    } else {
+        Some((open_pos, text.next(close_pos)?))
+    }
+}
+
+/// put the deleted or yanked text into the named register and the kill ring,
+/// an uppercase register name appends to the register. Nothing is kept with `"_`
+fn store<XMSG>(ctx: &mut Ctx<XMSG>, register: Option<char>, content: RegisterContent) {
+    let registers = ctx.editor.registers_mut();
+    match register {
+        Some('_') => return,
+        Some(name) if name.is_ascii_uppercase() => {
+            let name = name.to_ascii_lowercase();
+            let appended = match registers.get(name) {
+                Some(existing) => RegisterContent::new(
+                    format!("{}{}", existing.text, content.text),
+                    existing.mode,
+                ),
+                None => content.clone(),
+            };
+            registers.set(name, appended);
+        }
+        Some(name) => registers.set(name, content.clone()),
+        None => (),
+    }
+    registers.kill(content);
+}
+
+/// the content of the named register, or the most recent text in the kill ring
+fn register_content<XMSG>(ctx: &Ctx<XMSG>, register: Option<char>) -> Option<RegisterContent> {
+    let registers = ctx.editor.registers();
+    match register {
+        Some(name) => registers.get(name.to_ascii_lowercase()).cloned(),
+        None => registers.kill_ring().next().cloned(),
+    }
+}
+
+/// put the register content after or before the cursor,
+/// a text ending with a line break is put below or above the cursor line
+fn paste<XMSG>(ctx: &mut Ctx<XMSG>, register: Option<char>, n: usize, before: bool) {
+    let Some(content) = register_content(ctx, register) else {
+        return;
+    };
+    let cursor = ctx.cursor();
+    let text = ctx.text();
+    let line_len = text.line_len(cursor.y);
+    if content.mode == SelectionMode::Block {
+        let x = if before || line_len == 0 {
+            cursor.x
+        } else {
+            cursor.x + 1
+        };
+        ctx.set_cursor(Point2::new(x, cursor.y));
+        match register {
+            Some(name) => ctx.command(Command::PasteFromRegister(name.to_ascii_lowercase())),
+            None => ctx.command(Command::Yank),
+        }
+        return;
+    }
+    let pasted = content.text.repeat(n);
+    if pasted.ends_with('\n') {
+        let y = if before { cursor.y } else { cursor.y + 1 };
+        if y < text.lines.len() {
+            let start = Point2::new(0, y);
+            ctx.replace(start, start, &pasted);
+        } else {
+            let end = Point2::new(line_len, cursor.y);
+            ctx.replace(end, end, &format!("\n{}", &pasted[..pasted.len() - 1]));
+        }
+        let x = ctx.text().indent_len(y);
+        ctx.set_cursor(Point2::new(x, y));
+    } else {
+        let x = if before {
+            cursor.x
+        } else {
+            (cursor.x + 1).min(line_len)
+        };
+        let start = Point2::new(x, cursor.y);
+        ctx.replace(start, start, &pasted);
+        // the cursor is on the last pasted character
+        let end = ctx.cursor();
+        if let Some(last) = ctx.text().prev(end) {
+            ctx.set_cursor(last);
+        }
+    }
+}
+
+/// join the line with the `n` lines below it, the indentation of the joined lines is
+/// replaced with a space
+fn join_lines<XMSG>(ctx: &mut Ctx<XMSG>, y: usize, n: usize) {
+    for _ in 0..n {
+        let text = ctx.text();
+        if y + 1 >= text.lines.len() {
+            break;
+        }
+        let line = text.line(y);
+        let line_len = text.line_len(y);
+        let next_indent = text.indent(y + 1).chars().count();
+        let separator = if line_len == 0
+            || line.ends_with(char::is_whitespace)
+            || next_indent == text.line_len(y + 1)
+        {
+            ""
+        } else {
+            " "
+        };
+        let end = Point2::new(line_len, y);
+        ctx.replace(end, Point2::new(next_indent, y + 1), separator);
+        ctx.set_cursor(end);
+    }
+}
+
+/// add or remove the indentation of the lines
+fn shift_lines<XMSG>(ctx: &mut Ctx<XMSG>, top: usize, bottom: usize, n: usize, indent: bool) {
+    for y in top..=bottom {
+        let text = ctx.text();
+        if text.line_len(y) == 0 {
+            continue;
+        }
+        let start = Point2::new(0, y);
+        if indent {
+            ctx.replace(start, start, &" ".repeat(SHIFT_WIDTH * n));
+        } else {
+            let line = text.line(y);
+            let mut width = 0;
+            let removed = line
+                .chars()
+                .take_while(|&ch| {
+                    let is_removed = width < SHIFT_WIDTH * n && (ch == ' ' || ch == '\t');
+                    width += if ch == '\t' { SHIFT_WIDTH } else { 1 };
+                    is_removed
+                })
+                .count();
+            ctx.replace(start, Point2::new(removed, y), "");
+        }
+    }
+    let x = ctx.text().indent_len(top);
+    ctx.set_cursor(Point2::new(x, top));
+}
+
+/// `~` toggles the case, `u` changes into lowercase and `U` into uppercase
+fn change_case<XMSG>(ctx: &mut Ctx<XMSG>, spans: &[(Point2<usize>, Point2<usize>)], key: char) {
+    for &(start, end) in spans {
+        let text = ctx.text().text(start, end);
+        let changed: String = text
+            .chars()
+            .map(|ch| match key {
+                'u' => ch.to_lowercase().collect::<String>(),
+                'U' => ch.to_uppercase().collect(),
+                _ if ch.is_uppercase() => ch.to_lowercase().collect(),
+                _ => ch.to_uppercase().collect(),
+            })
+            .collect();
+        if changed != text {
+            ctx.replace(start, end, &changed);
+        }
+    }
+}
+
+/// split the text on the delimiter which is not escaped with a backslash
+fn split_unescaped(text: &str, delimiter: char) -> Vec<String> {
+    let mut parts = vec![String::new()];
+    let mut chars = text.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => match chars.next() {
+                Some(next) if next == delimiter => parts.last_mut().expect("a part").push(next),
+                Some(next) => {
+                    let part = parts.last_mut().expect("a part");
+                    part.push('\\');
+                    part.push(next);
+                }
+                None => parts.last_mut().expect("a part").push('\\'),
+            },
+            _ if ch == delimiter => parts.push(String::new()),
+            _ => parts.last_mut().expect("a part").push(ch),
+        }
+    }
+    parts
+}
+
+/// convert the vim replacement, where `&` and `\1` are the matched text and the groups,
+/// into the replacement of the `regex` crate
+fn to_regex_replacement(replacement: &str) -> String {
+    let mut converted = String::new();
+    let mut chars = replacement.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => match chars.next() {
+                Some(digit @ '0'..='9') => converted.push_str(&format!("${{{digit}}}")),
+                Some('n') => converted.push('\n'),
+                Some('t') => converted.push('\t'),
+                Some('$') => converted.push_str("$$"),
+                Some(next) => converted.push(next),
+                None => converted.push('\\'),
+            },
+            '&' => converted.push_str("${0}"),
+            '$' => converted.push_str("$$"),
+            _ => converted.push(ch),
+        }
+    }
+    converted
+}
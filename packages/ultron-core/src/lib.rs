@@ -1,16 +1,58 @@
 #![deny(warnings)]
-pub use base_editor::{BaseEditor, Command};
+pub use base_editor::{BaseEditor, Command, CommandResponse};
 pub use base_options::BaseOptions;
+pub use change_delta::ChangeDelta;
+pub use clipboard::{Clipboard, MemoryClipboard, Osc52Clipboard};
+pub use command_registry::{fuzzy_match, CommandEntry, CommandRegistry, FuzzyMatch};
+pub use completion::{
+    filter_completions, is_word_char, word_before, BufferWords, CompletionItem, CompletionKind,
+    CompletionProvider,
+};
+pub use decorations::{Decoration, DecorationId, DecorationKind, Decorations};
+pub use diagnostics::{Diagnostic, Diagnostics, Fix, Severity};
+pub use diff::{Diff, DiffCommand, DiffEditor, DiffHunk, DiffKind, DiffRow};
+pub use edit::Edit;
+pub use folding::{FoldRange, FoldStrategy, Folding};
+pub use formatter::{diff_edits, FormatError, Formatted, Formatter};
+pub use go_to::{GoToTarget, InvalidGoToTarget};
+pub use keymap::{
+    Binding, Emacs, Kakoune, KakouneMode, Key, KeyChord, KeyContext, KeyEvent, KeyOutcome,
+    KeyResult, Keymap, KeymapError, Modifiers, Vim, VimMode,
+};
+pub use line_changes::{LineChange, LineChanges};
+pub use macros::{Macro, Macros};
+pub use marks::Marks;
 pub use nalgebra::Point2;
+pub use registers::{RegisterContent, Registers};
+pub use snippet::{InvalidSnippet, Snippet, SnippetDefinition, SnippetSession, Snippets, TabStop};
+pub use soft_wrap::SoftWrap;
 pub use text_buffer::{Ch, TextBuffer, BLANK_CH};
-pub use text_edit::{Selection, SelectionMode, TextEdit};
-pub use ultron_syntaxes_themes::{Color, Style, TextHighlighter};
+pub use text_edit::{Selection, SelectionMode, TextEdit, TextTransform};
+pub use ultron_syntaxes_themes::{Color, CommentTokens, Style, TextHighlighter};
 
 pub use nalgebra;
 pub use unicode_width;
 
 pub mod base_editor;
 mod base_options;
+mod change_delta;
+mod clipboard;
+mod command_registry;
+mod completion;
+mod decorations;
+mod diagnostics;
+mod diff;
+mod edit;
+mod folding;
+mod formatter;
+mod go_to;
+mod keymap;
+mod line_changes;
+mod macros;
+mod marks;
+mod registers;
+mod snippet;
+mod soft_wrap;
 mod text_buffer;
 mod text_edit;
 pub mod util;
@@ -0,0 +1,232 @@
+use crate::diff::lines_edit;
+use crate::text_edit::Action;
+use crate::{Diff, DiffHunk, DiffKind, Edit, TextBuffer};
+use std::collections::BTreeSet;
+
+/// How a line is changed from the base text
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LineChange {
+    /// the line is not in the base text
+    Added,
+    /// the line is changed from its line in the base text
+    Modified,
+}
+
+impl LineChange {
+    /// the lowercase name, ie: `added`, also used as the class of the gutter marker
+    pub fn name(&self) -> &'static str {
+        match self {
+            LineChange::Added => "added",
+            LineChange::Modified => "modified",
+        }
+    }
+}
+
+/// the line of the base text a line of the content comes from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct LineOrigin {
+    /// None when the line is added
+    base_line: Option<usize>,
+    is_modified: bool,
+}
+
+/// The changes of the lines of the content from a base text, ie: the last saved or committed text.
+/// The lines are compared with the base text once, then they follow the edits of the content
+/// and only the edited lines are compared again
+#[derive(Clone, Debug, Default)]
+pub struct LineChanges {
+    base: Vec<String>,
+    /// the origin of each line of the content
+    origins: Vec<LineOrigin>,
+    /// the lines edited since the last refresh
+    dirty: BTreeSet<usize>,
+}
+
+impl LineChanges {
+    pub fn new(base: &str, buffer: &TextBuffer) -> Self {
+        let mut line_changes = Self {
+            base: TextBuffer::new_from_str(base).lines(),
+            ..Default::default()
+        };
+        line_changes.rebuild(buffer);
+        line_changes
+    }
+
+    pub fn base_text(&self) -> String {
+        self.base.join("\n")
+    }
+
+    /// compare all the lines of the content with the base text
+    pub(crate) fn rebuild(&mut self, buffer: &TextBuffer) {
+        let diff = Diff::from_lines(&self.base, &buffer.lines());
+        self.origins = diff
+            .side_by_side_rows()
+            .into_iter()
+            .filter(|row| row.new_line.is_some())
+            .map(|row| LineOrigin {
+                base_line: row.old_line,
+                is_modified: row.kind == DiffKind::Changed,
+            })
+            .collect();
+        self.dirty.clear();
+    }
+
+    /// move the origins of the lines with the lines which are broken or joined
+    pub(crate) fn apply_action(&mut self, action: &Action) {
+        let added = LineOrigin {
+            base_line: None,
+            is_modified: false,
+        };
+        match *action {
+            Action::Insert(loc, _) | Action::Delete(loc, _) | Action::Replace(loc, _, _) => {
+                self.dirty.insert(loc.y);
+            }
+            // the line moves down when it is broken at its start
+            Action::BreakLine(loc) if loc.x == 0 => {
+                self.insert_origin(loc.y, added);
+            }
+            Action::BreakLine(loc) => {
+                self.insert_origin(loc.y + 1, added);
+                self.dirty.insert(loc.y);
+            }
+            // an empty line joined with the next line takes its origin
+            Action::JoinLine(loc) if loc.x == 0 => {
+                self.remove_origin(loc.y);
+            }
+            Action::JoinLine(loc) => {
+                self.remove_origin(loc.y + 1);
+                self.dirty.insert(loc.y);
+            }
+        }
+    }
+
+    fn insert_origin(&mut self, index: usize, origin: LineOrigin) {
+        if index <= self.origins.len() {
+            self.origins.insert(index, origin);
+        }
+        self.dirty = self
+            .dirty
+            .iter()
+            .map(|&line| if line >= index { line + 1 } else { line })
+            .collect();
+        self.dirty.insert(index);
+    }
+
+    fn remove_origin(&mut self, index: usize) {
+        if index < self.origins.len() {
+            self.origins.remove(index);
+        }
+        self.dirty = self
+            .dirty
+            .iter()
+            .filter(|&&line| line != index)
+            .map(|&line| if line > index { line - 1 } else { line })
+            .collect();
+    }
+
+    /// compare the edited lines with their line in the base text
+    pub(crate) fn refresh(&mut self, buffer: &TextBuffer) {
+        if self.origins.len() != buffer.total_lines() {
+            self.rebuild(buffer);
+            return;
+        }
+        let chars = buffer.chars();
+        for line in std::mem::take(&mut self.dirty) {
+            let Some(origin) = self.origins.get_mut(line) else {
+                continue;
+            };
+            origin.is_modified = origin.base_line.is_some_and(|base_line| {
+                let text: String = chars[line].iter().map(|ch| ch.ch).collect();
+                self.base[base_line] != text
+            });
+        }
+    }
+
+    /// the change of this line, None when it is the same as in the base text
+    pub fn line_change(&self, line: usize) -> Option<LineChange> {
+        let origin = self.origins.get(line)?;
+        match origin.base_line {
+            None => Some(LineChange::Added),
+            Some(_) if origin.is_modified => Some(LineChange::Modified),
+            Some(_) => None,
+        }
+    }
+
+    /// lines of the base text are deleted right before this line
+    pub fn is_deleted_before(&self, line: usize) -> bool {
+        let Some(base_line) = self.origins.get(line).and_then(|origin| origin.base_line) else {
+            return false;
+        };
+        match line.checked_sub(1) {
+            Some(previous) => self.origins[previous]
+                .base_line
+                .is_some_and(|previous| base_line > previous + 1),
+            None => base_line > 0,
+        }
+    }
+
+    /// lines at the end of the base text are deleted after this last line
+    pub fn is_deleted_after(&self, line: usize) -> bool {
+        line + 1 == self.origins.len()
+            && self.origins[line]
+                .base_line
+                .is_some_and(|base_line| base_line + 1 < self.base.len())
+    }
+
+    /// the changed lines of the content with the lines of the base text they replace,
+    /// the old lines of the hunks are the lines of the base text
+    pub fn hunks(&self) -> Vec<DiffHunk> {
+        // the unchanged lines are the anchors between the hunks
+        let anchors = self
+            .origins
+            .iter()
+            .enumerate()
+            .filter(|(_, origin)| !origin.is_modified)
+            .filter_map(|(line, origin)| Some((line, origin.base_line?)))
+            .chain([(self.origins.len(), self.base.len())]);
+        let mut hunks = vec![];
+        let (mut line, mut base_line) = (0, 0);
+        for (anchor_line, anchor_base_line) in anchors {
+            if anchor_line > line || anchor_base_line > base_line {
+                hunks.push(DiffHunk {
+                    old_lines: base_line..anchor_base_line.max(base_line),
+                    new_lines: line..anchor_line,
+                });
+            }
+            (line, base_line) = (anchor_line + 1, anchor_base_line + 1);
+        }
+        hunks
+    }
+
+    /// the hunk containing this line, or the deleted lines right before or after this line
+    pub fn hunk_at(&self, line: usize) -> Option<DiffHunk> {
+        self.hunks().into_iter().find(|hunk| {
+            hunk.new_lines.contains(&line)
+                || (hunk.new_lines.is_empty()
+                    && (hunk.new_lines.start == line || hunk.new_lines.start == line + 1))
+        })
+    }
+
+    /// the edit which puts back the lines of the base text in place of the hunk
+    pub fn revert_edit(&self, hunk: &DiffHunk, buffer: &TextBuffer) -> Option<Edit> {
+        lines_edit(
+            &buffer.lines(),
+            hunk.new_lines.clone(),
+            &self.base[hunk.old_lines.clone()],
+        )
+    }
+
+    /// the lines starting at `line` are the same as these lines of the base text
+    pub(crate) fn set_unchanged(&mut self, line: usize, base_lines: std::ops::Range<usize>) {
+        for (i, base_line) in base_lines.enumerate() {
+            if let Some(origin) = self.origins.get_mut(line + i) {
+                *origin = LineOrigin {
+                    base_line: Some(base_line),
+                    is_modified: false,
+                };
+                self.dirty.remove(&(line + i));
+            }
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use crate::Command;
+use std::collections::BTreeMap;
+
+/// A recorded sequence of commands which can be played back
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Macro {
+    pub commands: Vec<Command>,
+}
+
+impl Macro {
+    pub fn new(commands: impl IntoIterator<Item = Command>) -> Self {
+        Self {
+            commands: commands.into_iter().collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+}
+
+/// The macro being recorded, the last recorded macro and the named macro slots
+#[derive(Clone, Debug, Default)]
+pub struct Macros {
+    /// the slot name and the commands captured so far, None if not recording
+    recording: Option<(Option<String>, Macro)>,
+    /// the last recorded macro, played back when no slot name is given
+    last: Option<Macro>,
+    named: BTreeMap<String, Macro>,
+}
+
+impl Macros {
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    /// start capturing the commands, the macro is saved into the named slot when stopped.
+    /// The macro currently being recorded is discarded
+    pub fn start_recording(&mut self, name: Option<String>) {
+        self.recording = Some((name, Macro::default()));
+    }
+
+    /// stop capturing the commands and save the recorded macro
+    pub fn stop_recording(&mut self) -> Option<&Macro> {
+        let (name, recorded) = self.recording.take()?;
+        match name {
+            Some(name) => {
+                self.named.insert(name.clone(), recorded.clone());
+                self.last = Some(recorded);
+                self.named.get(&name)
+            }
+            None => {
+                self.last = Some(recorded);
+                self.last.as_ref()
+            }
+        }
+    }
+
+    /// capture the command if recording, the macro commands themselves are not captured
+    pub(crate) fn record(&mut self, command: &Command) {
+        if let Some((_name, recording)) = self.recording.as_mut() {
+            if !command.is_macro_command() {
+                recording.commands.push(command.clone());
+            }
+        }
+    }
+
+    /// the last recorded macro
+    pub fn last(&self) -> Option<&Macro> {
+        self.last.as_ref()
+    }
+
+    /// the macro in the named slot, or the last recorded macro if no name is given
+    pub fn get(&self, name: Option<&str>) -> Option<&Macro> {
+        match name {
+            Some(name) => self.named.get(name),
+            None => self.last.as_ref(),
+        }
+    }
+
+    /// put a macro into the named slot, ie: a macro restored by the host application.
+    /// The macro commands are left out as they are when recording,
+    /// so a macro can not play itself back
+    pub fn set(&mut self, name: &str, mut recorded: Macro) {
+        recorded
+            .commands
+            .retain(|command| !command.is_macro_command());
+        self.named.insert(name.to_string(), recorded);
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Macro> {
+        self.named.remove(name)
+    }
+
+    /// the named macros sorted by name
+    pub fn named(&self) -> impl Iterator<Item = (&str, &Macro)> {
+        self.named
+            .iter()
+            .map(|(name, recorded)| (name.as_str(), recorded))
+    }
+
+    pub fn clear(&mut self) {
+        self.recording = None;
+        self.last = None;
+        self.named.clear();
+    }
+}
@@ -0,0 +1,111 @@
+use crate::text_edit::Action;
+use nalgebra::Point2;
+use std::collections::BTreeMap;
+
+/// The marks dropped in the document, which follow their text as it is edited
+#[derive(Clone, Debug, Default)]
+pub struct Marks {
+    /// marks which are set and jumped to by name, ie: vim's `ma` and `'a`
+    named: BTreeMap<String, Point2<usize>>,
+    /// unnamed marks which are cycled through, sorted by location
+    bookmarks: Vec<Point2<usize>>,
+}
+
+impl Marks {
+    /// set the named mark at this location, replacing the old location of the mark
+    pub fn set_mark(&mut self, name: &str, loc: Point2<usize>) {
+        self.named.insert(name.to_string(), loc);
+    }
+
+    /// the location of the named mark
+    pub fn mark(&self, name: &str) -> Option<Point2<usize>> {
+        self.named.get(name).copied()
+    }
+
+    pub fn remove_mark(&mut self, name: &str) -> Option<Point2<usize>> {
+        self.named.remove(name)
+    }
+
+    /// the named marks sorted by name
+    pub fn named_marks(&self) -> impl Iterator<Item = (&str, Point2<usize>)> {
+        self.named.iter().map(|(name, loc)| (name.as_str(), *loc))
+    }
+
+    /// the bookmarks sorted by location
+    pub fn bookmarks(&self) -> &[Point2<usize>] {
+        &self.bookmarks
+    }
+
+    pub fn add_bookmark(&mut self, loc: Point2<usize>) {
+        if let Err(index) = self.bookmarks.binary_search_by(|b| cmp_points(b, &loc)) {
+            self.bookmarks.insert(index, loc);
+        }
+    }
+
+    /// remove the bookmarks on this line if there is any, otherwise add a bookmark at `loc`.
+    /// Returns true if a bookmark is added
+    pub fn toggle_bookmark(&mut self, loc: Point2<usize>) -> bool {
+        if self.has_bookmark_on_line(loc.y) {
+            self.bookmarks.retain(|bookmark| bookmark.y != loc.y);
+            false
+        } else {
+            self.add_bookmark(loc);
+            true
+        }
+    }
+
+    pub fn has_bookmark_on_line(&self, line: usize) -> bool {
+        self.bookmarks.iter().any(|bookmark| bookmark.y == line)
+    }
+
+    /// returns true if there is a named mark or a bookmark on this line
+    pub fn has_mark_on_line(&self, line: usize) -> bool {
+        self.has_bookmark_on_line(line) || self.named.values().any(|loc| loc.y == line)
+    }
+
+    /// the first bookmark after this location, wrapping around to the first bookmark
+    pub fn next_bookmark(&self, loc: Point2<usize>) -> Option<Point2<usize>> {
+        self.bookmarks
+            .iter()
+            .find(|bookmark| cmp_points(bookmark, &loc).is_gt())
+            .or_else(|| self.bookmarks.first())
+            .copied()
+    }
+
+    /// the last bookmark before this location, wrapping around to the last bookmark
+    pub fn prev_bookmark(&self, loc: Point2<usize>) -> Option<Point2<usize>> {
+        self.bookmarks
+            .iter()
+            .rev()
+            .find(|bookmark| cmp_points(bookmark, &loc).is_lt())
+            .or_else(|| self.bookmarks.last())
+            .copied()
+    }
+
+    pub fn clear_bookmarks(&mut self) {
+        self.bookmarks.clear();
+    }
+
+    /// remove all the named marks and bookmarks
+    pub fn clear(&mut self) {
+        self.named.clear();
+        self.bookmarks.clear();
+    }
+
+    /// move the marks along with the text changed by this action
+    pub(crate) fn apply_action(&mut self, action: &Action) {
+        for loc in self.named.values_mut() {
+            *loc = action.shift_point(*loc);
+        }
+        for bookmark in self.bookmarks.iter_mut() {
+            *bookmark = action.shift_point(*bookmark);
+        }
+        // bookmarks in a deleted text could collapse into the same location
+        self.bookmarks.dedup();
+    }
+}
+
+/// compare the points top to bottom, left to right
+fn cmp_points(a: &Point2<usize>, b: &Point2<usize>) -> std::cmp::Ordering {
+    a.y.cmp(&b.y).then(a.x.cmp(&b.x))
+}
@@ -0,0 +1,104 @@
+use crate::SelectionMode;
+use std::collections::{BTreeMap, VecDeque};
+
+/// the maximum number of texts kept in the kill ring
+const KILL_RING_SIZE: usize = 60;
+
+/// A text kept in a register and the selection mode it was taken from
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RegisterContent {
+    pub text: String,
+    /// a text cut or copied from a block selection is pasted back as a block
+    pub mode: SelectionMode,
+}
+
+impl RegisterContent {
+    pub fn new(text: impl Into<String>, mode: SelectionMode) -> Self {
+        Self {
+            text: text.into(),
+            mode,
+        }
+    }
+
+    pub fn linear(text: impl Into<String>) -> Self {
+        Self::new(text, SelectionMode::Linear)
+    }
+
+    pub fn block(text: impl Into<String>) -> Self {
+        Self::new(text, SelectionMode::Block)
+    }
+}
+
+/// An emacs style kill ring of the cut and copied texts, and vim style named registers
+#[derive(Clone, Debug, Default)]
+pub struct Registers {
+    /// the most recent kill is at the front
+    kill_ring: VecDeque<RegisterContent>,
+    /// the index in the kill ring of the last yanked text
+    yank_index: usize,
+    named: BTreeMap<char, RegisterContent>,
+}
+
+impl Registers {
+    /// put the cut or copied text at the front of the kill ring
+    pub fn kill(&mut self, content: RegisterContent) {
+        self.kill_ring.push_front(content);
+        self.kill_ring.truncate(KILL_RING_SIZE);
+        self.yank_index = 0;
+    }
+
+    /// append the text to the most recent kill, ie: the consecutive emacs `C-k`
+    pub fn kill_append(&mut self, text: &str) {
+        match self.kill_ring.front_mut() {
+            Some(content) => content.text.push_str(text),
+            None => self.kill(RegisterContent::linear(text)),
+        }
+        self.yank_index = 0;
+    }
+
+    /// the texts in the kill ring, the most recent first
+    pub fn kill_ring(&self) -> impl Iterator<Item = &RegisterContent> {
+        self.kill_ring.iter()
+    }
+
+    /// the most recent text in the kill ring
+    pub fn yank(&mut self) -> Option<&RegisterContent> {
+        self.yank_index = 0;
+        self.kill_ring.front()
+    }
+
+    /// the next older text in the kill ring after the last yanked text,
+    /// wrapping around to the most recent one
+    pub fn yank_pop(&mut self) -> Option<&RegisterContent> {
+        if self.kill_ring.is_empty() {
+            return None;
+        }
+        self.yank_index = (self.yank_index + 1) % self.kill_ring.len();
+        self.kill_ring.get(self.yank_index)
+    }
+
+    /// set the content of the named register
+    pub fn set(&mut self, name: char, content: RegisterContent) {
+        self.named.insert(name, content);
+    }
+
+    /// the content of the named register
+    pub fn get(&self, name: char) -> Option<&RegisterContent> {
+        self.named.get(&name)
+    }
+
+    pub fn remove(&mut self, name: char) -> Option<RegisterContent> {
+        self.named.remove(&name)
+    }
+
+    /// the named registers sorted by name
+    pub fn named(&self) -> impl Iterator<Item = (char, &RegisterContent)> {
+        self.named.iter().map(|(name, content)| (*name, content))
+    }
+
+    pub fn clear(&mut self) {
+        self.kill_ring.clear();
+        self.yank_index = 0;
+        self.named.clear();
+    }
+}
@@ -0,0 +1,536 @@
+use crate::text_edit::Action;
+use nalgebra::Point2;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use unicode_width::UnicodeWidthChar;
+
+/// A tab stop of a snippet, with the placeholder and its mirrors which are edited together
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TabStop {
+    /// the number of the tab stop, the final tab stop is `0`
+    pub index: usize,
+    /// the start and end of the placeholder followed by its mirrors.
+    /// The locations of a parsed snippet are relative to the start of the snippet
+    pub ranges: Vec<(Point2<usize>, Point2<usize>)>,
+    /// the choices offered at this tab stop, ie: `${1|one,two|}`
+    pub choices: Vec<String>,
+}
+
+impl TabStop {
+    /// the range of the placeholder which is selected
+    pub fn placeholder(&self) -> (Point2<usize>, Point2<usize>) {
+        self.ranges[0]
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvalidSnippet(pub String);
+
+impl fmt::Display for InvalidSnippet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid snippet: {}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidSnippet {}
+
+/// A snippet in the TextMate snippet syntax, ie: `for ${1:item} in ${2:items} {\n\t$0\n}`.
+///
+/// The tab stops are `$1` or `${1}`, the placeholders are `${1:default}`,
+/// the choices are `${1|one,two|}` and the final tab stop is `$0`.
+/// A tab stop used more than once is mirrored
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Snippet {
+    /// the text of the snippet with the placeholders filled with their default
+    text: String,
+    /// the tab stops in the order they are visited, the final tab stop last
+    stops: Vec<TabStop>,
+}
+
+impl Snippet {
+    pub fn parse(body: &str) -> Result<Self, InvalidSnippet> {
+        Self::parse_with_variables(body, |_| None)
+    }
+
+    /// parse the snippet, the variables such as `$TM_CURRENT_LINE` are resolved with `variables`.
+    /// The unknown variables are replaced with their default, ie: `${TM_FILENAME:main.rs}`
+    pub fn parse_with_variables(
+        body: &str,
+        variables: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self, InvalidSnippet> {
+        // the defaults of the placeholders are found first,
+        // so the mirrors before their placeholder are filled with it
+        let mut first = Parser::new(body, &variables, BTreeMap::new());
+        first.parse(false)?;
+        let mut parser = Parser::new(body, &variables, first.defaults());
+        parser.parse(false)?;
+        Ok(parser.into_snippet())
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn stops(&self) -> &[TabStop] {
+        &self.stops
+    }
+
+    /// add the indentation after each line break, ie: the indentation of the line
+    /// the snippet is inserted in
+    pub fn indented(mut self, indent: &str) -> Self {
+        if indent.is_empty() {
+            return self;
+        }
+        let indent_width: usize = indent.chars().map(|ch| ch.width().unwrap_or(0)).sum();
+        self.text = self.text.replace('\n', &format!("\n{indent}"));
+        for stop in self.stops.iter_mut() {
+            for (start, end) in stop.ranges.iter_mut() {
+                for loc in [start, end] {
+                    if loc.y > 0 {
+                        loc.x += indent_width;
+                    }
+                }
+            }
+        }
+        self
+    }
+
+    /// the tab stops located in the content when the snippet is inserted at `loc`
+    pub fn stops_at(&self, loc: Point2<usize>) -> Vec<TabStop> {
+        let translate = |p: Point2<usize>| {
+            if p.y == 0 {
+                Point2::new(loc.x + p.x, loc.y)
+            } else {
+                Point2::new(p.x, loc.y + p.y)
+            }
+        };
+        self.stops
+            .iter()
+            .map(|stop| TabStop {
+                ranges: stop
+                    .ranges
+                    .iter()
+                    .map(|(start, end)| (translate(*start), translate(*end)))
+                    .collect(),
+                ..stop.clone()
+            })
+            .collect()
+    }
+}
+
+/// a range of a tab stop while parsing, with its byte offsets in the text
+struct ParsedRange {
+    start: Point2<usize>,
+    end: Point2<usize>,
+    bytes: (usize, usize),
+}
+
+struct Parser<'a> {
+    chars: Vec<char>,
+    index: usize,
+    text: String,
+    loc: Point2<usize>,
+    ranges: BTreeMap<usize, Vec<ParsedRange>>,
+    /// the tab stops which have a placeholder, the placeholder is put before the mirrors
+    placeholders: BTreeSet<usize>,
+    choices: BTreeMap<usize, Vec<String>>,
+    defaults: BTreeMap<usize, String>,
+    variables: &'a dyn Fn(&str) -> Option<String>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(
+        body: &str,
+        variables: &'a dyn Fn(&str) -> Option<String>,
+        defaults: BTreeMap<usize, String>,
+    ) -> Self {
+        Self {
+            chars: body.chars().collect(),
+            index: 0,
+            text: String::new(),
+            loc: Point2::new(0, 0),
+            ranges: BTreeMap::new(),
+            placeholders: BTreeSet::new(),
+            choices: BTreeMap::new(),
+            defaults,
+            variables,
+        }
+    }
+
+    fn peek(&self, ahead: usize) -> Option<char> {
+        self.chars.get(self.index + ahead).copied()
+    }
+
+    fn push(&mut self, ch: char) {
+        self.text.push(ch);
+        if ch == '\n' {
+            self.loc = Point2::new(0, self.loc.y + 1);
+        } else {
+            self.loc.x += ch.width().unwrap_or(0);
+        }
+    }
+
+    fn push_str(&mut self, text: &str) {
+        text.chars().for_each(|ch| self.push(ch));
+    }
+
+    /// parse until the end of the body, or until the `}` which closes a placeholder
+    fn parse(&mut self, is_nested: bool) -> Result<(), InvalidSnippet> {
+        while let Some(ch) = self.peek(0) {
+            match ch {
+                '}' if is_nested => return Ok(()),
+                '\\' if matches!(self.peek(1), Some('$' | '}' | '\\')) => {
+                    self.push(self.chars[self.index + 1]);
+                    self.index += 2;
+                }
+                '$' => self.parse_dollar()?,
+                _ => {
+                    self.push(ch);
+                    self.index += 1;
+                }
+            }
+        }
+        if is_nested {
+            Err(self.error("unclosed placeholder"))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn parse_dollar(&mut self) -> Result<(), InvalidSnippet> {
+        match self.peek(1) {
+            Some(ch) if ch.is_ascii_digit() => {
+                self.index += 1;
+                let number = self.parse_number();
+                self.add_tab_stop(number);
+            }
+            Some('{') => {
+                self.index += 2;
+                match self.peek(0) {
+                    Some(ch) if ch.is_ascii_digit() => self.parse_braced_tab_stop()?,
+                    Some(ch) if is_variable_start(ch) => self.parse_braced_variable()?,
+                    _ => return Err(self.error("expecting a number or a variable after `${`")),
+                }
+            }
+            Some(ch) if is_variable_start(ch) => {
+                self.index += 1;
+                let name = self.parse_name();
+                let value = (self.variables)(&name).unwrap_or_default();
+                self.push_str(&value);
+            }
+            _ => {
+                self.push('$');
+                self.index += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// `${1}`, `${1:placeholder}` or `${1|one,two|}`
+    fn parse_braced_tab_stop(&mut self) -> Result<(), InvalidSnippet> {
+        let number = self.parse_number();
+        match self.peek(0) {
+            Some('}') => {
+                self.index += 1;
+                self.add_tab_stop(number);
+            }
+            Some(':') => {
+                self.index += 1;
+                let (start, start_byte) = (self.loc, self.text.len());
+                self.parse(true)?;
+                self.index += 1;
+                self.add_range(number, start, start_byte, true);
+            }
+            Some('|') => {
+                self.index += 1;
+                let choices = self.parse_choices()?;
+                let (start, start_byte) = (self.loc, self.text.len());
+                self.push_str(&choices[0]);
+                self.add_range(number, start, start_byte, true);
+                self.choices.insert(number, choices);
+            }
+            _ => return Err(self.error("expecting `}`, `:` or `|` after the tab stop number")),
+        }
+        Ok(())
+    }
+
+    /// `${NAME}` or `${NAME:default}`
+    fn parse_braced_variable(&mut self) -> Result<(), InvalidSnippet> {
+        let name = self.parse_name();
+        let value = (self.variables)(&name);
+        match self.peek(0) {
+            Some('}') => {
+                self.index += 1;
+                self.push_str(&value.unwrap_or_default());
+            }
+            Some(':') => {
+                self.index += 1;
+                match value {
+                    Some(value) => {
+                        self.skip_to_closing_brace()?;
+                        self.push_str(&value);
+                    }
+                    None => {
+                        self.parse(true)?;
+                        self.index += 1;
+                    }
+                }
+            }
+            _ => return Err(self.error("expecting `}` or `:` after the variable name")),
+        }
+        Ok(())
+    }
+
+    /// the choices separated with `,` until `|}`
+    fn parse_choices(&mut self) -> Result<Vec<String>, InvalidSnippet> {
+        let mut choices = vec![String::new()];
+        loop {
+            match (self.peek(0), self.peek(1)) {
+                (Some('|'), Some('}')) => {
+                    self.index += 2;
+                    return Ok(choices);
+                }
+                (Some('\\'), Some(ch @ ('$' | '}' | '\\' | ',' | '|'))) => {
+                    choices.last_mut().expect("has a choice").push(ch);
+                    self.index += 2;
+                }
+                (Some(','), _) => {
+                    choices.push(String::new());
+                    self.index += 1;
+                }
+                (Some(ch), _) => {
+                    choices.last_mut().expect("has a choice").push(ch);
+                    self.index += 1;
+                }
+                (None, _) => return Err(self.error("unclosed choices")),
+            }
+        }
+    }
+
+    /// skip the default of a variable which has a value
+    fn skip_to_closing_brace(&mut self) -> Result<(), InvalidSnippet> {
+        let mut depth = 0;
+        while let Some(ch) = self.peek(0) {
+            self.index += 1;
+            match ch {
+                '\\' => self.index += 1,
+                '{' => depth += 1,
+                '}' if depth == 0 => return Ok(()),
+                '}' => depth -= 1,
+                _ => (),
+            }
+        }
+        Err(self.error("unclosed variable"))
+    }
+
+    fn parse_number(&mut self) -> usize {
+        let mut number = 0;
+        while let Some(digit) = self.peek(0).and_then(|ch| ch.to_digit(10)) {
+            number = number * 10 + digit as usize;
+            self.index += 1;
+        }
+        number
+    }
+
+    fn parse_name(&mut self) -> String {
+        let mut name = String::new();
+        while let Some(ch) = self
+            .peek(0)
+            .filter(|ch| ch.is_ascii_alphanumeric() || *ch == '_')
+        {
+            name.push(ch);
+            self.index += 1;
+        }
+        name
+    }
+
+    /// a tab stop without a placeholder, filled with the default of its placeholder
+    fn add_tab_stop(&mut self, number: usize) {
+        let (start, start_byte) = (self.loc, self.text.len());
+        if let Some(default) = self.defaults.get(&number).cloned() {
+            self.push_str(&default);
+        }
+        self.add_range(number, start, start_byte, false);
+    }
+
+    fn add_range(
+        &mut self,
+        number: usize,
+        start: Point2<usize>,
+        start_byte: usize,
+        is_placeholder: bool,
+    ) {
+        let range = ParsedRange {
+            start,
+            end: self.loc,
+            bytes: (start_byte, self.text.len()),
+        };
+        let ranges = self.ranges.entry(number).or_default();
+        if is_placeholder && self.placeholders.insert(number) {
+            ranges.insert(0, range);
+        } else {
+            ranges.push(range);
+        }
+    }
+
+    /// the text of the placeholders
+    fn defaults(&self) -> BTreeMap<usize, String> {
+        self.ranges
+            .iter()
+            .filter(|(number, _)| self.placeholders.contains(number))
+            .map(|(number, ranges)| {
+                let (start, end) = ranges[0].bytes;
+                (*number, self.text[start..end].to_string())
+            })
+            .collect()
+    }
+
+    fn into_snippet(mut self) -> Snippet {
+        let final_stop = self.ranges.remove(&0);
+        let to_stop =
+            |index: usize, ranges: Vec<ParsedRange>, choices: Option<Vec<String>>| TabStop {
+                index,
+                ranges: ranges
+                    .iter()
+                    .map(|range| (range.start, range.end))
+                    .collect(),
+                choices: choices.unwrap_or_default(),
+            };
+        let mut stops: Vec<TabStop> = std::mem::take(&mut self.ranges)
+            .into_iter()
+            .map(|(index, ranges)| to_stop(index, ranges, self.choices.remove(&index)))
+            .collect();
+        // the final tab stop is at the end of the snippet when it is not specified
+        let final_ranges = final_stop.unwrap_or_else(|| {
+            vec![ParsedRange {
+                start: self.loc,
+                end: self.loc,
+                bytes: (self.text.len(), self.text.len()),
+            }]
+        });
+        stops.push(to_stop(0, final_ranges, self.choices.remove(&0)));
+        Snippet {
+            text: self.text,
+            stops,
+        }
+    }
+
+    fn error(&self, message: &str) -> InvalidSnippet {
+        InvalidSnippet(format!("{message} at {}", self.index))
+    }
+}
+
+fn is_variable_start(ch: char) -> bool {
+    ch.is_ascii_alphabetic() || ch == '_'
+}
+
+/// The tab stops of the snippet being filled in, they follow their text as it is edited
+#[derive(Clone, Debug)]
+pub struct SnippetSession {
+    stops: Vec<TabStop>,
+    current: usize,
+    /// the placeholder of the current tab stop is replaced once something is typed
+    pub(crate) is_placeholder_selected: bool,
+}
+
+impl SnippetSession {
+    pub(crate) fn new(stops: Vec<TabStop>) -> Self {
+        Self {
+            stops,
+            current: 0,
+            is_placeholder_selected: false,
+        }
+    }
+
+    /// the tab stops in the order they are visited, the final tab stop last
+    pub fn stops(&self) -> &[TabStop] {
+        &self.stops
+    }
+
+    pub fn current_stop(&self) -> &TabStop {
+        &self.stops[self.current]
+    }
+
+    /// move to the next or previous tab stop,
+    /// returns false when it is moved to the final tab stop and the session is over
+    pub(crate) fn advance(&mut self, forward: bool) -> bool {
+        self.current = if forward {
+            (self.current + 1).min(self.stops.len() - 1)
+        } else {
+            self.current.saturating_sub(1)
+        };
+        self.current + 1 < self.stops.len()
+    }
+
+    /// the ranges of the current tab stop grow with the text typed at their start,
+    /// so an empty placeholder and its mirrors contain what is typed in them
+    pub(crate) fn apply_action(&mut self, action: &Action) {
+        for (i, stop) in self.stops.iter_mut().enumerate() {
+            for (start, end) in stop.ranges.iter_mut() {
+                let is_typed_at_start = matches!(
+                    *action,
+                    Action::Insert(loc, _) | Action::BreakLine(loc) if loc == *start
+                );
+                if !(i == self.current && is_typed_at_start) {
+                    *start = action.shift_point(*start);
+                }
+                *end = action.shift_point(*end);
+            }
+        }
+    }
+}
+
+/// A snippet which is inserted when its prefix is completed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SnippetDefinition {
+    /// the word typed to insert the snippet, ie: `fn`
+    pub prefix: String,
+    pub body: String,
+    pub description: Option<String>,
+}
+
+/// The snippets of each syntax, ie: `rust` or `python`
+#[derive(Clone, Debug, Default)]
+pub struct Snippets {
+    snippets: BTreeMap<String, Vec<SnippetDefinition>>,
+}
+
+impl Snippets {
+    /// add a snippet for the syntax token, replacing the snippet with the same prefix
+    pub fn register(
+        &mut self,
+        syntax_token: &str,
+        prefix: impl Into<String>,
+        body: impl Into<String>,
+        description: Option<String>,
+    ) {
+        let definition = SnippetDefinition {
+            prefix: prefix.into(),
+            body: body.into(),
+            description,
+        };
+        let snippets = self.snippets.entry(syntax_token.to_string()).or_default();
+        snippets.retain(|snippet| snippet.prefix != definition.prefix);
+        snippets.push(definition);
+    }
+
+    pub fn unregister(&mut self, syntax_token: &str, prefix: &str) {
+        if let Some(snippets) = self.snippets.get_mut(syntax_token) {
+            snippets.retain(|snippet| snippet.prefix != prefix);
+        }
+    }
+
+    /// the snippets of the syntax in the order they are registered
+    pub fn for_syntax(&self, syntax_token: &str) -> &[SnippetDefinition] {
+        self.snippets
+            .get(syntax_token)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    pub fn get(&self, syntax_token: &str, prefix: &str) -> Option<&SnippetDefinition> {
+        self.for_syntax(syntax_token)
+            .iter()
+            .find(|snippet| snippet.prefix == prefix)
+    }
+}
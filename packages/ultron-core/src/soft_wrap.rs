@@ -0,0 +1,173 @@
+use crate::text_edit::Action;
+use crate::{Ch, TextBuffer};
+use std::collections::BTreeSet;
+
+/// the row starts of a line which is not wrapped
+const UNWRAPPED: &[usize] = &[0];
+
+/// Splits the long lines into visual rows which fits in the wrap column
+#[derive(Clone, Debug, Default)]
+pub struct SoftWrap {
+    /// the maximum width of a visual row, None when soft wrap is disabled
+    wrap_column: Option<usize>,
+    /// the start column of each visual row of each line
+    row_starts: Vec<Vec<usize>>,
+    /// the number of visual rows before each line and the total rows at the end,
+    /// not taking the folded lines into account
+    row_offsets: Vec<usize>,
+    /// the lines changed since the rows were last updated
+    changed: BTreeSet<usize>,
+}
+
+impl SoftWrap {
+    pub fn wrap_column(&self) -> Option<usize> {
+        self.wrap_column
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.wrap_column.is_some()
+    }
+
+    /// set the width of the visual rows, None disables the soft wrap
+    pub fn set_wrap_column(&mut self, wrap_column: Option<usize>, text_buffer: &TextBuffer) {
+        self.wrap_column = wrap_column;
+        self.update(text_buffer);
+    }
+
+    /// recompute the visual rows of each line in the text buffer
+    pub fn update(&mut self, text_buffer: &TextBuffer) {
+        self.row_starts = match self.wrap_column {
+            Some(wrap_column) => text_buffer
+                .chars()
+                .iter()
+                .map(|line| wrap_line(line, wrap_column))
+                .collect(),
+            None => vec![],
+        };
+        self.changed.clear();
+        self.update_offsets(0);
+    }
+
+    /// wrap only the lines changed since the last update,
+    /// the row offsets are recomputed from the first changed line
+    pub(crate) fn update_changed_lines(&mut self, text_buffer: &TextBuffer) {
+        let Some(wrap_column) = self.wrap_column else {
+            return;
+        };
+        if self.row_starts.len() != text_buffer.total_lines() {
+            return self.update(text_buffer);
+        }
+        let changed = std::mem::take(&mut self.changed);
+        let Some(first) = changed.first().copied() else {
+            return;
+        };
+        for y in changed {
+            if let (Some(starts), Some(line)) =
+                (self.row_starts.get_mut(y), text_buffer.chars().get(y))
+            {
+                *starts = wrap_line(line, wrap_column);
+            }
+        }
+        self.update_offsets(first);
+    }
+
+    /// keep the visual rows with their lines as lines are inserted or removed,
+    /// the changed lines are wrapped again in `update_changed_lines`
+    pub(crate) fn apply_action(&mut self, action: &Action) {
+        if self.is_enabled() {
+            action.apply_to_lines(&mut self.row_starts, &mut self.changed);
+        }
+    }
+
+    /// recompute the number of rows before the lines from this line onward
+    fn update_offsets(&mut self, from: usize) {
+        let from = from.min(self.row_starts.len());
+        self.row_offsets.truncate(from + 1);
+        if self.row_offsets.is_empty() {
+            self.row_offsets.push(0);
+        }
+        let start = self.row_offsets.len() - 1;
+        let mut total_rows = self.row_offsets[start];
+        for starts in self.row_starts[start..].iter() {
+            total_rows += starts.len();
+            self.row_offsets.push(total_rows);
+        }
+    }
+
+    /// the number of visual rows before this line, the lines past the content are not wrapped
+    pub fn rows_before(&self, line: usize) -> usize {
+        let total_lines = self.row_starts.len();
+        match self.row_offsets.get(line) {
+            Some(offset) => *offset,
+            None => self.row_offsets.last().copied().unwrap_or(0) + line - total_lines,
+        }
+    }
+
+    /// the line displayed at this visual row, not taking the folded lines into account
+    pub fn line_at_row(&self, row: usize) -> usize {
+        let total_lines = self.row_starts.len();
+        let total_rows = self.rows_before(total_lines);
+        if row >= total_rows {
+            total_lines + row - total_rows
+        } else {
+            self.row_offsets.partition_point(|offset| *offset <= row) - 1
+        }
+    }
+
+    /// the start column of each visual row of this line
+    pub fn row_starts(&self, line: usize) -> &[usize] {
+        self.row_starts
+            .get(line)
+            .map(|starts| starts.as_slice())
+            .unwrap_or(UNWRAPPED)
+    }
+
+    /// the number of visual rows this line is displayed in
+    pub fn row_count(&self, line: usize) -> usize {
+        self.row_starts(line).len()
+    }
+
+    /// which visual row of the line this column is displayed in
+    pub fn row_in_line(&self, line: usize, x: usize) -> usize {
+        self.row_starts(line)
+            .iter()
+            .rposition(|start| *start <= x)
+            .unwrap_or(0)
+    }
+
+    /// the column range of each visual row of this line, the end is exclusive
+    pub fn row_ranges(&self, line: usize, line_width: usize) -> Vec<(usize, usize)> {
+        let starts = self.row_starts(line);
+        starts
+            .iter()
+            .enumerate()
+            .map(|(i, start)| (*start, starts.get(i + 1).copied().unwrap_or(line_width)))
+            .collect()
+    }
+}
+
+/// calculate the start column of each visual row of this line,
+/// breaking after the whitespaces whenever possible
+fn wrap_line(line: &[Ch], wrap_column: usize) -> Vec<usize> {
+    let wrap_column = wrap_column.max(1);
+    let mut starts = vec![0];
+    let mut row_start = 0;
+    // the column after the last whitespace in the current row
+    let mut last_break = 0;
+    let mut column = 0;
+    for ch in line {
+        if column + ch.width > row_start + wrap_column && column > row_start {
+            row_start = if last_break > row_start {
+                last_break
+            } else {
+                column
+            };
+            starts.push(row_start);
+        }
+        column += ch.width;
+        if ch.ch.is_whitespace() {
+            last_break = column;
+        }
+    }
+    starts
+}
@@ -204,6 +204,30 @@ impl TextBuffer {
             .join("\n")
     }
 
+    /// get the text from start up to but not including the end position.
+    /// Unlike `get_text_in_linear_mode`, the end position is exclusive, which makes it possible
+    /// to express an empty range and a range that includes the line ending.
+    pub fn get_text(&self, start: Point2<usize>, end: Point2<usize>) -> String {
+        let end_y = end.y.min(self.total_lines().saturating_sub(1));
+        (start.y..=end_y)
+            .map(|y| {
+                let line = &self.chars[y];
+                let from = if y == start.y {
+                    self.char_index_at(start.x, y)
+                } else {
+                    0
+                };
+                let to = if y == end.y {
+                    self.char_index_at(end.x, y)
+                } else {
+                    line.len()
+                };
+                String::from_iter(line[from..to.max(from)].iter().map(|ch| ch.ch))
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
     /// paste the text block in the cursor location
     pub fn paste_text_in_block_mode(&mut self, text_block: String) {
         for (line_index, line) in text_block.lines().enumerate() {
@@ -300,6 +324,58 @@ impl TextBuffer {
         Point2::new(bottom_last_x, last_line)
     }
 
+    /// the width of the leading whitespaces of line `n`
+    pub fn line_indent(&self, n: usize) -> usize {
+        self.chars
+            .get(n)
+            .map(|line| {
+                line.iter()
+                    .take_while(|ch| ch.ch.is_whitespace())
+                    .map(|ch| ch.width)
+                    .sum()
+            })
+            .unwrap_or(0)
+    }
+
+    /// returns true if the line `n` has only whitespace or has no characters at all
+    pub fn is_blank_line(&self, n: usize) -> bool {
+        self.chars
+            .get(n)
+            .map(|line| line.iter().all(|ch| ch.ch.is_whitespace()))
+            .unwrap_or(true)
+    }
+
+    /// the start and end(exclusive) location of the word at this location.
+    /// If the location is right after a word, that word is used.
+    /// A word consists of alphanumeric characters and underscores.
+    pub fn word_range_at(&self, loc: Point2<usize>) -> Option<(Point2<usize>, Point2<usize>)> {
+        let line = self.chars.get(loc.y)?;
+        let is_word_ch = |ch: &Ch| ch.ch.is_alphanumeric() || ch.ch == '_';
+        let index = self.char_index_at(loc.x, loc.y);
+        let index = if line.get(index).map(is_word_ch).unwrap_or(false) {
+            index
+        } else if index > 0 && is_word_ch(&line[index - 1]) {
+            index - 1
+        } else {
+            return None;
+        };
+        let start = line[..index]
+            .iter()
+            .rposition(|ch| !is_word_ch(ch))
+            .map(|i| i + 1)
+            .unwrap_or(0);
+        let end = line[index..]
+            .iter()
+            .position(|ch| !is_word_ch(ch))
+            .map(|i| index + i)
+            .unwrap_or(line.len());
+        let width_until = |i: usize| line[..i].iter().map(|ch| ch.width).sum::<usize>();
+        Some((
+            Point2::new(width_until(start), loc.y),
+            Point2::new(width_until(end), loc.y),
+        ))
+    }
+
     /// break at line y and put the characters after x on the next line
     pub fn break_line(&mut self, loc: Point2<usize>) {
         self.ensure_before_cell_exist(loc);
@@ -383,6 +459,23 @@ impl TextBuffer {
         }
     }
 
+    /// the index of the character which occupies column `x` of line `y`.
+    /// This is the number of characters in the line when `x` is at or beyond the end of the line
+    pub(crate) fn char_index_at(&self, x: usize, y: usize) -> usize {
+        if let Some(line) = self.chars.get(y) {
+            let mut width_sum = 0;
+            for (i, ch) in line.iter().enumerate() {
+                if width_sum >= x {
+                    return i;
+                }
+                width_sum += ch.width;
+            }
+            line.len()
+        } else {
+            0
+        }
+    }
+
     /// translate this point into the correct index position
     /// considering the character widths
     fn point_to_index(&self, point: Point2<usize>) -> Point2<usize> {
@@ -3,10 +3,13 @@ pub use action::Action;
 pub use history::Recorded;
 use nalgebra::Point2;
 use std::fmt;
-use unicode_width::UnicodeWidthChar;
+pub use transform::TextTransform;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 mod action;
+mod comment;
 mod history;
+mod transform;
 
 /// A struct with text_buffer, selection commands, and history recording for undo and redo editing
 #[derive(Default, Clone)]
@@ -17,12 +20,47 @@ pub struct TextEdit {
     pub selection: Selection,
 }
 
-#[derive(Default, Clone)]
+#[derive(Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Selection {
     pub start: Option<Point2<i32>>,
     pub end: Option<Point2<i32>>,
 }
 
+impl Selection {
+    /// the location is selected when the text is selected line by line, both ends included
+    pub fn contains_in_linear_mode(&self, loc: Point2<i32>) -> bool {
+        match (self.start, self.end) {
+            (Some(start), Some(end)) => {
+                let (start, end) = util::reorder_top_down_left_right(start, end);
+                let only_one_line = start.y == end.y;
+                let in_first_line = loc.y == start.y;
+                let in_inner_line = loc.y > start.y && loc.y < end.y;
+                let in_last_line = loc.y == end.y;
+                if in_first_line {
+                    if only_one_line {
+                        loc.x >= start.x && loc.x <= end.x
+                    } else {
+                        loc.x >= start.x
+                    }
+                } else if in_inner_line {
+                    true
+                } else if in_last_line {
+                    if only_one_line {
+                        loc.x >= start.x && loc.x <= end.x
+                    } else {
+                        loc.x <= end.x
+                    }
+                } else {
+                    // outside line
+                    false
+                }
+            }
+            _ => false,
+        }
+    }
+}
+
 impl fmt::Debug for Selection {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let start = if let Some(start) = self.start {
@@ -40,7 +78,7 @@ impl fmt::Debug for Selection {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SelectionMode {
     Linear,
     Block,
@@ -181,8 +219,103 @@ impl TextEdit {
         self.recorded.join_line(pos);
     }
 
+    /// insert the text at the cursor, which is undone in one step
     pub fn command_insert_text(&mut self, text: &str) {
-        self.text_buffer.command_insert_text(text);
+        let cursor = self.text_buffer.get_position();
+        self.begin_group();
+        let end = self.insert_text_at(cursor, text);
+        self.end_group();
+        self.text_buffer.set_position(end);
+    }
+
+    /// insert the text at this location and record it into the history.
+    /// Returns the location right after the inserted text
+    pub fn insert_text_at(&mut self, loc: Point2<usize>, text: &str) -> Point2<usize> {
+        let mut pos = loc;
+        for ch in text.chars() {
+            if ch == '\n' {
+                self.text_buffer.break_line(pos);
+                self.recorded.break_line(pos);
+                pos = Point2::new(0, pos.y + 1);
+            } else {
+                self.text_buffer.insert_char(pos, ch);
+                self.recorded.insert_char(pos, ch);
+                pos.x += ch.width().unwrap_or(0);
+            }
+        }
+        pos
+    }
+
+    /// delete the text from start up to but not including the end location
+    /// and record it into the history.
+    /// Returns the deleted text
+    pub fn delete_text(&mut self, start: Point2<usize>, end: Point2<usize>) -> String {
+        let deleted = self.text_buffer.get_text(start, end);
+        for ch in deleted.chars() {
+            if ch == '\n' {
+                let loc = Point2::new(self.text_buffer.line_width(start.y), start.y);
+                self.text_buffer.join_line(loc);
+                self.recorded.join_line(loc);
+            } else {
+                let deleted_ch = self.text_buffer.delete_char(start);
+                self.recorded.delete(start, deleted_ch);
+            }
+        }
+        deleted
+    }
+
+    /// replace the text from start up to but not including the end location with `text`.
+    /// Returns the location right after the inserted text
+    pub fn replace_text(
+        &mut self,
+        start: Point2<usize>,
+        end: Point2<usize>,
+        text: &str,
+    ) -> Point2<usize> {
+        self.delete_text(start, end);
+        self.insert_text_at(start, text)
+    }
+
+    /// insert each line of the text block at the same column of the succeeding lines,
+    /// shifting the characters after it to the right
+    pub fn insert_text_block(&mut self, loc: Point2<usize>, text_block: &str) {
+        for (i, line) in text_block.split('\n').enumerate() {
+            self.insert_text_at(Point2::new(loc.x, loc.y + i), line);
+        }
+    }
+
+    /// remove the text block which was inserted at this location with `insert_text_block`
+    pub fn delete_text_block(&mut self, loc: Point2<usize>, text_block: &str) {
+        for (i, line) in text_block.split('\n').enumerate() {
+            let y = loc.y + i;
+            self.delete_text(Point2::new(loc.x, y), Point2::new(loc.x + line.width(), y));
+        }
+    }
+
+    /// move the cursor and the selection end points along with the text
+    /// when `delta` columns are inserted(positive) or removed(negative) at `column` of line `y`
+    pub(crate) fn shift_columns(&mut self, y: usize, column: usize, delta: isize) {
+        let cursor = self.get_position();
+        if cursor.y == y && cursor.x >= column {
+            let x = (cursor.x as isize + delta).max(column as isize) as usize;
+            self.text_buffer.set_position(Point2::new(x, y));
+        }
+        let selection_points = [&mut self.selection.start, &mut self.selection.end];
+        for point in selection_points.into_iter().flatten() {
+            if point.y == y as i32 && point.x >= column as i32 {
+                point.x = (point.x + delta as i32).max(column as i32);
+            }
+        }
+    }
+
+    /// Group the succeeding edits, so they are undone and redone in one step
+    pub fn begin_group(&mut self) {
+        self.recorded.begin_group();
+    }
+
+    /// end the group started in `begin_group`
+    pub fn end_group(&mut self) {
+        self.recorded.end_group();
     }
 
     pub fn command_set_position(&mut self, cursor: Point2<usize>) {
@@ -221,6 +354,14 @@ impl TextEdit {
         self.recorded.bump_history();
     }
 
+    pub fn can_undo(&self) -> bool {
+        self.recorded.can_undo()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.recorded.can_redo()
+    }
+
     pub fn command_undo(&mut self) {
         if let Some(location) = self.recorded.undo(&mut self.text_buffer) {
             self.text_buffer.set_position(location);
@@ -234,34 +375,7 @@ impl TextEdit {
     }
 
     pub fn is_selected_in_linear_mode(&self, loc: Point2<i32>) -> bool {
-        match (self.selection.start, self.selection.end) {
-            (Some(start), Some(end)) => {
-                let (start, end) = util::reorder_top_down_left_right(start, end);
-                let only_one_line = start.y == end.y;
-                let in_first_line = loc.y == start.y;
-                let in_inner_line = loc.y > start.y && loc.y < end.y;
-                let in_last_line = loc.y == end.y;
-                if in_first_line {
-                    if only_one_line {
-                        loc.x >= start.x && loc.x <= end.x
-                    } else {
-                        loc.x >= start.x
-                    }
-                } else if in_inner_line {
-                    true
-                } else if in_last_line {
-                    if only_one_line {
-                        loc.x >= start.x && loc.x <= end.x
-                    } else {
-                        loc.x <= end.x
-                    }
-                } else {
-                    // outside line
-                    false
-                }
-            }
-            _ => false,
-        }
+        self.selection.contains_in_linear_mode(loc)
     }
 
     pub fn is_selected_in_block_mode(&self, loc: Point2<i32>) -> bool {
@@ -297,24 +411,23 @@ impl TextEdit {
     pub fn cut_selected_text_in_linear_mode(&mut self) -> Option<String> {
         match self.selection_reorder_casted() {
             Some((start, end)) => {
-                let cut_text = self.text_buffer.cut_text_in_linear_mode(start, end);
-                if !cut_text.is_empty() {
-                    self.record_deleted_text(start, end, &cut_text);
-                }
-                Some(cut_text)
+                let end = self.exclusive_end(end);
+                Some(self.delete_text(start, end))
             }
             _ => None,
         }
     }
 
-    fn record_deleted_text(&mut self, start: Point2<usize>, _end: Point2<usize>, cut_text: &str) {
-        let lines = cut_text.lines();
-        for (y, line) in lines.enumerate() {
-            for (_x, ch) in line.chars().enumerate() {
-                let position = Point2::new(start.x, start.y + y);
-                self.recorded.delete(position, Some(ch));
-            }
-        }
+    /// the exclusive end location of a selection which ends at `end` inclusively
+    pub(crate) fn exclusive_end(&self, end: Point2<usize>) -> Point2<usize> {
+        let end_width = self.get_char(end).and_then(|ch| ch.width()).unwrap_or(1);
+        Point2::new(end.x + end_width, end.y)
+    }
+
+    /// take the actions applied to the text buffer since the last call,
+    /// this is used to move the locations which follow the text such as marks
+    pub(crate) fn take_changes(&mut self) -> Vec<Action> {
+        self.recorded.take_changes()
     }
 
     /// return the selection points which is normalized and casted into usize
@@ -349,18 +462,30 @@ impl TextEdit {
     pub fn cut_selected_text_in_block_mode(&mut self) -> Option<String> {
         match self.selection_normalized_casted() {
             Some((start, end)) => {
-                let cut_text = self.text_buffer.cut_text_in_block_mode(start, end);
-                if !cut_text.is_empty() {
-                    self.record_deleted_text(start, end, &cut_text);
-                }
+                let cut_text = (start.y..=end.y)
+                    .map(|y| self.delete_text(Point2::new(start.x, y), Point2::new(end.x + 1, y)))
+                    .collect::<Vec<_>>()
+                    .join("\n");
                 Some(cut_text)
             }
             _ => None,
         }
     }
 
+    /// paste the text block in the cursor location, overwriting the characters under it
     pub fn paste_text_in_block_mode(&mut self, text_block: String) {
-        self.text_buffer.paste_text_in_block_mode(text_block);
+        let cursor = self.text_buffer.get_position();
+        for (line_index, line) in text_block.lines().enumerate() {
+            let mut width = 0;
+            let y = cursor.y + line_index;
+            for ch in line.chars() {
+                let loc = Point2::new(cursor.x + width, y);
+                if let Some(old_ch) = self.text_buffer.replace_char(loc, ch) {
+                    self.recorded.replace_char(loc, old_ch, ch);
+                }
+                width += ch.width().unwrap_or(0);
+            }
+        }
     }
 
     /// paste the text block overlaying on the text content of the buffer
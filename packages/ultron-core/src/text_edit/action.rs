@@ -1,5 +1,7 @@
 use crate::TextBuffer;
 use nalgebra::Point2;
+use std::collections::BTreeSet;
+use unicode_width::UnicodeWidthChar;
 
 #[derive(Clone, Debug)]
 pub enum Action {
@@ -53,6 +55,67 @@ impl Action {
         }
     }
 
+    /// move the point along with the text when this action is applied,
+    /// points inside a deleted text collapses to the location of the deletion
+    pub fn shift_point(&self, point: Point2<usize>) -> Point2<usize> {
+        let width = |ch: char| ch.width().unwrap_or(0);
+        match *self {
+            Action::Insert(loc, ch) if point.y == loc.y && point.x >= loc.x => {
+                Point2::new(point.x + width(ch), point.y)
+            }
+            Action::Delete(loc, ch) if point.y == loc.y && point.x > loc.x => {
+                Point2::new(point.x.saturating_sub(width(ch)).max(loc.x), point.y)
+            }
+            Action::Replace(loc, old_ch, ch) if point.y == loc.y && point.x > loc.x => {
+                let x = point.x + width(ch);
+                Point2::new(x.saturating_sub(width(old_ch)).max(loc.x), point.y)
+            }
+            Action::BreakLine(loc) if point.y == loc.y && point.x >= loc.x => {
+                Point2::new(point.x - loc.x, point.y + 1)
+            }
+            Action::BreakLine(loc) if point.y > loc.y => Point2::new(point.x, point.y + 1),
+            // the location of the join is at the end of the line
+            Action::JoinLine(loc) if point.y == loc.y + 1 => Point2::new(point.x + loc.x, loc.y),
+            Action::JoinLine(loc) if point.y > loc.y + 1 => Point2::new(point.x, point.y - 1),
+            _ => point,
+        }
+    }
+
+    /// keep the values computed for each line next to their lines when this action is applied,
+    /// the lines whose characters are changed are added to `changed`
+    pub(crate) fn apply_to_lines<T: Default>(
+        &self,
+        lines: &mut Vec<T>,
+        changed: &mut BTreeSet<usize>,
+    ) {
+        match *self {
+            Action::Insert(loc, _) | Action::Delete(loc, _) | Action::Replace(loc, _, _) => {
+                changed.insert(loc.y);
+            }
+            Action::BreakLine(loc) => {
+                *changed = changed
+                    .iter()
+                    .map(|y| if *y > loc.y { y + 1 } else { *y })
+                    .collect();
+                changed.extend([loc.y, loc.y + 1]);
+                if loc.y < lines.len() {
+                    lines.insert(loc.y + 1, T::default());
+                }
+            }
+            Action::JoinLine(loc) => {
+                *changed = changed
+                    .iter()
+                    .filter(|y| **y != loc.y + 1)
+                    .map(|y| if *y > loc.y + 1 { y - 1 } else { *y })
+                    .collect();
+                changed.insert(loc.y);
+                if loc.y + 1 < lines.len() {
+                    lines.remove(loc.y + 1);
+                }
+            }
+        }
+    }
+
     pub fn same_variant(&self, other: &Action) -> bool {
         std::mem::discriminant(self) == std::mem::discriminant(other)
     }
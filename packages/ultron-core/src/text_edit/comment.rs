@@ -0,0 +1,147 @@
+use super::TextEdit;
+use crate::CommentTokens;
+use nalgebra::Point2;
+use unicode_width::UnicodeWidthStr;
+
+impl TextEdit {
+    /// comment or uncomment the selected lines or the line where the cursor is.
+    /// Line comments are used whenever the language has it, otherwise block comment is used.
+    pub fn command_toggle_comment(&mut self, comment_tokens: &CommentTokens) {
+        let (start_line, end_line) = self.selected_lines();
+        self.begin_group();
+        if let Some(prefix) = &comment_tokens.line {
+            self.toggle_line_comment(prefix, start_line, end_line);
+        } else if let Some((open, close)) = &comment_tokens.block {
+            self.toggle_block_comment(open, close, start_line, end_line);
+        }
+        self.end_group();
+    }
+
+    /// the first and last line of the selection,
+    /// or the line of the cursor when there is no selection
+    pub fn selected_lines(&self) -> (usize, usize) {
+        let last_line = self.total_lines().saturating_sub(1);
+        match self.selection_reorder_casted() {
+            Some((start, end)) => (start.y.min(last_line), end.y.min(last_line)),
+            None => {
+                let y = self.get_position().y.min(last_line);
+                (y, y)
+            }
+        }
+    }
+
+    /// returns true if the text after the indentation of line `y` is `token` followed by
+    /// a space or the end of the line, so a `///` doc comment is not taken as a `//` comment
+    fn is_prefixed_after_indent(&self, y: usize, token: &str) -> bool {
+        let indent = self.text_buffer.line_indent(y);
+        let line_width = self.text_buffer.line_width(y);
+        let text = self
+            .text_buffer
+            .get_text(Point2::new(indent, y), Point2::new(line_width, y));
+        text.strip_prefix(token)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with(' '))
+    }
+
+    /// Add the comment prefix to each non blank lines aligned to the smallest indentation.
+    /// If all of the lines are already commented, the prefix is removed instead.
+    fn toggle_line_comment(&mut self, prefix: &str, start_line: usize, end_line: usize) {
+        let non_blank_lines: Vec<usize> = (start_line..=end_line)
+            .filter(|y| !self.text_buffer.is_blank_line(*y))
+            .collect();
+        let lines = if non_blank_lines.is_empty() {
+            (start_line..=end_line).collect()
+        } else {
+            non_blank_lines
+        };
+        let token = prefix.trim_end();
+        let is_commented = lines
+            .iter()
+            .all(|y| self.is_prefixed_after_indent(*y, token));
+
+        if is_commented {
+            for y in lines {
+                let indent = self.text_buffer.line_indent(y);
+                let start = Point2::new(indent, y);
+                // also remove the space after the comment token
+                let with_space = self
+                    .text_buffer
+                    .get_text(start, Point2::new(indent + prefix.width(), y))
+                    == prefix;
+                let token_width = if with_space {
+                    prefix.width()
+                } else {
+                    token.width()
+                };
+                self.delete_text(start, Point2::new(indent + token_width, y));
+                self.shift_columns(y, indent, -(token_width as isize));
+            }
+        } else {
+            let indent = lines
+                .iter()
+                .map(|y| self.text_buffer.line_indent(*y))
+                .min()
+                .unwrap_or(0);
+            for y in lines {
+                self.insert_text_at(Point2::new(indent, y), prefix);
+                self.shift_columns(y, indent, prefix.width() as isize);
+            }
+        }
+    }
+
+    /// wrap the lines with the block comment tokens,
+    /// or unwrap it if the lines are already wrapped
+    fn toggle_block_comment(
+        &mut self,
+        open: &str,
+        close: &str,
+        start_line: usize,
+        end_line: usize,
+    ) {
+        let indent = self.text_buffer.line_indent(start_line);
+        let last_line = self.text_buffer.get_text(
+            Point2::new(0, end_line),
+            Point2::new(self.text_buffer.line_width(end_line), end_line),
+        );
+        let last_line_end = last_line.trim_end().width();
+        let text = self.text_buffer.get_text(
+            Point2::new(indent, start_line),
+            Point2::new(last_line_end, end_line),
+        );
+        let is_commented = text.len() >= open.len() + close.len()
+            && text.starts_with(open)
+            && text.ends_with(close);
+
+        if is_commented {
+            // remove the closing token first, so the location of the opening token is unaffected
+            let close_start = last_line_end - close.width();
+            let inner = &text[open.len()..text.len() - close.len()];
+            let trailing_space = inner.len() > 1 && inner.ends_with(' ');
+            let leading_space = inner.starts_with(' ');
+            let close_start = if trailing_space {
+                close_start - 1
+            } else {
+                close_start
+            };
+            self.delete_text(
+                Point2::new(close_start, end_line),
+                Point2::new(last_line_end, end_line),
+            );
+            let open_width = if leading_space {
+                open.width() + 1
+            } else {
+                open.width()
+            };
+            self.delete_text(
+                Point2::new(indent, start_line),
+                Point2::new(indent + open_width, start_line),
+            );
+            self.shift_columns(start_line, indent, -(open_width as isize));
+        } else {
+            let close = format!(" {close}");
+            let open = format!("{open} ");
+            self.insert_text_at(Point2::new(last_line_end, end_line), &close);
+            self.insert_text_at(Point2::new(indent, start_line), &open);
+            self.shift_columns(start_line, indent, open.width() as isize);
+        }
+    }
+}
@@ -10,6 +10,11 @@ const UNDO_SIZE: usize = 100;
 pub struct Recorded {
     history: VecDeque<ActionList>,
     undone: VecDeque<ActionList>,
+    /// when greater than 0, all the recorded actions are merged into one action list
+    group_depth: usize,
+    /// the actions applied to the text buffer, including the ones from undo and redo,
+    /// which are not yet taken with `take_changes`
+    changes: Vec<Action>,
 }
 
 #[derive(Debug, Clone)]
@@ -38,6 +43,8 @@ impl Recorded {
         Recorded {
             history: VecDeque::new(),
             undone: VecDeque::new(),
+            group_depth: 0,
+            changes: vec![],
         }
     }
 
@@ -46,7 +53,32 @@ impl Recorded {
     pub fn bump_history(&mut self) {
         // pushing an empty new action list, will ensure that the next action
         // will in a separate action list from the previous ones
-        self.history.push_front(ActionList::from(vec![]));
+        let is_last_empty = self
+            .history
+            .front()
+            .map(|a| a.actions.is_empty())
+            .unwrap_or(false);
+        if !is_last_empty {
+            self.history.push_front(ActionList::from(vec![]));
+        }
+    }
+
+    /// Start a group, where all the succeeding actions are undone and redone in one step
+    /// until the matching `end_group` is called.
+    /// Groups can be nested, only the outer most group makes the history separator.
+    pub fn begin_group(&mut self) {
+        if self.group_depth == 0 {
+            self.bump_history();
+        }
+        self.group_depth += 1;
+    }
+
+    /// end the group started with `begin_group`
+    pub fn end_group(&mut self) {
+        self.group_depth = self.group_depth.saturating_sub(1);
+        if self.group_depth == 0 {
+            self.bump_history();
+        }
     }
 
     /// if the last action list is empty, add it there
@@ -55,7 +87,7 @@ impl Recorded {
     /// returns true if the action `act` is added to history.
     fn try_merge(&mut self, act: Action) -> Result<(), bool> {
         if let Some(a) = self.history.front_mut() {
-            if a.actions.is_empty() || a.same_variant_to_last(&act) {
+            if a.actions.is_empty() || self.group_depth > 0 || a.same_variant_to_last(&act) {
                 a.actions.push(act);
                 return Ok(());
             }
@@ -72,6 +104,7 @@ impl Recorded {
     /// otherwise record it as a new action list
     fn record(&mut self, act: Action) {
         self.undone.clear(); // we are branching to a new sequence of events
+        self.changes.push(act.clone());
         if self.try_merge(act.clone()).is_err() {
             self.record_new(act);
         }
@@ -91,9 +124,22 @@ impl Recorded {
         }
     }
 
+    /// there are actions in the history which can be undone
+    pub fn can_undo(&self) -> bool {
+        self.history.iter().any(|a| !a.actions.is_empty())
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.undone.is_empty()
+    }
+
     /// undo the history and return the location of the last occurence
     pub(crate) fn undo(&mut self, text_buffer: &mut TextBuffer) -> Option<Point2<usize>> {
         let mut last_location = None;
+        // skip the history separators, there is nothing to undo in there
+        while let Some(true) = self.history.front().map(|a| a.actions.is_empty()) {
+            self.history.pop_front();
+        }
         if let Some(to_undo) = self.history.pop_front() {
             self.undone.push_front(to_undo.clone());
             self.freeup_undone();
@@ -102,6 +148,7 @@ impl Recorded {
                 let inverted = tu.invert();
                 inverted.apply(text_buffer);
                 last_location = Some(inverted.location());
+                self.changes.push(inverted);
             });
         }
         last_location
@@ -119,12 +166,18 @@ impl Recorded {
             to_redo.actions.iter().for_each(|tr| {
                 tr.apply(text_buffer);
                 last_location = Some(tr.location());
+                self.changes.push(tr.clone());
             });
             self.history.push_front(to_redo);
         }
         last_location
     }
 
+    /// take the actions applied to the text buffer since the last call
+    pub(crate) fn take_changes(&mut self) -> Vec<Action> {
+        std::mem::take(&mut self.changes)
+    }
+
     pub(crate) fn insert_char(&mut self, cursor: Point2<usize>, ch: char) {
         self.record(Action::Insert(cursor, ch));
     }
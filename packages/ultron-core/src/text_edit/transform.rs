@@ -0,0 +1,332 @@
+use super::TextEdit;
+use crate::SelectionMode;
+use nalgebra::Point2;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// the number of columns a tab occupies when converting tabs to spaces and back
+pub const TAB_WIDTH: usize = 4;
+
+/// A transformation of the selected text, or the word under the cursor when there is no selection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TextTransform {
+    /// `Hello world` -> `HELLO WORLD`
+    Uppercase,
+    /// `Hello World` -> `hello world`
+    Lowercase,
+    /// `hello world` -> `Hello World`
+    TitleCase,
+    /// `helloWorld` -> `hello_world`
+    SnakeCase,
+    /// `hello_world` -> `helloWorld`
+    CamelCase,
+    /// `helloWorld` -> `hello-world`
+    KebabCase,
+    /// `helloWorld` -> `HELLO_WORLD`
+    ScreamingSnakeCase,
+    /// remove the whitespaces at the end of the lines
+    TrimTrailingWhitespace,
+    /// expand the tabs into spaces
+    TabsToSpaces,
+    /// convert the spaces in the indentation into tabs
+    SpacesToTabs,
+    /// reverse the order of the lines
+    ReverseLines,
+}
+
+impl TextTransform {
+    /// line transforms works on the whole lines of the selection,
+    /// or the whole content when there is no selection
+    fn is_line_transform(&self) -> bool {
+        matches!(
+            self,
+            Self::TrimTrailingWhitespace
+                | Self::TabsToSpaces
+                | Self::SpacesToTabs
+                | Self::ReverseLines
+        )
+    }
+
+    /// apply the transformation to this text
+    pub fn apply(&self, text: &str) -> String {
+        match self {
+            Self::Uppercase => text.to_uppercase(),
+            Self::Lowercase => text.to_lowercase(),
+            Self::TitleCase => title_case(text),
+            Self::SnakeCase => map_identifiers(text, |words| join_words(words, "_", lowercase)),
+            Self::KebabCase => map_identifiers(text, |words| join_words(words, "-", lowercase)),
+            Self::ScreamingSnakeCase => {
+                map_identifiers(text, |words| join_words(words, "_", uppercase))
+            }
+            Self::CamelCase => map_identifiers(text, |words| {
+                words
+                    .iter()
+                    .enumerate()
+                    .map(|(i, word)| {
+                        if i == 0 {
+                            lowercase(word)
+                        } else {
+                            capitalize(word)
+                        }
+                    })
+                    .collect()
+            }),
+            Self::TrimTrailingWhitespace => map_lines(text, |line| line.trim_end().to_string()),
+            Self::TabsToSpaces => map_lines(text, tabs_to_spaces),
+            Self::SpacesToTabs => map_lines(text, spaces_to_tabs),
+            Self::ReverseLines => text.split('\n').rev().collect::<Vec<_>>().join("\n"),
+        }
+    }
+}
+
+impl TextEdit {
+    /// transform the selected text, or the word under the cursor if there is no selection.
+    /// The whole transformation is undone in one step.
+    /// Returns true if the text is changed
+    pub fn command_transform(
+        &mut self,
+        transform: TextTransform,
+        selection_mode: SelectionMode,
+    ) -> bool {
+        self.begin_group();
+        let is_changed = match selection_mode {
+            SelectionMode::Block if self.selection_normalized_casted().is_some() => {
+                self.transform_block(transform)
+            }
+            _ => self.transform_linear(transform),
+        };
+        self.end_group();
+        is_changed
+    }
+
+    fn transform_linear(&mut self, transform: TextTransform) -> bool {
+        let selection = self.selection_reorder_casted();
+        let range = if transform.is_line_transform() {
+            let last_line = self.total_lines().saturating_sub(1);
+            let (start_line, end_line) = selection
+                .map(|(start, end)| (start.y, end.y.min(last_line)))
+                .unwrap_or((0, last_line));
+            Some((
+                Point2::new(0, start_line),
+                Point2::new(self.text_buffer.line_width(end_line), end_line),
+            ))
+        } else if let Some((start, end)) = selection {
+            // the selection end is inclusive
+            Some((start, self.exclusive_end(end)))
+        } else {
+            self.text_buffer.word_range_at(self.get_position())
+        };
+
+        let Some((start, end)) = range else {
+            return false;
+        };
+        let text = self.text_buffer.get_text(start, end);
+        let new_text = transform.apply(&text);
+        if new_text == text {
+            return false;
+        }
+        self.replace_text(start, end, &new_text);
+        if selection.is_some() {
+            let last = last_char_location(start, &new_text);
+            self.set_selection(
+                Point2::new(start.x as i32, start.y as i32),
+                Point2::new(last.x as i32, last.y as i32),
+            );
+        }
+        true
+    }
+
+    /// transform each row of the block selection,
+    /// reversing lines will reverse the order of the rows in the block
+    fn transform_block(&mut self, transform: TextTransform) -> bool {
+        let mut is_changed = false;
+        if let Some((start, end)) = self.selection_normalized_casted() {
+            let rows: Vec<(Point2<usize>, Point2<usize>)> = (start.y..=end.y)
+                .map(|y| (Point2::new(start.x, y), Point2::new(end.x + 1, y)))
+                .collect();
+            let segments: Vec<String> = rows
+                .iter()
+                .map(|(row_start, row_end)| self.text_buffer.get_text(*row_start, *row_end))
+                .collect();
+            let new_segments: Vec<String> = if transform == TextTransform::ReverseLines {
+                segments.iter().rev().cloned().collect()
+            } else {
+                segments
+                    .iter()
+                    .map(|segment| transform.apply(segment))
+                    .collect()
+            };
+            for ((row_start, row_end), (segment, new_segment)) in rows
+                .into_iter()
+                .zip(segments.iter().zip(new_segments.iter()))
+            {
+                if segment != new_segment {
+                    self.replace_text(row_start, row_end, new_segment);
+                    is_changed = true;
+                }
+            }
+        }
+        is_changed
+    }
+}
+
+/// the location of the last character of `text` if it is inserted at `start`
+fn last_char_location(start: Point2<usize>, text: &str) -> Point2<usize> {
+    let line_count = text.split('\n').count();
+    let last_line = text.rsplit('\n').next().unwrap_or("");
+    let last_ch_width = last_line
+        .chars()
+        .last()
+        .and_then(|ch| ch.width())
+        .unwrap_or(0);
+    let x = if line_count > 1 { 0 } else { start.x };
+    Point2::new(
+        x + last_line.width().saturating_sub(last_ch_width),
+        start.y + line_count - 1,
+    )
+}
+
+fn map_lines(text: &str, f: impl Fn(&str) -> String) -> String {
+    text.split('\n').map(f).collect::<Vec<_>>().join("\n")
+}
+
+fn lowercase(word: &str) -> String {
+    word.to_lowercase()
+}
+
+fn uppercase(word: &str) -> String {
+    word.to_uppercase()
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first
+            .to_uppercase()
+            .chain(chars.flat_map(char::to_lowercase))
+            .collect(),
+        None => String::new(),
+    }
+}
+
+fn join_words(words: &[String], separator: &str, f: fn(&str) -> String) -> String {
+    words
+        .iter()
+        .map(|word| f(word))
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+fn title_case(text: &str) -> String {
+    let mut prev: Option<char> = None;
+    text.chars()
+        .flat_map(|ch| {
+            let is_word_start = !prev
+                .map(|p| p.is_alphanumeric() || p == '\'')
+                .unwrap_or(false);
+            prev = Some(ch);
+            if is_word_start {
+                ch.to_uppercase().collect::<Vec<_>>()
+            } else {
+                ch.to_lowercase().collect::<Vec<_>>()
+            }
+        })
+        .collect()
+}
+
+fn is_identifier_ch(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '_' || ch == '-'
+}
+
+/// convert each identifier in the text using `f`,
+/// the leading and trailing separators of the identifiers are kept
+fn map_identifiers(text: &str, f: impl Fn(&[String]) -> String) -> String {
+    let mut result = String::new();
+    let mut identifier = String::new();
+    let flush = |identifier: &mut String, result: &mut String| {
+        let words = split_words(identifier);
+        if words.is_empty() {
+            result.push_str(identifier);
+        } else {
+            let trimmed = identifier.trim_start_matches(['_', '-']);
+            let prefix = &identifier[..identifier.len() - trimmed.len()];
+            let suffix = &trimmed[trimmed.trim_end_matches(['_', '-']).len()..];
+            result.push_str(prefix);
+            result.push_str(&f(&words));
+            result.push_str(suffix);
+        }
+        identifier.clear();
+    };
+    for ch in text.chars() {
+        if is_identifier_ch(ch) {
+            identifier.push(ch);
+        } else {
+            flush(&mut identifier, &mut result);
+            result.push(ch);
+        }
+    }
+    flush(&mut identifier, &mut result);
+    result
+}
+
+/// split an identifier into words, ie: `parseHTTPServer_v2` -> `parse`, `HTTP`, `Server`, `v2`
+fn split_words(identifier: &str) -> Vec<String> {
+    let chars: Vec<char> = identifier.chars().collect();
+    let mut words = vec![];
+    let mut word = String::new();
+    for (i, &ch) in chars.iter().enumerate() {
+        if ch == '_' || ch == '-' {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            continue;
+        }
+        let prev = if i > 0 { Some(chars[i - 1]) } else { None };
+        let next = chars.get(i + 1);
+        let is_boundary = ch.is_uppercase()
+            && match prev {
+                Some(prev) if prev.is_lowercase() || prev.is_numeric() => true,
+                Some(prev) if prev.is_uppercase() => {
+                    next.map(|n| n.is_lowercase()).unwrap_or(false)
+                }
+                _ => false,
+            };
+        if is_boundary && !word.is_empty() {
+            words.push(std::mem::take(&mut word));
+        }
+        word.push(ch);
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+fn tabs_to_spaces(line: &str) -> String {
+    let mut column = 0;
+    let mut result = String::new();
+    for ch in line.chars() {
+        if ch == '\t' {
+            let spaces = TAB_WIDTH - column % TAB_WIDTH;
+            result.push_str(&" ".repeat(spaces));
+            column += spaces;
+        } else {
+            result.push(ch);
+            column += ch.width().unwrap_or(0);
+        }
+    }
+    result
+}
+
+/// only the spaces in the indentation are converted into tabs
+fn spaces_to_tabs(line: &str) -> String {
+    let content = line.trim_start_matches([' ', '\t']);
+    let indent = &line[..line.len() - content.len()];
+    let indent_width = tabs_to_spaces(indent).len();
+    format!(
+        "{}{}{}",
+        "\t".repeat(indent_width / TAB_WIDTH),
+        " ".repeat(indent_width % TAB_WIDTH),
+        content
+    )
+}
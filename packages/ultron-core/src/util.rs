@@ -41,3 +41,12 @@ where
         unreachable!()
     }
 }
+
+/// the location right after the text if it is inserted at `start`
+pub fn text_end(start: Point2<usize>, text: &str) -> Point2<usize> {
+    use unicode_width::UnicodeWidthStr;
+    let line_count = text.split('\n').count();
+    let last_line = text.rsplit('\n').next().unwrap_or("");
+    let x = if line_count > 1 { 0 } else { start.x };
+    Point2::new(x + last_line.width(), start.y + line_count - 1)
+}
@@ -0,0 +1,124 @@
+use ultron_core::{
+    BaseEditor, BaseOptions, Clipboard, Command, Osc52Clipboard, Point2, RegisterContent,
+    SelectionMode,
+};
+
+fn editor(content: &str) -> BaseEditor<()> {
+    BaseEditor::from_str(&BaseOptions::default(), content)
+}
+
+#[test]
+fn copy_and_paste_over_selection() {
+    let mut editor = editor("hello world");
+    editor.set_selection(Point2::new(0, 0), Point2::new(4, 0));
+    editor.process_command(Command::Copy);
+    assert_eq!(
+        editor.clipboard_content(),
+        Some(RegisterContent::linear("hello"))
+    );
+    editor.set_selection(Point2::new(6, 0), Point2::new(10, 0));
+    editor.process_command(Command::Paste);
+    assert_eq!(editor.get_content(), "hello hello");
+    assert_eq!(editor.get_position(), Point2::new(11, 0));
+    editor.process_command(Command::Undo);
+    assert_eq!(editor.get_content(), "hello world");
+}
+
+#[test]
+fn cut_and_paste() {
+    let mut editor = editor("abc\ndef");
+    editor.set_selection(Point2::new(1, 0), Point2::new(2, 0));
+    editor.process_command(Command::Cut);
+    assert_eq!(editor.get_content(), "a\ndef");
+    editor.process_command(Command::SetPosition(Point2::new(3, 1)));
+    editor.process_command(Command::Paste);
+    assert_eq!(editor.get_content(), "a\ndefbc");
+}
+
+#[test]
+fn block_content_is_pasted_as_block() {
+    let options = BaseOptions {
+        selection_mode: SelectionMode::Block,
+        ..Default::default()
+    };
+    let mut editor: BaseEditor<()> = BaseEditor::from_str(&options, "abcd\nefgh");
+    editor.set_selection(Point2::new(1, 0), Point2::new(2, 1));
+    editor.process_command(Command::Copy);
+    assert_eq!(
+        editor.clipboard_content(),
+        Some(RegisterContent::block("bc\nfg"))
+    );
+    editor.process_command(Command::ClearSelection);
+    editor.process_command(Command::SetPosition(Point2::new(4, 0)));
+    editor.process_command(Command::Paste);
+    assert_eq!(editor.get_content(), "abcdbc\nefghfg");
+}
+
+#[test]
+fn clipboard_is_shared_with_clones() {
+    let mut editor = editor("one two");
+    let other = editor.clone();
+    editor.set_selection(Point2::new(0, 0), Point2::new(2, 0));
+    editor.process_command(Command::Copy);
+    assert_eq!(
+        other.clipboard_content(),
+        Some(RegisterContent::linear("one"))
+    );
+}
+
+#[test]
+fn osc52_escape_sequence() {
+    let mut clipboard = Osc52Clipboard::new(vec![]);
+    clipboard.write(RegisterContent::block("hello\nworld"));
+    assert_eq!(
+        String::from_utf8_lossy(clipboard.get_ref()),
+        "\x1b]52;c;aGVsbG8Kd29ybGQ=\x07"
+    );
+    assert_eq!(
+        clipboard.read(),
+        Some(RegisterContent::block("hello\nworld"))
+    );
+    assert_eq!(
+        Osc52Clipboard::<Vec<u8>>::escape_sequence("ab"),
+        "\x1b]52;c;YWI=\x07"
+    );
+}
+
+#[test]
+fn paste_text_from_system_clipboard() {
+    let options = BaseOptions {
+        selection_mode: SelectionMode::Block,
+        ..Default::default()
+    };
+    let mut editor: BaseEditor<()> = BaseEditor::from_str(&options, "abcd\nefgh");
+    editor.set_selection(Point2::new(1, 0), Point2::new(2, 1));
+    editor.process_command(Command::Copy);
+    editor.process_command(Command::ClearSelection);
+    editor.process_command(Command::SetPosition(Point2::new(0, 0)));
+    // the same text copied from this editor is still pasted as a block
+    editor.process_command(Command::PasteText("bc\nfg".to_string()));
+    assert_eq!(editor.get_content(), "bcabcd\nfgefgh");
+    // a text copied from somewhere else is pasted as a linear text,
+    // the cursor is still at the start of the pasted block
+    editor.process_command(Command::PasteText("x\ny".to_string()));
+    assert_eq!(editor.get_content(), "x\nybcabcd\nfgefgh");
+}
+
+#[test]
+fn delete_selection_is_undoable() {
+    let mut editor = editor("hello world");
+    assert!(!editor.can_undo());
+    assert!(!editor.has_selection());
+    assert!(!editor.process_command(Command::DeleteSelection));
+    editor.set_selection(Point2::new(5, 0), Point2::new(10, 0));
+    assert!(editor.has_selection());
+    assert!(editor.process_command(Command::DeleteSelection));
+    assert_eq!(editor.get_content(), "hello");
+    assert_eq!(editor.clipboard_content(), None);
+    assert!(editor.can_undo());
+    assert!(!editor.can_redo());
+    editor.process_command(Command::Undo);
+    assert_eq!(editor.get_content(), "hello world");
+    assert!(!editor.can_undo());
+    assert!(editor.can_redo());
+}
@@ -0,0 +1,94 @@
+use ultron_core::{BaseEditor, BaseOptions, ChangeDelta, Command, Point2};
+
+fn editor(content: &str) -> BaseEditor<()> {
+    BaseEditor::from_str(&BaseOptions::default(), content)
+}
+
+#[test]
+fn typed_characters_are_merged_into_one_change() {
+    let mut editor = editor("hello");
+    let response = editor.process_commands_with_response([
+        Command::SetPosition(Point2::new(5, 0)),
+        Command::InsertChar(' '),
+        Command::InsertChar('w'),
+        Command::BreakLine,
+        Command::InsertChar('!'),
+    ]);
+    assert!(response.is_content_changed);
+    assert_eq!(response.cursor, Point2::new(1, 1));
+    assert_eq!(
+        response.changes,
+        [ChangeDelta::new(Point2::new(5, 0), "", " w\n!")]
+    );
+}
+
+#[test]
+fn backspaces_are_merged_into_one_change() {
+    let mut editor = editor("ab\ncdef");
+    let response = editor.process_commands_with_response([
+        Command::SetPosition(Point2::new(3, 1)),
+        Command::DeleteBack,
+        Command::DeleteBack,
+        Command::DeleteBack,
+    ]);
+    assert_eq!(editor.get_content(), "ab\nf");
+    assert_eq!(
+        response.changes,
+        [ChangeDelta::new(Point2::new(0, 1), "cde", "")]
+    );
+}
+
+#[test]
+fn replacing_the_selection_is_one_change() {
+    let mut editor = editor("hello world");
+    let response = editor.process_commands_with_response([
+        Command::SetSelection(Point2::new(6, 0), Point2::new(10, 0)),
+        Command::DeleteSelection,
+        Command::InsertText("there".to_string()),
+    ]);
+    assert_eq!(editor.get_content(), "hello there");
+    assert_eq!(
+        response.changes,
+        [ChangeDelta::new(Point2::new(6, 0), "world", "there")]
+    );
+    let response = editor.process_commands_with_response([Command::MoveLeft]);
+    assert!(!response.is_content_changed);
+    assert!(response.changes.is_empty());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn json_commands() {
+    let mut editor = editor("abc");
+    let response = editor
+        .process_json_commands(r#"[{"SetPosition": [3, 0]}, {"InsertText": "de"}, "MoveLeft"]"#)
+        .expect("must be valid commands");
+    assert_eq!(
+        response,
+        r#"{"cursor":[4,0],"selection":{"start":null,"end":null},"is_content_changed":true,"changes":[{"start":[3,0],"deleted":"","inserted":"de"}]}"#
+    );
+    assert!(editor
+        .process_json_commands(r#"["NoSuchCommand"]"#)
+        .is_err());
+}
+
+#[test]
+fn tracked_changes_are_collected_from_every_command() {
+    let mut editor = editor("abc");
+    editor.process_command(Command::InsertChar('x'));
+    editor.track_changes();
+    assert!(editor.take_changes().is_empty());
+    editor.process_command(Command::SetPosition(Point2::new(4, 0)));
+    editor.process_command(Command::InsertChar('d'));
+    editor.process_commands_with_response([Command::InsertChar('e')]);
+    assert_eq!(
+        editor.take_changes(),
+        [ChangeDelta::new(Point2::new(4, 0), "", "de")]
+    );
+    assert!(editor.take_changes().is_empty());
+    editor.process_command(Command::SetContent("new".to_string()));
+    assert_eq!(
+        editor.take_changes(),
+        [ChangeDelta::new(Point2::origin(), "xabcde", "new")]
+    );
+}
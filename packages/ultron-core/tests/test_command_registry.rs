@@ -0,0 +1,55 @@
+use ultron_core::{fuzzy_match, Binding, Command, CommandEntry, CommandRegistry};
+
+#[test]
+fn fuzzy_match_prefers_word_starts_and_consecutive_characters() {
+    let m = fuzzy_match("sa", "Selection: Select All").unwrap();
+    assert_eq!(m.indices, vec![0, 18]);
+    assert!(fuzzy_match("tc", "Edit: Toggle Comment").is_some());
+    assert!(fuzzy_match("xyz", "Edit: Toggle Comment").is_none());
+    let word_starts = fuzzy_match("tc", "Toggle Comment").unwrap();
+    let scattered = fuzzy_match("tc", "Tactic").unwrap();
+    assert_eq!(word_starts.indices, vec![0, 7]);
+    assert!(word_starts.score > scattered.score);
+    assert_eq!(
+        fuzzy_match("", "Undo").unwrap().indices,
+        Vec::<usize>::new()
+    );
+}
+
+#[test]
+fn search_the_standard_commands() {
+    let registry = CommandRegistry::standard();
+    let found = registry.search("undo");
+    assert_eq!(found[0].0.id, "editor.undo");
+    assert_eq!(found[0].0.binding, Binding::Command(Command::Undo));
+    let found = registry.search("tr upper");
+    assert_eq!(found[0].0.id, "transform.uppercase");
+    assert_eq!(found[0].0.label(), "Transform: Uppercase");
+    assert_eq!(registry.search("").len(), registry.entries().len());
+}
+
+#[test]
+fn register_host_commands() {
+    let mut registry = CommandRegistry::standard();
+    registry.register(CommandEntry::action("file.save", "Save", "File", "save"));
+    let found = registry.search("save");
+    assert_eq!(found[0].0.binding, Binding::Action("save".to_string()));
+    // registering the same id replaces the command
+    registry.register(CommandEntry::action(
+        "file.save",
+        "Save File",
+        "File",
+        "save",
+    ));
+    assert_eq!(registry.get("file.save").unwrap().title, "Save File");
+    assert_eq!(
+        registry
+            .entries()
+            .iter()
+            .filter(|e| e.id == "file.save")
+            .count(),
+        1
+    );
+    assert!(registry.unregister("file.save").is_some());
+    assert!(registry.get("file.save").is_none());
+}
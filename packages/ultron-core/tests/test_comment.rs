@@ -0,0 +1,81 @@
+use ultron_core::{BaseEditor, BaseOptions, Command, Point2};
+
+fn editor(syntax_token: &str, content: &str) -> BaseEditor<()> {
+    let mut editor = BaseEditor::from_str(&BaseOptions::default(), content);
+    editor.set_syntax_token(syntax_token);
+    editor
+}
+
+#[test]
+fn comment_lines_aligned_to_smallest_indent() {
+    let raw = "fn main() {\n    let x = 1;\n\n        foo(x);\n}";
+    let mut editor = editor("rust", raw);
+    editor.set_selection(Point2::new(0, 1), Point2::new(4, 3));
+    editor.process_command(Command::ToggleComment);
+    assert_eq!(
+        editor.get_content(),
+        "fn main() {\n    // let x = 1;\n\n    //     foo(x);\n}"
+    );
+}
+
+#[test]
+fn uncomment_lines() {
+    let raw = "fn main() {\n    // let x = 1;\n    //\n    // foo(x);\n}";
+    let mut editor = editor("rust", raw);
+    editor.set_selection(Point2::new(0, 1), Point2::new(4, 3));
+    editor.process_command(Command::ToggleComment);
+    assert_eq!(
+        editor.get_content(),
+        "fn main() {\n    let x = 1;\n    \n    foo(x);\n}"
+    );
+}
+
+#[test]
+fn doc_comments_are_not_uncommented() {
+    let raw = "/// the answer\nconst ANSWER: u8 = 42;";
+    let mut editor = editor("rust", raw);
+    editor.process_command(Command::ToggleComment);
+    assert_eq!(
+        editor.get_content(),
+        "// /// the answer\nconst ANSWER: u8 = 42;"
+    );
+    editor.process_command(Command::ToggleComment);
+    assert_eq!(editor.get_content(), raw);
+}
+
+#[test]
+fn toggle_comment_is_undone_in_one_step() {
+    let raw = "a = 1\nb = 2";
+    let mut editor = editor("py", raw);
+    editor.set_selection(Point2::new(0, 0), Point2::new(2, 1));
+    editor.process_command(Command::ToggleComment);
+    assert_eq!(editor.get_content(), "# a = 1\n# b = 2");
+    editor.process_command(Command::Undo);
+    assert_eq!(editor.get_content(), raw);
+    editor.process_command(Command::Redo);
+    assert_eq!(editor.get_content(), "# a = 1\n# b = 2");
+}
+
+#[test]
+fn comment_moves_the_cursor_along() {
+    let mut editor = editor("rust", "let x = 1;");
+    editor.process_command(Command::SetPosition(Point2::new(4, 0)));
+    editor.process_command(Command::ToggleComment);
+    assert_eq!(editor.get_position(), Point2::new(7, 0));
+}
+
+#[test]
+fn fallback_to_block_comment() {
+    let mut editor = editor("css", "  color: red;");
+    editor.process_command(Command::ToggleComment);
+    assert_eq!(editor.get_content(), "  /* color: red; */");
+    editor.process_command(Command::ToggleComment);
+    assert_eq!(editor.get_content(), "  color: red;");
+}
+
+#[test]
+fn no_comment_tokens_in_plain_text() {
+    let mut editor = editor("txt", "hello");
+    assert!(!editor.process_command(Command::ToggleComment));
+    assert_eq!(editor.get_content(), "hello");
+}
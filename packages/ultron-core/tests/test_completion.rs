@@ -0,0 +1,112 @@
+use ultron_core::{
+    word_before, BaseEditor, BaseOptions, Command, CompletionItem, CompletionKind,
+    CompletionProvider, Edit, Point2, TextBuffer,
+};
+
+fn editor(content: &str) -> BaseEditor<()> {
+    BaseEditor::from_str(&BaseOptions::default(), content)
+}
+
+fn set_position(editor: &mut BaseEditor<()>, x: i32, y: i32) {
+    editor.process_command(Command::SetPosition(Point2::new(x, y)));
+}
+
+/// suggests the methods of a string after a `.`
+struct Methods;
+
+impl CompletionProvider for Methods {
+    fn complete(
+        &self,
+        _buffer: &TextBuffer,
+        cursor: Point2<usize>,
+        trigger: Option<char>,
+    ) -> Vec<CompletionItem> {
+        if trigger != Some('.') {
+            return vec![];
+        }
+        vec![
+            CompletionItem::new("len", CompletionKind::Method)
+                .with_edit(Edit::insert(cursor, "len()"))
+                .with_detail("fn len(&self) -> usize"),
+            CompletionItem::new("is_empty", CompletionKind::Method),
+        ]
+    }
+
+    fn trigger_characters(&self) -> &[char] {
+        &['.']
+    }
+}
+
+#[test]
+fn words_of_the_content_are_suggested() {
+    let mut editor = editor("let total = 1;\nlet totem = 2;\nto");
+    set_position(&mut editor, 2, 2);
+    assert_eq!(editor.completion_prefix(), (Point2::new(0, 2), "to".into()));
+    let labels: Vec<String> = editor
+        .completions(None)
+        .into_iter()
+        .map(|item| item.label)
+        .collect();
+    assert_eq!(labels, vec!["total", "totem"]);
+
+    let item = editor.completions(None).remove(1);
+    editor.process_command(Command::AcceptCompletion(item));
+    assert_eq!(
+        editor.get_content(),
+        "let total = 1;\nlet totem = 2;\ntotem"
+    );
+    assert_eq!(editor.get_position(), Point2::new(5, 2));
+    editor.process_command(Command::Undo);
+    assert_eq!(editor.get_content(), "let total = 1;\nlet totem = 2;\nto");
+}
+
+#[test]
+fn the_words_are_collected_again_when_the_content_changes() {
+    let mut editor = editor("let total = 1;\nto");
+    set_position(&mut editor, 2, 1);
+    assert_eq!(editor.completions(None).len(), 1);
+    set_position(&mut editor, 0, 0);
+    editor.process_command(Command::InsertText("topic ".to_string()));
+    set_position(&mut editor, 2, 1);
+    let labels: Vec<String> = editor
+        .completions(None)
+        .into_iter()
+        .map(|item| item.label)
+        .collect();
+    assert_eq!(labels, vec!["topic", "total"]);
+}
+
+#[test]
+fn providers_are_triggered_by_their_characters() {
+    let mut editor = editor("name.");
+    editor.clear_completion_providers();
+    editor.add_completion_provider(Methods);
+    assert!(editor.is_completion_trigger('.'));
+    assert!(!editor.is_completion_trigger(':'));
+    set_position(&mut editor, 5, 0);
+    let items = editor.completions(Some('.'));
+    assert_eq!(items.len(), 2);
+
+    // the edit is extended over the characters typed after the completion is shown
+    editor.process_command(Command::InsertText("le".into()));
+    let typed = editor.completion_prefix().1;
+    let item = ultron_core::filter_completions(&items, &typed).remove(0);
+    assert_eq!(item.label, "len");
+    editor.process_command(Command::AcceptCompletion(item));
+    assert_eq!(editor.get_content(), "name.len()");
+    // only the methods are suggested after a `.`
+    assert!(editor.completions(None).is_empty());
+}
+
+#[test]
+fn the_word_before_the_cursor_counts_the_wide_characters() {
+    let buffer = TextBuffer::new_from_str("x = 世界ab + c");
+    assert_eq!(
+        word_before(&buffer, Point2::new(10, 0)),
+        (Point2::new(4, 0), "世界ab".into())
+    );
+    assert_eq!(
+        word_before(&buffer, Point2::new(11, 0)),
+        (Point2::new(11, 0), String::new())
+    );
+}
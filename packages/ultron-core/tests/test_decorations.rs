@@ -0,0 +1,90 @@
+use ultron_core::{
+    BaseEditor, BaseOptions, Command, Decoration, DecorationKind, Diagnostic, Point2,
+};
+
+fn editor(content: &str) -> BaseEditor<()> {
+    BaseEditor::from_str(&BaseOptions::default(), content)
+}
+
+fn set_position(editor: &mut BaseEditor<()>, x: i32, y: i32) {
+    editor.process_command(Command::SetPosition(Point2::new(x, y)));
+}
+
+#[test]
+fn gutter_and_line_decorations() {
+    let mut editor = editor("one\ntwo\nthree");
+    let breakpoint = editor.add_decoration(Decoration::gutter(1, "●", "breakpoint"));
+    editor.add_decoration(Decoration::line(2, "error").with_title("unexpected token"));
+    let decorations = editor.decorations();
+    assert!(decorations.has_gutter_icons());
+    let icons: Vec<&str> = decorations.gutter_icons(1).map(|(icon, _)| icon).collect();
+    assert_eq!(icons, vec!["●"]);
+    let line = decorations.line_decorations(2).next().unwrap();
+    assert_eq!(line.class, "error");
+    assert_eq!(line.title.as_deref(), Some("unexpected token"));
+    assert!(editor.remove_decoration(breakpoint).is_some());
+    assert!(!editor.decorations().has_gutter_icons());
+    editor.decorations_mut().remove_class("error");
+    assert!(editor.decorations().is_empty());
+}
+
+#[test]
+fn line_decorations_follow_the_lines() {
+    let mut editor = editor("one\ntwo\nthree");
+    let id = editor.add_decoration(Decoration::gutter(1, "●", "breakpoint"));
+    // breaking a line after the start of the line keeps the decoration
+    set_position(&mut editor, 2, 1);
+    editor.process_command(Command::BreakLine);
+    assert_eq!(editor.decorations().get(id).unwrap().line_index(), 1);
+    // the lines inserted above move the decoration down
+    set_position(&mut editor, 0, 0);
+    editor.process_command(Command::BreakLine);
+    assert_eq!(editor.decorations().get(id).unwrap().line_index(), 2);
+    editor.process_command(Command::BumpHistory);
+    set_position(&mut editor, 0, 2);
+    editor.process_command(Command::BreakLine);
+    assert_eq!(editor.decorations().get(id).unwrap().line_index(), 3);
+    // undoing the line break joins the lines and moves the decoration up
+    editor.process_command(Command::Undo);
+    assert_eq!(editor.decorations().get(id).unwrap().line_index(), 2);
+}
+
+#[test]
+fn range_decorations_follow_the_text() {
+    let mut editor = editor("let x = foo;\nbar();");
+    let id = editor.add_decoration(Decoration::range(
+        Point2::new(8, 0),
+        Point2::new(11, 0),
+        "squiggle",
+    ));
+    set_position(&mut editor, 0, 0);
+    editor.process_command(Command::InsertText("    ".to_string()));
+    let decoration = editor.decorations().get(id).unwrap();
+    assert_eq!(decoration.kind, DecorationKind::Range);
+    assert_eq!(decoration.start, Point2::new(12, 0));
+    assert_eq!(decoration.end, Point2::new(15, 0));
+    assert_eq!(decoration.columns_in_line(0), Some((12, Some(15))));
+    assert_eq!(decoration.columns_in_line(1), None);
+    let spanning = Decoration::range(Point2::new(2, 1), Point2::new(4, 0), "highlight");
+    assert_eq!(spanning.columns_in_line(0), Some((4, None)));
+    assert_eq!(spanning.columns_in_line(1), Some((0, Some(2))));
+    editor.add_decoration(spanning);
+    assert_eq!(editor.decorations().range_decorations(1).count(), 1);
+    assert_eq!(editor.decorations().range_decorations(0).count(), 2);
+}
+
+#[test]
+fn decorations_and_diagnostics_are_cleared_when_the_content_is_set() {
+    let mut editor = editor("one\ntwo\nthree");
+    editor.add_decoration(Decoration::gutter(1, "●", "breakpoint"));
+    editor.set_diagnostics([Diagnostic::error(
+        Point2::new(0, 2),
+        Point2::new(5, 2),
+        "unexpected token",
+    )]);
+    editor.add_selection(Point2::new(0, 0), Point2::new(3, 0));
+    editor.process_command(Command::SetContent("four\nfive".to_string()));
+    assert!(editor.decorations().is_empty());
+    assert!(editor.diagnostics().is_empty());
+    assert!(editor.secondary_selections().is_empty());
+}
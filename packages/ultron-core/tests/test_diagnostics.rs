@@ -0,0 +1,98 @@
+use ultron_core::{
+    BaseEditor, BaseOptions, Command, Diagnostic, Fix, Key, KeyChord, Keymap, Modifiers, Point2,
+    Severity,
+};
+
+fn editor(content: &str) -> BaseEditor<()> {
+    BaseEditor::from_str(&BaseOptions::default(), content)
+}
+
+fn set_position(editor: &mut BaseEditor<()>, x: i32, y: i32) {
+    editor.process_command(Command::SetPosition(Point2::new(x, y)));
+}
+
+fn diagnostics() -> Vec<Diagnostic> {
+    vec![
+        Diagnostic::warning(Point2::new(4, 2), Point2::new(5, 2), "unused variable: `y`")
+            .with_source("rustc")
+            .with_fix(Fix::new(
+                "prefix it with an underscore",
+                Point2::new(4, 2),
+                Point2::new(5, 2),
+                "_y",
+            )),
+        Diagnostic::error(
+            Point2::new(8, 0),
+            Point2::new(11, 0),
+            "cannot find value `foo`",
+        ),
+        Diagnostic::new(Point2::new(0, 2), Point2::new(3, 2), Severity::Hint, "let"),
+    ]
+}
+
+#[test]
+fn diagnostics_are_sorted_and_queried() {
+    let mut editor = editor("let x = foo;\nbar();\nlet y = 1;");
+    editor.set_diagnostics(diagnostics());
+    let all = editor.diagnostics().all();
+    assert_eq!(all[0].severity, Severity::Error);
+    assert_eq!(all[1].severity, Severity::Hint);
+    assert_eq!(editor.diagnostics().count(Severity::Warning), 1);
+    assert_eq!(
+        editor.diagnostics().severity_on_line(2),
+        Some(Severity::Warning)
+    );
+    assert_eq!(editor.diagnostics().severity_on_line(1), None);
+    let at: Vec<String> = editor
+        .diagnostics()
+        .at(Point2::new(4, 2))
+        .map(|diagnostic| diagnostic.message)
+        .collect();
+    assert_eq!(at, vec!["unused variable: `y`"]);
+    assert_eq!(
+        all[2].label(),
+        "warning[rustc]: unused variable: `y`".to_string()
+    );
+    editor.clear_diagnostics();
+    assert!(editor.diagnostics().is_empty());
+}
+
+#[test]
+fn jump_to_the_next_and_previous_diagnostics() {
+    let mut editor = editor("let x = foo;\nbar();\nlet y = 1;");
+    editor.set_diagnostics(diagnostics());
+    editor.process_command(Command::NextDiagnostic);
+    assert_eq!(editor.get_position(), Point2::new(8, 0));
+    editor.process_command(Command::NextDiagnostic);
+    assert_eq!(editor.get_position(), Point2::new(0, 2));
+    editor.process_command(Command::NextDiagnostic);
+    assert_eq!(editor.get_position(), Point2::new(4, 2));
+    // wraps around to the first one
+    editor.process_command(Command::NextDiagnostic);
+    assert_eq!(editor.get_position(), Point2::new(8, 0));
+    editor.process_command(Command::PrevDiagnostic);
+    assert_eq!(editor.get_position(), Point2::new(4, 2));
+    let keymap = Keymap::standard();
+    let f8 = [KeyChord::new(Key::F(8), Modifiers::NONE)];
+    assert_eq!(keymap.get(&f8), Some(&Command::NextDiagnostic.into()));
+}
+
+#[test]
+fn diagnostics_follow_edits_and_apply_fixes() {
+    let mut editor = editor("let x = foo;\nbar();\nlet y = 1;");
+    editor.set_diagnostics(diagnostics());
+    set_position(&mut editor, 0, 2);
+    editor.process_command(Command::InsertText("    ".to_string()));
+    let warning = editor.diagnostics().all().remove(2);
+    assert_eq!(warning.start, Point2::new(8, 2));
+    assert_eq!(warning.fix.as_ref().unwrap().start, Point2::new(8, 2));
+    set_position(&mut editor, 8, 2);
+    assert!(editor.process_command(Command::ApplyFix));
+    assert_eq!(
+        editor.get_content(),
+        "let x = foo;\nbar();\n    let _y = 1;"
+    );
+    // there is no fix at the cursor
+    set_position(&mut editor, 0, 1);
+    assert!(!editor.process_command(Command::ApplyFix));
+}
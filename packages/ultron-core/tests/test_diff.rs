@@ -0,0 +1,110 @@
+use ultron_core::{Diff, DiffCommand, DiffEditor, DiffKind, TextBuffer};
+
+const OLD: &str = "fn main() {\n    let x = 1;\n    let y = 2;\n    println!(\"{x}\");\n}";
+const NEW: &str =
+    "fn main() {\n    let x = 10;\n    println!(\"{x}\");\n    println!(\"done\");\n}";
+
+fn diff_editor() -> DiffEditor {
+    DiffEditor::new(
+        &TextBuffer::new_from_str(OLD),
+        &TextBuffer::new_from_str(NEW),
+    )
+}
+
+#[test]
+fn lines_and_characters_are_compared() {
+    let diff = Diff::new(
+        &TextBuffer::new_from_str(OLD),
+        &TextBuffer::new_from_str(NEW),
+    );
+    let kinds: Vec<DiffKind> = diff.hunks().iter().map(|hunk| hunk.kind()).collect();
+    assert_eq!(kinds, vec![DiffKind::Changed, DiffKind::Added]);
+    assert_eq!(diff.hunks()[0].old_lines, 1..3);
+    assert_eq!(diff.hunks()[0].new_lines, 1..2);
+    // only the inserted `0` is highlighted in the changed line
+    assert_eq!(diff.old_highlights(1), &[] as &[(usize, usize)]);
+    assert_eq!(diff.new_highlights(1), &[(13, 14)]);
+
+    let side_by_side: Vec<(Option<usize>, Option<usize>, DiffKind)> = diff
+        .side_by_side_rows()
+        .into_iter()
+        .map(|row| (row.old_line, row.new_line, row.kind))
+        .collect();
+    assert_eq!(
+        side_by_side,
+        vec![
+            (Some(0), Some(0), DiffKind::Unchanged),
+            (Some(1), Some(1), DiffKind::Changed),
+            (Some(2), None, DiffKind::Removed),
+            (Some(3), Some(2), DiffKind::Unchanged),
+            (None, Some(3), DiffKind::Added),
+            (Some(4), Some(4), DiffKind::Unchanged),
+        ]
+    );
+    let inline: Vec<(Option<usize>, Option<usize>)> = diff
+        .inline_rows()
+        .into_iter()
+        .map(|row| (row.old_line, row.new_line))
+        .collect();
+    assert_eq!(
+        inline,
+        vec![
+            (Some(0), Some(0)),
+            (Some(1), None),
+            (Some(2), None),
+            (None, Some(1)),
+            (Some(3), Some(2)),
+            (None, Some(3)),
+            (Some(4), Some(4)),
+        ]
+    );
+}
+
+#[test]
+fn hunks_are_navigated_and_accepted() {
+    let mut diff_editor = diff_editor();
+    assert_eq!(diff_editor.current_hunk(), None);
+    diff_editor.process_command(DiffCommand::PrevHunk);
+    assert_eq!(diff_editor.current_hunk(), Some(1));
+    diff_editor.process_command(DiffCommand::NextHunk);
+    assert_eq!(diff_editor.current_hunk(), Some(0));
+
+    assert!(diff_editor.process_command(DiffCommand::AcceptHunk(1)));
+    assert_eq!(
+        diff_editor.old().get_content(),
+        "fn main() {\n    let x = 1;\n    let y = 2;\n    println!(\"{x}\");\n    println!(\"done\");\n}"
+    );
+    assert_eq!(diff_editor.diff().hunks().len(), 1);
+    assert_eq!(diff_editor.current_hunk(), Some(0));
+
+    assert!(diff_editor.process_command(DiffCommand::AcceptHunk(0)));
+    assert_eq!(diff_editor.old().get_content(), NEW);
+    assert!(diff_editor.diff().is_empty());
+    assert_eq!(diff_editor.current_hunk(), None);
+}
+
+#[test]
+fn hunks_are_reverted() {
+    let mut diff_editor = diff_editor();
+    assert!(diff_editor.process_command(DiffCommand::RevertHunk(1)));
+    assert_eq!(
+        diff_editor.new_editor().get_content(),
+        "fn main() {\n    let x = 10;\n    println!(\"{x}\");\n}"
+    );
+    assert!(diff_editor.process_command(DiffCommand::RevertAll));
+    assert_eq!(diff_editor.new_editor().get_content(), OLD);
+    assert!(diff_editor.diff().is_empty());
+
+    // the lines removed at the end of the content
+    let mut diff_editor = DiffEditor::new(
+        &TextBuffer::new_from_str("a\nb\nc"),
+        &TextBuffer::new_from_str("a"),
+    );
+    assert_eq!(diff_editor.diff().hunks()[0].kind(), DiffKind::Removed);
+    diff_editor.process_command(DiffCommand::AcceptAll);
+    assert_eq!(diff_editor.old().get_content(), "a");
+    diff_editor.set_contents("a", "x\na\ny");
+    diff_editor.process_command(DiffCommand::AcceptHunk(1));
+    diff_editor.process_command(DiffCommand::AcceptHunk(0));
+    assert_eq!(diff_editor.old().get_content(), "x\na\ny");
+}
@@ -0,0 +1,38 @@
+use ultron_core::{BaseEditor, BaseOptions, Command, Edit, Point2};
+
+fn editor(content: &str) -> BaseEditor<()> {
+    BaseEditor::from_str(&BaseOptions::default(), content)
+}
+
+#[test]
+fn edits_are_applied_in_one_undo_step() {
+    let mut editor = editor("fn main(){\nlet x=1;\n}");
+    editor.process_command(Command::SetPosition(Point2::new(4, 1)));
+    editor.process_command(Command::ApplyEdits(vec![
+        Edit::insert(Point2::new(0, 1), "    "),
+        Edit::new(Point2::new(5, 1), Point2::new(6, 1), " = "),
+        Edit::insert(Point2::new(9, 0), " "),
+    ]));
+    assert_eq!(editor.get_content(), "fn main() {\n    let x = 1;\n}");
+    // the cursor stays in front of `x`
+    assert_eq!(editor.get_position(), Point2::new(8, 1));
+
+    editor.process_command(Command::Undo);
+    assert_eq!(editor.get_content(), "fn main(){\nlet x=1;\n}");
+}
+
+#[test]
+fn edits_at_the_same_location_are_inserted_in_order() {
+    let mut editor = editor("ac");
+    editor.process_command(Command::ApplyEdits(vec![
+        Edit::insert(Point2::new(1, 0), "b\n"),
+        Edit::insert(Point2::new(1, 0), "b"),
+    ]));
+    assert_eq!(editor.get_content(), "ab\nbc");
+
+    let edit = Edit::new(Point2::new(2, 0), Point2::new(1, 1), "\n\n");
+    assert_eq!(edit.shift_point(Point2::new(1, 0)), Point2::new(1, 0));
+    assert_eq!(edit.shift_point(Point2::new(0, 1)), Point2::new(2, 0));
+    assert_eq!(edit.shift_point(Point2::new(3, 1)), Point2::new(2, 2));
+    assert_eq!(edit.shift_point(Point2::new(0, 3)), Point2::new(0, 4));
+}
@@ -0,0 +1,170 @@
+use ultron_core::{
+    BaseEditor, BaseOptions, Command, Emacs, Key, KeyEvent, KeyOutcome, Modifiers, Point2,
+};
+
+fn editor(content: &str) -> BaseEditor<()> {
+    BaseEditor::from_str(&BaseOptions::default(), content)
+}
+
+/// type the space separated keys, ie: `C-x C-x`, `M-f`, `Enter` or the characters of a word
+fn type_keys(emacs: &mut Emacs, editor: &mut BaseEditor<()>, keys: &str) -> KeyOutcome {
+    let mut outcome = KeyOutcome::default();
+    for keys in keys.split(' ') {
+        let events = if let Some(key) = keys.strip_prefix("C-") {
+            vec![KeyEvent::new(
+                Key::from_name(key).expect("a key"),
+                Modifiers::ctrl(),
+            )]
+        } else if let Some(key) = keys.strip_prefix("M-") {
+            vec![KeyEvent::new(
+                Key::from_name(key).expect("a key"),
+                Modifiers::alt(),
+            )]
+        } else if let Some(key) = Key::from_name(keys).filter(|_| keys.chars().count() > 1) {
+            vec![KeyEvent::new(key, Modifiers::NONE)]
+        } else {
+            keys.chars()
+                .map(|ch| KeyEvent::new(Key::Char(ch), Modifiers::NONE))
+                .collect()
+        };
+        for event in events {
+            let key_outcome = emacs.process_key(editor, event);
+            outcome.is_content_changed |= key_outcome.is_content_changed;
+            outcome.actions.extend(key_outcome.actions);
+        }
+    }
+    outcome
+}
+
+#[test]
+fn motions() {
+    let mut editor = editor("hello world\nfoo-bar baz");
+    let mut emacs = Emacs::new();
+    type_keys(&mut emacs, &mut editor, "C-f C-f C-n");
+    assert_eq!(editor.get_position(), Point2::new(2, 1));
+    type_keys(&mut emacs, &mut editor, "C-e");
+    assert_eq!(editor.get_position(), Point2::new(11, 1));
+    type_keys(&mut emacs, &mut editor, "M-b");
+    assert_eq!(editor.get_position(), Point2::new(8, 1));
+    type_keys(&mut emacs, &mut editor, "M-b M-b");
+    assert_eq!(editor.get_position(), Point2::new(0, 1));
+    type_keys(&mut emacs, &mut editor, "M-f");
+    assert_eq!(editor.get_position(), Point2::new(3, 1));
+    type_keys(&mut emacs, &mut editor, "C-p C-a");
+    assert_eq!(editor.get_position(), Point2::new(0, 0));
+    type_keys(&mut emacs, &mut editor, "M-> C-b");
+    assert_eq!(editor.get_position(), Point2::new(10, 1));
+}
+
+#[test]
+fn kill_line_and_yank() {
+    let mut editor = editor("one\ntwo\nthree");
+    let mut emacs = Emacs::new();
+    let outcome = type_keys(&mut emacs, &mut editor, "C-k");
+    assert!(outcome.is_content_changed);
+    assert_eq!(editor.get_content(), "\ntwo\nthree");
+    // the consecutive kills are appended into one kill
+    type_keys(&mut emacs, &mut editor, "C-k C-k");
+    assert_eq!(editor.get_content(), "\nthree");
+    assert_eq!(
+        editor.registers().kill_ring().next().unwrap().text,
+        "one\ntwo"
+    );
+    type_keys(&mut emacs, &mut editor, "C-n C-k C-p C-y");
+    assert_eq!(editor.get_content(), "three\n");
+    type_keys(&mut emacs, &mut editor, "M-y");
+    assert_eq!(editor.get_content(), "one\ntwo\n");
+    type_keys(&mut emacs, &mut editor, "C-/");
+    assert_eq!(editor.get_content(), "three\n");
+}
+
+#[test]
+fn mark_and_region() {
+    let mut editor = editor("alpha beta gamma");
+    let mut emacs = Emacs::new();
+    type_keys(&mut emacs, &mut editor, "M-f C-f C-Space M-f");
+    assert!(emacs.is_mark_active());
+    assert_eq!(emacs.status(), "");
+    assert_eq!(editor.selected_text(), Some("beta".to_string()));
+    type_keys(&mut emacs, &mut editor, "C-x C-x");
+    assert_eq!(editor.get_position(), Point2::new(6, 0));
+    assert_eq!(emacs.mark(&editor), Some(Point2::new(10, 0)));
+    assert_eq!(editor.selected_text(), Some("beta".to_string()));
+    type_keys(&mut emacs, &mut editor, "C-w");
+    assert!(!emacs.is_mark_active());
+    assert_eq!(editor.get_content(), "alpha  gamma");
+    assert_eq!(editor.registers().kill_ring().next().unwrap().text, "beta");
+    type_keys(&mut emacs, &mut editor, "C-Space C-a M-w C-e C-y");
+    assert_eq!(editor.get_content(), "alpha  gammaalpha ");
+    type_keys(&mut emacs, &mut editor, "C-Space C-b C-g");
+    assert!(!emacs.is_mark_active());
+    assert_eq!(editor.selected_text(), None);
+    assert_eq!(emacs.status(), "Quit");
+}
+
+#[test]
+fn mark_follows_the_edits() {
+    let mut editor = editor("alpha beta");
+    let mut emacs = Emacs::new();
+    type_keys(&mut emacs, &mut editor, "M-f C-f C-Space C-g C-a");
+    editor.process_command(Command::InsertText("new\n".to_string()));
+    assert_eq!(emacs.mark(&editor), Some(Point2::new(6, 1)));
+    type_keys(&mut emacs, &mut editor, "C-x C-x");
+    assert_eq!(editor.get_position(), Point2::new(6, 1));
+}
+
+#[test]
+fn incremental_search() {
+    let mut editor = editor("foo bar\nFoo baz\nbar");
+    let mut emacs = Emacs::new();
+    type_keys(&mut emacs, &mut editor, "C-s ba");
+    assert_eq!(emacs.status(), "I-search: ba");
+    assert_eq!(editor.get_position(), Point2::new(6, 0));
+    assert_eq!(editor.selected_text(), Some("ba".to_string()));
+    type_keys(&mut emacs, &mut editor, "z");
+    assert_eq!(editor.get_position(), Point2::new(7, 1));
+    type_keys(&mut emacs, &mut editor, "x");
+    assert_eq!(emacs.status(), "Failing I-search: bazx");
+    type_keys(&mut emacs, &mut editor, "Backspace Backspace C-s C-s");
+    assert_eq!(editor.get_position(), Point2::new(2, 2));
+    // wrapping around to the first match
+    type_keys(&mut emacs, &mut editor, "C-s");
+    assert_eq!(editor.get_position(), Point2::new(6, 0));
+    type_keys(&mut emacs, &mut editor, "Enter");
+    assert!(!emacs.is_searching());
+    assert_eq!(emacs.mark(&editor), Some(Point2::new(0, 0)));
+    // the search ignores the case when the query is in lowercase
+    type_keys(&mut emacs, &mut editor, "C-r foo");
+    assert_eq!(emacs.status(), "I-search backward: foo");
+    assert_eq!(editor.get_position(), Point2::new(0, 0));
+    type_keys(&mut emacs, &mut editor, "C-r");
+    assert_eq!(editor.get_position(), Point2::new(0, 1));
+    type_keys(&mut emacs, &mut editor, "C-g");
+    assert_eq!(editor.get_position(), Point2::new(6, 0));
+    // a key not used in the search ends it and is processed
+    type_keys(&mut emacs, &mut editor, "C-s baz C-a");
+    assert!(!emacs.is_searching());
+    assert_eq!(editor.get_position(), Point2::new(0, 1));
+}
+
+#[test]
+fn key_sequences_and_host_actions() {
+    let mut editor = editor("text");
+    let mut emacs = Emacs::new();
+    let outcome = type_keys(&mut emacs, &mut editor, "C-x C-s");
+    assert_eq!(outcome.actions, vec!["save"]);
+    assert_eq!(editor.get_content(), "text");
+    let outcome = type_keys(&mut emacs, &mut editor, "M-x");
+    assert_eq!(outcome.actions, vec!["command-palette"]);
+    let outcome = type_keys(&mut emacs, &mut editor, "M-g g");
+    assert_eq!(outcome.actions, vec!["go-to-line"]);
+    emacs
+        .keymap_mut()
+        .load_config("C-x C-f = @open-file\nC-x u = none")
+        .unwrap();
+    let outcome = type_keys(&mut emacs, &mut editor, "C-x C-f");
+    assert_eq!(outcome.actions, vec!["open-file"]);
+    type_keys(&mut emacs, &mut editor, "ab C-x u");
+    assert_eq!(editor.get_content(), "abtext");
+    assert_eq!(emacs.status(), "Ctrl+x u is undefined");
+}
@@ -0,0 +1,163 @@
+use ultron_core::{
+    BaseEditor, BaseOptions, Command, FoldRange, FoldStrategy, Folding, Point2, TextBuffer,
+};
+
+const CODE: &str = "fn main() {\n    if x {\n        foo();\n    }\n    bar();\n}\nfn other() {}";
+const PY: &str = "def main():\n    if x:\n        foo()\n    bar()\nother()";
+
+fn editor(content: &str) -> BaseEditor<()> {
+    BaseEditor::from_str(&BaseOptions::default(), content)
+}
+
+#[test]
+fn indentation_ranges() {
+    let folding = Folding::new(FoldStrategy::Indentation, &TextBuffer::new_from_str(PY));
+    assert_eq!(
+        folding.ranges(),
+        &[
+            FoldRange {
+                start: 0,
+                end: 3,
+                level: 1
+            },
+            FoldRange {
+                start: 1,
+                end: 2,
+                level: 2
+            },
+        ]
+    );
+}
+
+#[test]
+fn bracket_ranges_keep_closing_line_visible() {
+    let folding = Folding::new(FoldStrategy::Brackets, &TextBuffer::new_from_str(CODE));
+    assert_eq!(
+        folding.ranges(),
+        &[
+            FoldRange {
+                start: 0,
+                end: 4,
+                level: 1
+            },
+            FoldRange {
+                start: 1,
+                end: 2,
+                level: 2
+            },
+        ]
+    );
+}
+
+#[test]
+fn marker_ranges() {
+    let raw = "// region imports\nuse a;\nuse b;\n// endregion\nfn main() {}";
+    let strategy = FoldStrategy::Markers {
+        start: "region".to_string(),
+        end: "endregion".to_string(),
+    };
+    let folding = Folding::new(strategy, &TextBuffer::new_from_str(raw));
+    assert_eq!(
+        folding.ranges(),
+        &[FoldRange {
+            start: 0,
+            end: 3,
+            level: 1
+        }]
+    );
+}
+
+#[test]
+fn rows_skip_folded_lines() {
+    let mut editor = editor(PY);
+    editor.process_command(Command::SetPosition(Point2::new(0, 2)));
+    editor.process_command(Command::Fold);
+    assert!(editor.folding().is_folded(1));
+    assert!(editor.folding().is_hidden(2));
+    assert!(!editor.folding().is_hidden(3));
+    // the cursor is moved out of the hidden line
+    assert_eq!(editor.get_position(), Point2::new(0, 1));
+    assert_eq!(editor.line_to_row(3), 2);
+    assert_eq!(editor.row_to_line(2), 3);
+    assert_eq!(editor.line_to_row(2), 1);
+}
+
+#[test]
+fn move_down_skips_folded_lines() {
+    let mut editor = editor(PY);
+    editor.process_command(Command::ToggleFoldAt(1));
+    editor.process_command(Command::SetPosition(Point2::new(4, 1)));
+    editor.process_command(Command::MoveDown);
+    assert_eq!(editor.get_position(), Point2::new(4, 3));
+    editor.process_command(Command::MoveUp);
+    // moving up into the folded range lands on the line it is folded into
+    assert_eq!(editor.get_position(), Point2::new(4, 1));
+}
+
+#[test]
+fn fold_to_level() {
+    let mut editor = editor(PY);
+    editor.process_command(Command::FoldToLevel(1));
+    assert!(!editor.folding().is_folded(0));
+    assert!(editor.folding().is_folded(1));
+    editor.process_command(Command::FoldAll);
+    assert!(editor.folding().is_folded(0));
+    assert_eq!(editor.row_to_line(1), 4);
+    editor.process_command(Command::UnfoldAll);
+    assert!(!editor.folding().is_hidden(2));
+}
+
+#[test]
+fn folds_survive_edits_elsewhere() {
+    let mut editor = editor(PY);
+    editor.process_command(Command::ToggleFoldAt(1));
+    editor.process_command(Command::SetPosition(Point2::new(7, 4)));
+    editor.process_command(Command::InsertChar('x'));
+    assert!(editor.folding().is_folded(1));
+}
+
+#[test]
+fn folds_follow_inserted_and_removed_lines() {
+    let mut editor = editor(PY);
+    editor.process_command(Command::ToggleFoldAt(1));
+    editor.process_command(Command::SetPosition(Point2::new(0, 0)));
+    editor.process_command(Command::BreakLine);
+    assert!(editor.folding().is_folded(2));
+    assert!(editor.folding().is_hidden(3));
+    assert!(!editor.folding().is_folded(1));
+    editor.process_command(Command::ReplaceText(
+        Point2::new(0, 0),
+        Point2::new(0, 1),
+        String::new(),
+    ));
+    assert!(editor.folding().is_folded(1));
+    assert!(editor.folding().is_hidden(2));
+}
+
+#[test]
+fn ranges_of_changed_lines_match_a_full_scan() {
+    for strategy in [FoldStrategy::Indentation, FoldStrategy::Brackets] {
+        let options = BaseOptions {
+            fold_strategy: strategy.clone(),
+            ..Default::default()
+        };
+        let mut editor = BaseEditor::<()>::from_str(&options, CODE);
+        let commands = [
+            Command::SetPosition(Point2::new(16, 2)),
+            Command::BreakLine,
+            Command::InsertText("    baz(".to_string()),
+            Command::BreakLine,
+            Command::InsertChar(')'),
+            Command::SetPosition(Point2::new(0, 6)),
+            Command::DeleteBack,
+            Command::InsertChar('}'),
+            Command::SetPosition(Point2::new(0, 1)),
+            Command::DeleteForward,
+        ];
+        for command in commands {
+            editor.process_command(command);
+            let scanned = Folding::new(strategy.clone(), editor.text_buffer());
+            assert_eq!(editor.folding().ranges(), scanned.ranges());
+        }
+    }
+}
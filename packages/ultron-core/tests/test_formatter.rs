@@ -0,0 +1,114 @@
+use ultron_core::{
+    diff_edits, BaseEditor, BaseOptions, Command, Edit, FormatError, Formatted, Formatter, Point2,
+    TextBuffer,
+};
+
+fn editor(content: &str) -> BaseEditor<()> {
+    BaseEditor::from_str(&BaseOptions::default(), content)
+}
+
+/// puts a space after the commas and trims the trailing whitespaces
+struct CommaFormatter;
+
+impl Formatter for CommaFormatter {
+    fn format(
+        &self,
+        buffer: &TextBuffer,
+        range: Option<(Point2<usize>, Point2<usize>)>,
+    ) -> Result<Formatted, FormatError> {
+        let (start, end) = range
+            .map(|(start, end)| (start.y, end.y))
+            .unwrap_or((0, usize::MAX));
+        let lines: Vec<String> = buffer
+            .lines()
+            .into_iter()
+            .enumerate()
+            .map(|(y, line)| {
+                if (start..end).contains(&y) {
+                    line.replace(", ", ",")
+                        .replace(',', ", ")
+                        .trim_end()
+                        .to_string()
+                } else {
+                    line
+                }
+            })
+            .collect();
+        Ok(Formatted::Text(lines.join("\n")))
+    }
+}
+
+/// fails on every content
+struct Failing;
+
+impl Formatter for Failing {
+    fn format(
+        &self,
+        _buffer: &TextBuffer,
+        _range: Option<(Point2<usize>, Point2<usize>)>,
+    ) -> Result<Formatted, FormatError> {
+        Err(FormatError("syntax error".into()))
+    }
+}
+
+#[test]
+fn only_the_changed_characters_are_edited() {
+    assert_eq!(
+        diff_edits("fn f(a,b) {}\nlet x = 1;\n", "fn f(a, b) {}\nlet x = 1;\n"),
+        vec![Edit::new(Point2::new(7, 0), Point2::new(7, 0), " ")]
+    );
+    assert_eq!(
+        diff_edits("a\nb\nc\nd", "a\nc\nd\ne"),
+        vec![
+            Edit::new(Point2::new(0, 1), Point2::new(0, 2), ""),
+            Edit::new(Point2::new(1, 3), Point2::new(1, 3), "\ne"),
+        ]
+    );
+    assert_eq!(
+        diff_edits("", "世界"),
+        vec![Edit::new(Point2::new(0, 0), Point2::new(0, 0), "世界")]
+    );
+    assert!(diff_edits("same\n", "same\n").is_empty());
+}
+
+#[test]
+fn formatting_keeps_the_cursor_marks_and_history() {
+    let mut editor = editor("let a = f(x,y);   \nlet b = g(1,2);");
+    editor.set_formatter(CommaFormatter);
+    editor.process_command(Command::InsertText("// ".into()));
+    editor.process_command(Command::SetPosition(Point2::new(12, 1)));
+    editor.process_command(Command::SetMark("a".into()));
+    editor.process_command(Command::SetPosition(Point2::new(14, 1)));
+
+    assert!(editor.process_command(Command::Format));
+    assert_eq!(
+        editor.get_content(),
+        "// let a = f(x, y);\nlet b = g(1, 2);"
+    );
+    // the cursor and the mark follow the text they are in
+    assert_eq!(editor.get_position(), Point2::new(15, 1));
+    assert_eq!(editor.marks().mark("a"), Some(Point2::new(13, 1)));
+
+    // the formatting is undone in one step, and the edits before it are kept
+    editor.process_command(Command::Undo);
+    assert_eq!(
+        editor.get_content(),
+        "// let a = f(x,y);   \nlet b = g(1,2);"
+    );
+    editor.process_command(Command::Undo);
+    assert_eq!(editor.get_content(), "let a = f(x,y);   \nlet b = g(1,2);");
+}
+
+#[test]
+fn the_selected_lines_are_formatted() {
+    let mut editor = editor("f(a,b)\ng(c,d)\nh(e,f)");
+    editor.set_formatter(CommaFormatter);
+    editor.process_command(Command::SetSelection(Point2::new(2, 1), Point2::new(1, 2)));
+    editor.process_command(Command::FormatSelection);
+    assert_eq!(editor.get_content(), "f(a,b)\ng(c, d)\nh(e, f)");
+
+    editor.set_formatter(Failing);
+    assert_eq!(editor.format(None), Err(FormatError("syntax error".into())));
+    assert!(!editor.process_command(Command::Format));
+    assert_eq!(editor.get_content(), "f(a,b)\ng(c, d)\nh(e, f)");
+}
@@ -0,0 +1,56 @@
+use ultron_core::{BaseEditor, BaseOptions, Command, GoToTarget, InvalidGoToTarget, Point2};
+
+fn editor(content: &str) -> BaseEditor<()> {
+    BaseEditor::from_str(&BaseOptions::default(), content)
+}
+
+#[test]
+fn parse_go_to_targets() {
+    assert_eq!(
+        "12".parse(),
+        Ok(GoToTarget::Line {
+            line: 12,
+            column: None
+        })
+    );
+    assert_eq!(
+        " 12:5 ".parse(),
+        Ok(GoToTarget::Line {
+            line: 12,
+            column: Some(5)
+        })
+    );
+    assert_eq!("+3".parse(), Ok(GoToTarget::Relative(3)));
+    assert_eq!("-3".parse(), Ok(GoToTarget::Relative(-3)));
+    assert_eq!(
+        "+-3".parse::<GoToTarget>(),
+        Err(InvalidGoToTarget("+-3".to_string()))
+    );
+    assert!("abc".parse::<GoToTarget>().is_err());
+    assert!("12:".parse::<GoToTarget>().is_err());
+}
+
+#[test]
+fn go_to_lines_and_columns() {
+    let mut editor = editor("first\nsecond line\nthird\nfourth");
+    assert_eq!(editor.go_to("2:8"), Ok(Point2::new(7, 1)));
+    assert_eq!(editor.go_to("+1"), Ok(Point2::new(0, 2)));
+    assert_eq!(editor.go_to("-2"), Ok(Point2::new(0, 0)));
+    // the location is clamped to the content
+    assert_eq!(editor.go_to("100"), Ok(Point2::new(0, 3)));
+    assert_eq!(editor.go_to("-100"), Ok(Point2::new(0, 0)));
+    assert_eq!(editor.go_to_line(3, Some(100)), Point2::new(4, 2));
+    assert!(editor.go_to("line").is_err());
+    assert_eq!(editor.get_position(), Point2::new(4, 2));
+}
+
+#[test]
+fn go_to_a_folded_line_unfolds_it() {
+    let mut editor = editor("fn main() {\n    one();\n    two();\n}");
+    editor.process_command(Command::SetPosition(Point2::new(0, 0)));
+    editor.process_command(Command::Fold);
+    assert!(editor.folding().is_hidden(2));
+    editor.go_to_line(3, Some(5));
+    assert!(!editor.folding().is_hidden(2));
+    assert_eq!(editor.get_position(), Point2::new(4, 2));
+}
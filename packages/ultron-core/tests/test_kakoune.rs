@@ -0,0 +1,158 @@
+use ultron_core::{
+    BaseEditor, BaseOptions, Command, Kakoune, KakouneMode, Key, KeyEvent, Modifiers, Point2,
+};
+
+fn editor(content: &str) -> BaseEditor<()> {
+    BaseEditor::from_str(&BaseOptions::default(), content)
+}
+
+/// type the keys, where `<Esc>`, `<Enter>` and `<A-x>` are the special keys
+fn type_keys(kakoune: &mut Kakoune, editor: &mut BaseEditor<()>, keys: &str) {
+    let mut rest = keys;
+    while let Some(ch) = rest.chars().next() {
+        let special = rest
+            .strip_prefix('<')
+            .and_then(|after| after.split_once('>'))
+            .filter(|(name, _)| name.len() > 1);
+        let event = match special {
+            Some((name, after)) => {
+                rest = after;
+                match name.strip_prefix("A-") {
+                    Some(key) => {
+                        KeyEvent::new(Key::from_name(key).expect("a key"), Modifiers::alt())
+                    }
+                    None => KeyEvent::new(Key::from_name(name).expect("a key"), Modifiers::NONE),
+                }
+            }
+            None => {
+                rest = &rest[ch.len_utf8()..];
+                KeyEvent::new(Key::Char(ch), Modifiers::NONE)
+            }
+        };
+        kakoune.process_key(editor, event);
+    }
+}
+
+fn selected_texts(editor: &BaseEditor<()>) -> Vec<String> {
+    editor
+        .selections()
+        .iter()
+        .map(|selection| {
+            let start = selection.start.unwrap();
+            let end = selection.end.unwrap();
+            let (start, end) = if (start.y, start.x) <= (end.y, end.x) {
+                (start, end)
+            } else {
+                (end, start)
+            };
+            editor.text_buffer().get_text(
+                Point2::new(start.x as usize, start.y as usize),
+                Point2::new(end.x as usize + 1, end.y as usize),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn motions_select_and_operators_act() {
+    let mut editor = editor("foo bar baz");
+    let mut kakoune = Kakoune::new();
+    type_keys(&mut kakoune, &mut editor, "w");
+    assert_eq!(selected_texts(&editor), vec!["foo "]);
+    type_keys(&mut kakoune, &mut editor, "w");
+    assert_eq!(selected_texts(&editor), vec!["bar "]);
+    type_keys(&mut kakoune, &mut editor, "E");
+    assert_eq!(selected_texts(&editor), vec!["bar baz"]);
+    type_keys(&mut kakoune, &mut editor, "d");
+    assert_eq!(editor.get_content(), "foo ");
+    type_keys(&mut kakoune, &mut editor, "bc");
+    assert_eq!(kakoune.mode(), KakouneMode::Insert);
+    type_keys(&mut kakoune, &mut editor, "qux<Esc>");
+    assert_eq!(editor.get_content(), "qux ");
+    type_keys(&mut kakoune, &mut editor, "u");
+    assert_eq!(editor.get_content(), "foo ");
+    type_keys(&mut kakoune, &mut editor, "u");
+    assert_eq!(editor.get_content(), "foo bar baz");
+}
+
+#[test]
+fn select_matches_inside_the_selections() {
+    let mut editor = editor("foo bar baz\nbat");
+    let mut kakoune = Kakoune::new();
+    type_keys(&mut kakoune, &mut editor, "%s");
+    assert_eq!(kakoune.mode(), KakouneMode::Prompt);
+    type_keys(&mut kakoune, &mut editor, "ba.");
+    assert_eq!(kakoune.status(), "select: ba.");
+    type_keys(&mut kakoune, &mut editor, "<Enter>");
+    assert_eq!(selected_texts(&editor), vec!["bar", "baz", "bat"]);
+    type_keys(&mut kakoune, &mut editor, "cx<Esc>");
+    assert_eq!(editor.get_content(), "foo x x\nx");
+    // the change at every selection is undone in one step
+    type_keys(&mut kakoune, &mut editor, "u");
+    assert_eq!(editor.get_content(), "foo bar baz\nbat");
+    type_keys(&mut kakoune, &mut editor, "%s(<Enter>");
+    assert_eq!(kakoune.message(), Some("invalid regex: ("));
+}
+
+#[test]
+fn split_and_keep_or_remove_matching_selections() {
+    let mut editor = editor("one,two,three");
+    let mut kakoune = Kakoune::new();
+    type_keys(&mut kakoune, &mut editor, "%S,<Enter>");
+    assert_eq!(selected_texts(&editor), vec!["one", "two", "three"]);
+    type_keys(&mut kakoune, &mut editor, "<A-k>t<Enter>");
+    assert_eq!(selected_texts(&editor), vec!["two", "three"]);
+    type_keys(&mut kakoune, &mut editor, "<A-K>w<Enter>");
+    assert_eq!(selected_texts(&editor), vec!["three"]);
+    type_keys(&mut kakoune, &mut editor, "~");
+    assert_eq!(editor.get_content(), "one,two,THREE");
+    type_keys(&mut kakoune, &mut editor, "<A-k>x<Enter>");
+    assert_eq!(kakoune.message(), Some("no selections remaining"));
+    assert_eq!(selected_texts(&editor), vec!["THREE"]);
+}
+
+#[test]
+fn multiple_insert_cursors() {
+    let mut editor = editor("a\nb\nc");
+    let mut kakoune = Kakoune::new();
+    type_keys(&mut kakoune, &mut editor, "CC");
+    assert_eq!(editor.selections().len(), 3);
+    type_keys(&mut kakoune, &mut editor, "i- <Esc>");
+    assert_eq!(editor.get_content(), "- a\n- b\n- c");
+    type_keys(&mut kakoune, &mut editor, "ax<Esc>");
+    assert_eq!(editor.get_content(), "- ax\n- bx\n- cx");
+    type_keys(&mut kakoune, &mut editor, ",");
+    assert_eq!(editor.selections().len(), 1);
+    assert_eq!(editor.get_position(), Point2::new(3, 2));
+}
+
+#[test]
+fn yank_and_paste_lines() {
+    let mut editor = editor("one\ntwo");
+    let mut kakoune = Kakoune::new();
+    type_keys(&mut kakoune, &mut editor, "xy");
+    assert_eq!(editor.registers().kill_ring().next().unwrap().text, "one\n");
+    type_keys(&mut kakoune, &mut editor, "jxP");
+    assert_eq!(editor.get_content(), "one\none\ntwo");
+    type_keys(&mut kakoune, &mut editor, ";xrz");
+    assert_eq!(editor.get_content(), "one\nzzz\ntwo");
+}
+
+#[test]
+fn secondary_selections_follow_the_edits() {
+    let mut editor = editor("abc\ndef");
+    for command in [
+        Command::AddSelection(Point2::new(1, 1), Point2::new(2, 1)),
+        Command::SetPosition(Point2::new(0, 0)),
+        Command::BreakLine,
+    ] {
+        editor.process_command(command);
+    }
+    assert_eq!(editor.get_content(), "\nabc\ndef");
+    let selection = &editor.secondary_selections()[0];
+    assert_eq!(selection.start, Some(Point2::new(1, 2)));
+    assert_eq!(selection.end, Some(Point2::new(2, 2)));
+    assert!(editor.is_selected(Point2::new(2, 2)));
+    editor.process_command(Command::ClearSelection);
+    assert!(editor.secondary_selections().is_empty());
+}
@@ -0,0 +1,238 @@
+use ultron_core::{
+    Binding, Command, Key, KeyChord, KeyContext, KeyEvent, KeyResult, Keymap, KeymapError,
+    Modifiers, TextTransform,
+};
+
+fn key(name: &str) -> KeyEvent {
+    KeyEvent::new(Key::from_name(name).unwrap(), Modifiers::NONE)
+}
+
+fn ctrl(name: &str) -> KeyEvent {
+    KeyEvent::new(Key::from_name(name).unwrap(), Modifiers::ctrl())
+}
+
+#[test]
+fn parse_chords() {
+    let chord: KeyChord = "Ctrl+Shift+z".parse().unwrap();
+    assert_eq!(chord, KeyChord::new(Key::Char('Z'), Modifiers::ctrl()));
+    assert_eq!(chord.to_string(), "Ctrl+Z");
+    let chord: KeyChord = "C-M-f".parse().unwrap();
+    assert_eq!(chord.to_string(), "Ctrl+Alt+f");
+    let chord: KeyChord = "Ctrl+-".parse().unwrap();
+    assert_eq!(chord.key, Key::Char('-'));
+    assert_eq!(
+        "Shift+F2".parse::<KeyChord>().unwrap().to_string(),
+        "Shift+F2"
+    );
+    assert_eq!("ArrowUp".parse::<KeyChord>().unwrap().key, Key::Up);
+    assert!("Ctrl+Nothing".parse::<KeyChord>().is_err());
+}
+
+#[test]
+fn parse_commands() {
+    assert_eq!("Undo".parse::<Command>(), Ok(Command::Undo));
+    assert_eq!(
+        r#"InsertText("a, b")"#.parse::<Command>(),
+        Ok(Command::InsertText("a, b".to_string()))
+    );
+    assert_eq!(
+        "SetPosition(3, 10)".parse::<Command>(),
+        Ok(Command::SetPosition(ultron_core::Point2::new(3, 10)))
+    );
+    assert_eq!(
+        "Transform(Uppercase)".parse::<Command>(),
+        Ok(Command::Transform(TextTransform::Uppercase))
+    );
+    assert_eq!(
+        "SetSelection((0, 1), (5, 1))".parse::<Command>(),
+        Ok(Command::SetSelection(
+            ultron_core::Point2::new(0, 1),
+            ultron_core::Point2::new(5, 1)
+        ))
+    );
+    assert_eq!(
+        "InsertChar('x')".parse::<Command>(),
+        Ok(Command::InsertChar('x'))
+    );
+    assert_eq!(
+        "StartRecording".parse::<Command>(),
+        Ok(Command::StartRecording(None))
+    );
+    assert_eq!(
+        r#"PlayMacro("q", 3)"#.parse::<Command>(),
+        Ok(Command::PlayMacro(Some("q".to_string()), 3))
+    );
+    assert_eq!(
+        "Undo(1)".parse::<Command>(),
+        Err(KeymapError::InvalidCommand("Undo(1)".to_string()))
+    );
+}
+
+#[test]
+fn standard_keymap() {
+    let mut keymap = Keymap::standard();
+    assert_eq!(
+        keymap.process_key(ctrl("z")),
+        KeyResult::Bound(Binding::Command(Command::Undo))
+    );
+    let ctrl_shift_z = KeyEvent::new(
+        Key::Char('Z'),
+        Modifiers {
+            ctrl: true,
+            shift: true,
+            ..Modifiers::NONE
+        },
+    );
+    assert_eq!(
+        keymap.process_key(ctrl_shift_z),
+        KeyResult::Bound(Binding::Command(Command::Redo))
+    );
+    assert_eq!(
+        keymap.process_key(key("ArrowLeft")),
+        KeyResult::Bound(Binding::Command(Command::MoveLeft))
+    );
+    assert_eq!(
+        keymap.process_key(key("a")),
+        KeyResult::Unbound(vec![key("a")])
+    );
+    assert_eq!(key("a").typed_char(), Some('a'));
+    assert_eq!(ctrl("b").typed_char(), None);
+    assert_eq!(
+        keymap.process_key(ctrl("P")),
+        KeyResult::Bound(Binding::Action("command-palette".to_string()))
+    );
+    assert_eq!(
+        keymap.process_key(ctrl("g")),
+        KeyResult::Bound(Binding::Action("go-to-line".to_string()))
+    );
+}
+
+#[test]
+fn shifted_keys_fall_back_to_their_unshifted_binding() {
+    let mut keymap = Keymap::standard();
+    for (name, command) in [
+        ("Enter", Command::BreakLine),
+        ("Backspace", Command::DeleteBack),
+        ("Up", Command::MoveUp),
+        ("Down", Command::MoveDown),
+    ] {
+        let shifted = KeyEvent::new(Key::from_name(name).unwrap(), Modifiers::shift());
+        assert_eq!(
+            keymap.process_key(shifted),
+            KeyResult::Bound(Binding::Command(command))
+        );
+    }
+    // the shifted binding is used when there is one
+    let shift_f2 = KeyEvent::new(Key::F(2), Modifiers::shift());
+    assert_eq!(
+        keymap.process_key(shift_f2),
+        KeyResult::Bound(Binding::Command(Command::PrevBookmark))
+    );
+    // a shifted character is a different character
+    let shift_y = KeyEvent::new(Key::Char('Y'), Modifiers::shift());
+    assert_eq!(
+        keymap.process_key(shift_y),
+        KeyResult::Unbound(vec![shift_y])
+    );
+}
+
+#[test]
+fn alt_gr_keys_are_typed() {
+    let alt_gr = Modifiers {
+        ctrl: true,
+        alt: true,
+        ..Modifiers::NONE
+    };
+    assert_eq!(
+        KeyEvent::new(Key::Char('@'), alt_gr).typed_char(),
+        Some('@')
+    );
+    assert_eq!(
+        KeyEvent::new(Key::Char('f'), Modifiers::alt()).typed_char(),
+        None
+    );
+}
+
+#[test]
+fn multi_key_sequences_and_overrides() {
+    let mut keymap = Keymap::standard();
+    keymap
+        .load_config(
+            "# uppercase the selection
+            Ctrl+k Ctrl+u = Transform(Uppercase)
+            Ctrl+s = @save
+            Ctrl+r = none",
+        )
+        .unwrap();
+    assert_eq!(keymap.process_key(ctrl("k")), KeyResult::Pending);
+    assert!(keymap.is_pending());
+    assert_eq!(
+        keymap.process_key(ctrl("u")),
+        KeyResult::Bound(Binding::Command(Command::Transform(
+            TextTransform::Uppercase
+        )))
+    );
+    assert_eq!(keymap.process_key(ctrl("k")), KeyResult::Pending);
+    assert_eq!(
+        keymap.process_key(key("x")),
+        KeyResult::Unbound(vec![ctrl("k"), key("x")])
+    );
+    assert_eq!(
+        keymap.process_key(ctrl("s")),
+        KeyResult::Bound(Binding::Action("save".to_string()))
+    );
+    assert_eq!(
+        keymap.process_key(ctrl("r")),
+        KeyResult::Unbound(vec![ctrl("r")])
+    );
+    let keys = keymap.keys_for(&Binding::Command(Command::Redo)).unwrap();
+    assert_eq!(keys, ["Ctrl+Z".parse::<KeyChord>().unwrap()]);
+}
+
+#[test]
+fn context_bindings_apply_while_their_context_is_active() {
+    let mut keymap = Keymap::standard();
+    let shift_tab = KeyEvent::new(Key::Tab, Modifiers::shift());
+    assert_eq!(
+        keymap.process_key(key("Tab")),
+        KeyResult::Bound(Binding::Command(Command::IndentForward))
+    );
+    keymap.set_context(KeyContext::Snippet, true);
+    assert_eq!(
+        keymap.process_key(key("Tab")),
+        KeyResult::Bound(Binding::Command(Command::NextTabStop))
+    );
+    assert_eq!(
+        keymap.process_key(shift_tab),
+        KeyResult::Bound(Binding::Command(Command::PrevTabStop))
+    );
+    assert_eq!(
+        keymap.context_binding(&[key("Escape").chord()]),
+        Some(&Binding::Command(Command::ExitSnippet))
+    );
+    // the other bindings still apply
+    assert_eq!(
+        keymap.process_key(key("Enter")),
+        KeyResult::Bound(Binding::Command(Command::BreakLine))
+    );
+    keymap.set_context(KeyContext::Snippet, false);
+    assert_eq!(
+        keymap.process_key(shift_tab),
+        KeyResult::Bound(Binding::Command(Command::IndentForward))
+    );
+    keymap.load_config("Tab when snippet = none").unwrap();
+    assert_eq!(
+        keymap.get_in(KeyContext::Snippet, &[key("Tab").chord()]),
+        None
+    );
+    let error = Keymap::from_config("Tab when nowhere = NextTabStop").unwrap_err();
+    assert_eq!(error.to_string(), "line 1: invalid context: nowhere");
+}
+
+#[test]
+fn invalid_config() {
+    let error = Keymap::from_config("Ctrl+z = Undo\nCtrl+q Quit").unwrap_err();
+    assert!(matches!(error, KeymapError::InvalidConfig(2, _)));
+    let error = Keymap::from_config("Ctrl+z = Nothing").unwrap_err();
+    assert_eq!(error.to_string(), "line 1: invalid command: Nothing");
+}
@@ -0,0 +1,97 @@
+use ultron_core::{BaseEditor, BaseOptions, Command, LineChange, Point2};
+
+const BASE: &str = "fn main() {\n    let x = 1;\n    let y = 2;\n    println!(\"{x}\");\n}";
+
+fn editor(content: &str) -> BaseEditor<()> {
+    let mut editor = BaseEditor::from_str(&BaseOptions::default(), content);
+    editor.set_base_text(BASE);
+    editor
+}
+
+fn changes(editor: &BaseEditor<()>) -> Vec<Option<LineChange>> {
+    let line_changes = editor.line_changes().expect("base text is set");
+    (0..editor.total_lines())
+        .map(|line| line_changes.line_change(line))
+        .collect()
+}
+
+#[test]
+fn lines_are_compared_with_the_base_text() {
+    let editor = editor("fn main() {\n    let x = 10;\n    println!(\"{x}\");\n    done();\n}");
+    assert_eq!(
+        changes(&editor),
+        vec![
+            None,
+            Some(LineChange::Modified),
+            None,
+            Some(LineChange::Added),
+            None
+        ]
+    );
+    let line_changes = editor.line_changes().unwrap();
+    assert!(line_changes.is_deleted_before(2));
+    assert!(!line_changes.is_deleted_before(1));
+    assert!(!line_changes.is_deleted_after(4));
+    assert_eq!(line_changes.hunks().len(), 2);
+}
+
+#[test]
+fn changes_follow_the_edits() {
+    let mut editor = editor(BASE);
+    assert_eq!(changes(&editor), vec![None; 5]);
+
+    // a line broken at its end adds the next line
+    editor.process_command(Command::SetPosition(Point2::new(14, 1)));
+    editor.process_command(Command::BreakLine);
+    editor.process_command(Command::InsertText("    let z = 3;".to_string()));
+    assert_eq!(changes(&editor)[1..3], [None, Some(LineChange::Added)]);
+
+    // typing in a line modifies it, and typing it back unmodifies it
+    editor.process_command(Command::SetPosition(Point2::new(12, 3)));
+    editor.process_command(Command::InsertChar('0'));
+    assert_eq!(changes(&editor)[3], Some(LineChange::Modified));
+    editor.process_command(Command::DeleteBack);
+    assert_eq!(changes(&editor)[3], None);
+
+    // a line broken at its start moves down, the empty line above it is added
+    editor.process_command(Command::SetPosition(Point2::new(0, 4)));
+    editor.process_command(Command::BreakLine);
+    assert_eq!(changes(&editor)[4..], [Some(LineChange::Added), None, None]);
+    editor.process_command(Command::ReplaceText(
+        Point2::new(0, 4),
+        Point2::new(0, 5),
+        String::new(),
+    ));
+    assert_eq!(changes(&editor)[4..], [None, None]);
+
+    // the line joined with the line above it is deleted
+    editor.process_command(Command::ReplaceText(
+        Point2::new(20, 4),
+        Point2::new(0, 5),
+        String::new(),
+    ));
+    assert_eq!(changes(&editor)[4], Some(LineChange::Modified));
+    assert!(editor.line_changes().unwrap().is_deleted_after(4));
+}
+
+#[test]
+fn hunk_at_the_cursor_is_reverted() {
+    let mut editor = editor("fn main() {\n    let x = 10;\n    println!(\"{x}\");\n    done();\n}");
+    editor.process_command(Command::SetPosition(Point2::new(0, 3)));
+    assert!(editor.process_command(Command::RevertHunk));
+    assert_eq!(
+        editor.get_content(),
+        "fn main() {\n    let x = 10;\n    println!(\"{x}\");\n}"
+    );
+    // the modified line is reverted with the deleted line after it
+    editor.process_command(Command::SetPosition(Point2::new(0, 1)));
+    assert!(editor.process_command(Command::RevertHunk));
+    assert_eq!(editor.get_content(), BASE);
+    assert_eq!(changes(&editor), vec![None; 5]);
+    assert!(editor.line_changes().unwrap().hunks().is_empty());
+    assert!(!editor.process_command(Command::RevertHunk));
+
+    // undo keeps following the lines
+    editor.process_command(Command::Undo);
+    assert_eq!(changes(&editor)[1], Some(LineChange::Modified));
+}
@@ -0,0 +1,77 @@
+use ultron_core::{BaseEditor, BaseOptions, Command, Macro, Point2};
+
+fn editor(content: &str) -> BaseEditor<()> {
+    BaseEditor::from_str(&BaseOptions::default(), content)
+}
+
+#[test]
+fn record_and_play_last_macro() {
+    let mut editor = editor("a\nb\nc\nd");
+    editor.process_command(Command::StartRecording(None));
+    assert!(editor.macros().is_recording());
+    editor.process_command(Command::InsertChar('-'));
+    editor.process_command(Command::MoveLeftStart);
+    editor.process_command(Command::MoveDown);
+    editor.process_command(Command::StopRecording);
+    assert!(!editor.macros().is_recording());
+    assert_eq!(
+        editor.macros().last(),
+        Some(&Macro::new([
+            Command::InsertChar('-'),
+            Command::MoveLeftStart,
+            Command::MoveDown,
+        ]))
+    );
+    editor.process_command(Command::PlayMacro(None, 2));
+    assert_eq!(editor.get_content(), "-a\n-b\n-c\nd");
+    assert_eq!(editor.get_position(), Point2::new(0, 3));
+}
+
+#[test]
+fn playback_is_undone_in_one_step() {
+    let mut editor = editor("a\nb\nc\nd");
+    editor.process_command(Command::StartRecording(Some("dash".to_string())));
+    editor.process_command(Command::InsertChar('-'));
+    editor.process_command(Command::MoveLeftStart);
+    editor.process_command(Command::MoveDown);
+    editor.process_command(Command::StopRecording);
+    editor.process_command(Command::PlayMacro(Some("dash".to_string()), 3));
+    assert_eq!(editor.get_content(), "-a\n-b\n-c\n-d");
+    editor.process_command(Command::Undo);
+    assert_eq!(editor.get_content(), "-a\nb\nc\nd");
+}
+
+#[test]
+fn named_macro_slots() {
+    let mut editor = editor("");
+    editor.macros_mut().set(
+        "hello",
+        Macro::new([Command::InsertText("hello ".to_string())]),
+    );
+    editor.process_command(Command::StartRecording(Some("bang".to_string())));
+    editor.process_command(Command::InsertChar('!'));
+    editor.process_command(Command::StopRecording);
+    editor.process_command(Command::PlayMacro(Some("hello".to_string()), 1));
+    editor.process_command(Command::PlayMacro(Some("missing".to_string()), 1));
+    assert_eq!(editor.get_content(), "!hello ");
+    let names: Vec<&str> = editor.macros().named().map(|(name, _)| name).collect();
+    assert_eq!(names, ["bang", "hello"]);
+}
+
+#[test]
+fn restored_macro_can_not_play_itself() {
+    let mut editor = editor("");
+    editor.macros_mut().set(
+        "loop",
+        Macro::new([
+            Command::InsertChar('x'),
+            Command::PlayMacro(Some("loop".to_string()), 1),
+        ]),
+    );
+    assert_eq!(
+        editor.macros().get(Some("loop")),
+        Some(&Macro::new([Command::InsertChar('x')]))
+    );
+    editor.process_command(Command::PlayMacro(Some("loop".to_string()), 2));
+    assert_eq!(editor.get_content(), "xx");
+}
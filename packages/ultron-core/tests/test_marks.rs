@@ -0,0 +1,79 @@
+use ultron_core::{BaseEditor, BaseOptions, Command, Point2};
+
+fn editor(content: &str) -> BaseEditor<()> {
+    BaseEditor::from_str(&BaseOptions::default(), content)
+}
+
+fn set_position(editor: &mut BaseEditor<()>, x: i32, y: i32) {
+    editor.process_command(Command::SetPosition(Point2::new(x, y)));
+}
+
+#[test]
+fn named_marks_follow_inserted_text() {
+    let mut editor = editor("hello world\nsecond line");
+    set_position(&mut editor, 6, 0);
+    editor.process_command(Command::SetMark("a".to_string()));
+    set_position(&mut editor, 0, 0);
+    editor.process_command(Command::InsertText("say: ".to_string()));
+    assert_eq!(editor.marks().mark("a"), Some(Point2::new(11, 0)));
+    editor.process_command(Command::BreakLine);
+    assert_eq!(editor.marks().mark("a"), Some(Point2::new(6, 1)));
+    editor.process_command(Command::JumpToMark("a".to_string()));
+    assert_eq!(editor.get_position(), Point2::new(6, 1));
+}
+
+#[test]
+fn marks_follow_deleted_and_undone_text() {
+    let mut editor = editor("first\nsecond line");
+    set_position(&mut editor, 7, 1);
+    editor.process_command(Command::SetMark("a".to_string()));
+    editor.set_selection(Point2::new(2, 0), Point2::new(2, 1));
+    editor.cut_selected_text();
+    assert_eq!(editor.get_content(), "fiond line");
+    editor.process_command(Command::BumpHistory);
+    assert_eq!(editor.marks().mark("a"), Some(Point2::new(6, 0)));
+}
+
+#[test]
+fn cycle_through_bookmarks() {
+    let mut editor = editor("a\nb\nc\nd");
+    set_position(&mut editor, 0, 1);
+    editor.process_command(Command::ToggleBookmark);
+    set_position(&mut editor, 0, 3);
+    editor.process_command(Command::ToggleBookmark);
+    assert!(editor.marks().has_bookmark_on_line(1));
+    editor.process_command(Command::NextBookmark);
+    assert_eq!(editor.get_position(), Point2::new(0, 1));
+    editor.process_command(Command::NextBookmark);
+    assert_eq!(editor.get_position(), Point2::new(0, 3));
+    editor.process_command(Command::PrevBookmark);
+    assert_eq!(editor.get_position(), Point2::new(0, 1));
+    editor.process_command(Command::ToggleBookmark);
+    assert_eq!(editor.marks().bookmarks(), &[Point2::new(0, 3)]);
+    editor.process_command(Command::ClearMarks);
+    assert!(editor.marks().bookmarks().is_empty());
+}
+
+#[test]
+fn bookmarks_move_with_inserted_lines() {
+    let mut editor = editor("a\nb");
+    set_position(&mut editor, 0, 1);
+    editor.process_command(Command::ToggleBookmark);
+    set_position(&mut editor, 0, 0);
+    editor.process_command(Command::InsertText("x\ny\n".to_string()));
+    assert_eq!(editor.marks().bookmarks(), &[Point2::new(0, 3)]);
+    editor.process_command(Command::Undo);
+    assert_eq!(editor.get_content(), "a\nb");
+    assert_eq!(editor.marks().bookmarks(), &[Point2::new(0, 1)]);
+}
+
+#[test]
+fn marks_are_cleared_when_the_content_is_set() {
+    let mut editor = editor("a\nb\nc");
+    set_position(&mut editor, 0, 2);
+    editor.process_command(Command::SetMark("a".to_string()));
+    editor.process_command(Command::ToggleBookmark);
+    editor.process_command(Command::SetContent("x".to_string()));
+    assert_eq!(editor.marks().mark("a"), None);
+    assert!(editor.marks().bookmarks().is_empty());
+}
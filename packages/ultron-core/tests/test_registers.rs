@@ -0,0 +1,94 @@
+use ultron_core::{BaseEditor, BaseOptions, Command, Point2, RegisterContent, SelectionMode};
+
+fn editor(content: &str) -> BaseEditor<()> {
+    BaseEditor::from_str(&BaseOptions::default(), content)
+}
+
+#[test]
+fn cut_and_yank() {
+    let mut editor = editor("hello world");
+    editor.set_selection(Point2::new(0, 0), Point2::new(5, 0));
+    editor.process_command(Command::Cut);
+    assert_eq!(editor.get_content(), "world");
+    assert_eq!(editor.get_position(), Point2::new(0, 0));
+    editor.process_command(Command::SetPosition(Point2::new(5, 0)));
+    editor.process_command(Command::Yank);
+    assert_eq!(editor.get_content(), "worldhello ");
+}
+
+#[test]
+fn yank_pop_cycles_through_older_kills() {
+    let mut editor = editor("one two three");
+    editor.set_selection(Point2::new(0, 0), Point2::new(2, 0));
+    editor.process_command(Command::Copy);
+    editor.set_selection(Point2::new(4, 0), Point2::new(6, 0));
+    editor.process_command(Command::Copy);
+    editor.process_command(Command::ClearSelection);
+    editor.process_command(Command::SetPosition(Point2::new(13, 0)));
+    editor.process_command(Command::Yank);
+    assert_eq!(editor.get_content(), "one two threetwo");
+    editor.process_command(Command::YankPop);
+    assert_eq!(editor.get_content(), "one two threeone");
+    editor.process_command(Command::YankPop);
+    assert_eq!(editor.get_content(), "one two threetwo");
+    editor.process_command(Command::Undo);
+    assert_eq!(editor.get_content(), "one two threeone");
+}
+
+#[test]
+fn yank_pop_only_after_yank() {
+    let mut editor = editor("ab");
+    editor.registers_mut().kill(RegisterContent::linear("x"));
+    editor.registers_mut().kill(RegisterContent::linear("y"));
+    editor.process_command(Command::YankPop);
+    assert_eq!(editor.get_content(), "ab");
+}
+
+#[test]
+fn yank_pop_does_not_follow_a_register_paste() {
+    let mut editor = editor("ab");
+    editor.registers_mut().kill(RegisterContent::linear("x"));
+    editor.registers_mut().kill(RegisterContent::linear("y"));
+    editor
+        .registers_mut()
+        .set('a', RegisterContent::linear("z"));
+    editor.process_command(Command::PasteFromRegister('a'));
+    editor.process_command(Command::YankPop);
+    assert_eq!(editor.get_content(), "zab");
+}
+
+#[test]
+fn named_registers() {
+    let mut editor = editor("abc\ndef");
+    editor.set_selection(Point2::new(0, 1), Point2::new(2, 1));
+    editor.process_command(Command::CopyToRegister('a'));
+    assert_eq!(
+        editor.registers().get('a'),
+        Some(&RegisterContent::linear("def"))
+    );
+    editor.process_command(Command::ClearSelection);
+    editor.process_command(Command::SetPosition(Point2::new(0, 0)));
+    editor.process_command(Command::PasteFromRegister('a'));
+    assert_eq!(editor.get_content(), "defabc\ndef");
+    editor.process_command(Command::PasteFromRegister('z'));
+    assert_eq!(editor.get_content(), "defabc\ndef");
+}
+
+#[test]
+fn block_cut_is_pasted_back_as_block() {
+    let options = BaseOptions {
+        selection_mode: SelectionMode::Block,
+        ..Default::default()
+    };
+    let mut editor = BaseEditor::<()>::from_str(&options, "abcd\nefgh\nijkl");
+    editor.set_selection(Point2::new(1, 0), Point2::new(2, 1));
+    editor.process_command(Command::Cut);
+    assert_eq!(editor.get_content(), "ad\neh\nijkl");
+    assert_eq!(
+        editor.registers().kill_ring().next(),
+        Some(&RegisterContent::block("bc\nfg"))
+    );
+    editor.process_command(Command::SetPosition(Point2::new(0, 1)));
+    editor.process_command(Command::Yank);
+    assert_eq!(editor.get_content(), "ad\nbceh\nfgijkl");
+}
@@ -0,0 +1,118 @@
+use ultron_core::{BaseEditor, BaseOptions, Command, Point2, Snippet};
+
+fn editor(content: &str) -> BaseEditor<()> {
+    BaseEditor::from_str(&BaseOptions::default(), content)
+}
+
+fn set_position(editor: &mut BaseEditor<()>, x: i32, y: i32) {
+    editor.process_command(Command::SetPosition(Point2::new(x, y)));
+}
+
+#[test]
+fn snippets_are_parsed_into_text_and_tab_stops() {
+    let snippet = Snippet::parse("for ${1:item} in ${2:items} {\n    $1$0\n}").unwrap();
+    assert_eq!(snippet.text(), "for item in items {\n    item\n}");
+    let indexes: Vec<usize> = snippet.stops().iter().map(|stop| stop.index).collect();
+    assert_eq!(indexes, vec![1, 2, 0]);
+    // the placeholder comes first, then its mirror
+    assert_eq!(
+        snippet.stops()[0].ranges,
+        vec![
+            (Point2::new(4, 0), Point2::new(8, 0)),
+            (Point2::new(4, 1), Point2::new(8, 1))
+        ]
+    );
+    assert_eq!(snippet.stops()[2].ranges[0].0, Point2::new(8, 1));
+
+    // the mirror before its placeholder, choices, escapes and an implicit final tab stop
+    let snippet = Snippet::parse("$1 = ${1:x}; ${2|let,const|} \\$3 ${TM_FILENAME:a.rs}").unwrap();
+    assert_eq!(snippet.text(), "x = x; let $3 a.rs");
+    assert_eq!(snippet.stops()[1].choices, vec!["let", "const"]);
+    assert_eq!(snippet.stops()[2].ranges[0].0, Point2::new(18, 0));
+
+    let snippet =
+        Snippet::parse_with_variables("${TM_LINE_NUMBER}: ${1:$TM_LINE_NUMBER}", |name| {
+            (name == "TM_LINE_NUMBER").then(|| "7".to_string())
+        })
+        .unwrap();
+    assert_eq!(snippet.text(), "7: 7");
+    assert!(Snippet::parse("${1:unclosed").is_err());
+}
+
+#[test]
+fn tab_moves_between_stops_and_mirrors_are_edited_together() {
+    let mut editor = editor("    ");
+    set_position(&mut editor, 4, 0);
+    editor.process_command(Command::InsertSnippet(
+        "for ${1:item} in ${2:items} {\n    print($1);$0\n}".into(),
+    ));
+    assert_eq!(
+        editor.get_content(),
+        "    for item in items {\n        print(item);\n    }"
+    );
+    assert_eq!(editor.selected_text(), Some("item".into()));
+
+    // the placeholder is replaced by the typed text, and so are its mirrors
+    editor.process_command(Command::InsertText("x".into()));
+    editor.process_command(Command::InsertChar('s'));
+    assert_eq!(
+        editor.get_content(),
+        "    for xs in items {\n        print(xs);\n    }"
+    );
+
+    editor.process_command(Command::NextTabStop);
+    assert_eq!(editor.selected_text(), Some("items".into()));
+    editor.process_command(Command::DeleteBack);
+    assert_eq!(
+        editor.get_content(),
+        "    for xs in  {\n        print(xs);\n    }"
+    );
+    editor.process_command(Command::InsertText("list".into()));
+
+    editor.process_command(Command::PrevTabStop);
+    assert_eq!(editor.selected_text(), Some("xs".into()));
+    editor.process_command(Command::InsertChar('y'));
+    assert_eq!(
+        editor.get_content(),
+        "    for y in list {\n        print(y);\n    }"
+    );
+
+    // the snippet is done at the final tab stop
+    editor.process_command(Command::NextTabStop);
+    editor.process_command(Command::NextTabStop);
+    assert!(editor.snippet_session().is_none());
+    assert_eq!(editor.get_position(), Point2::new(17, 1));
+
+    // the edit in the placeholder is undone together with its mirror
+    editor.process_command(Command::Undo);
+    assert_eq!(
+        editor.get_content(),
+        "    for xs in list {\n        print(xs);\n    }"
+    );
+}
+
+#[test]
+fn snippets_of_the_syntax_are_completed() {
+    let mut editor = editor("fo");
+    editor.register_snippet("rust", "for", "for ${1:item} in ${2|items,0..10|} {}", None);
+    editor.register_snippet("python", "for", "for ${1:x} in ${2:xs}:", None);
+    set_position(&mut editor, 2, 0);
+    assert!(editor.completions(None).is_empty());
+
+    editor.set_syntax_token("rust");
+    let item = editor.completions(None).remove(0);
+    assert!(item.is_snippet);
+    editor.process_command(Command::AcceptCompletion(item));
+    assert_eq!(editor.get_content(), "for item in items {}");
+    assert_eq!(editor.selected_text(), Some("item".into()));
+    assert!(editor.snippet_choices().is_empty());
+
+    editor.process_command(Command::NextTabStop);
+    let choices = editor.snippet_choices();
+    assert_eq!(choices.len(), 2);
+    editor.process_command(Command::AcceptCompletion(choices[1].clone()));
+    assert_eq!(editor.get_content(), "for item in 0..10 {}");
+
+    editor.process_command(Command::ExitSnippet);
+    assert!(editor.snippet_session().is_none());
+}
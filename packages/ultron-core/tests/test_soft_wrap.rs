@@ -0,0 +1,126 @@
+use ultron_core::{BaseEditor, BaseOptions, Command, Point2};
+
+fn editor(content: &str, wrap_column: usize) -> BaseEditor<()> {
+    let mut editor = BaseEditor::from_str(&BaseOptions::default(), content);
+    editor.set_wrap_column(Some(wrap_column));
+    editor
+}
+
+#[test]
+fn wrap_at_word_boundaries() {
+    let editor = editor("hello world foo\nbar", 8);
+    assert_eq!(editor.soft_wrap().row_starts(0), &[0, 6, 12]);
+    assert_eq!(editor.soft_wrap().row_starts(1), &[0]);
+    assert_eq!(editor.total_rows(), 4);
+    assert_eq!(editor.line_to_row(1), 3);
+    assert_eq!(editor.row_to_line(2), 0);
+}
+
+#[test]
+fn wrap_long_words_at_the_column() {
+    let editor = editor("abcdefghij", 4);
+    assert_eq!(editor.soft_wrap().row_starts(0), &[0, 4, 8]);
+}
+
+#[test]
+fn visual_positions() {
+    let editor = editor("hello world foo\nbar", 8);
+    assert_eq!(
+        editor.position_to_visual(Point2::new(8, 0)),
+        Point2::new(2, 1)
+    );
+    assert_eq!(
+        editor.visual_to_position(Point2::new(2, 1)),
+        Point2::new(8, 0)
+    );
+    // clicking past the end of a wrapped row stays in that row
+    assert_eq!(
+        editor.visual_to_position(Point2::new(7, 0)),
+        Point2::new(5, 0)
+    );
+    assert_eq!(
+        editor.visual_to_position(Point2::new(1, 3)),
+        Point2::new(1, 1)
+    );
+}
+
+#[test]
+fn move_by_visual_rows() {
+    let mut editor = editor("hello world foo\nbar", 8);
+    editor.process_command(Command::SetPosition(Point2::new(1, 0)));
+    editor.process_command(Command::MoveDown);
+    assert_eq!(editor.get_position(), Point2::new(7, 0));
+    editor.process_command(Command::MoveDown);
+    assert_eq!(editor.get_position(), Point2::new(13, 0));
+    editor.process_command(Command::MoveDown);
+    assert_eq!(editor.get_position(), Point2::new(1, 1));
+    editor.process_command(Command::MoveUp);
+    assert_eq!(editor.get_position(), Point2::new(13, 0));
+}
+
+#[test]
+fn rewrap_after_edits() {
+    let mut editor = editor("hello", 8);
+    assert_eq!(editor.total_rows(), 1);
+    editor.process_command(Command::SetPosition(Point2::new(5, 0)));
+    editor.process_command(Command::InsertText(" world".to_string()));
+    assert_eq!(editor.soft_wrap().row_starts(0), &[0, 6]);
+    editor.set_wrap_column(None);
+    assert_eq!(editor.total_rows(), 1);
+}
+
+#[test]
+fn wrapped_rows_skip_folded_lines() {
+    let mut editor = editor("def f():\n    hello world foo\n    x\ny z w long line", 8);
+    editor.process_command(Command::ToggleFoldAt(0));
+    assert_eq!(editor.soft_wrap().row_starts(3), &[0, 6, 11]);
+    assert_eq!(editor.total_rows(), 4);
+    assert_eq!(editor.line_to_row(3), 1);
+    assert_eq!(editor.row_to_line(3), 3);
+    assert_eq!(
+        editor.position_to_visual(Point2::new(12, 3)),
+        Point2::new(1, 3)
+    );
+    assert_eq!(
+        editor.visual_to_position(Point2::new(1, 2)),
+        Point2::new(7, 3)
+    );
+    // hidden lines are displayed at the start of the line they are folded into
+    assert_eq!(
+        editor.position_to_visual(Point2::new(3, 1)),
+        Point2::new(0, 0)
+    );
+    assert_eq!(
+        editor.visual_to_position(Point2::new(2, 5)),
+        Point2::new(2, 5)
+    );
+}
+
+#[test]
+fn rows_of_changed_lines_match_a_full_wrap() {
+    let mut editor = editor("hello world foo\nbar\nbaz qux quux", 8);
+    let commands = [
+        Command::SetPosition(Point2::new(3, 1)),
+        Command::InsertText(" long words here".to_string()),
+        Command::BreakLine,
+        Command::SetPosition(Point2::new(0, 1)),
+        Command::DeleteBack,
+        Command::SetPosition(Point2::new(5, 3)),
+        Command::DeleteForward,
+    ];
+    for command in commands {
+        editor.process_command(command);
+        let wrapped = self::editor(&editor.get_content(), 8);
+        let total_lines = editor.total_lines();
+        for line in 0..=total_lines {
+            assert_eq!(
+                editor.soft_wrap().row_starts(line),
+                wrapped.soft_wrap().row_starts(line)
+            );
+            assert_eq!(
+                editor.soft_wrap().rows_before(line),
+                wrapped.soft_wrap().rows_before(line)
+            );
+        }
+    }
+}
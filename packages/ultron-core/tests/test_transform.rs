@@ -0,0 +1,80 @@
+use ultron_core::{BaseEditor, BaseOptions, Command, Point2, SelectionMode, TextTransform};
+
+#[test]
+fn case_styles() {
+    let text = "let parseHTTPServer = my_value_2;";
+    assert_eq!(
+        TextTransform::SnakeCase.apply(text),
+        "let parse_http_server = my_value_2;"
+    );
+    assert_eq!(
+        TextTransform::CamelCase.apply("_hello_big-world"),
+        "_helloBigWorld"
+    );
+    assert_eq!(TextTransform::KebabCase.apply("helloWorld"), "hello-world");
+    assert_eq!(
+        TextTransform::ScreamingSnakeCase.apply("helloWorld"),
+        "HELLO_WORLD"
+    );
+    assert_eq!(
+        TextTransform::TitleCase.apply("the QUICK brown fox's"),
+        "The Quick Brown Fox's"
+    );
+}
+
+#[test]
+fn whitespace_transforms() {
+    assert_eq!(TextTransform::TabsToSpaces.apply("\tab\tc"), "    ab  c");
+    assert_eq!(TextTransform::SpacesToTabs.apply("      x  y"), "\t  x  y");
+    assert_eq!(
+        TextTransform::TrimTrailingWhitespace.apply("a  \nb\t"),
+        "a\nb"
+    );
+    assert_eq!(TextTransform::ReverseLines.apply("1\n2\n3"), "3\n2\n1");
+}
+
+#[test]
+fn transform_word_under_cursor() {
+    let mut editor = BaseEditor::<()>::from_str(&BaseOptions::default(), "let fooBar = 1;");
+    editor.process_command(Command::SetPosition(Point2::new(6, 0)));
+    assert!(editor.process_command(Command::Transform(TextTransform::SnakeCase)));
+    assert_eq!(editor.get_content(), "let foo_bar = 1;");
+    // the word is already in snake case
+    assert!(!editor.process_command(Command::Transform(TextTransform::SnakeCase)));
+    editor.process_command(Command::Undo);
+    assert_eq!(editor.get_content(), "let fooBar = 1;");
+}
+
+#[test]
+fn transform_linear_selection_with_cjk() {
+    let mut editor = BaseEditor::<()>::from_str(&BaseOptions::default(), "文件 hello world");
+    editor.set_selection(Point2::new(5, 0), Point2::new(9, 0));
+    editor.process_command(Command::Transform(TextTransform::Uppercase));
+    assert_eq!(editor.get_content(), "文件 HELLO world");
+}
+
+#[test]
+fn reverse_selected_lines() {
+    let mut editor = BaseEditor::<()>::from_str(&BaseOptions::default(), "a\nb\nc\nd");
+    editor.set_selection(Point2::new(0, 1), Point2::new(0, 3));
+    editor.process_command(Command::Transform(TextTransform::ReverseLines));
+    assert_eq!(editor.get_content(), "a\nd\nc\nb");
+    editor.process_command(Command::Undo);
+    assert_eq!(editor.get_content(), "a\nb\nc\nd");
+}
+
+#[test]
+fn transform_block_selection() {
+    let options = BaseOptions {
+        selection_mode: SelectionMode::Block,
+        ..Default::default()
+    };
+    let mut editor = BaseEditor::<()>::from_str(&options, "abc def\nghi jkl\nmno pqr");
+    editor.set_selection(Point2::new(4, 0), Point2::new(6, 1));
+    editor.process_command(Command::Transform(TextTransform::Uppercase));
+    assert_eq!(editor.get_content(), "abc DEF\nghi JKL\nmno pqr");
+    editor.process_command(Command::Transform(TextTransform::ReverseLines));
+    assert_eq!(editor.get_content(), "abc JKL\nghi DEF\nmno pqr");
+    editor.process_command(Command::Undo);
+    assert_eq!(editor.get_content(), "abc DEF\nghi JKL\nmno pqr");
+}
@@ -0,0 +1,198 @@
+use ultron_core::{
+    BaseEditor, BaseOptions, Key, KeyEvent, KeyOutcome, Modifiers, Point2, RegisterContent, Vim,
+    VimMode,
+};
+
+fn editor(content: &str) -> BaseEditor<()> {
+    BaseEditor::from_str(&BaseOptions::default(), content)
+}
+
+/// type the keys, where `<Esc>`, `<Enter>` and `<C-x>` are the special keys
+fn type_keys(vim: &mut Vim, editor: &mut BaseEditor<()>, keys: &str) -> KeyOutcome {
+    let mut outcome = KeyOutcome::default();
+    let mut rest = keys;
+    while let Some(ch) = rest.chars().next() {
+        let special = rest
+            .strip_prefix('<')
+            .and_then(|after| after.split_once('>'))
+            .filter(|(name, _)| name.len() > 1);
+        let event = match special {
+            Some((name, after)) => {
+                rest = after;
+                match name.strip_prefix("C-") {
+                    Some(key) => {
+                        KeyEvent::new(Key::from_name(key).expect("a key"), Modifiers::ctrl())
+                    }
+                    None => KeyEvent::new(Key::from_name(name).expect("a key"), Modifiers::NONE),
+                }
+            }
+            None => {
+                rest = &rest[ch.len_utf8()..];
+                KeyEvent::new(Key::Char(ch), Modifiers::NONE)
+            }
+        };
+        let key_outcome = vim.process_key(editor, event);
+        outcome.is_content_changed |= key_outcome.is_content_changed;
+        outcome.actions.extend(key_outcome.actions);
+    }
+    outcome
+}
+
+#[test]
+fn operators_with_motions_and_counts() {
+    let mut editor = editor("one two three four five");
+    let mut vim = Vim::new();
+    type_keys(&mut vim, &mut editor, "dw");
+    assert_eq!(editor.get_content(), "two three four five");
+    type_keys(&mut vim, &mut editor, "2dw");
+    assert_eq!(editor.get_content(), "four five");
+    type_keys(&mut vim, &mut editor, "d$");
+    assert_eq!(editor.get_content(), "");
+    type_keys(&mut vim, &mut editor, "u");
+    assert_eq!(editor.get_content(), "four five");
+    type_keys(&mut vim, &mut editor, "uu");
+    assert_eq!(editor.get_content(), "one two three four five");
+    type_keys(&mut vim, &mut editor, "fhx");
+    assert_eq!(editor.get_content(), "one two tree four five");
+    assert_eq!(editor.get_position(), Point2::new(9, 0));
+    type_keys(&mut vim, &mut editor, "dtf");
+    assert_eq!(editor.get_content(), "one two tfour five");
+}
+
+#[test]
+fn change_word_and_repeat_with_dot() {
+    let mut editor = editor("foo bar baz");
+    let mut vim = Vim::new();
+    type_keys(&mut vim, &mut editor, "cwqux");
+    assert_eq!(vim.mode(), VimMode::Insert);
+    assert_eq!(vim.mode().to_string(), "INSERT");
+    type_keys(&mut vim, &mut editor, "<Esc>");
+    assert_eq!(vim.mode(), VimMode::Normal);
+    assert_eq!(editor.get_content(), "qux bar baz");
+    assert_eq!(editor.get_position(), Point2::new(2, 0));
+    type_keys(&mut vim, &mut editor, "w.");
+    assert_eq!(editor.get_content(), "qux qux baz");
+    // the change and the repeat are each undone in one step
+    type_keys(&mut vim, &mut editor, "u");
+    assert_eq!(editor.get_content(), "qux bar baz");
+    type_keys(&mut vim, &mut editor, "u");
+    assert_eq!(editor.get_content(), "foo bar baz");
+    type_keys(&mut vim, &mut editor, "<C-r>");
+    assert_eq!(editor.get_content(), "qux bar baz");
+}
+
+#[test]
+fn lines_are_yanked_and_put_below_or_above() {
+    let mut editor = editor("a\nb\nc");
+    let mut vim = Vim::new();
+    type_keys(&mut vim, &mut editor, "yyjp");
+    assert_eq!(editor.get_content(), "a\nb\na\nc");
+    assert_eq!(editor.get_position(), Point2::new(0, 2));
+    type_keys(&mut vim, &mut editor, "2ddG");
+    assert_eq!(editor.get_content(), "a\nb");
+    type_keys(&mut vim, &mut editor, "p");
+    assert_eq!(editor.get_content(), "a\nb\na\nc");
+    type_keys(&mut vim, &mut editor, "ggP");
+    assert_eq!(editor.get_content(), "a\nc\na\nb\na\nc");
+    type_keys(&mut vim, &mut editor, "3J");
+    assert_eq!(editor.get_content(), "a c a\nb\na\nc");
+    type_keys(&mut vim, &mut editor, ">>");
+    assert_eq!(editor.get_content(), "    a c a\nb\na\nc");
+    assert_eq!(editor.get_position(), Point2::new(4, 0));
+}
+
+#[test]
+fn text_objects() {
+    let mut editor = editor("call(foo, bar) \"hello world\" end");
+    let mut vim = Vim::new();
+    type_keys(&mut vim, &mut editor, "fbci(x<Esc>");
+    assert_eq!(editor.get_content(), "call(x) \"hello world\" end");
+    type_keys(&mut vim, &mut editor, "fwdi\"");
+    assert_eq!(editor.get_content(), "call(x) \"\" end");
+    type_keys(&mut vim, &mut editor, "0daw");
+    assert_eq!(editor.get_content(), "(x) \"\" end");
+    type_keys(&mut vim, &mut editor, "da(");
+    assert_eq!(editor.get_content(), " \"\" end");
+}
+
+#[test]
+fn named_registers() {
+    let mut editor = editor("alpha beta");
+    let mut vim = Vim::new();
+    type_keys(&mut vim, &mut editor, "\"ayiww\"Ayiw");
+    assert_eq!(
+        editor.registers().get('a'),
+        Some(&RegisterContent::linear("alphabeta"))
+    );
+    type_keys(&mut vim, &mut editor, "\"_dw");
+    assert_eq!(editor.get_content(), "alpha ");
+    type_keys(&mut vim, &mut editor, "\"ap");
+    assert_eq!(editor.get_content(), "alpha alphabeta");
+}
+
+#[test]
+fn visual_modes() {
+    let mut editor = editor("hello world\nsecond line\nthird");
+    let mut vim = Vim::new();
+    type_keys(&mut vim, &mut editor, "vey");
+    assert_eq!(vim.mode(), VimMode::Normal);
+    assert_eq!(editor.registers().kill_ring().next().unwrap().text, "hello");
+    type_keys(&mut vim, &mut editor, "wvU");
+    assert_eq!(editor.get_content(), "hello World\nsecond line\nthird");
+    type_keys(&mut vim, &mut editor, "Vj");
+    assert_eq!(vim.mode(), VimMode::VisualLine);
+    assert_eq!(vim.mode().to_string(), "VISUAL LINE");
+    type_keys(&mut vim, &mut editor, "d");
+    assert_eq!(editor.get_content(), "third");
+    type_keys(&mut vim, &mut editor, "u");
+    assert_eq!(editor.get_content(), "hello World\nsecond line\nthird");
+}
+
+#[test]
+fn visual_block_mode() {
+    let mut editor = editor("abcd\nefgh\nijkl");
+    let mut vim = Vim::new();
+    type_keys(&mut vim, &mut editor, "l<C-v>jl");
+    assert_eq!(vim.mode(), VimMode::VisualBlock);
+    assert_eq!(editor.selected_text(), Some("bc\nfg".to_string()));
+    type_keys(&mut vim, &mut editor, "d");
+    assert_eq!(editor.get_content(), "ad\neh\nijkl");
+    assert_eq!(
+        editor.registers().kill_ring().next(),
+        Some(&RegisterContent::block("bc\nfg"))
+    );
+    assert_eq!(editor.selection_mode(), Default::default());
+}
+
+#[test]
+fn ex_commands() {
+    let mut editor = editor("foo foo\nbar\nfoo");
+    let mut vim = Vim::new();
+    type_keys(&mut vim, &mut editor, ":%s/o/0/");
+    assert_eq!(vim.mode(), VimMode::CommandLine);
+    assert_eq!(vim.status(), ":%s/o/0/");
+    type_keys(&mut vim, &mut editor, "<Enter>");
+    assert_eq!(editor.get_content(), "f0o foo\nbar\nf0o");
+    type_keys(&mut vim, &mut editor, ":1s/(o+)/[\\1]/g<Enter>");
+    assert_eq!(editor.get_content(), "f0[o] f[oo]\nbar\nf0o");
+    type_keys(&mut vim, &mut editor, "u");
+    assert_eq!(editor.get_content(), "f0o foo\nbar\nf0o");
+    type_keys(&mut vim, &mut editor, ":2<Enter>");
+    assert_eq!(editor.get_position(), Point2::new(0, 1));
+    let outcome = type_keys(&mut vim, &mut editor, ":wq<Enter>");
+    assert_eq!(outcome.actions, vec!["write", "quit"]);
+    type_keys(&mut vim, &mut editor, ":nope<Enter>");
+    assert_eq!(vim.message(), Some("not an editor command: nope"));
+}
+
+#[test]
+fn failed_motion_cancels_the_operator() {
+    let mut editor = editor("one\ntwo");
+    let mut vim = Vim::new();
+    type_keys(&mut vim, &mut editor, "jdj");
+    assert_eq!(editor.get_content(), "one\ntwo");
+    type_keys(&mut vim, &mut editor, "ggdk");
+    assert_eq!(editor.get_content(), "one\ntwo");
+    type_keys(&mut vim, &mut editor, "dj");
+    assert_eq!(editor.get_content(), "");
+}
@@ -0,0 +1,567 @@
+use crate::{Document, LspError, Transport};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use ultron_core::{
+    BaseEditor, ChangeDelta, Command, CompletionItem, CompletionKind, Diagnostic, Edit, Point2,
+    Severity,
+};
+
+/// The requests waiting for a response of the language server
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Request {
+    Initialize,
+    Completion,
+    Hover,
+    Definition,
+    /// the version of the document the edits are computed for
+    Formatting(i64),
+    Rename(i64),
+    Shutdown,
+}
+
+/// What is received from the language server.
+/// The diagnostics, the definitions and the edits are already applied to the editor
+#[derive(Clone, Debug, PartialEq)]
+pub enum LspEvent {
+    /// the language server is ready and the document is opened
+    Initialized,
+    /// the diagnostics of the editor are replaced
+    Diagnostics,
+    Completion(Vec<CompletionItem>),
+    /// the information about the symbol at the cursor, None when there is nothing to show
+    Hover(Option<String>),
+    /// the cursor is moved to the definition,
+    /// None when it is not found or is in another document
+    Definition(Option<Point2<usize>>),
+    /// the content is formatted with these changes
+    Formatted(Vec<ChangeDelta>),
+    /// the symbol is renamed in the content with these changes,
+    /// the edits in the other documents are ignored
+    Renamed(Vec<ChangeDelta>),
+    /// the edits are dropped since the content has changed after they are requested
+    Outdated,
+    /// the language server responded to a request with an error
+    Error(String),
+    Shutdown,
+}
+
+/// A language server protocol client for a document edited in a `BaseEditor`.
+///
+/// The changes of the editor are sent with `did_change`, the requests are sent
+/// with the `request_*` methods and their responses are applied to the editor in `poll`.
+pub struct LspClient<T> {
+    transport: T,
+    uri: String,
+    language_id: String,
+    /// the content as it is known to the language server
+    document: Document,
+    version: i64,
+    next_id: i64,
+    pending: HashMap<i64, Request>,
+    is_initialized: bool,
+    is_opened: bool,
+    /// the language server only accepts the whole content on every change
+    is_full_sync: bool,
+    /// the characters which trigger a completion, ie: `.`
+    trigger_characters: Vec<String>,
+}
+
+impl<T: Transport> LspClient<T> {
+    /// a client of the document at `uri`, ie: `file:///src/main.rs` and its language, ie: `rust`
+    pub fn new(transport: T, uri: impl Into<String>, language_id: impl Into<String>) -> Self {
+        Self {
+            transport,
+            uri: uri.into(),
+            language_id: language_id.into(),
+            document: Document::new(""),
+            version: 0,
+            next_id: 0,
+            pending: HashMap::new(),
+            is_initialized: false,
+            is_opened: false,
+            is_full_sync: false,
+            trigger_characters: vec![],
+        }
+    }
+
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    pub fn transport_mut(&mut self) -> &mut T {
+        &mut self.transport
+    }
+
+    pub fn uri(&self) -> &str {
+        &self.uri
+    }
+
+    pub fn document(&self) -> &Document {
+        &self.document
+    }
+
+    pub fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+
+    pub fn trigger_characters(&self) -> &[String] {
+        &self.trigger_characters
+    }
+
+    /// start the initialization of the language server,
+    /// the document is opened once the language server is initialized
+    pub fn initialize(&mut self, root_uri: Option<&str>) -> Result<(), LspError> {
+        let params = json!({
+            "processId": null,
+            "rootUri": root_uri,
+            "capabilities": {
+                "textDocument": {
+                    "synchronization": {"didSave": false},
+                    "publishDiagnostics": {"versionSupport": true},
+                    "completion": {"completionItem": {"snippetSupport": true}},
+                    "hover": {"contentFormat": ["markdown", "plaintext"]},
+                    "definition": {"linkSupport": true},
+                    "formatting": {},
+                    "rename": {},
+                },
+            },
+        });
+        self.send_request(Request::Initialize, "initialize", params)
+    }
+
+    /// open the document with the content of the editor,
+    /// the changes of the editor are tracked from now on
+    pub fn did_open<XMSG>(&mut self, editor: &mut BaseEditor<XMSG>) -> Result<(), LspError> {
+        editor.track_changes();
+        self.document = Document::new(&editor.get_content());
+        self.version = 1;
+        self.is_opened = true;
+        if self.is_initialized {
+            self.send_did_open()?;
+        }
+        Ok(())
+    }
+
+    /// send the changes of the editor made since the last call,
+    /// they are also sent before the messages of the language server are processed in `poll`
+    pub fn did_change<XMSG>(&mut self, editor: &mut BaseEditor<XMSG>) -> Result<(), LspError> {
+        let changes = editor.take_changes();
+        self.send_changes(&changes)
+    }
+
+    fn send_changes(&mut self, changes: &[ChangeDelta]) -> Result<(), LspError> {
+        if changes.is_empty() {
+            return Ok(());
+        }
+        let content_changes: Vec<Value> = changes
+            .iter()
+            .map(|delta| self.document.apply_change(delta))
+            .collect();
+        self.version += 1;
+        // the whole content is sent once the language server is initialized
+        if !self.is_initialized || !self.is_opened {
+            return Ok(());
+        }
+        let content_changes = if self.is_full_sync {
+            vec![json!({"text": self.document.text()})]
+        } else {
+            content_changes
+        };
+        self.send_notification(
+            "textDocument/didChange",
+            json!({
+                "textDocument": {"uri": self.uri, "version": self.version},
+                "contentChanges": content_changes,
+            }),
+        )
+    }
+
+    pub fn did_close(&mut self) -> Result<(), LspError> {
+        let was_opened = std::mem::replace(&mut self.is_opened, false);
+        if !self.is_initialized || !was_opened {
+            return Ok(());
+        }
+        self.send_notification(
+            "textDocument/didClose",
+            json!({"textDocument": {"uri": self.uri}}),
+        )
+    }
+
+    /// request the completions at the cursor, optionally triggered by a typed character
+    pub fn request_completion(
+        &mut self,
+        cursor: Point2<usize>,
+        trigger_character: Option<char>,
+    ) -> Result<(), LspError> {
+        let context = match trigger_character {
+            Some(ch) => json!({"triggerKind": 2, "triggerCharacter": ch.to_string()}),
+            None => json!({"triggerKind": 1}),
+        };
+        let mut params = self.position_params(cursor);
+        params["context"] = context;
+        self.send_request(Request::Completion, "textDocument/completion", params)
+    }
+
+    pub fn request_hover(&mut self, cursor: Point2<usize>) -> Result<(), LspError> {
+        let params = self.position_params(cursor);
+        self.send_request(Request::Hover, "textDocument/hover", params)
+    }
+
+    pub fn request_definition(&mut self, cursor: Point2<usize>) -> Result<(), LspError> {
+        let params = self.position_params(cursor);
+        self.send_request(Request::Definition, "textDocument/definition", params)
+    }
+
+    pub fn request_formatting(
+        &mut self,
+        tab_size: usize,
+        insert_spaces: bool,
+    ) -> Result<(), LspError> {
+        let params = json!({
+            "textDocument": {"uri": self.uri},
+            "options": {"tabSize": tab_size, "insertSpaces": insert_spaces},
+        });
+        self.send_request(
+            Request::Formatting(self.version),
+            "textDocument/formatting",
+            params,
+        )
+    }
+
+    /// rename the symbol at the cursor and its references
+    pub fn request_rename(
+        &mut self,
+        cursor: Point2<usize>,
+        new_name: &str,
+    ) -> Result<(), LspError> {
+        let mut params = self.position_params(cursor);
+        params["newName"] = json!(new_name);
+        self.send_request(Request::Rename(self.version), "textDocument/rename", params)
+    }
+
+    /// ask the language server to shut down, it is told to exit once it responds
+    pub fn shutdown(&mut self) -> Result<(), LspError> {
+        self.send_request(Request::Shutdown, "shutdown", Value::Null)
+    }
+
+    /// process the messages received from the language server,
+    /// the editor must be the one opened with `did_open`
+    pub fn poll<XMSG>(&mut self, editor: &mut BaseEditor<XMSG>) -> Result<Vec<LspEvent>, LspError> {
+        // the responses are applied to the content as it is known to the language server
+        self.did_change(editor)?;
+        let mut events = vec![];
+        while let Some(message) = self.transport.try_receive()? {
+            let message: Value = serde_json::from_str(&message)?;
+            if let Some(event) = self.process_message(message, editor)? {
+                events.push(event);
+            }
+        }
+        Ok(events)
+    }
+
+    fn process_message<XMSG>(
+        &mut self,
+        message: Value,
+        editor: &mut BaseEditor<XMSG>,
+    ) -> Result<Option<LspEvent>, LspError> {
+        let method = message.get("method").and_then(Value::as_str);
+        match (method, message.get("id")) {
+            (Some(method), Some(id)) => {
+                let result = match method {
+                    // there are no settings, one for each of the requested items
+                    "workspace/configuration" => {
+                        let items = message["params"]["items"].as_array().map_or(0, Vec::len);
+                        Value::Array(vec![Value::Null; items])
+                    }
+                    // the other requests of the language server are not supported
+                    _ => {
+                        log::debug!("unsupported request of the language server: {method}");
+                        Value::Null
+                    }
+                };
+                let response = json!({"jsonrpc": "2.0", "id": id, "result": result});
+                self.transport.send(response.to_string())?;
+                Ok(None)
+            }
+            (Some(method), None) => Ok(self.process_notification(method, &message, editor)),
+            (None, Some(id)) => {
+                let request = id.as_i64().and_then(|id| self.pending.remove(&id));
+                match request {
+                    Some(request) => self.process_response(request, &message, editor),
+                    None => {
+                        log::warn!("response to an unknown request: {message}");
+                        Ok(None)
+                    }
+                }
+            }
+            (None, None) => Err(LspError::InvalidMessage(message.to_string())),
+        }
+    }
+
+    fn process_notification<XMSG>(
+        &mut self,
+        method: &str,
+        message: &Value,
+        editor: &mut BaseEditor<XMSG>,
+    ) -> Option<LspEvent> {
+        let params = message.get("params")?;
+        match method {
+            "textDocument/publishDiagnostics" => {
+                if params.get("uri")?.as_str()? != self.uri {
+                    return None;
+                }
+                // the diagnostics of an older content would be at the wrong locations
+                let version = params.get("version").and_then(Value::as_i64);
+                if version.is_some_and(|version| version != self.version) {
+                    return None;
+                }
+                let diagnostics = params
+                    .get("diagnostics")?
+                    .as_array()?
+                    .iter()
+                    .filter_map(|diagnostic| self.to_diagnostic(diagnostic));
+                editor.set_diagnostics(diagnostics);
+                Some(LspEvent::Diagnostics)
+            }
+            "window/showMessage" | "window/logMessage" => {
+                log::info!("{}", params.get("message")?.as_str()?);
+                None
+            }
+            _ => None,
+        }
+    }
+
+    fn process_response<XMSG>(
+        &mut self,
+        request: Request,
+        message: &Value,
+        editor: &mut BaseEditor<XMSG>,
+    ) -> Result<Option<LspEvent>, LspError> {
+        if let Some(error) = message.get("error") {
+            let error = error
+                .get("message")
+                .and_then(Value::as_str)
+                .unwrap_or("unknown error");
+            return Ok(Some(LspEvent::Error(error.to_string())));
+        }
+        let result = message.get("result").unwrap_or(&Value::Null);
+        let event = match request {
+            Request::Initialize => {
+                let capabilities = &result["capabilities"];
+                let sync = &capabilities["textDocumentSync"];
+                let sync_kind = sync.get("change").unwrap_or(sync).as_i64();
+                self.is_full_sync = sync_kind == Some(1);
+                self.trigger_characters = capabilities["completionProvider"]["triggerCharacters"]
+                    .as_array()
+                    .map(|characters| {
+                        characters
+                            .iter()
+                            .filter_map(|ch| ch.as_str().map(String::from))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                self.is_initialized = true;
+                self.send_notification("initialized", json!({}))?;
+                if self.is_opened {
+                    self.send_did_open()?;
+                }
+                LspEvent::Initialized
+            }
+            Request::Completion => {
+                let items = result.get("items").unwrap_or(result);
+                let items = items
+                    .as_array()
+                    .map(|items| {
+                        items
+                            .iter()
+                            .filter_map(|item| self.to_completion_item(item))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                LspEvent::Completion(items)
+            }
+            Request::Hover => LspEvent::Hover(result.get("contents").and_then(hover_text)),
+            Request::Definition => {
+                let locations = match result {
+                    Value::Array(locations) => locations.as_slice(),
+                    location => std::slice::from_ref(location),
+                };
+                let definition = locations.iter().find_map(|location| {
+                    let uri = location.get("uri").or(location.get("targetUri"))?;
+                    if uri.as_str()? != self.uri {
+                        return None;
+                    }
+                    let range = location
+                        .get("range")
+                        .or(location.get("targetSelectionRange"))?;
+                    Some(self.document.from_range(range)?.0)
+                });
+                if let Some(loc) = definition {
+                    editor.process_command(Command::SetPosition(Point2::new(
+                        loc.x as i32,
+                        loc.y as i32,
+                    )));
+                }
+                LspEvent::Definition(definition)
+            }
+            Request::Formatting(version) | Request::Rename(version) if version != self.version => {
+                LspEvent::Outdated
+            }
+            Request::Formatting(_) => {
+                let edits = self.to_edits(result);
+                LspEvent::Formatted(self.apply_edits(edits, editor)?)
+            }
+            Request::Rename(_) => {
+                let edits = match result.get("documentChanges") {
+                    Some(document_changes) => document_changes
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .filter(|change| change["textDocument"]["uri"] == self.uri.as_str())
+                        .flat_map(|change| self.to_edits(&change["edits"]))
+                        .collect(),
+                    None => self.to_edits(&result["changes"][&self.uri]),
+                };
+                LspEvent::Renamed(self.apply_edits(edits, editor)?)
+            }
+            Request::Shutdown => {
+                self.is_initialized = false;
+                self.send_notification("exit", Value::Null)?;
+                LspEvent::Shutdown
+            }
+        };
+        Ok(Some(event))
+    }
+
+    /// apply the edits as one undo step and send the changes back to the language server
+    fn apply_edits<XMSG>(
+        &mut self,
+        edits: Vec<Edit>,
+        editor: &mut BaseEditor<XMSG>,
+    ) -> Result<Vec<ChangeDelta>, LspError> {
+        let response = editor.process_commands_with_response([Command::ApplyEdits(edits)]);
+        // the same changes are also tracked by the editor
+        editor.take_changes();
+        self.send_changes(&response.changes)?;
+        Ok(response.changes)
+    }
+
+    fn to_edits(&self, edits: &Value) -> Vec<Edit> {
+        edits
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|edit| self.to_edit(edit))
+            .collect()
+    }
+
+    fn to_edit(&self, edit: &Value) -> Option<Edit> {
+        // the range of an insert replace edit is the range to replace
+        let range = edit.get("range").or(edit.get("replace"))?;
+        let (start, end) = self.document.from_range(range)?;
+        Some(Edit::new(start, end, edit.get("newText")?.as_str()?))
+    }
+
+    fn to_diagnostic(&self, diagnostic: &Value) -> Option<Diagnostic> {
+        let (start, end) = self.document.from_range(diagnostic.get("range")?)?;
+        let severity = match diagnostic.get("severity").and_then(Value::as_i64) {
+            Some(2) => Severity::Warning,
+            Some(3) => Severity::Info,
+            Some(4) => Severity::Hint,
+            _ => Severity::Error,
+        };
+        let message = diagnostic.get("message")?.as_str()?;
+        let source = diagnostic.get("source").and_then(Value::as_str);
+        let diagnostic = Diagnostic::new(start, end, severity, message);
+        Some(match source {
+            Some(source) => diagnostic.with_source(source),
+            None => diagnostic,
+        })
+    }
+
+    fn to_completion_item(&self, item: &Value) -> Option<CompletionItem> {
+        let label = item.get("label")?.as_str()?;
+        let kind = match item.get("kind").and_then(Value::as_i64) {
+            Some(2) => CompletionKind::Method,
+            Some(3) | Some(4) => CompletionKind::Function,
+            Some(5) | Some(10) => CompletionKind::Field,
+            Some(6) => CompletionKind::Variable,
+            Some(7) | Some(8) | Some(13) | Some(22) | Some(25) => CompletionKind::Type,
+            Some(9) => CompletionKind::Module,
+            Some(14) => CompletionKind::Keyword,
+            Some(15) => CompletionKind::Snippet,
+            Some(12) | Some(20) | Some(21) => CompletionKind::Constant,
+            _ => CompletionKind::Text,
+        };
+        let mut completion = CompletionItem::new(label, kind);
+        if let Some(insert_text) = item.get("insertText").and_then(Value::as_str) {
+            completion = completion.with_insert_text(insert_text);
+        }
+        if let Some(edit) = item.get("textEdit").and_then(|edit| self.to_edit(edit)) {
+            completion = completion.with_edit(edit);
+        }
+        if let Some(detail) = item.get("detail").and_then(Value::as_str) {
+            completion = completion.with_detail(detail);
+        }
+        // the insert text format 2 is a snippet
+        completion.is_snippet = item.get("insertTextFormat").and_then(Value::as_i64) == Some(2);
+        Some(completion)
+    }
+
+    fn position_params(&self, cursor: Point2<usize>) -> Value {
+        json!({
+            "textDocument": {"uri": self.uri},
+            "position": self.document.to_position(cursor),
+        })
+    }
+
+    fn send_did_open(&mut self) -> Result<(), LspError> {
+        self.send_notification(
+            "textDocument/didOpen",
+            json!({
+                "textDocument": {
+                    "uri": self.uri,
+                    "languageId": self.language_id,
+                    "version": self.version,
+                    "text": self.document.text(),
+                },
+            }),
+        )
+    }
+
+    fn send_request(
+        &mut self,
+        request: Request,
+        method: &str,
+        params: Value,
+    ) -> Result<(), LspError> {
+        if request != Request::Initialize && !self.is_initialized {
+            return Err(LspError::NotInitialized);
+        }
+        self.next_id += 1;
+        let id = self.next_id;
+        self.pending.insert(id, request);
+        let message = json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params});
+        self.transport.send(message.to_string())
+    }
+
+    fn send_notification(&mut self, method: &str, params: Value) -> Result<(), LspError> {
+        let message = json!({"jsonrpc": "2.0", "method": method, "params": params});
+        self.transport.send(message.to_string())
+    }
+}
+
+/// the text of the hover contents, which is a markup content, a marked string
+/// or a list of marked strings
+fn hover_text(contents: &Value) -> Option<String> {
+    let text = match contents {
+        Value::String(text) => text.clone(),
+        Value::Array(contents) => contents
+            .iter()
+            .filter_map(hover_text)
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+        contents => contents.get("value")?.as_str()?.to_string(),
+    };
+    (!text.is_empty()).then_some(text)
+}
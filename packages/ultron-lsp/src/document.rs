@@ -0,0 +1,122 @@
+use serde_json::{json, Value};
+use ultron_core::{unicode_width::UnicodeWidthChar, ChangeDelta, Point2};
+
+/// A copy of the content as it is known to the language server.
+/// Converts the locations between the display columns of ultron
+/// and the utf-16 positions of the protocol
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Document {
+    lines: Vec<String>,
+}
+
+impl Document {
+    pub fn new(content: &str) -> Self {
+        Self {
+            lines: content.split('\n').map(String::from).collect(),
+        }
+    }
+
+    pub fn text(&self) -> String {
+        self.lines.join("\n")
+    }
+
+    /// the lsp position of the location, clamped to the end of its line
+    pub fn to_position(&self, loc: Point2<usize>) -> Value {
+        self.position_at_index(loc.y, char_index(self.line(loc.y), loc.x))
+    }
+
+    /// the location of the lsp position, clamped to the end of its line
+    pub fn from_position(&self, position: &Value) -> Option<Point2<usize>> {
+        let y = position.get("line")?.as_u64()? as usize;
+        let character = position.get("character")?.as_u64()? as usize;
+        let mut utf16 = 0;
+        let mut x = 0;
+        for ch in self.line(y).chars() {
+            if utf16 >= character {
+                break;
+            }
+            utf16 += ch.len_utf16();
+            x += ch.width().unwrap_or(0);
+        }
+        Some(Point2::new(x, y))
+    }
+
+    pub fn to_range(&self, start: Point2<usize>, end: Point2<usize>) -> Value {
+        json!({"start": self.to_position(start), "end": self.to_position(end)})
+    }
+
+    pub fn from_range(&self, range: &Value) -> Option<(Point2<usize>, Point2<usize>)> {
+        Some((
+            self.from_position(range.get("start")?)?,
+            self.from_position(range.get("end")?)?,
+        ))
+    }
+
+    /// apply the change of the editor and return it as an incremental change of the protocol.
+    /// The lines and the columns past the end of the content are filled with blanks
+    /// the same way the editor does
+    pub fn apply_change(&mut self, delta: &ChangeDelta) -> Value {
+        let last_line = self.lines.len() - 1;
+        // nothing is deleted past the end of the content
+        let (start, padding) = if delta.start.y > last_line {
+            let end = Point2::new(line_width(&self.lines[last_line]), last_line);
+            let padding = "\n".repeat(delta.start.y - last_line) + &" ".repeat(delta.start.x);
+            (end, padding)
+        } else {
+            let width = line_width(&self.lines[delta.start.y]);
+            let end = Point2::new(width.min(delta.start.x), delta.start.y);
+            (end, " ".repeat(delta.start.x.saturating_sub(width)))
+        };
+        let start_index = char_index(&self.lines[start.y], start.x);
+        let (end_y, end_index) = if padding.is_empty() {
+            let deleted_lines: Vec<&str> = delta.deleted.split('\n').collect();
+            let end_y = (start.y + deleted_lines.len() - 1).min(last_line);
+            match deleted_lines.as_slice() {
+                [deleted] => (end_y, start_index + deleted.chars().count()),
+                [.., last] => (end_y, last.chars().count()),
+                [] => (end_y, start_index),
+            }
+        } else {
+            (start.y, start_index)
+        };
+        let range = json!({
+            "start": self.position_at_index(start.y, start_index),
+            "end": self.position_at_index(end_y, end_index),
+        });
+        let text = padding + &delta.inserted;
+        let prefix: String = self.lines[start.y].chars().take(start_index).collect();
+        let suffix: String = self.lines[end_y].chars().skip(end_index).collect();
+        let new_lines: Vec<String> = format!("{prefix}{text}{suffix}")
+            .split('\n')
+            .map(String::from)
+            .collect();
+        self.lines.splice(start.y..=end_y, new_lines);
+        json!({"range": range, "text": text})
+    }
+
+    fn position_at_index(&self, y: usize, index: usize) -> Value {
+        let character: usize = self.line(y).chars().take(index).map(char::len_utf16).sum();
+        json!({"line": y, "character": character})
+    }
+
+    fn line(&self, y: usize) -> &str {
+        self.lines.get(y).map(String::as_str).unwrap_or("")
+    }
+}
+
+/// the index of the character at the display column, the number of characters
+/// when the column is at or beyond the end of the line
+fn char_index(line: &str, x: usize) -> usize {
+    let mut width = 0;
+    for (i, ch) in line.chars().enumerate() {
+        if width >= x {
+            return i;
+        }
+        width += ch.width().unwrap_or(0);
+    }
+    line.chars().count()
+}
+
+fn line_width(line: &str) -> usize {
+    line.chars().map(|ch| ch.width().unwrap_or(0)).sum()
+}
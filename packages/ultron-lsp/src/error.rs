@@ -0,0 +1,40 @@
+use std::fmt;
+use std::io;
+
+#[derive(Debug)]
+pub enum LspError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    /// the message is not a valid json-rpc message or the header of the message is invalid
+    InvalidMessage(String),
+    /// the language server has exited or the connection to it is closed
+    Closed,
+    /// a request is sent before the language server is initialized
+    NotInitialized,
+}
+
+impl fmt::Display for LspError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LspError::Io(error) => write!(f, "io error: {error}"),
+            LspError::Json(error) => write!(f, "json error: {error}"),
+            LspError::InvalidMessage(message) => write!(f, "invalid message: {message}"),
+            LspError::Closed => write!(f, "the connection to the language server is closed"),
+            LspError::NotInitialized => write!(f, "the language server is not initialized"),
+        }
+    }
+}
+
+impl std::error::Error for LspError {}
+
+impl From<io::Error> for LspError {
+    fn from(error: io::Error) -> Self {
+        LspError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for LspError {
+    fn from(error: serde_json::Error) -> Self {
+        LspError::Json(error)
+    }
+}
@@ -0,0 +1,19 @@
+#![deny(warnings)]
+//! A language server protocol client for ultron.
+//!
+//! The messages are carried by a `Transport`: the stdio of a language server process,
+//! a queue for the host to send through a WebSocket, or a language server in the same process.
+pub use client::{LspClient, LspEvent};
+pub use document::Document;
+pub use error::LspError;
+pub use transport::{
+    encode_message, read_message, InProcessTransport, LanguageServer, QueueTransport,
+    StdioTransport, Transport,
+};
+
+pub use serde_json;
+
+mod client;
+mod document;
+mod error;
+mod transport;
@@ -0,0 +1,216 @@
+use crate::LspError;
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+
+/// Sends and receives the json-rpc messages to and from the language server
+pub trait Transport {
+    fn send(&mut self, message: String) -> Result<(), LspError>;
+
+    /// the next message received from the language server,
+    /// None when no message is received yet
+    fn try_receive(&mut self) -> Result<Option<String>, LspError>;
+}
+
+/// A language server running in the same process, ie: a mock server in tests
+pub trait LanguageServer {
+    /// handle a request or a notification of the client,
+    /// returns the responses and the notifications sent back to the client
+    fn handle(&mut self, message: Value) -> Vec<Value>;
+}
+
+/// Connects to a language server running in the same process
+pub struct InProcessTransport<S> {
+    server: S,
+    incoming: VecDeque<String>,
+}
+
+impl<S: LanguageServer> InProcessTransport<S> {
+    pub fn new(server: S) -> Self {
+        Self {
+            server,
+            incoming: VecDeque::new(),
+        }
+    }
+
+    pub fn server(&self) -> &S {
+        &self.server
+    }
+
+    pub fn server_mut(&mut self) -> &mut S {
+        &mut self.server
+    }
+}
+
+impl<S: LanguageServer> Transport for InProcessTransport<S> {
+    fn send(&mut self, message: String) -> Result<(), LspError> {
+        let message = serde_json::from_str(&message)?;
+        for reply in self.server.handle(message) {
+            self.incoming.push_back(serde_json::to_string(&reply)?);
+        }
+        Ok(())
+    }
+
+    fn try_receive(&mut self) -> Result<Option<String>, LspError> {
+        Ok(self.incoming.pop_front())
+    }
+}
+
+/// The messages are queued for the host application to carry, ie: through a WebSocket.
+/// The host sends the messages from `take_outgoing` and pushes the received messages
+/// with `push_incoming`
+#[derive(Debug, Default)]
+pub struct QueueTransport {
+    outgoing: VecDeque<String>,
+    incoming: VecDeque<String>,
+    is_closed: bool,
+}
+
+impl QueueTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// add a message received from the language server
+    pub fn push_incoming(&mut self, message: impl Into<String>) {
+        self.incoming.push_back(message.into());
+    }
+
+    /// the messages to be sent to the language server
+    pub fn take_outgoing(&mut self) -> Vec<String> {
+        self.outgoing.drain(..).collect()
+    }
+
+    /// the connection is closed, ie: the WebSocket is closed
+    pub fn close(&mut self) {
+        self.is_closed = true;
+    }
+}
+
+impl Transport for QueueTransport {
+    fn send(&mut self, message: String) -> Result<(), LspError> {
+        if self.is_closed {
+            return Err(LspError::Closed);
+        }
+        self.outgoing.push_back(message);
+        Ok(())
+    }
+
+    fn try_receive(&mut self) -> Result<Option<String>, LspError> {
+        match self.incoming.pop_front() {
+            Some(message) => Ok(Some(message)),
+            None if self.is_closed => Err(LspError::Closed),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Connects to a language server process through its stdin and stdout,
+/// the messages are read in a separate thread
+pub struct StdioTransport {
+    child: Child,
+    stdin: ChildStdin,
+    incoming: Receiver<Result<String, LspError>>,
+}
+
+impl StdioTransport {
+    /// start the language server, ie: `Command::new("rust-analyzer")`
+    pub fn spawn(mut command: Command) -> Result<Self, LspError> {
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let stdin = child.stdin.take().ok_or(LspError::Closed)?;
+        let stdout = child.stdout.take().ok_or(LspError::Closed)?;
+        let (sender, incoming) = mpsc::channel();
+        thread::spawn(move || {
+            let mut reader = BufReader::new(stdout);
+            loop {
+                let message = match read_message(&mut reader) {
+                    Ok(Some(message)) => Ok(message),
+                    Ok(None) => break,
+                    Err(error) => Err(error),
+                };
+                let is_error = message.is_err();
+                if sender.send(message).is_err() || is_error {
+                    break;
+                }
+            }
+        });
+        Ok(Self {
+            child,
+            stdin,
+            incoming,
+        })
+    }
+}
+
+impl Transport for StdioTransport {
+    fn send(&mut self, message: String) -> Result<(), LspError> {
+        self.stdin.write_all(encode_message(&message).as_bytes())?;
+        self.stdin.flush()?;
+        Ok(())
+    }
+
+    fn try_receive(&mut self) -> Result<Option<String>, LspError> {
+        match self.incoming.try_recv() {
+            Ok(message) => message.map(Some),
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Disconnected) => Err(LspError::Closed),
+        }
+    }
+}
+
+impl Drop for StdioTransport {
+    fn drop(&mut self) {
+        // the killed process is waited on, so it does not linger as a zombie
+        if self.child.kill().is_ok() {
+            let _ = self.child.wait();
+        }
+    }
+}
+
+/// prefix the message with its `Content-Length` header
+pub fn encode_message(message: &str) -> String {
+    format!("Content-Length: {}\r\n\r\n{}", message.len(), message)
+}
+
+/// read the next message with its `Content-Length` header,
+/// None when the reader has reached the end before the start of a message
+pub fn read_message(reader: &mut impl BufRead) -> Result<Option<String>, LspError> {
+    let mut content_length = None;
+    let mut is_first_line = true;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            if is_first_line {
+                return Ok(None);
+            }
+            return Err(LspError::InvalidMessage("unexpected end of header".into()));
+        }
+        is_first_line = false;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                let length = value
+                    .trim()
+                    .parse::<usize>()
+                    .map_err(|_| LspError::InvalidMessage(line.to_string()))?;
+                content_length = Some(length);
+            }
+        }
+    }
+    let content_length = content_length
+        .ok_or_else(|| LspError::InvalidMessage("missing Content-Length header".into()))?;
+    let mut content = vec![0; content_length];
+    reader.read_exact(&mut content)?;
+    String::from_utf8(content)
+        .map(Some)
+        .map_err(|error| LspError::InvalidMessage(error.to_string()))
+}
@@ -0,0 +1,388 @@
+use serde_json::{json, Value};
+use std::io::Cursor;
+use ultron_core::{BaseEditor, BaseOptions, Command, CompletionKind, Point2, Severity};
+use ultron_lsp::{
+    encode_message, read_message, InProcessTransport, LanguageServer, LspClient, LspError,
+    LspEvent, QueueTransport, Transport,
+};
+
+const URI: &str = "file:///main.rs";
+
+/// A language server which keeps the content it is sent,
+/// warns about every `todo` and answers the requests with canned results
+#[derive(Default)]
+struct MockServer {
+    text: String,
+    version: i64,
+    methods: Vec<String>,
+}
+
+impl MockServer {
+    /// the byte offset of the lsp position
+    fn offset(&self, position: &Value) -> usize {
+        let line = position["line"].as_u64().unwrap() as usize;
+        let character = position["character"].as_u64().unwrap() as usize;
+        let line_start: usize = self
+            .text
+            .split('\n')
+            .take(line)
+            .map(|line| line.len() + 1)
+            .sum();
+        let mut utf16 = 0;
+        let line_text = self.text[line_start..].split('\n').next().unwrap();
+        for (i, ch) in line_text.char_indices() {
+            if utf16 >= character {
+                return line_start + i;
+            }
+            utf16 += ch.len_utf16();
+        }
+        line_start + line_text.len()
+    }
+
+    /// the lsp position of the byte offset
+    fn position(&self, offset: usize) -> Value {
+        let before = &self.text[..offset];
+        let line = before.matches('\n').count();
+        let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let character: usize = before[line_start..].chars().map(char::len_utf16).sum();
+        json!({"line": line, "character": character})
+    }
+
+    fn range(&self, start: usize, end: usize) -> Value {
+        json!({"start": self.position(start), "end": self.position(end)})
+    }
+
+    /// the byte range of the word at the position
+    fn word_at(&self, position: &Value) -> (usize, usize) {
+        let offset = self.offset(position);
+        let is_word = |ch: char| ch.is_alphanumeric() || ch == '_';
+        let start = self.text[..offset]
+            .rfind(|ch| !is_word(ch))
+            .map(|i| i + 1)
+            .unwrap_or(0);
+        let end = self.text[offset..]
+            .find(|ch| !is_word(ch))
+            .map(|i| offset + i)
+            .unwrap_or(self.text.len());
+        (start, end)
+    }
+
+    fn publish_diagnostics(&self) -> Value {
+        let diagnostics: Vec<Value> = self
+            .text
+            .match_indices("todo")
+            .map(|(i, _)| {
+                json!({
+                    "range": self.range(i, i + 4),
+                    "severity": 2,
+                    "source": "mock",
+                    "message": "unfinished code",
+                })
+            })
+            .collect();
+        json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/publishDiagnostics",
+            "params": {"uri": URI, "version": self.version, "diagnostics": diagnostics},
+        })
+    }
+
+    fn result(&mut self, method: &str, params: &Value) -> Value {
+        match method {
+            "initialize" => json!({
+                "capabilities": {
+                    "textDocumentSync": 2,
+                    "completionProvider": {"triggerCharacters": ["."]},
+                },
+            }),
+            "textDocument/completion" => {
+                let (start, end) = self.word_at(&params["position"]);
+                json!({
+                    "isIncomplete": false,
+                    "items": [
+                        {"label": "len", "kind": 2, "detail": "fn len(&self) -> usize",
+                         "textEdit": {"range": self.range(start, end), "newText": "len()"}},
+                        {"label": "is_empty", "kind": 2},
+                    ],
+                })
+            }
+            "textDocument/hover" => {
+                json!({"contents": {"kind": "markdown", "value": "let total: usize"}})
+            }
+            "textDocument/definition" => {
+                let (start, end) = self.word_at(&params["position"]);
+                let declaration = format!("let {}", &self.text[start..end]);
+                match self.text.find(&declaration) {
+                    Some(i) => json!([{"uri": URI, "range": self.range(i + 4, i + 4)}]),
+                    None => Value::Null,
+                }
+            }
+            // puts spaces around the `=` which have none
+            "textDocument/formatting" => {
+                let edits: Vec<Value> = self
+                    .text
+                    .match_indices('=')
+                    .filter(|(i, _)| !self.text[..*i].ends_with(' '))
+                    .map(|(i, _)| json!({"range": self.range(i, i + 1), "newText": " = "}))
+                    .collect();
+                json!(edits)
+            }
+            "textDocument/rename" => {
+                let (start, end) = self.word_at(&params["position"]);
+                let word = &self.text[start..end];
+                let edits: Vec<Value> = self
+                    .text
+                    .match_indices(word)
+                    .map(|(i, _)| {
+                        json!({"range": self.range(i, i + word.len()), "newText": params["newName"]})
+                    })
+                    .collect();
+                json!({"changes": {URI: edits}})
+            }
+            _ => Value::Null,
+        }
+    }
+}
+
+impl LanguageServer for MockServer {
+    fn handle(&mut self, message: Value) -> Vec<Value> {
+        let method = message["method"].as_str().unwrap().to_string();
+        self.methods.push(method.clone());
+        let params = &message["params"];
+        match method.as_str() {
+            "textDocument/didOpen" => {
+                self.text = params["textDocument"]["text"].as_str().unwrap().to_string();
+                self.version = params["textDocument"]["version"].as_i64().unwrap();
+                vec![self.publish_diagnostics()]
+            }
+            "textDocument/didChange" => {
+                for change in params["contentChanges"].as_array().unwrap() {
+                    let start = self.offset(&change["range"]["start"]);
+                    let end = self.offset(&change["range"]["end"]);
+                    self.text
+                        .replace_range(start..end, change["text"].as_str().unwrap());
+                }
+                self.version = params["textDocument"]["version"].as_i64().unwrap();
+                vec![self.publish_diagnostics()]
+            }
+            _ => match message.get("id") {
+                Some(id) => {
+                    let result = self.result(&method, params);
+                    vec![json!({"jsonrpc": "2.0", "id": id, "result": result})]
+                }
+                None => vec![],
+            },
+        }
+    }
+}
+
+type Client = LspClient<InProcessTransport<MockServer>>;
+
+fn connect(content: &str) -> (BaseEditor<()>, Client) {
+    let mut editor = BaseEditor::from_str(&BaseOptions::default(), content);
+    let mut client = LspClient::new(InProcessTransport::new(MockServer::default()), URI, "rust");
+    client.initialize(None).unwrap();
+    client.did_open(&mut editor).unwrap();
+    let events = client.poll(&mut editor).unwrap();
+    assert_eq!(events, vec![LspEvent::Initialized, LspEvent::Diagnostics]);
+    (editor, client)
+}
+
+fn edit(editor: &mut BaseEditor<()>, client: &mut Client, commands: Vec<Command>) {
+    for command in commands {
+        editor.process_command(command);
+    }
+    client.did_change(editor).unwrap();
+    client.poll(editor).unwrap();
+    assert_eq!(client.transport().server().text, editor.get_content());
+}
+
+fn set_position(x: i32, y: i32) -> Command {
+    Command::SetPosition(Point2::new(x, y))
+}
+
+#[test]
+fn changes_are_sent_incrementally() {
+    let (mut editor, mut client) = connect("let 世界 = \"😀\";\nlet x = 1;");
+    assert_eq!(
+        client.transport().server().methods,
+        vec!["initialize", "initialized", "textDocument/didOpen"]
+    );
+    assert_eq!(client.trigger_characters(), &[".".to_string()]);
+
+    // after the wide characters and the emoji which is 2 utf-16 units
+    edit(
+        &mut editor,
+        &mut client,
+        vec![set_position(14, 0), Command::InsertText("!".into())],
+    );
+    edit(
+        &mut editor,
+        &mut client,
+        vec![set_position(6, 0), Command::BreakLine],
+    );
+    edit(
+        &mut editor,
+        &mut client,
+        vec![Command::DeleteBack, Command::DeleteBack],
+    );
+    // the columns and lines past the end of the content are filled with blanks
+    edit(
+        &mut editor,
+        &mut client,
+        vec![set_position(12, 1), Command::InsertChar('y')],
+    );
+    edit(
+        &mut editor,
+        &mut client,
+        vec![set_position(2, 4), Command::InsertChar('z')],
+    );
+    edit(&mut editor, &mut client, vec![Command::Undo, Command::Undo]);
+    edit(
+        &mut editor,
+        &mut client,
+        vec![Command::SetContent("fn main() {}".into())],
+    );
+    assert_eq!(client.document().text(), "fn main() {}");
+
+    client.did_close().unwrap();
+    assert_eq!(
+        client
+            .transport()
+            .server()
+            .methods
+            .last()
+            .map(String::as_str),
+        Some("textDocument/didClose")
+    );
+}
+
+#[test]
+fn diagnostics_are_published_to_the_editor() {
+    let (mut editor, mut client) = connect("let 世界 = todo();");
+    let diagnostic = &editor.diagnostics().all()[0];
+    assert_eq!(
+        (diagnostic.start, diagnostic.end),
+        (Point2::new(11, 0), Point2::new(15, 0))
+    );
+    assert_eq!(diagnostic.severity, Severity::Warning);
+    assert_eq!(diagnostic.label(), "warning[mock]: unfinished code");
+
+    edit(
+        &mut editor,
+        &mut client,
+        vec![Command::InsertText("todo\n".into())],
+    );
+    let starts: Vec<Point2<usize>> = editor
+        .diagnostics()
+        .all()
+        .iter()
+        .map(|diagnostic| diagnostic.start)
+        .collect();
+    assert_eq!(starts, vec![Point2::new(0, 0), Point2::new(11, 1)]);
+}
+
+#[test]
+fn completion_hover_and_definition() {
+    let (mut editor, mut client) = connect("let total = 1;\nlet n = total.le");
+
+    client
+        .request_completion(Point2::new(16, 1), Some('.'))
+        .unwrap();
+    let events = client.poll(&mut editor).unwrap();
+    let LspEvent::Completion(items) = &events[0] else {
+        panic!("expecting completion items: {events:?}");
+    };
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0].kind, CompletionKind::Method);
+    assert_eq!(items[0].detail.as_deref(), Some("fn len(&self) -> usize"));
+    let completion_edit = items[0].edit.as_ref().unwrap();
+    assert_eq!(
+        (completion_edit.start, completion_edit.end),
+        (Point2::new(14, 1), Point2::new(16, 1))
+    );
+    assert_eq!(items[1].insert_text, "is_empty");
+
+    client.request_hover(Point2::new(10, 1)).unwrap();
+    assert_eq!(
+        client.poll(&mut editor).unwrap(),
+        vec![LspEvent::Hover(Some("let total: usize".into()))]
+    );
+
+    client.request_definition(Point2::new(10, 1)).unwrap();
+    assert_eq!(
+        client.poll(&mut editor).unwrap(),
+        vec![LspEvent::Definition(Some(Point2::new(4, 0)))]
+    );
+    assert_eq!(editor.get_position(), Point2::new(4, 0));
+}
+
+#[test]
+fn formatting_and_rename_are_applied_as_one_undo_step() {
+    let (mut editor, mut client) = connect("let x=1;\nlet y=x+x;");
+
+    client.request_formatting(4, true).unwrap();
+    let events = client.poll(&mut editor).unwrap();
+    assert!(matches!(events[0], LspEvent::Formatted(_)));
+    assert_eq!(editor.get_content(), "let x = 1;\nlet y = x+x;");
+    assert_eq!(client.transport().server().text, editor.get_content());
+
+    client.request_rename(Point2::new(4, 0), "count").unwrap();
+    client.poll(&mut editor).unwrap();
+    assert_eq!(editor.get_content(), "let count = 1;\nlet y = count+count;");
+    assert_eq!(client.transport().server().text, editor.get_content());
+
+    edit(&mut editor, &mut client, vec![Command::Undo]);
+    assert_eq!(editor.get_content(), "let x = 1;\nlet y = x+x;");
+
+    // the edits computed for an older content are dropped
+    client.request_formatting(4, true).unwrap();
+    // the changes are sent before the response is processed
+    editor.process_command(Command::InsertText("z=".into()));
+    let events = client.poll(&mut editor).unwrap();
+    assert!(events.contains(&LspEvent::Outdated));
+    assert_eq!(client.transport().server().text, editor.get_content());
+}
+
+#[test]
+fn messages_are_framed_and_queued() {
+    let message = r#"{"jsonrpc":"2.0","method":"exit"}"#;
+    let framed = encode_message(message).repeat(2);
+    let mut reader = Cursor::new(framed.as_bytes());
+    assert_eq!(read_message(&mut reader).unwrap().as_deref(), Some(message));
+    assert_eq!(read_message(&mut reader).unwrap().as_deref(), Some(message));
+    assert_eq!(read_message(&mut reader).unwrap(), None);
+
+    let mut transport = QueueTransport::new();
+    transport.send(message.into()).unwrap();
+    assert_eq!(transport.take_outgoing(), vec![message.to_string()]);
+    transport.push_incoming(message);
+    assert_eq!(transport.try_receive().unwrap().as_deref(), Some(message));
+    transport.close();
+    assert!(matches!(transport.try_receive(), Err(LspError::Closed)));
+
+    let mut client = LspClient::new(QueueTransport::new(), URI, "rust");
+    assert!(matches!(
+        client.request_hover(Point2::new(0, 0)),
+        Err(LspError::NotInitialized)
+    ));
+}
+
+#[test]
+fn configuration_is_answered_with_one_item_for_each_section() {
+    let mut editor = BaseEditor::<()>::from_str(&BaseOptions::default(), "");
+    let mut client = LspClient::new(QueueTransport::new(), URI, "rust");
+    client.transport_mut().push_incoming(
+        json!({
+            "jsonrpc": "2.0",
+            "id": 3,
+            "method": "workspace/configuration",
+            "params": {"items": [{"section": "rust"}, {"section": "editor"}]},
+        })
+        .to_string(),
+    );
+    client.poll(&mut editor).unwrap();
+    let response: Value = serde_json::from_str(&client.transport_mut().take_outgoing()[0]).unwrap();
+    assert_eq!(response["id"], 3);
+    assert_eq!(response["result"], json!([null, null]));
+}
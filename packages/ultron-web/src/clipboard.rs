@@ -0,0 +1,39 @@
+use ultron_core::{Clipboard, RegisterContent};
+
+/// A clipboard which writes the text into the browser clipboard using the
+/// [Clipboard API](https://developer.mozilla.org/en-US/docs/Web/API/Clipboard_API)
+///
+/// The browser clipboard can only be read asynchronously,
+/// so the last written content is also kept in memory, including its selection mode.
+#[derive(Clone, Debug, Default)]
+pub struct NavigatorClipboard {
+    content: Option<RegisterContent>,
+}
+
+impl NavigatorClipboard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Clipboard for NavigatorClipboard {
+    fn write(&mut self, content: RegisterContent) {
+        #[cfg(web_sys_unstable_apis)]
+        if let Some(clipboard) = sauron::window().navigator().clipboard() {
+            let fut =
+                sauron::wasm_bindgen_futures::JsFuture::from(clipboard.write_text(&content.text));
+            sauron::dom::spawn_local(async move {
+                if let Err(e) = fut.await {
+                    log::error!("unable to write to the clipboard: {e:?}");
+                }
+            });
+        } else {
+            log::error!("Clipboard is not supported");
+        }
+        self.content = Some(content);
+    }
+
+    fn read(&self) -> Option<RegisterContent> {
+        self.content.clone()
+    }
+}
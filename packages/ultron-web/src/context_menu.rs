@@ -1,6 +1,6 @@
 use sauron::Callback;
 use sauron::{html::attributes::*, html::events::*, html::units::*, html::*, *};
-use ultron_core::nalgebra::Point2;
+use ultron_core::{nalgebra::Point2, Selection};
 
 #[derive(Debug, Clone)]
 pub enum Msg {
@@ -12,6 +12,10 @@ pub enum Msg {
 pub struct Menu<XMSG> {
     listeners: Vec<Callback<MenuAction, XMSG>>,
     position: Option<Point2<i32>>,
+    /// the actions which don't apply to the current state of the editor
+    disabled: Vec<MenuAction>,
+    /// the entries added by the host application, shown after the editor actions
+    host_entries: Vec<MenuEntry>,
 }
 
 impl<XMSG> Default for Menu<XMSG> {
@@ -19,11 +23,13 @@ impl<XMSG> Default for Menu<XMSG> {
         Self {
             listeners: vec![],
             position: None,
+            disabled: vec![],
+            host_entries: vec![],
         }
     }
 }
 
-#[derive(Clone, Debug, Copy)]
+#[derive(Clone, Debug, Copy, PartialEq, Eq)]
 pub enum MenuAction {
     Undo,
     Redo,
@@ -32,6 +38,171 @@ pub enum MenuAction {
     Paste,
     Delete,
     SelectAll,
+    /// an entry added by the host application, identified by its order in the menu
+    Host(usize),
+}
+
+/// The state of the editor when the context menu is opened,
+/// passed to the host menu items
+#[derive(Clone, Debug)]
+pub struct MenuContext {
+    pub cursor: Point2<usize>,
+    pub selection: Selection,
+    pub selected_text: Option<String>,
+}
+
+/// An entry of the context menu provided by the host application
+pub enum MenuItem<XMSG> {
+    Item {
+        label: String,
+        /// the key combination displayed next to the label, ie: "Ctrl+Shift+F"
+        shortcut: Option<String>,
+        /// decides if the item can be selected, the item is always enabled if None
+        is_enabled: Option<Callback<MenuContext, bool>>,
+        on_select: Callback<MenuContext, XMSG>,
+    },
+    Submenu {
+        label: String,
+        items: Vec<MenuItem<XMSG>>,
+    },
+    Separator,
+}
+
+impl<XMSG> MenuItem<XMSG> {
+    /// an item which sends the message of `on_select` to the host when selected
+    pub fn new<F>(label: impl Into<String>, on_select: F) -> Self
+    where
+        F: Fn(MenuContext) -> XMSG + 'static,
+    {
+        MenuItem::Item {
+            label: label.into(),
+            shortcut: None,
+            is_enabled: None,
+            on_select: Callback::from(on_select),
+        }
+    }
+
+    pub fn submenu(label: impl Into<String>, items: impl IntoIterator<Item = Self>) -> Self {
+        MenuItem::Submenu {
+            label: label.into(),
+            items: items.into_iter().collect(),
+        }
+    }
+
+    pub fn separator() -> Self {
+        MenuItem::Separator
+    }
+
+    /// display this shortcut next to the label of the item
+    pub fn with_shortcut(mut self, shortcut: impl Into<String>) -> Self {
+        if let MenuItem::Item {
+            shortcut: ref mut item_shortcut,
+            ..
+        } = self
+        {
+            *item_shortcut = Some(shortcut.into());
+        }
+        self
+    }
+
+    /// grey out the item when the predicate returns false
+    pub fn enabled_if<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(MenuContext) -> bool + 'static,
+    {
+        if let MenuItem::Item {
+            ref mut is_enabled, ..
+        } = self
+        {
+            *is_enabled = Some(Callback::from(predicate));
+        }
+        self
+    }
+}
+
+/// The host menu items to be displayed, with their state evaluated when the menu is opened
+#[derive(Clone, Debug)]
+pub(crate) enum MenuEntry {
+    Item {
+        id: usize,
+        label: String,
+        shortcut: Option<String>,
+        is_enabled: bool,
+    },
+    Submenu {
+        label: String,
+        entries: Vec<MenuEntry>,
+    },
+    Separator,
+}
+
+impl MenuEntry {
+    /// evaluate the state of the host menu items, the items are numbered in the order they are displayed
+    pub(crate) fn from_items<XMSG>(items: &[MenuItem<XMSG>], context: &MenuContext) -> Vec<Self> {
+        fn entries<XMSG>(
+            items: &[MenuItem<XMSG>],
+            context: &MenuContext,
+            next_id: &mut usize,
+        ) -> Vec<MenuEntry> {
+            items
+                .iter()
+                .map(|item| match item {
+                    MenuItem::Item {
+                        label,
+                        shortcut,
+                        is_enabled,
+                        ..
+                    } => {
+                        let id = *next_id;
+                        *next_id += 1;
+                        MenuEntry::Item {
+                            id,
+                            label: label.clone(),
+                            shortcut: shortcut.clone(),
+                            is_enabled: is_enabled
+                                .as_ref()
+                                .map(|predicate| predicate.emit(context.clone()))
+                                .unwrap_or(true),
+                        }
+                    }
+                    MenuItem::Submenu { label, items } => MenuEntry::Submenu {
+                        label: label.clone(),
+                        entries: entries(items, context, next_id),
+                    },
+                    MenuItem::Separator => MenuEntry::Separator,
+                })
+                .collect()
+        }
+        entries(items, context, &mut 0)
+    }
+}
+
+/// find the host menu item with this id, numbered in the same order as `MenuEntry::from_items`
+pub(crate) fn find_menu_item<XMSG>(items: &[MenuItem<XMSG>], id: usize) -> Option<&MenuItem<XMSG>> {
+    fn find<'a, XMSG>(
+        items: &'a [MenuItem<XMSG>],
+        id: usize,
+        next_id: &mut usize,
+    ) -> Option<&'a MenuItem<XMSG>> {
+        for item in items {
+            match item {
+                MenuItem::Item { .. } => {
+                    if *next_id == id {
+                        return Some(item);
+                    }
+                    *next_id += 1;
+                }
+                MenuItem::Submenu { items, .. } => {
+                    if let Some(found) = find(items, id, next_id) {
+                        return Some(found);
+                    }
+                }
+                MenuItem::Separator => (),
+            }
+        }
+        None
+    }
+    find(items, id, &mut 0)
 }
 
 impl<XMSG> Menu<XMSG> {
@@ -39,6 +210,8 @@ impl<XMSG> Menu<XMSG> {
         Self {
             listeners: vec![],
             position: None,
+            disabled: vec![],
+            host_entries: vec![],
         }
     }
 }
@@ -51,6 +224,64 @@ impl<XMSG> Menu<XMSG> {
         self.listeners.push(Callback::from(f));
         self
     }
+
+    /// grey out these actions, they can not be selected
+    pub(crate) fn set_disabled(&mut self, disabled: Vec<MenuAction>) {
+        self.disabled = disabled;
+    }
+
+    /// set the host menu items to be displayed
+    pub(crate) fn set_host_entries(&mut self, host_entries: Vec<MenuEntry>) {
+        self.host_entries = host_entries;
+    }
+
+    fn view_host_entry(entry: &MenuEntry) -> Node<Msg> {
+        match entry {
+            MenuEntry::Item {
+                id,
+                label,
+                shortcut,
+                is_enabled,
+            } => {
+                let id = *id;
+                let content = [
+                    span([class("label")], [text(label)]),
+                    span(
+                        [class("shortcut")],
+                        [text(shortcut.as_deref().unwrap_or(""))],
+                    ),
+                ];
+                if *is_enabled {
+                    li(
+                        [on_click(move |_| Msg::SelectAction(MenuAction::Host(id)))],
+                        content,
+                    )
+                } else {
+                    li([class("disabled")], content)
+                }
+            }
+            MenuEntry::Submenu { label, entries } => li(
+                [class("submenu")],
+                [
+                    span([class("label")], [text(label)]),
+                    span([class("shortcut")], [text("▸")]),
+                    ul([], entries.iter().map(Self::view_host_entry)),
+                ],
+            ),
+            MenuEntry::Separator => li([class("separator")], []),
+        }
+    }
+
+    fn view_menu_item(&self, action: MenuAction, label: &str) -> Node<Msg> {
+        if self.disabled.contains(&action) {
+            li([class("disabled")], [text(label)])
+        } else {
+            li(
+                [on_click(move |_| Msg::SelectAction(action))],
+                [text(label)],
+            )
+        }
+    }
 }
 
 impl<XMSG> Component<Msg, XMSG> for Menu<XMSG>
@@ -92,35 +323,16 @@ where
                 [open(true)],
                 [
                     html::summary([], [text(" ")]),
-                    li(
-                        [on_click(|_| Msg::SelectAction(MenuAction::Undo))],
-                        [text("Undo")],
-                    ),
-                    li(
-                        [on_click(|_| Msg::SelectAction(MenuAction::Redo))],
-                        [text("Redo")],
-                    ),
-                    li(
-                        [on_click(|_| Msg::SelectAction(MenuAction::Cut))],
-                        [text("Cut")],
-                    ),
-                    li(
-                        [on_click(|_| Msg::SelectAction(MenuAction::Copy))],
-                        [text("Copy")],
-                    ),
-                    li(
-                        [on_click(|_| Msg::SelectAction(MenuAction::Paste))],
-                        [text("Paste")],
-                    ),
-                    li(
-                        [on_click(|_| Msg::SelectAction(MenuAction::Delete))],
-                        [text("Delete")],
-                    ),
-                    li(
-                        [on_click(|_| Msg::SelectAction(MenuAction::SelectAll))],
-                        [text("Select All")],
-                    ),
-                ],
+                    self.view_menu_item(MenuAction::Undo, "Undo"),
+                    self.view_menu_item(MenuAction::Redo, "Redo"),
+                    self.view_menu_item(MenuAction::Cut, "Cut"),
+                    self.view_menu_item(MenuAction::Copy, "Copy"),
+                    self.view_menu_item(MenuAction::Paste, "Paste"),
+                    self.view_menu_item(MenuAction::Delete, "Delete"),
+                    self.view_menu_item(MenuAction::SelectAll, "Select All"),
+                ]
+                .into_iter()
+                .chain(self.host_entries.iter().map(Self::view_host_entry)),
             )],
         )
     }
@@ -138,7 +350,7 @@ where
             ".context_menu details": {
                 position: "absolute",
                 display: "flex",
-                width: px(120),
+                min_width: px(120),
                 flex_direction: "column",
                 justify_content: "center",
                 align_content: "center",
@@ -167,6 +379,9 @@ where
             },
 
             ".context_menu details li": {
+                display: "flex",
+                justify_content: "space-between",
+                white_space: "nowrap",
                 list_style: "none",
                 padding: px([5, 5]),
                 border_bottom: format!("{} solid #ddd", px(1)),
@@ -175,6 +390,44 @@ where
             ".context_menu details li:hover": {
                 background_color: "#ddd",
             },
+
+            ".context_menu details li.disabled": {
+                color: "#aaa",
+            },
+
+            ".context_menu details li.disabled:hover": {
+                background_color: "#eee",
+            },
+
+            ".context_menu details li .shortcut": {
+                color: "#888",
+                padding_left: px(20),
+            },
+
+            ".context_menu details li.separator": {
+                padding: 0,
+                border_bottom: format!("{} solid #ccc", px(1)),
+            },
+
+            ".context_menu details li.submenu": {
+                position: "relative",
+            },
+
+            ".context_menu details li.submenu > ul": {
+                display: "none",
+                position: "absolute",
+                top: 0,
+                left: percent(100),
+                margin: 0,
+                padding: 0,
+                min_width: px(120),
+                background_color: "#eee",
+                border: format!("{} solid #ccc", px(1)),
+            },
+
+            ".context_menu details li.submenu:hover > ul": {
+                display: "block",
+            },
         }]
     }
 
@@ -0,0 +1,474 @@
+use crate::util;
+use css_colors::{rgba, Color, RGBA};
+use sauron::Callback;
+use sauron::{html::attributes::*, html::events::*, html::units::*, html::*, *};
+use ultron_core::{
+    Ch, DiffCommand, DiffEditor, DiffKind, DiffRow, Key, Style, TextBuffer, TextHighlighter,
+};
+
+pub const DIFF_VIEW_NAME: &str = "ultron_diff";
+
+#[derive(Debug, Clone)]
+pub enum Msg {
+    Keydown(web_sys::KeyboardEvent),
+    Command(DiffCommand),
+    SetMode(DiffMode),
+}
+
+/// how the old and new lines are laid out
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DiffMode {
+    /// the old lines on the left and the new lines on the right
+    #[default]
+    SideBySide,
+    /// the removed lines followed by the added lines in one column
+    Inline,
+}
+
+/// which of the contents a line is from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Side {
+    Old,
+    New,
+}
+
+/// Shows the differences of an old and a new content, ie: to review a generated code.
+/// The hunks are navigated with `F7` and `Shift+F7`, accepted with `a` and reverted with `r`
+pub struct DiffView<XMSG> {
+    diff_editor: DiffEditor,
+    mode: DiffMode,
+    text_highlighter: TextHighlighter,
+    old_lines: Vec<Vec<(Style, Vec<Ch>)>>,
+    new_lines: Vec<Vec<(Style, Vec<Ch>)>>,
+    /// notified with the old and the new content when a hunk is accepted or reverted
+    change_listeners: Vec<Callback<(String, String), XMSG>>,
+}
+
+impl<XMSG> DiffView<XMSG> {
+    pub fn new(old: &TextBuffer, new: &TextBuffer, syntax_token: &str) -> Self {
+        let mut text_highlighter = TextHighlighter::default();
+        text_highlighter.set_syntax_token(syntax_token);
+        let mut diff_view = Self {
+            diff_editor: DiffEditor::new(old, new),
+            mode: DiffMode::default(),
+            text_highlighter,
+            old_lines: vec![],
+            new_lines: vec![],
+            change_listeners: vec![],
+        };
+        diff_view.rehighlight();
+        diff_view
+    }
+
+    pub fn set_theme(&mut self, theme_name: &str) {
+        self.text_highlighter.select_theme(theme_name);
+        self.rehighlight();
+    }
+
+    pub fn set_mode(&mut self, mode: DiffMode) {
+        self.mode = mode;
+    }
+
+    /// compare these contents instead
+    pub fn set_contents(&mut self, old: &str, new: &str) {
+        self.diff_editor.set_contents(old, new);
+        self.rehighlight();
+    }
+
+    pub fn diff_editor(&self) -> &DiffEditor {
+        &self.diff_editor
+    }
+
+    pub fn old_content(&self) -> String {
+        self.diff_editor.old().get_content()
+    }
+
+    pub fn new_content(&self) -> String {
+        self.diff_editor.new_editor().get_content()
+    }
+
+    /// listen to the accepted and reverted hunks, with the old and the new content
+    pub fn on_change<F>(&mut self, f: F)
+    where
+        F: Fn((String, String)) -> XMSG + 'static,
+    {
+        self.change_listeners.push(Callback::from(f));
+    }
+
+    /// run the command on the hunks, the hunk commands are done on the current hunk
+    pub fn process_command(&mut self, command: DiffCommand) -> Vec<XMSG> {
+        if !self.diff_editor.process_command(command) {
+            return vec![];
+        }
+        self.rehighlight();
+        let contents = (self.old_content(), self.new_content());
+        self.change_listeners
+            .iter()
+            .map(|listener| listener.emit(contents.clone()))
+            .collect()
+    }
+
+    /// highlight the lines of both contents, each with its own highlighting state
+    fn rehighlight(&mut self) {
+        let mut highlight = |buffer: &TextBuffer| {
+            self.text_highlighter.reset();
+            buffer
+                .lines()
+                .iter()
+                .map(|line| {
+                    self.text_highlighter
+                        .highlight_line(line)
+                        .expect("must highlight")
+                        .into_iter()
+                        .map(|(style, range)| (style, range.chars().map(Ch::new).collect()))
+                        .collect()
+                })
+                .collect::<Vec<Vec<(Style, Vec<Ch>)>>>()
+        };
+        self.old_lines = highlight(self.diff_editor.old().text_buffer());
+        self.new_lines = highlight(self.diff_editor.new_editor().text_buffer());
+    }
+
+    fn process_key(&mut self, ke: &web_sys::KeyboardEvent) -> Vec<XMSG> {
+        let current = self.diff_editor.current_hunk();
+        let command = match (Key::from_name(&ke.key()), current) {
+            (Some(Key::F(7)), _) if ke.shift_key() => DiffCommand::PrevHunk,
+            (Some(Key::F(7)), _) => DiffCommand::NextHunk,
+            (Some(Key::Up), _) if ke.alt_key() => DiffCommand::PrevHunk,
+            (Some(Key::Down), _) if ke.alt_key() => DiffCommand::NextHunk,
+            (Some(Key::Char('a')), Some(current)) => DiffCommand::AcceptHunk(current),
+            (Some(Key::Char('r')), Some(current)) => DiffCommand::RevertHunk(current),
+            _ => return vec![],
+        };
+        ke.prevent_default();
+        self.process_command(command)
+    }
+
+    fn theme_background(&self) -> RGBA {
+        self.text_highlighter
+            .active_theme()
+            .settings
+            .background
+            .map(util::to_rgba)
+            .unwrap_or(rgba(255, 255, 255, 1.0))
+    }
+
+    fn gutter_foreground(&self) -> RGBA {
+        self.text_highlighter
+            .active_theme()
+            .settings
+            .gutter_foreground
+            .map(util::to_rgba)
+            .unwrap_or(rgba(0, 0, 0, 0.5))
+    }
+
+    fn view_toolbar(&self) -> Node<Msg> {
+        let class_ns = |class_names| class_namespaced(DIFF_VIEW_NAME, class_names);
+        let hunks = self.diff_editor.diff().hunks().len();
+        let status = match self.diff_editor.current_hunk() {
+            Some(current) => format!("change {} of {}", current + 1, hunks),
+            None => format!("{hunks} changes"),
+        };
+        let next_mode = match self.mode {
+            DiffMode::SideBySide => DiffMode::Inline,
+            DiffMode::Inline => DiffMode::SideBySide,
+        };
+        div(
+            [class_ns("toolbar")],
+            [
+                button(
+                    [on_click(|_| Msg::Command(DiffCommand::PrevHunk))],
+                    [text("↑ Previous")],
+                ),
+                button(
+                    [on_click(|_| Msg::Command(DiffCommand::NextHunk))],
+                    [text("↓ Next")],
+                ),
+                span([class_ns("status")], [text(status)]),
+                button(
+                    [on_click(|_| Msg::Command(DiffCommand::AcceptAll))],
+                    [text("Accept All")],
+                ),
+                button(
+                    [on_click(|_| Msg::Command(DiffCommand::RevertAll))],
+                    [text("Revert All")],
+                ),
+                button(
+                    [on_click(move |_| Msg::SetMode(next_mode))],
+                    [text(match next_mode {
+                        DiffMode::Inline => "Inline",
+                        DiffMode::SideBySide => "Side by Side",
+                    })],
+                ),
+            ],
+        )
+    }
+
+    /// the accept and revert buttons shown on the first row of a hunk
+    fn view_hunk_actions(&self, hunk: usize) -> Node<Msg> {
+        div(
+            [class_namespaced(DIFF_VIEW_NAME, "hunk_actions")],
+            [
+                button(
+                    [
+                        title("Accept the change"),
+                        on_click(move |_| Msg::Command(DiffCommand::AcceptHunk(hunk))),
+                    ],
+                    [text("✓")],
+                ),
+                button(
+                    [
+                        title("Revert the change"),
+                        on_click(move |_| Msg::Command(DiffCommand::RevertHunk(hunk))),
+                    ],
+                    [text("↺")],
+                ),
+            ],
+        )
+    }
+
+    fn view_rows(&self) -> Node<Msg> {
+        let rows = match self.mode {
+            DiffMode::SideBySide => self.diff_editor.diff().side_by_side_rows(),
+            DiffMode::Inline => self.diff_editor.diff().inline_rows(),
+        };
+        let mut previous_hunk = None;
+        div(
+            [class_namespaced(DIFF_VIEW_NAME, "rows")],
+            rows.iter().map(|row| {
+                let is_hunk_start = row.hunk.is_some() && row.hunk != previous_hunk;
+                previous_hunk = row.hunk;
+                self.view_row(row, is_hunk_start)
+            }),
+        )
+    }
+
+    fn view_row(&self, row: &DiffRow, is_hunk_start: bool) -> Node<Msg> {
+        let is_current = row.hunk.is_some() && row.hunk == self.diff_editor.current_hunk();
+        let cells = match self.mode {
+            DiffMode::SideBySide => vec![
+                self.view_line_number(row.old_line),
+                self.view_cell(Side::Old, row.old_line, row.kind),
+                self.view_line_number(row.new_line),
+                self.view_cell(Side::New, row.new_line, row.kind),
+            ],
+            // the unchanged lines are the same in both contents
+            DiffMode::Inline => {
+                let (side, line) = match row.new_line {
+                    Some(line) => (Side::New, Some(line)),
+                    None => (Side::Old, row.old_line),
+                };
+                vec![
+                    self.view_line_number(row.old_line),
+                    self.view_line_number(row.new_line),
+                    self.view_cell(side, line, row.kind),
+                ]
+            }
+        };
+        div(
+            [
+                class_namespaced(DIFF_VIEW_NAME, "row"),
+                classes_flag_namespaced(DIFF_VIEW_NAME, [("current", is_current)]),
+            ],
+            cells.into_iter().chain(
+                row.hunk
+                    .filter(|_| is_hunk_start)
+                    .map(|hunk| self.view_hunk_actions(hunk)),
+            ),
+        )
+    }
+
+    fn view_line_number(&self, line: Option<usize>) -> Node<Msg> {
+        // the line numbers start at 1
+        span(
+            [
+                class_namespaced(DIFF_VIEW_NAME, "number"),
+                style! { color: self.gutter_foreground().to_css() },
+            ],
+            [text(
+                line.map(|line| (line + 1).to_string()).unwrap_or_default(),
+            )],
+        )
+    }
+
+    /// the highlighted line of one side of the row,
+    /// the changed characters are highlighted over the syntax styles
+    fn view_cell(&self, side: Side, line: Option<usize>, kind: DiffKind) -> Node<Msg> {
+        let class_ns = |class_names| class_namespaced(DIFF_VIEW_NAME, class_names);
+        let diff = self.diff_editor.diff();
+        let (lines, highlights) = match (side, line) {
+            (Side::Old, Some(line)) => (&self.old_lines, diff.old_highlights(line)),
+            (Side::New, Some(line)) => (&self.new_lines, diff.new_highlights(line)),
+            (_, None) => return div([class_ns("cell"), class_ns("blank")], []),
+        };
+        let line = line.expect("has a line");
+        let cell_kind = match (kind, side) {
+            (DiffKind::Changed, Side::Old) => "removed",
+            (DiffKind::Changed, Side::New) => "added",
+            (kind, _) => kind.name(),
+        };
+        let mut x = 0;
+        let ranges = lines
+            .get(line)
+            .into_iter()
+            .flatten()
+            .flat_map(|(style, chars)| {
+                let foreground = util::to_rgba(style.foreground).to_css();
+                chars
+                    .iter()
+                    .map(|ch| {
+                        let column = x;
+                        x += ch.width;
+                        let is_changed = highlights
+                            .iter()
+                            .any(|(start, end)| (*start..*end).contains(&column));
+                        (foreground.clone(), is_changed, ch.ch)
+                    })
+                    .collect::<Vec<_>>()
+            });
+        // the consecutive characters of the same style are put in one span
+        let mut spans: Vec<(String, bool, String)> = vec![];
+        for (foreground, is_changed, ch) in ranges {
+            match spans.last_mut() {
+                Some((last_foreground, last_changed, text))
+                    if *last_foreground == foreground && *last_changed == is_changed =>
+                {
+                    text.push(ch)
+                }
+                _ => spans.push((foreground, is_changed, ch.to_string())),
+            }
+        }
+        div(
+            [class_ns("cell"), class_ns(cell_kind)],
+            [code(
+                [class_ns("code")],
+                spans
+                    .into_iter()
+                    .map(|(foreground, is_changed, text_content)| {
+                        span(
+                            [
+                                classes_flag_namespaced(
+                                    DIFF_VIEW_NAME,
+                                    [("char_changed", is_changed)],
+                                ),
+                                style! { color: foreground },
+                            ],
+                            [text(text_content)],
+                        )
+                    }),
+            )],
+        )
+    }
+}
+
+impl<XMSG> Component<Msg, XMSG> for DiffView<XMSG>
+where
+    XMSG: 'static,
+{
+    fn init(&mut self) -> Effects<Msg, XMSG> {
+        Effects::none()
+    }
+
+    fn update(&mut self, msg: Msg) -> Effects<Msg, XMSG> {
+        match msg {
+            Msg::Keydown(ke) => Effects::with_external(self.process_key(&ke)),
+            Msg::Command(command) => Effects::with_external(self.process_command(command)),
+            Msg::SetMode(mode) => {
+                self.mode = mode;
+                Effects::none()
+            }
+        }
+    }
+
+    fn view(&self) -> Node<Msg> {
+        div(
+            [
+                class(DIFF_VIEW_NAME),
+                classes_flag_namespaced(
+                    DIFF_VIEW_NAME,
+                    [("inline", self.mode == DiffMode::Inline)],
+                ),
+                tabindex(0),
+                on_keydown(Msg::Keydown),
+                style! { background_color: self.theme_background().to_css() },
+            ],
+            [self.view_toolbar(), self.view_rows()],
+        )
+    }
+
+    fn stylesheet() -> Vec<String> {
+        vec![jss_ns_pretty! {DIFF_VIEW_NAME,
+            ".": {
+                font_family: "monospace",
+                outline: "none",
+            },
+
+            ".toolbar": {
+                display: "flex",
+                gap: px(8),
+                align_items: "center",
+                padding: px(4),
+                background_color: "#eee",
+                user_select: "none",
+                "-webkit-user-select": "none",
+            },
+
+            ".status": {
+                flex: 1,
+                opacity: 0.7,
+            },
+
+            ".row": {
+                display: "flex",
+                position: "relative",
+                white_space: "pre",
+            },
+
+            ".current": {
+                outline: format!("{} solid rgba(0, 120, 215, 0.5)", px(1)),
+            },
+
+            ".cell": {
+                display: "flex",
+                flex: 1,
+                min_width: 0,
+            },
+
+            ".number": {
+                display: "inline-block",
+                min_width: px(40),
+                padding_right: px(8),
+                text_align: "right",
+                user_select: "none",
+                "-webkit-user-select": "none",
+            },
+
+            ".added": {
+                background_color: "rgba(40, 200, 80, 0.15)",
+            },
+
+            ".removed": {
+                background_color: "rgba(240, 60, 60, 0.15)",
+            },
+
+            ".blank": {
+                background_color: "rgba(128, 128, 128, 0.1)",
+            },
+
+            ".added .char_changed": {
+                background_color: "rgba(40, 200, 80, 0.4)",
+            },
+
+            ".removed .char_changed": {
+                background_color: "rgba(240, 60, 60, 0.4)",
+            },
+
+            ".hunk_actions": {
+                position: "absolute",
+                right: px(4),
+                display: "flex",
+                gap: px(2),
+            },
+        }]
+    }
+}
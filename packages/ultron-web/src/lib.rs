@@ -1,4 +1,6 @@
 #![deny(warnings)]
+pub use clipboard::NavigatorClipboard;
+pub use diff_view::{DiffMode, DiffView};
 pub use font_loader::FontLoader;
 pub use sauron;
 use sauron::*;
@@ -7,12 +9,15 @@ pub use ultron_core::{base_editor, nalgebra, SelectionMode, TextBuffer};
 #[cfg(feature = "custom_element")]
 pub use web_editor::custom_element::{attributes, register, ultron_editor};
 pub use web_editor::{
-    BaseOptions, Call, Command, FontSettings, MouseCursor, Options, WebEditor, COMPONENT_NAME,
+    BaseOptions, Call, Command, FontSettings, KeymapProfile, MenuContext, MenuItem, MouseCursor,
+    Options, WebEditor, WrapWidth, COMPONENT_NAME,
 };
 
 pub use ultron_core;
 
+mod clipboard;
 pub(crate) mod context_menu;
+pub mod diff_view;
 pub mod font_loader;
 pub mod spinner;
 pub(crate) mod util;
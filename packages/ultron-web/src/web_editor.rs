@@ -1,30 +1,41 @@
-use crate::context_menu::{self, Menu};
+use crate::context_menu::{self, Menu, MenuEntry};
 use crate::util;
 use css_colors::{rgba, Color, RGBA};
 use sauron::prelude::*;
+use command_palette::{CommandPalette, PaletteOutcome};
+use completion_popup::{CompletionPopup, PopupOutcome};
+use diagnostic_popup::DiagnosticPopup;
+use go_to_line::{GoToLinePrompt, PromptOutcome};
 use selection::SelectionSplits;
 use std::cell::RefCell;
 use std::rc::Rc;
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering;
 use ultron_core::{
-    base_editor::Callback, nalgebra::Point2, BaseEditor, Ch, SelectionMode, Style, TextBuffer,
-    TextEdit, TextHighlighter,
+    base_editor::Callback, nalgebra::Point2, BaseEditor, Binding, Ch, CommandEntry,
+    CommandRegistry, CompletionItem, CompletionProvider, Decoration, DecorationId, Diagnostic,
+    Emacs, Formatter, GoToTarget, Kakoune, Key, KeyChord, KeyContext, KeyEvent, KeyOutcome,
+    KeyResult, Keymap, LineChange, LineChanges, Modifiers, SelectionMode, Severity, Style,
+    TextBuffer, TextEdit, TextHighlighter, Vim,
 };
 use crate::Spinner;
 use sauron::dom::Widget;
 use sauron::dom::{IdleCallbackHandle, IdleDeadline, request_idle_callback};
 use web_sys::HtmlElement;
 
-pub use crate::context_menu::MenuAction;
+pub use crate::context_menu::{MenuAction, MenuContext, MenuItem};
 pub use crate::font_loader::FontSettings;
 use crate::wasm_bindgen::JsCast;
 use crate::{font_loader, FontLoader};
 pub use mouse_cursor::MouseCursor;
-pub use options::Options;
+pub use options::{KeymapProfile, Options, WrapWidth};
 pub use ultron_core;
 pub use ultron_core::{BaseOptions, Command};
 
+mod command_palette;
+mod completion_popup;
+mod diagnostic_popup;
+mod go_to_line;
 mod mouse_cursor;
 mod selection;
 
@@ -34,6 +45,13 @@ mod options;
 
 pub const COMPONENT_NAME: &str = "ultron";
 
+/// the action bound in the keymaps to show the command palette, ie: `M-x = @command-palette`
+const COMMAND_PALETTE: &str = "command-palette";
+/// the action bound in the keymaps to show the go to line prompt, ie: `Ctrl+g = @go-to-line`
+const GO_TO_LINE: &str = "go-to-line";
+/// the actions run by the editor instead of being emitted to the host
+const EDITOR_ACTIONS: &[&str] = &[COMMAND_PALETTE, GO_TO_LINE];
+
 #[derive(Debug)]
 pub enum Msg {
     EditorMounted(MountEvent),
@@ -46,6 +64,8 @@ pub enum Msg {
     ChangeSyntax(String),
     /// Change the theme of the editor
     ChangeTheme(String),
+    /// Change the keymap profile by its name, ie: `vim` or `emacs`
+    ChangeKeymapProfile(String),
     CursorMounted(MountEvent),
     Keydown(web_sys::KeyboardEvent),
     Mouseup(web_sys::MouseEvent),
@@ -53,12 +73,21 @@ pub enum Msg {
     Mousedown(web_sys::MouseEvent),
     Mousemove(web_sys::MouseEvent),
     Measurements(Measurements),
+    /// the window is resized, forwarded by the host like the mouse events of the window.
+    /// The long lines are wrapped again to the new width of the editor
+    WindowResized,
     Focused(web_sys::FocusEvent),
     Blur(web_sys::FocusEvent),
     ContextMenu(web_sys::MouseEvent),
     ContextMenuMsg(context_menu::Msg),
+    /// run the command of the registry with this id, ie: clicked in the command palette
+    RunPaletteCommand(String),
+    /// insert the completion at this index of the completion popup, ie: when it is clicked
+    AcceptCompletion(usize),
     ScrollCursorIntoView,
     MenuAction(MenuAction),
+    /// the text read asynchronously from the clipboard, to be pasted in the editor
+    PasteText(String),
     /// set focus to the editor
     SetFocus,
     NoOp,
@@ -97,6 +126,31 @@ pub struct WebEditor<XMSG> {
     pub is_focused: bool,
     context_menu: Menu<Msg>,
     show_context_menu: bool,
+    /// the context menu items added by the host application
+    menu_items: Vec<MenuItem<XMSG>>,
+    /// the key bindings of the editor
+    keymap: Keymap,
+    /// the state of the vim modal editing, used when the keymap profile is vim
+    vim: Vim,
+    /// the state of the emacs editing, used when the keymap profile is emacs
+    emacs: Emacs,
+    /// the state of the selection first editing, used when the keymap profile is kakoune
+    kakoune: Kakoune,
+    /// the commands listed in the command palette
+    command_registry: CommandRegistry,
+    /// the command palette overlay, shown with `Ctrl+Shift+P`
+    command_palette: Option<CommandPalette>,
+    /// the go to line prompt, shown with `Ctrl+G`
+    go_to_line_prompt: Option<GoToLinePrompt>,
+    /// the completions of the word before the cursor, shown with `Ctrl+Space`
+    /// or when a trigger character is typed
+    completion_popup: Option<CompletionPopup>,
+    /// the diagnostics under the mouse
+    diagnostic_popup: Option<DiagnosticPopup>,
+    /// the line briefly highlighted after going to it
+    flash_line: Option<usize>,
+    /// emitted when a key bound to a host action is pressed
+    action_listeners: Vec<Callback<String, XMSG>>,
     is_fonts_ready: bool,
     /// emitted when the editor is ready
     /// meaning the fonts has been loaded and the editor has been mounted
@@ -130,6 +184,18 @@ impl<XMSG> Default for WebEditor<XMSG> {
             is_focused: false,
             context_menu: Menu::new(),
             show_context_menu: false,
+            menu_items: vec![],
+            keymap: Keymap::standard(),
+            vim: Vim::new(),
+            emacs: Emacs::new(),
+            kakoune: Kakoune::new(),
+            command_registry: CommandRegistry::standard(),
+            command_palette: None,
+            go_to_line_prompt: None,
+            completion_popup: None,
+            diagnostic_popup: None,
+            flash_line: None,
+            action_listeners: vec![],
             is_fonts_ready: false,
             ready_listener: vec![],
             is_background_highlighting_ongoing: Rc::new(AtomicBool::new(false)),
@@ -148,7 +214,19 @@ where
     XMSG: 'static,
 {
     pub fn from_str(options: &Options, content: &str) -> Self {
-        let base_editor = BaseEditor::from_str(&options.base_options, content);
+        let mut base_editor = BaseEditor::from_str(&options.base_options, content);
+        base_editor.set_syntax_token(&options.syntax_token);
+        #[cfg(feature = "with-navigator-clipboard")]
+        base_editor.set_clipboard(crate::NavigatorClipboard::new());
+        if let Some(WrapWidth::Column(wrap_column)) = options.soft_wrap {
+            base_editor.set_wrap_column(Some(wrap_column));
+        }
+        let mut keymap = Keymap::standard();
+        if let Some(keymap_config) = &options.keymap_config {
+            if let Err(e) = keymap.load_config(keymap_config) {
+                log::error!("invalid keymap config: {e}");
+            }
+        }
         let mut text_highlighter = TextHighlighter::default();
         if let Some(theme_name) = &options.theme_name {
             text_highlighter.select_theme(theme_name);
@@ -175,6 +253,7 @@ where
             highlighted_lines,
             context_menu: Menu::new().on_activate(Msg::MenuAction),
             show_context_menu: false,
+            keymap,
             ..Default::default()
         }
     }
@@ -201,6 +280,7 @@ where
                     self.host_element = Some(host_element);
                 }
                 self.editor_element = Some(mount_element);
+                self.update_wrap_column();
                 let xmsgs = self.try_ready_listener();
                 Effects::new([], xmsgs)
             }
@@ -211,6 +291,7 @@ where
                 self.options.ch_width = ch_width;
                 self.options.ch_height = ch_height;
                 self.is_fonts_ready = true;
+                self.update_wrap_column();
                 let xmsgs = self.try_ready_listener();
                 Effects::new([], xmsgs)
             }
@@ -227,16 +308,33 @@ where
                 self.set_theme(&theme_name);
                 Effects::none()
             }
+            Msg::ChangeKeymapProfile(name) => {
+                match KeymapProfile::from_name(&name) {
+                    Some(profile) => self.set_keymap_profile(profile),
+                    None => log::warn!("unknown keymap profile: {name}"),
+                }
+                Effects::none()
+            }
             Msg::CursorMounted(mount_event) => {
                 let cursor_element: web_sys::Element = mount_event.target_node.unchecked_into();
                 self.cursor_element = Some(cursor_element);
                 Effects::none()
             }
             Msg::Click(me) => {
+                self.completion_popup = None;
                 if self.is_ready() {
                     let client_x = me.client_x();
                     let client_y = me.client_y();
-                    let cursor = self.client_to_grid_clamped(client_x, client_y);
+                    let grid = self.client_to_grid(client_x, client_y);
+                    // clicking on the fold marker folds or unfolds the range
+                    if self.options.show_fold_markers && grid.x == -1 && grid.y >= 0 {
+                        let line = grid.y as usize;
+                        if self.base_editor.folding().range_starting_at(line).is_some() {
+                            self.base_editor
+                                .process_commands([Command::ToggleFoldAt(line)]);
+                        }
+                    }
+                    let cursor = util::clamp_to_edge(grid);
                     let msgs = self
                         .base_editor
                         .process_commands([Command::SetPosition(cursor)]);
@@ -274,6 +372,13 @@ where
                     let client_x = me.client_x();
                     let client_y = me.client_y();
                     let cursor = self.client_to_grid_clamped(client_x, client_y);
+                    let hovered = self.client_to_grid(client_x, client_y);
+                    self.diagnostic_popup = if hovered.x >= 0 && hovered.y >= 0 {
+                        let location = Point2::new(hovered.x as usize, hovered.y as usize);
+                        DiagnosticPopup::at(self.base_editor.diagnostics(), location)
+                    } else {
+                        None
+                    };
                     if self.options.allow_text_selection
                         && self.is_selecting
                         && !self.show_context_menu
@@ -330,6 +435,10 @@ where
             Msg::Keydown(ke) => {
                 self.process_keypress(&ke)
             }
+            Msg::WindowResized => {
+                self.update_wrap_column();
+                Effects::none()
+            }
             Msg::Measurements(measure) => {
                 self.update_measure(&measure);
                 Effects::none()
@@ -358,6 +467,11 @@ where
                 let (start, _end) = self.bounding_rect().expect("must have a bounding rect");
                 let x = me.client_x() - start.x as i32;
                 let y = me.client_y() - start.y as i32;
+                self.context_menu.set_disabled(self.disabled_menu_actions());
+                self.context_menu.set_host_entries(MenuEntry::from_items(
+                    &self.menu_items,
+                    &self.menu_context(),
+                ));
                 self.context_menu
                     .update(context_menu::Msg::ShowAt(Point2::new(x, y)))
                     .localize(Msg::ContextMenuMsg)
@@ -366,6 +480,23 @@ where
                 .context_menu
                 .update(cm_msg)
                 .localize(Msg::ContextMenuMsg),
+            Msg::RunPaletteCommand(id) => {
+                self.command_palette = None;
+                match self.command_registry.get(&id) {
+                    Some(entry) => self.run_binding(entry.binding.clone()),
+                    None => Effects::none(),
+                }
+            }
+            Msg::AcceptCompletion(index) => {
+                let item = self
+                    .completion_popup
+                    .take()
+                    .and_then(|popup| popup.get(index).cloned());
+                match item {
+                    Some(item) => self.accept_completion(item),
+                    None => Effects::none(),
+                }
+            }
             Msg::ScrollCursorIntoView => {
                 if self.options.scroll_cursor_into_view {
                     let cursor_element = self.cursor_element.as_ref().unwrap();
@@ -381,25 +512,37 @@ where
                 self.show_context_menu = false;
                 match menu_action {
                     MenuAction::Undo => {
-                        self.process_calls_with_effects([Call::Command(Command::Undo)]);
+                        self.process_calls_with_effects([Call::Command(Command::Undo)])
                     }
                     MenuAction::Redo => {
-                        self.process_calls_with_effects([Call::Command(Command::Redo)]);
-                    }
-                    MenuAction::Cut => {
-                        self.cut_selected_text_to_clipboard();
+                        self.process_calls_with_effects([Call::Command(Command::Redo)])
                     }
+                    MenuAction::Cut => self.process_calls_with_effects([Call::CutText]),
                     MenuAction::Copy => {
                         self.copy_selected_text_to_clipboard();
+                        Effects::none()
+                    }
+                    MenuAction::Paste => self.paste_from_clipboard(),
+                    MenuAction::Delete => {
+                        self.process_calls_with_effects([Call::Command(Command::DeleteSelection)])
                     }
-                    MenuAction::Paste => todo!(),
-                    MenuAction::Delete => todo!(),
                     MenuAction::SelectAll => {
                         self.process_calls_with_effects([Call::Command(Command::SelectAll)]);
                         log::info!("selected text: {:?}", self.selected_text());
+                        Effects::none()
+                    }
+                    MenuAction::Host(id) => {
+                        match context_menu::find_menu_item(&self.menu_items, id) {
+                            Some(MenuItem::Item { on_select, .. }) => {
+                                Effects::with_external([on_select.emit(self.menu_context())])
+                            }
+                            _ => Effects::none(),
+                        }
                     }
                 }
-                Effects::none()
+            }
+            Msg::PasteText(text) => {
+                self.process_calls_with_effects([Call::Command(Command::PasteText(text))])
             }
             Msg::NoOp => Effects::none(),
         }
@@ -461,6 +604,122 @@ where
                 display: "block",
             },
 
+            // lines with a bookmark or a named mark
+            ".marked": {
+                box_shadow: "inset 3px 0 0 0 #4a90d9",
+                font_weight: "bold",
+            },
+
+            // the changes of the lines from the base text
+            ".change_added": {
+                background_image: "linear-gradient(to left, #2ea043 3px, transparent 3px)",
+            },
+
+            ".change_modified": {
+                background_image: "linear-gradient(to left, #0078d4 3px, transparent 3px)",
+            },
+
+            ".change_deleted_before": {
+                background_image: "linear-gradient(to bottom, #f85149 2px, transparent 2px)",
+            },
+
+            ".change_deleted_after": {
+                background_image: "linear-gradient(to top, #f85149 2px, transparent 2px)",
+            },
+
+            // the line moved to with the go to line prompt
+            ".flash": {
+                animation: "flash_line-anim 1000ms ease-out",
+            },
+
+            "@keyframes flash_line-anim": {
+                "0%": {
+                    background_color: "rgba(255, 220, 0, 0.6)",
+                },
+                "100%": {
+                    background_color: "transparent",
+                },
+            },
+
+            ".gutter_icon": {
+                flex: "none",
+                display: "inline-block",
+                text_align: "center",
+                user_select: "none",
+                "-webkit-user-select": "none",
+            },
+
+            ".gutter_breakpoint": {
+                color: "#e51400",
+            },
+
+            ".gutter_error": {
+                color: "#e51400",
+            },
+
+            ".gutter_warning": {
+                color: "#bf8803",
+            },
+
+            ".gutter_info": {
+                color: "#1a85ff",
+            },
+
+            ".gutter_hint": {
+                color: "#888",
+            },
+
+            ".line_error": {
+                background_color: "rgba(229, 20, 0, 0.12)",
+            },
+
+            ".line_warning": {
+                background_color: "rgba(191, 136, 3, 0.12)",
+            },
+
+            ".line_highlight": {
+                background_color: "rgba(74, 144, 217, 0.15)",
+            },
+
+            ".range_error": {
+                text_decoration: "underline wavy #e51400",
+            },
+
+            ".range_warning": {
+                text_decoration: "underline wavy #bf8803",
+            },
+
+            ".range_info": {
+                text_decoration: "underline wavy #1a85ff",
+            },
+
+            ".range_hint": {
+                text_decoration: "underline dotted #888",
+            },
+
+            ".range_highlight": {
+                background_color: "rgba(255, 220, 0, 0.35)",
+            },
+
+            ".range_snippet_placeholder": {
+                outline: format!("{} solid rgba(0, 120, 215, 0.5)", px(1)),
+            },
+
+            ".fold_marker": {
+                flex: "none",
+                display: "inline-block",
+                text_align: "center",
+                cursor: "pointer",
+                user_select: "none",
+                "-webkit-user-select": "none",
+            },
+
+            ".fold_ellipsis": {
+                opacity: 0.6,
+                user_select: "none",
+                "-webkit-user-select": "none",
+            },
+
             "font_measure": {
                 bottom: px(30),
                 display: "inline-block",
@@ -507,7 +766,16 @@ where
             },
         };
 
-        [vec![main], FontLoader::<Msg>::stylesheet(), Menu::<Msg>::stylesheet()].concat()
+        [
+            vec![main],
+            FontLoader::<Msg>::stylesheet(),
+            Menu::<Msg>::stylesheet(),
+            CommandPalette::stylesheet(),
+            GoToLinePrompt::stylesheet(),
+            CompletionPopup::stylesheet(),
+            DiagnosticPopup::stylesheet(),
+        ]
+        .concat()
     }
 
     fn style(&self) -> Vec<String> {
@@ -577,13 +845,310 @@ where
         self.ready_listener.push(Callback::from(move |_| f()));
     }
 
+    /// add an entry to the context menu, displayed after the editor actions
+    pub fn add_menu_item(&mut self, item: MenuItem<XMSG>) {
+        self.menu_items.push(item);
+    }
+
+    /// the cursor and selection passed to the host menu items
+    pub fn menu_context(&self) -> MenuContext {
+        MenuContext {
+            cursor: self.base_editor.get_position(),
+            selection: self.base_editor.selection().clone(),
+            selected_text: self.selected_text(),
+        }
+    }
+
+    pub fn keymap(&self) -> &Keymap {
+        &self.keymap
+    }
+
+    /// access the keymap, ie: to override or add key bindings
+    pub fn keymap_mut(&mut self) -> &mut Keymap {
+        &mut self.keymap
+    }
+
+    pub fn set_keymap(&mut self, keymap: Keymap) {
+        self.keymap = keymap;
+    }
+
+    /// the state of the vim modal editing, ie: the current mode
+    pub fn vim(&self) -> &Vim {
+        &self.vim
+    }
+
+    /// the state of the emacs editing, ie: the mark
+    pub fn emacs(&self) -> &Emacs {
+        &self.emacs
+    }
+
+    /// access the emacs profile, ie: to change its key bindings
+    pub fn emacs_mut(&mut self) -> &mut Emacs {
+        &mut self.emacs
+    }
+
+    /// the state of the kakoune editing, ie: the current mode
+    pub fn kakoune(&self) -> &Kakoune {
+        &self.kakoune
+    }
+
+    pub fn keymap_profile(&self) -> KeymapProfile {
+        self.options.keymap_profile
+    }
+
+    /// switch the editing profile at runtime,
+    /// the state of the profile being left such as the vim mode is reset
+    pub fn set_keymap_profile(&mut self, keymap_profile: KeymapProfile) {
+        match self.options.keymap_profile {
+            KeymapProfile::Standard => self.keymap.cancel_pending(),
+            KeymapProfile::Vim => self.vim.reset(&mut self.base_editor),
+            KeymapProfile::Emacs => self.emacs.reset(&mut self.base_editor),
+            KeymapProfile::Kakoune => self.kakoune.reset(&mut self.base_editor),
+        }
+        self.options.keymap_profile = keymap_profile;
+    }
+
+    /// the commands listed in the command palette
+    pub fn command_registry(&self) -> &CommandRegistry {
+        &self.command_registry
+    }
+
+    pub fn command_registry_mut(&mut self) -> &mut CommandRegistry {
+        &mut self.command_registry
+    }
+
+    /// list a command in the command palette,
+    /// the host actions are emitted to the `on_action` listeners when run
+    pub fn register_command(&mut self, entry: CommandEntry) {
+        self.command_registry.register(entry);
+    }
+
+    pub fn show_command_palette(&mut self) {
+        self.command_palette = Some(CommandPalette::default());
+    }
+
+    pub fn hide_command_palette(&mut self) {
+        self.command_palette = None;
+    }
+
+    pub fn is_command_palette_shown(&self) -> bool {
+        self.command_palette.is_some()
+    }
+
+    /// decorate a line or a range of text, ie: a breakpoint in the gutter or a squiggle.
+    /// The decoration is styled with its class prefixed by its kind and namespaced like
+    /// the other classes of the editor, ie: `gutter_breakpoint`, `line_error`, `range_error`
+    pub fn add_decoration(&mut self, decoration: Decoration) -> DecorationId {
+        self.base_editor.add_decoration(decoration)
+    }
+
+    pub fn remove_decoration(&mut self, id: DecorationId) -> Option<Decoration> {
+        self.base_editor.remove_decoration(id)
+    }
+
+    /// replace the diagnostics, they are underlined with their severity shown in the gutter
+    /// and their message shown when hovered
+    pub fn set_diagnostics(&mut self, diagnostics: impl IntoIterator<Item = Diagnostic>) {
+        self.base_editor.set_diagnostics(diagnostics);
+        self.diagnostic_popup = None;
+    }
+
+    pub fn clear_diagnostics(&mut self) {
+        self.base_editor.clear_diagnostics();
+        self.diagnostic_popup = None;
+    }
+
+    /// mark the added, modified and deleted lines from this base text in the line numbers,
+    /// ie: the last saved or committed text
+    pub fn set_base_text(&mut self, base: &str) {
+        self.base_editor.set_base_text(base);
+    }
+
+    pub fn clear_base_text(&mut self) {
+        self.base_editor.clear_base_text();
+    }
+
+    /// add a provider of the completions, the words of the content are suggested by default
+    pub fn add_completion_provider(&mut self, provider: impl CompletionProvider + 'static) {
+        self.base_editor.add_completion_provider(provider);
+    }
+
+    /// show the completions of the providers, `trigger` is the typed character which triggers it
+    pub fn trigger_completion(&mut self, trigger: Option<char>) {
+        let items = self.base_editor.completions(trigger);
+        self.show_completions(items);
+    }
+
+    /// show these completions, ie: received asynchronously from a language server.
+    /// They are filtered with the word before the cursor
+    pub fn show_completions(&mut self, items: Vec<CompletionItem>) {
+        let (start, typed) = self.base_editor.completion_prefix();
+        let popup = CompletionPopup::new(items, start, &typed);
+        self.completion_popup = (!popup.is_empty()).then_some(popup);
+    }
+
+    pub fn hide_completions(&mut self) {
+        self.completion_popup = None;
+    }
+
+    pub fn is_completion_shown(&self) -> bool {
+        self.completion_popup.is_some()
+    }
+
+    /// insert the completion in place of the word before the cursor, undone in one step
+    fn accept_completion(&mut self, item: CompletionItem) -> Effects<Msg, XMSG> {
+        let is_snippet = item.is_snippet;
+        let effects = self
+            .process_calls_with_effects([Call::Command(Command::AcceptCompletion(item))])
+            .append_local([Msg::ScrollCursorIntoView]);
+        if is_snippet {
+            self.show_snippet_choices();
+        }
+        effects
+    }
+
+    /// add a snippet for the syntax, it is suggested in the completions when its prefix is typed
+    pub fn register_snippet(
+        &mut self,
+        syntax_token: &str,
+        prefix: &str,
+        body: &str,
+        description: Option<&str>,
+    ) {
+        self.base_editor
+            .register_snippet(syntax_token, prefix, body, description);
+    }
+
+    /// expand the snippet at the cursor, `Tab` and `Shift+Tab` move between its tab stops
+    pub fn insert_snippet(&mut self, body: &str) -> Effects<Msg, XMSG> {
+        let effects = self
+            .process_calls_with_effects([Call::Command(Command::InsertSnippet(body.to_string()))])
+            .append_local([Msg::ScrollCursorIntoView]);
+        self.show_snippet_choices();
+        effects
+    }
+
+    /// move between the tab stops of the snippet being filled in,
+    /// returns None when there is no snippet or the key is not for the snippet
+    /// show the choices of the current tab stop of the snippet in the completion popup
+    fn show_snippet_choices(&mut self) {
+        let items = self.base_editor.snippet_choices();
+        let Some(session) = self.base_editor.snippet_session() else {
+            return;
+        };
+        if !items.is_empty() {
+            let (start, _) = session.current_stop().placeholder();
+            self.completion_popup = Some(CompletionPopup::new(items, start, ""));
+        }
+    }
+
+    /// filter the completions with the word typed so far, the popup is closed once
+    /// the cursor leaves the word. The completion is triggered by the trigger characters
+    fn update_completion_popup(&mut self, key_event: KeyEvent) {
+        let (start, typed) = self.base_editor.completion_prefix();
+        if let Some(popup) = self.completion_popup.as_mut() {
+            if popup.start() == start {
+                popup.filter(&typed);
+            }
+            if popup.start() != start || popup.is_empty() {
+                self.completion_popup = None;
+            }
+            return;
+        }
+        if let Some(ch) = key_event.typed_char() {
+            if self.base_editor.is_completion_trigger(ch) {
+                self.trigger_completion(Some(ch));
+            }
+        }
+    }
+
+    pub fn show_go_to_line(&mut self) {
+        self.go_to_line_prompt = Some(GoToLinePrompt::default());
+    }
+
+    /// move the cursor to the target, scroll it into view and briefly highlight its line
+    pub fn go_to_target(&mut self, target: GoToTarget) -> Effects<Msg, XMSG> {
+        let cursor = self.base_editor.go_to_target(target);
+        self.flash_line = Some(cursor.y);
+        Effects::with_local([Msg::ScrollCursorIntoView])
+    }
+
+    /// go to a location such as `12`, `12:5`, `+3` or `-3`,
+    /// ie: for the host applications linking to a location in the content
+    pub fn go_to(&mut self, location: &str) -> Effects<Msg, XMSG> {
+        match location.parse() {
+            Ok(target) => self.go_to_target(target),
+            Err(e) => {
+                log::warn!("{e}");
+                Effects::none()
+            }
+        }
+    }
+
+    /// the keys bound to the command or action in the keymap of the active profile,
+    /// ie: `Ctrl+Z` or `C-x C-s`
+    fn keys_for(&self, binding: &Binding) -> Option<String> {
+        let keymap = match self.options.keymap_profile {
+            KeymapProfile::Standard => &self.keymap,
+            KeymapProfile::Emacs => self.emacs.keymap(),
+            KeymapProfile::Vim | KeymapProfile::Kakoune => return None,
+        };
+        let chords = keymap.keys_for(binding)?;
+        Some(
+            chords
+                .iter()
+                .map(|chord| chord.to_string())
+                .collect::<Vec<_>>()
+                .join(" "),
+        )
+    }
+
+    /// listen to the keys bound to a host action, ie: `Ctrl+s = @save`
+    pub fn on_action<F>(&mut self, f: F)
+    where
+        F: Fn(String) -> XMSG + 'static,
+    {
+        self.action_listeners.push(Callback::from(f));
+    }
+
     pub fn set_syntax_token(&mut self, syntax_token: &str) {
+        self.base_editor.set_syntax_token(syntax_token);
         self.text_highlighter
             .borrow_mut()
             .set_syntax_token(syntax_token);
         self.rehighlight_all();
     }
 
+    /// enable or disable the soft wrap of the long lines
+    pub fn set_soft_wrap(&mut self, soft_wrap: Option<WrapWidth>) {
+        self.options.soft_wrap = soft_wrap;
+        self.update_wrap_column();
+    }
+
+    /// recompute the column where the long lines are wrapped,
+    /// the viewport width is only known when the editor is mounted and the fonts are ready,
+    /// and it changes when the window is resized
+    fn update_wrap_column(&mut self) {
+        let wrap_column = match self.options.soft_wrap {
+            Some(WrapWidth::Column(wrap_column)) => Some(wrap_column),
+            Some(WrapWidth::Viewport) => self.viewport_columns(),
+            None => None,
+        };
+        self.base_editor.set_wrap_column(wrap_column);
+    }
+
+    /// the number of columns that fits in the editor, excluding the gutter
+    fn viewport_columns(&self) -> Option<usize> {
+        let ch_width = self.options.ch_width?;
+        let (start, end) = self.bounding_rect()?;
+        let columns = ((end.x - start.x) / ch_width) as usize;
+        Some(
+            columns
+                .saturating_sub(self.numberline_wide_with_padding())
+                .max(1),
+        )
+    }
+
     pub fn set_theme(&mut self, theme_name: &str) {
         self.text_highlighter.borrow_mut().select_theme(theme_name);
         self.rehighlight_all();
@@ -691,6 +1256,30 @@ where
                     self.is_focused && self.show_context_menu,
                     self.context_menu.view().map_msg(Msg::ContextMenuMsg),
                 ),
+                if let Some(command_palette) = &self.command_palette {
+                    command_palette.view(&self.command_registry, |binding| self.keys_for(binding))
+                } else {
+                    text("")
+                },
+                if let Some(go_to_line_prompt) = &self.go_to_line_prompt {
+                    go_to_line_prompt.view(
+                        self.base_editor.get_position().y,
+                        self.base_editor.total_lines(),
+                    )
+                } else {
+                    text("")
+                },
+                if let Some(completion_popup) = &self.completion_popup {
+                    completion_popup.view(self.cursor_to_client(), self.ch_height())
+                } else {
+                    text("")
+                },
+                if let Some(diagnostic_popup) = &self.diagnostic_popup {
+                    let position = self.location_to_client(diagnostic_popup.location());
+                    diagnostic_popup.view(position, self.ch_height())
+                } else {
+                    text("")
+                },
             ],
         )
     }
@@ -744,6 +1333,8 @@ where
             let text_highlighter = self.text_highlighter.clone();
             let highlighted_lines = self.highlighted_lines.clone();
             let lines = self.base_editor.as_ref().lines();
+            // the visible rows skips the folded lines
+            let end = self.base_editor.row_to_line(end).min(lines.len());
             for handle in self.highlight_task_handles.drain(..) {
                 //cancel the old ones, dropping the handle will call on the cancel_animation_frame
                 //for this handle
@@ -792,6 +1383,7 @@ where
             let text_highlighter = self.text_highlighter.clone();
             let highlighted_lines = self.highlighted_lines.clone();
             let lines = self.base_editor.as_ref().lines();
+            let end = self.base_editor.row_to_line(end).min(lines.len());
             let is_background_highlighting_ongoing =
                 self.is_background_highlighting_ongoing.clone();
 
@@ -829,60 +1421,219 @@ where
         Effects::none()
     }
 
-    pub fn keyevent_to_call(ke: &web_sys::KeyboardEvent) -> Option<Call> {
-        let is_ctrl = ke.ctrl_key();
-        let is_shift = ke.shift_key();
-        let key = ke.key();
-        if key.chars().count() == 1 {
-            let c = key.chars().next().expect("must be only 1 chr");
-            let command = match c {
-                'c' if is_ctrl => Call::CopyText,
-                'x' if is_ctrl => Call::CutText,
-                'v' if is_ctrl => {
-                    log::trace!("pasting is handled");
-                    Call::PasteTextBlock(String::new())
+    /// translate the browser keyboard event into a platform neutral key event
+    pub fn to_key_event(ke: &web_sys::KeyboardEvent) -> Option<KeyEvent> {
+        // ctrl+alt is AltGr, which types the character of the key
+        let is_alt = ke.alt_key() && !ke.ctrl_key();
+        let key = is_alt
+            .then(|| key_of_code(&ke.code(), ke.shift_key()))
+            .flatten()
+            .or_else(|| Key::from_name(&ke.key()))?;
+        let modifiers = Modifiers {
+            ctrl: ke.ctrl_key(),
+            alt: ke.alt_key(),
+            shift: ke.shift_key(),
+            meta: ke.meta_key(),
+        };
+        Some(KeyEvent::new(key, modifiers))
+    }
+
+    /// make this into keypress to command
+    pub fn process_keypress(&mut self, ke: &web_sys::KeyboardEvent) -> Effects<Msg, XMSG> {
+        match Self::to_key_event(ke) {
+            Some(key_event) => self.process_key_event(key_event),
+            None => Effects::none(),
+        }
+    }
+
+    /// run what the key is bound to in the keymap,
+    /// the characters of the unbound keys are inserted
+    pub fn process_key_event(&mut self, key_event: KeyEvent) -> Effects<Msg, XMSG> {
+        self.flash_line = None;
+        if let Some(go_to_line_prompt) = self.go_to_line_prompt.as_mut() {
+            return match go_to_line_prompt.process_key(key_event) {
+                PromptOutcome::Handled => Effects::none(),
+                PromptOutcome::Close => {
+                    self.go_to_line_prompt = None;
+                    Effects::none()
                 }
-                'z' | 'Z' if is_ctrl => {
-                    if is_shift {
-                        Call::Command(Command::Redo)
-                    } else {
-                        Call::Command(Command::Undo)
-                    }
+                PromptOutcome::GoTo(target) => {
+                    self.go_to_line_prompt = None;
+                    self.go_to_target(target)
                 }
-                'r' if is_ctrl => Call::Command(Command::Redo),
-                'a' if is_ctrl => Call::Command(Command::SelectAll),
-                _ => Call::Command(Command::InsertChar(c)),
             };
-
-            Some(command)
-        } else {
-            let editor_command = match &*key {
-                "Tab" => Some(Command::IndentForward),
-                "Enter" => Some(Command::BreakLine),
-                "Backspace" => Some(Command::DeleteBack),
-                "Delete" => Some(Command::DeleteForward),
-                "ArrowUp" => Some(Command::MoveUp),
-                "ArrowDown" => Some(Command::MoveDown),
-                "ArrowLeft" => Some(Command::MoveLeft),
-                "ArrowRight" => Some(Command::MoveRight),
-                "Home" => Some(Command::MoveLeftStart),
-                "End" => Some(Command::MoveRightEnd),
-                _ => None,
+        }
+        if let Some(command_palette) = self.command_palette.as_mut() {
+            return match command_palette.process_key(key_event, &self.command_registry) {
+                PaletteOutcome::Handled => Effects::none(),
+                PaletteOutcome::Close => {
+                    self.command_palette = None;
+                    Effects::none()
+                }
+                PaletteOutcome::Run(binding) => {
+                    self.command_palette = None;
+                    self.run_binding(binding)
+                }
             };
-            editor_command.map(Call::Command)
         }
-    }
-
-    /// make this into keypress to command
-    pub fn process_keypress(&mut self, ke: &web_sys::KeyboardEvent) -> Effects<Msg, XMSG> {
-        if let Some(command) = Self::keyevent_to_call(ke) {
+        if let Some(completion_popup) = self.completion_popup.as_mut() {
+            match completion_popup.process_key(key_event) {
+                PopupOutcome::Handled => return Effects::none(),
+                PopupOutcome::Close => {
+                    self.completion_popup = None;
+                    return Effects::none();
+                }
+                PopupOutcome::Accept(item) => {
+                    self.completion_popup = None;
+                    return self.accept_completion(item);
+                }
+                PopupOutcome::Pass => (),
+            }
+        }
+        let is_snippet_active = self.base_editor.snippet_session().is_some();
+        self.keymap
+            .set_context(KeyContext::Snippet, is_snippet_active);
+        let effects = self.process_editor_key(key_event);
+        self.update_completion_popup(key_event);
+        effects
+    }
+
+    /// process the key with the keymap of the current profile
+    fn process_editor_key(&mut self, key_event: KeyEvent) -> Effects<Msg, XMSG> {
+        // the bindings of the active contexts, ie: the snippet keys, apply in all the profiles
+        if self.options.keymap_profile != KeymapProfile::Standard {
+            if let Some(binding) = self.keymap.context_binding(&[key_event.chord()]) {
+                return self.run_binding(binding.clone());
+            }
+        }
+        // the modal profiles have no keymap of their own for the actions of the editor
+        if matches!(
+            self.options.keymap_profile,
+            KeymapProfile::Vim | KeymapProfile::Kakoune
+        ) {
+            if let Some(Binding::Action(action)) = self.keymap.get(&[key_event.chord()]) {
+                if EDITOR_ACTIONS.contains(&action.as_str()) {
+                    let action = action.clone();
+                    return self.run_action(&action);
+                }
+            }
+        }
+        let completion_key = KeyChord::new(Key::Char(' '), Modifiers::ctrl());
+        if key_event.chord() == completion_key {
+            self.trigger_completion(None);
+            return Effects::none();
+        }
+        match self.options.keymap_profile {
+            KeymapProfile::Standard => (),
+            KeymapProfile::Vim => {
+                let outcome = self.vim.process_key(&mut self.base_editor, key_event);
+                return self.key_outcome_effects(outcome);
+            }
+            KeymapProfile::Emacs => {
+                let outcome = self.emacs.process_key(&mut self.base_editor, key_event);
+                return self.key_outcome_effects(outcome);
+            }
+            KeymapProfile::Kakoune => {
+                let outcome = self.kakoune.process_key(&mut self.base_editor, key_event);
+                return self.key_outcome_effects(outcome);
+            }
+        }
+        let calls = match self.keymap.process_key(key_event) {
+            KeyResult::Bound(
+                binding @ Binding::Command(
+                    Command::Paste | Command::NextTabStop | Command::PrevTabStop,
+                ),
+            ) => return self.run_binding(binding),
+            KeyResult::Bound(Binding::Command(command)) => vec![Call::Command(command)],
+            KeyResult::Bound(Binding::Action(action)) => return self.run_action(&action),
+            KeyResult::Pending => vec![],
+            KeyResult::Unbound(key_events) => key_events
+                .iter()
+                .filter_map(KeyEvent::typed_char)
+                .map(|ch| Call::Command(Command::InsertChar(ch)))
+                .collect(),
+        };
+        if calls.is_empty() {
+            Effects::none()
+        } else {
             let effects = self
-                .process_calls_with_effects([command])
+                .process_calls_with_effects(calls)
                 .measure_with_name("keypress");
             effects.append_local([Msg::ScrollCursorIntoView])
+        }
+    }
+
+    /// the effects of a key processed by the vim, emacs or kakoune profile,
+    /// the actions such as `:w` or `C-x C-s` are emitted to the host
+    fn key_outcome_effects(&mut self, outcome: KeyOutcome) -> Effects<Msg, XMSG> {
+        let effects = if outcome.is_content_changed {
+            self.content_changed_effects()
         } else {
             Effects::none()
+        };
+        let action_effects: Vec<Effects<Msg, XMSG>> = outcome
+            .actions
+            .iter()
+            .map(|action| self.run_action(action))
+            .collect();
+        Effects::batch([effects].into_iter().chain(action_effects))
+            .append_local([Msg::ScrollCursorIntoView])
+    }
+
+    /// run the command or emit the host action chosen in the command palette
+    fn run_binding(&mut self, binding: Binding) -> Effects<Msg, XMSG> {
+        match binding {
+            Binding::Command(Command::Paste) => self
+                .paste_from_clipboard()
+                .append_local([Msg::ScrollCursorIntoView]),
+            // the choices of the next tab stop are shown in the completion popup
+            Binding::Command(command @ (Command::NextTabStop | Command::PrevTabStop)) => {
+                let effects = self
+                    .process_calls_with_effects([Call::Command(command)])
+                    .append_local([Msg::ScrollCursorIntoView]);
+                self.show_snippet_choices();
+                effects
+            }
+            Binding::Command(command) => self
+                .process_calls_with_effects([Call::Command(command)])
+                .append_local([Msg::ScrollCursorIntoView]),
+            Binding::Action(action) => self.run_action(&action),
+        }
+    }
+
+    /// emit the host action, the content is formatted before it is saved
+    /// when `format_on_save` is enabled. The actions of the editor are run by the editor itself
+    fn run_action(&mut self, action: &str) -> Effects<Msg, XMSG> {
+        match action {
+            COMMAND_PALETTE => {
+                self.show_command_palette();
+                return Effects::none();
+            }
+            GO_TO_LINE => {
+                self.show_go_to_line();
+                return Effects::none();
+            }
+            _ => (),
         }
+        let effects = if action == "save" && self.options.format_on_save {
+            self.process_calls_with_effects([Call::Command(Command::Format)])
+        } else {
+            Effects::none()
+        };
+        Effects::batch([effects, Effects::with_external(self.emit_action(action))])
+    }
+
+    /// use this formatter in `Command::Format` and when the content is saved
+    pub fn set_formatter(&mut self, formatter: impl Formatter + 'static) {
+        self.base_editor.set_formatter(formatter);
+    }
+
+    /// notify the action listeners of the host action
+    fn emit_action(&self, action: &str) -> Vec<XMSG> {
+        self.action_listeners
+            .iter()
+            .map(|listener| listener.emit(action.to_string()))
+            .collect()
     }
 
     /// process the calls and dispatch effects events when applicable
@@ -896,27 +1647,32 @@ where
             .collect();
         let is_content_changed = results.into_iter().any(|v| v);
         if is_content_changed {
-            let xmsgs = self.base_editor.emit_on_change_listeners();
-            let mut all_effects = vec![Effects::new([], xmsgs)];
-            if self.options.use_syntax_highlighter {
-                self.rehighlight_visible_lines();
-                let effects = self.rehighlight_non_visible_lines_in_background();
-                all_effects.push(effects);
-            }
-            if let Some(host_element) = self.host_element.as_ref() {
-                host_element
-                    .set_attribute("content", &self.get_content())
-                    .expect("set attr content");
-                host_element
-                    .dispatch_event(&InputEvent::create_web_event_composed())
-                    .expect("dispatch event");
-            }
-            Effects::batch(all_effects)
+            self.content_changed_effects()
         } else {
             Effects::none()
         }
     }
 
+    /// notify the listeners and rehighlight the lines after the content is changed
+    fn content_changed_effects(&mut self) -> Effects<Msg, XMSG> {
+        let xmsgs = self.base_editor.emit_on_change_listeners();
+        let mut all_effects = vec![Effects::new([], xmsgs)];
+        if self.options.use_syntax_highlighter {
+            self.rehighlight_visible_lines();
+            let effects = self.rehighlight_non_visible_lines_in_background();
+            all_effects.push(effects);
+        }
+        if let Some(host_element) = self.host_element.as_ref() {
+            host_element
+                .set_attribute("content", &self.get_content())
+                .expect("set attr content");
+            host_element
+                .dispatch_event(&InputEvent::create_web_event_composed())
+                .expect("dispatch event");
+        }
+        Effects::batch(all_effects)
+    }
+
     fn highlight_line(line: &str, text_highlighter: &mut TextHighlighter) -> Vec<(Style, Vec<Ch>)> {
         let h_ranges = text_highlighter
             .highlight_line(line)
@@ -1002,31 +1758,52 @@ where
         self.base_editor.set_selection(start, end);
     }
 
-    pub fn copy_selected_text_to_clipboard(&self) -> bool {
+    /// copy the selected text into the clipboard of the base editor
+    pub fn copy_selected_text_to_clipboard(&mut self) -> bool {
         log::warn!("Copying text to clipboard..");
-        #[cfg(web_sys_unstable_apis)]
-        if let Some(clipboard) = window().navigator().clipboard() {
-            if let Some(selected_text) = self.selected_text() {
-                log::info!("selected text: {selected_text}");
-                let fut = crate::wasm_bindgen_futures::JsFuture::from(clipboard.write_text(&selected_text));
-                sauron::dom::spawn_local(async move {
-                    fut.await.expect("must not error");
-                });
-                return true;
-            } else {
-                log::warn!("No selected text..")
-            }
-        } else {
-            log::error!("Clipboard is not supported");
-        }
-        false
+        self.base_editor.process_command(Command::Copy)
     }
 
+    /// cut the selected text into the clipboard of the base editor
     pub fn cut_selected_text_to_clipboard(&mut self) -> bool {
         log::warn!("Cutting text to clipboard");
-        let ret = self.copy_selected_text_to_clipboard();
-        self.cut_selected_text();
-        ret
+        self.base_editor.process_command(Command::Cut)
+    }
+
+    /// read the browser clipboard asynchronously and paste the text with `Msg::PasteText`,
+    /// the content of the editor clipboard is pasted when the browser clipboard can not be read.
+    /// Both the paste key and the context menu paste through here
+    fn paste_from_clipboard(&mut self) -> Effects<Msg, XMSG> {
+        #[cfg(web_sys_unstable_apis)]
+        if let Some(clipboard) = window().navigator().clipboard() {
+            let fut = crate::wasm_bindgen_futures::JsFuture::from(clipboard.read_text());
+            return Effects::with_local_async([async move {
+                match fut.await {
+                    Ok(text) => text.as_string().map(Msg::PasteText).unwrap_or(Msg::NoOp),
+                    Err(e) => {
+                        log::error!("unable to read the clipboard: {e:?}");
+                        Msg::NoOp
+                    }
+                }
+            }]);
+        }
+        self.process_calls_with_effects([Call::Command(Command::Paste)])
+    }
+
+    /// the context menu actions which don't apply to the current state of the editor
+    fn disabled_menu_actions(&self) -> Vec<MenuAction> {
+        let has_selection = self.base_editor.has_selection();
+        [
+            (MenuAction::Undo, self.base_editor.can_undo()),
+            (MenuAction::Redo, self.base_editor.can_redo()),
+            (MenuAction::Cut, has_selection),
+            (MenuAction::Copy, has_selection),
+            (MenuAction::Delete, has_selection),
+        ]
+        .into_iter()
+        .filter(|(_action, is_enabled)| !is_enabled)
+        .map(|(action, _)| action)
+        .collect()
     }
 
     /// calculate the bounding rect of the base_editor using a DOM call [getBoundingClientRect](https://developer.mozilla.org/en-US/docs/Web/API/Element/getBoundingClientRect)
@@ -1126,9 +1903,26 @@ where
     }
 
     /// how wide the numberline based on the character lengths of the number
+    /// including the column of the fold markers
     fn numberline_wide_with_padding(&self) -> usize {
+        let fold_marker_wide = if self.options.show_fold_markers { 1 } else { 0 };
+        let gutter_icon_wide = self.gutter_icon_wide();
         if self.options.show_line_numbers {
-            self.base_editor.total_lines().to_string().len() + self.numberline_padding_wide()
+            self.base_editor.total_lines().to_string().len()
+                + self.numberline_padding_wide()
+                + fold_marker_wide
+                + gutter_icon_wide
+        } else {
+            fold_marker_wide + gutter_icon_wide
+        }
+    }
+
+    /// the column of the gutter icons is only shown when there is a gutter icon or a diagnostic
+    fn gutter_icon_wide(&self) -> usize {
+        let has_icons = self.base_editor.decorations().has_gutter_icons()
+            || !self.base_editor.diagnostics().is_empty();
+        if self.options.show_gutter_icons && has_icons {
+            1
         } else {
             0
         }
@@ -1152,10 +1946,21 @@ where
         assert!(ch_width > 0.);
         assert!(ch_height > 0.);
         let col = rel_x / ch_width - numberline_wide_with_padding;
-        let line = rel_y / ch_height;
-        let x = col.floor() as i32;
-        let y = line.floor() as i32;
-        Point2::new(x, y)
+        let row = rel_y / ch_height;
+        let col = col.floor() as i32;
+        let row = row.floor() as i32;
+        // folded lines are not displayed and long lines can be wrapped into multiple rows,
+        // so the visual column and row is mapped back to the actual location
+        if row >= 0 {
+            let loc = self
+                .base_editor
+                .visual_to_position(Point2::new(col.max(0) as usize, row as usize));
+            // negative columns are in the gutter
+            let x = if col >= 0 { loc.x as i32 } else { col };
+            Point2::new(x, loc.y as i32)
+        } else {
+            Point2::new(col, row)
+        }
     }
 
     /// convert screen coordinate to grid coordinate
@@ -1167,10 +1972,15 @@ where
 
     /// convert current cursor position to client coordinate relative to the editor div
     pub fn cursor_to_client(&self) -> Point2<f32> {
-        let cursor = self.base_editor.get_position();
+        self.location_to_client(self.base_editor.get_position())
+    }
+
+    /// convert a location in the text to client coordinate relative to the editor div
+    pub fn location_to_client(&self, location: Point2<usize>) -> Point2<f32> {
+        let visual = self.base_editor.position_to_visual(location);
         Point2::new(
-            (cursor.x + self.numberline_wide_with_padding()) as f32 * self.ch_width(),
-            cursor.y as f32 * self.ch_height(),
+            (visual.x + self.numberline_wide_with_padding()) as f32 * self.ch_width(),
+            visual.y as f32 * self.ch_height(),
         )
     }
 
@@ -1214,7 +2024,27 @@ where
                 },
             ],
             [
+                match self.options.keymap_profile {
+                    KeymapProfile::Standard => text!(""),
+                    KeymapProfile::Vim => {
+                        text!(" -- {} -- {} ", self.vim.mode(), self.vim.status())
+                    }
+                    KeymapProfile::Emacs => text!(" {} ", self.emacs.status()),
+                    KeymapProfile::Kakoune => {
+                        text!(" -- {} -- {} ", self.kakoune.mode(), self.kakoune.status())
+                    }
+                },
                 text!(" |> line: {}, col: {} ", cursor.y + 1, cursor.x + 1),
+                if self.base_editor.diagnostics().is_empty() {
+                    text!("")
+                } else {
+                    let diagnostics = self.base_editor.diagnostics();
+                    text!(
+                        " |> errors: {}, warnings: {}",
+                        diagnostics.count(Severity::Error),
+                        diagnostics.count(Severity::Warning)
+                    )
+                },
                 text!(" |> version:{}", env!("CARGO_PKG_VERSION")),
                 text!(" |> lines: {}", self.base_editor.total_lines()),
                 if let Some(visible_lines) = self.max_visible_lines() {
@@ -1252,11 +2082,14 @@ where
 
     fn view_line_number<MSG>(&self, line_number: usize) -> Node<MSG> {
         let class_ns = |class_names| class_namespaced(COMPONENT_NAME, class_names);
+        let has_mark = self.base_editor.marks().has_mark_on_line(line_number - 1);
         view_if(
             self.options.show_line_numbers,
             span(
                 [
                     class_ns("number"),
+                    classes_flag_namespaced(COMPONENT_NAME, [("marked", has_mark)]),
+                    self.line_change_classes(line_number - 1, true),
                     style! {
                         background_color: self.gutter_background().to_css(),
                         color: self.gutter_foreground().to_css(),
@@ -1270,6 +2103,203 @@ where
         )
     }
 
+    /// the classes of the line number which mark the change of the line from the base text,
+    /// the deleted lines are marked only in the first row of the line
+    fn line_change_classes<MSG>(&self, line_index: usize, is_first_row: bool) -> Attribute<MSG> {
+        let line_changes = self.base_editor.line_changes();
+        let change = line_changes.and_then(|line_changes| line_changes.line_change(line_index));
+        let is_deleted = |deleted: fn(&LineChanges, usize) -> bool| {
+            is_first_row
+                && line_changes.is_some_and(|line_changes| deleted(line_changes, line_index))
+        };
+        let is_deleted_before = is_deleted(LineChanges::is_deleted_before);
+        let is_deleted_after = is_deleted(LineChanges::is_deleted_after);
+        classes_flag_namespaced(
+            COMPONENT_NAME,
+            [
+                ("change_added", change == Some(LineChange::Added)),
+                ("change_modified", change == Some(LineChange::Modified)),
+                ("change_deleted_before", is_deleted_before),
+                ("change_deleted_after", is_deleted_after),
+            ],
+        )
+    }
+
+    /// the marker in the gutter for the lines where a fold range starts
+    fn view_fold_marker<MSG>(&self, line_index: usize) -> Node<MSG> {
+        let class_ns = |class_names| class_namespaced(COMPONENT_NAME, class_names);
+        let folding = self.base_editor.folding();
+        let marker = if folding.range_starting_at(line_index).is_none() {
+            " "
+        } else if folding.is_folded(line_index) {
+            "▸"
+        } else {
+            "▾"
+        };
+        view_if(
+            self.options.show_fold_markers,
+            span(
+                [
+                    class_ns("fold_marker"),
+                    style! {
+                        background_color: self.gutter_background().to_css(),
+                        color: self.gutter_foreground().to_css(),
+                        width: px(self.ch_width()),
+                        height: px(self.ch_height()),
+                    },
+                ],
+                [text(marker)],
+            ),
+        )
+    }
+
+    /// the last gutter icon added to this line, ie: a breakpoint,
+    /// otherwise the severity of the diagnostics starting on this line
+    fn view_gutter_icon<MSG>(&self, line_index: usize) -> Node<MSG> {
+        let class_ns = |class_names| class_namespaced(COMPONENT_NAME, class_names);
+        let diagnostics = self.base_editor.diagnostics();
+        let icon = self
+            .base_editor
+            .decorations()
+            .gutter_icons(line_index)
+            .last()
+            .map(|(icon, decoration)| {
+                let title = decoration.title.clone().unwrap_or_default();
+                (icon, decoration.class.as_str(), title)
+            })
+            .or_else(|| {
+                let severity = diagnostics.severity_on_line(line_index)?;
+                let icon = match severity {
+                    Severity::Error => "●",
+                    Severity::Warning => "▲",
+                    Severity::Info | Severity::Hint => "•",
+                };
+                let title = diagnostics
+                    .all()
+                    .into_iter()
+                    .filter(|diagnostic| diagnostic.start.y == line_index)
+                    .map(|diagnostic| diagnostic.label())
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                Some((icon, severity.name(), title))
+            });
+        let (class_name, title) = match &icon {
+            Some((_, class_name, title)) => (format!("gutter_{class_name}"), title.clone()),
+            None => (String::new(), String::new()),
+        };
+        view_if(
+            self.gutter_icon_wide() > 0,
+            span(
+                [
+                    class_ns("gutter_icon"),
+                    classes_flag_namespaced(COMPONENT_NAME, [(class_name, icon.is_some())]),
+                    sauron::html::attributes::attr("title", title),
+                    style! {
+                        background_color: self.gutter_background().to_css(),
+                        color: self.gutter_foreground().to_css(),
+                        width: px(self.ch_width()),
+                        height: px(self.ch_height()),
+                    },
+                ],
+                [text(icon.map(|(icon, _, _)| icon).unwrap_or(" "))],
+            ),
+        )
+    }
+
+    /// the classes of the line decorations and the highlight of the line gone to
+    fn line_classes<MSG>(&self, line_index: usize) -> Attribute<MSG> {
+        let decorations = self.base_editor.decorations().line_decorations(line_index);
+        classes_flag_namespaced(
+            COMPONENT_NAME,
+            [("flash".to_string(), self.flash_line == Some(line_index))]
+                .into_iter()
+                .chain(decorations.map(|decoration| (format!("line_{}", decoration.class), true)))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// wrap the node of the text at column `x` with the classes of the range decorations covering it
+    fn view_range_decorations<MSG>(
+        &self,
+        node: Node<MSG>,
+        x: usize,
+        decorations: &[(usize, usize, &Decoration)],
+    ) -> Node<MSG> {
+        let covering: Vec<&Decoration> = decorations
+            .iter()
+            .filter(|(start, end, _)| *start <= x && x < *end)
+            .map(|(_, _, decoration)| *decoration)
+            .collect();
+        if covering.is_empty() {
+            return node;
+        }
+        let title = covering
+            .iter()
+            .filter_map(|decoration| decoration.title.clone())
+            .collect::<Vec<_>>()
+            .join("\n");
+        span(
+            [
+                classes_flag_namespaced(
+                    COMPONENT_NAME,
+                    covering
+                        .iter()
+                        .map(|decoration| (format!("range_{}", decoration.class), true))
+                        .collect::<Vec<_>>(),
+                ),
+                sauron::html::attributes::attr("title", title),
+            ],
+            [node],
+        )
+    }
+
+    /// the line number and fold marker are only displayed in the first row of a wrapped line,
+    /// the rest of the rows only have a blank gutter
+    fn view_gutter<MSG>(&self, line_index: usize, is_first_row: bool) -> Vec<Node<MSG>> {
+        let class_ns = |class_names| class_namespaced(COMPONENT_NAME, class_names);
+        if is_first_row {
+            vec![
+                self.view_gutter_icon(line_index),
+                self.view_line_number(line_index + 1),
+                self.view_fold_marker(line_index),
+            ]
+        } else {
+            let gutter_wide = self.numberline_wide_with_padding();
+            vec![view_if(
+                gutter_wide > 0,
+                span(
+                    [
+                        class_ns("number"),
+                        self.line_change_classes(line_index, false),
+                        style! {
+                            background_color: self.gutter_background().to_css(),
+                            width: px(self.ch_width() * gutter_wide as f32),
+                            height: px(self.ch_height()),
+                        },
+                    ],
+                    [text(" ")],
+                ),
+            )]
+        }
+    }
+
+    /// the column ranges of the visual rows of this line
+    fn row_ranges(&self, line_index: usize) -> Vec<(usize, usize)> {
+        let line_width = self.base_editor.text_buffer().line_width(line_index);
+        self.base_editor
+            .soft_wrap()
+            .row_ranges(line_index, line_width)
+    }
+
+    /// indicate that the lines after this line are folded
+    fn view_fold_ellipsis<MSG>(&self, line_index: usize) -> Node<MSG> {
+        let class_ns = |class_names| class_namespaced(COMPONENT_NAME, class_names);
+        view_if(
+            self.base_editor.folding().is_folded(line_index),
+            span([class_ns("fold_ellipsis")], [text(" ⋯ ")]),
+        )
+    }
+
     /// calculate the maximum number of visible lines
     fn max_visible_lines(&self) -> Option<usize> {
         if let Some((start, end)) = self.bounding_rect() {
@@ -1294,123 +2324,182 @@ where
     fn view_highlighted_line<MSG>(
         &self,
         line_index: usize,
+        row_start: usize,
         line: &[(Style, Vec<Ch>)],
     ) -> Vec<Node<MSG>> {
-        let mut range_x: usize = 0;
+        // the placeholder and the mirrors of the current tab stop of the snippet
+        let snippet_placeholders: Vec<Decoration> = self
+            .base_editor
+            .snippet_session()
+            .map(|session| session.current_stop().ranges.clone())
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|(start, end)| start != end)
+            .map(|(start, end)| Decoration::range(start, end, "snippet_placeholder"))
+            .collect();
+        // the range decorations are drawn over the syntax styles
+        let decorations: Vec<(usize, usize, &Decoration)> = self
+            .base_editor
+            .decorations()
+            .range_decorations(line_index)
+            .chain(snippet_placeholders.iter())
+            .filter_map(|decoration| {
+                let (start, end) = decoration.columns_in_line(line_index)?;
+                Some((start, end.unwrap_or(usize::MAX), decoration))
+            })
+            // the diagnostics are underlined like the range decorations of their severity
+            .chain(
+                self.base_editor
+                    .diagnostics()
+                    .decorations()
+                    .range_decorations(line_index)
+                    .filter_map(|decoration| {
+                        let (start, end) = decoration.columns_in_line(line_index)?;
+                        // the empty ranges are underlined on one character
+                        let end = end.map_or(usize::MAX, |end| end.max(start + 1));
+                        Some((start, end, decoration))
+                    }),
+            )
+            .collect();
+        let columns: Vec<usize> = decorations
+            .iter()
+            .flat_map(|(start, end, _)| [*start, *end])
+            .collect();
+        let mut range_x: usize = row_start;
         line.iter()
-            .map(|(style, range)| {
-                let range_str = String::from_iter(range.iter().map(|ch| ch.ch));
-
-                let range_start = Point2::new(range_x, line_index);
+            .flat_map(|(style, range)| {
+                let range_start_x = range_x;
                 range_x += range.iter().map(|ch| ch.width).sum::<usize>();
-                let range_end = Point2::new(range_x, line_index);
-
                 let foreground = util::to_rgba(style.foreground).to_css();
-
-                let selection_splits = match self.base_editor.as_ref().selection_reorder_casted() {
-                    Some((start, end)) => {
-                        // selection end points is only on the same line
-                        let selection_in_same_line = start.y == end.y;
-                        // this line is on the first line of selection
-                        let selection_start_within_first_line = line_index == start.y;
-                        // this line is on the last line of selection
-                        let selection_end_within_last_line = line_index == end.y;
-                        // this line is in between the selection end points
-                        let line_within_selection = line_index > start.y && line_index < end.y;
-                        let line_outside_selection = line_index < start.y || line_index > end.y;
-
-                        // the start selection is within this range  location
-                        let selection_start_within_range_start = start.x >= range_start.x;
-                        // the end selection is within this range location
-                        let selection_end_within_range_end = end.x <= range_end.x;
-                        // both selection endpoints is inside this range
-                        let selection_within_range =
-                            start.x >= range_start.x && end.x <= range_end.x;
-
-                        // range is in the right side of selection start
-                        let range_in_right_of_selection_start =
-                            range_start.x >= start.x && range_end.x >= start.x;
-                        let range_in_left_of_selection_end =
-                            range_start.x <= end.x && range_end.x <= end.x;
-                        let range_in_right_of_selection_end =
-                            range_start.x > end.x && range_end.x > end.x;
-
-                        let text_buffer = TextBuffer::from_ch(&[range]);
-
-                        if line_within_selection {
-                            SelectionSplits::SelectAll(range_str)
-                        } else if line_outside_selection {
-                            SelectionSplits::NotSelected(range_str)
-                        } else if selection_in_same_line {
-                            let range_within_selection =
-                                range_start.x >= start.x && range_end.x <= end.x;
-                            if range_within_selection {
-                                SelectionSplits::SelectAll(range_str)
-                            } else if selection_within_range {
-                                // the first is plain
-                                // the second is selected
-                                // the third is plain
-                                let break1 = Point2::new(start.x - range_start.x, 0);
-                                let break1 = text_buffer.clamp_position(break1);
-                                let break2 = Point2::new(end.x - range_start.x, 0);
-                                let break2 = text_buffer.clamp_position(break2);
-                                let (first, second, third) =
-                                    text_buffer.split_line_at_2_points(break1, break2);
-                                SelectionSplits::SelectMiddle(first, second, third)
-                            } else if selection_start_within_range_start {
-                                let break1 = Point2::new(start.x - range_start.x, 0);
-                                let break1 = text_buffer.clamp_position(break1);
-                                let (first, second) = text_buffer.split_line_at_point(break1);
-                                SelectionSplits::SelectRight(first, second)
-                            } else if range_in_right_of_selection_end {
-                                SelectionSplits::NotSelected(range_str)
-                            } else if selection_end_within_range_end {
-                                // the first is selected
-                                // the second is plain
-                                let break1 = Point2::new(end.x - range_start.x, 0);
-                                let break1 = text_buffer.clamp_position(break1);
-                                let (first, second) = text_buffer.split_line_at_point(break1);
-                                SelectionSplits::SelectLeft(first, second)
-                            } else {
-                                SelectionSplits::NotSelected(range_str)
-                            }
-                        } else if selection_start_within_first_line {
-                            if range_in_right_of_selection_start {
-                                SelectionSplits::SelectAll(range_str)
-                            } else if selection_start_within_range_start {
-                                let break1 = Point2::new(start.x - range_start.x, 0);
-                                let break1 = text_buffer.clamp_position(break1);
-                                let (first, second) = text_buffer.split_line_at_point(break1);
-                                SelectionSplits::SelectRight(first, second)
-                            } else {
-                                SelectionSplits::NotSelected(range_str)
-                            }
-                        } else if selection_end_within_last_line {
-                            if range_in_left_of_selection_end {
-                                SelectionSplits::SelectAll(range_str)
-                            } else if range_in_right_of_selection_end {
-                                SelectionSplits::NotSelected(range_str)
-                            } else if selection_end_within_range_end {
-                                // the first is selected
-                                // the second is plain
-                                let break1 = Point2::new(end.x - range_start.x, 0);
-                                let break1 = text_buffer.clamp_position(break1);
-                                let (first, second) = text_buffer.split_line_at_point(break1);
-                                SelectionSplits::SelectLeft(first, second)
-                            } else {
-                                SelectionSplits::NotSelected(range_str)
-                            }
-                        } else {
-                            SelectionSplits::NotSelected(range_str)
-                        }
-                    }
-                    None => SelectionSplits::NotSelected(range_str),
-                };
-                selection_splits.view_with_style(style! { color: foreground })
+                split_at_columns(range, range_start_x, &columns)
+                    .into_iter()
+                    .map(|(x, chars)| {
+                        let node = self
+                            .range_selection_splits(line_index, x, &chars)
+                            .view_with_style(style! { color: foreground.clone() });
+                        self.view_range_decorations(node, x, &decorations)
+                    })
+                    .collect::<Vec<_>>()
             })
             .collect()
     }
 
+    /// split this range of characters in the line which starts at `range_start_x`
+    /// into the selected and non selected parts
+    fn range_selection_splits(
+        &self,
+        line_index: usize,
+        range_start_x: usize,
+        range: &[Ch],
+    ) -> SelectionSplits {
+        let range_str = String::from_iter(range.iter().map(|ch| ch.ch));
+        let range_start = Point2::new(range_start_x, line_index);
+        let range_end = Point2::new(
+            range_start_x + range.iter().map(|ch| ch.width).sum::<usize>(),
+            line_index,
+        );
+
+        match self.base_editor.as_ref().selection_reorder_casted() {
+            Some((start, end)) => {
+                // selection end points is only on the same line
+                let selection_in_same_line = start.y == end.y;
+                // this line is on the first line of selection
+                let selection_start_within_first_line = line_index == start.y;
+                // this line is on the last line of selection
+                let selection_end_within_last_line = line_index == end.y;
+                // this line is in between the selection end points
+                let line_within_selection = line_index > start.y && line_index < end.y;
+                let line_outside_selection = line_index < start.y || line_index > end.y;
+
+                // the start selection is within this range  location
+                let selection_start_within_range_start = start.x >= range_start.x;
+                // the end selection is within this range location
+                let selection_end_within_range_end = end.x <= range_end.x;
+                // both selection endpoints is inside this range
+                let selection_within_range =
+                    start.x >= range_start.x && end.x <= range_end.x;
+
+                // range is in the right side of selection start
+                let range_in_right_of_selection_start =
+                    range_start.x >= start.x && range_end.x >= start.x;
+                let range_in_left_of_selection_end =
+                    range_start.x <= end.x && range_end.x <= end.x;
+                let range_in_right_of_selection_end =
+                    range_start.x > end.x && range_end.x > end.x;
+
+                let text_buffer = TextBuffer::from_ch(&[range]);
+
+                if line_within_selection {
+                    SelectionSplits::SelectAll(range_str)
+                } else if line_outside_selection {
+                    SelectionSplits::NotSelected(range_str)
+                } else if selection_in_same_line {
+                    let range_within_selection =
+                        range_start.x >= start.x && range_end.x <= end.x;
+                    if range_within_selection {
+                        SelectionSplits::SelectAll(range_str)
+                    } else if selection_within_range {
+                        // the first is plain
+                        // the second is selected
+                        // the third is plain
+                        let break1 = Point2::new(start.x - range_start.x, 0);
+                        let break1 = text_buffer.clamp_position(break1);
+                        let break2 = Point2::new(end.x - range_start.x, 0);
+                        let break2 = text_buffer.clamp_position(break2);
+                        let (first, second, third) =
+                            text_buffer.split_line_at_2_points(break1, break2);
+                        SelectionSplits::SelectMiddle(first, second, third)
+                    } else if selection_start_within_range_start {
+                        let break1 = Point2::new(start.x - range_start.x, 0);
+                        let break1 = text_buffer.clamp_position(break1);
+                        let (first, second) = text_buffer.split_line_at_point(break1);
+                        SelectionSplits::SelectRight(first, second)
+                    } else if range_in_right_of_selection_end {
+                        SelectionSplits::NotSelected(range_str)
+                    } else if selection_end_within_range_end {
+                        // the first is selected
+                        // the second is plain
+                        let break1 = Point2::new(end.x - range_start.x, 0);
+                        let break1 = text_buffer.clamp_position(break1);
+                        let (first, second) = text_buffer.split_line_at_point(break1);
+                        SelectionSplits::SelectLeft(first, second)
+                    } else {
+                        SelectionSplits::NotSelected(range_str)
+                    }
+                } else if selection_start_within_first_line {
+                    if range_in_right_of_selection_start {
+                        SelectionSplits::SelectAll(range_str)
+                    } else if selection_start_within_range_start {
+                        let break1 = Point2::new(start.x - range_start.x, 0);
+                        let break1 = text_buffer.clamp_position(break1);
+                        let (first, second) = text_buffer.split_line_at_point(break1);
+                        SelectionSplits::SelectRight(first, second)
+                    } else {
+                        SelectionSplits::NotSelected(range_str)
+                    }
+                } else if selection_end_within_last_line {
+                    if range_in_left_of_selection_end {
+                        SelectionSplits::SelectAll(range_str)
+                    } else if range_in_right_of_selection_end {
+                        SelectionSplits::NotSelected(range_str)
+                    } else if selection_end_within_range_end {
+                        // the first is selected
+                        // the second is plain
+                        let break1 = Point2::new(end.x - range_start.x, 0);
+                        let break1 = text_buffer.clamp_position(break1);
+                        let (first, second) = text_buffer.split_line_at_point(break1);
+                        SelectionSplits::SelectLeft(first, second)
+                    } else {
+                        SelectionSplits::NotSelected(range_str)
+                    }
+                } else {
+                    SelectionSplits::NotSelected(range_str)
+                }
+            }
+            None => SelectionSplits::NotSelected(range_str),
+        }
+    }
+
     // highlighted view
     pub fn view_highlighted_lines<MSG>(&self) -> Node<MSG> {
         let class_ns = |class_names| class_namespaced(COMPONENT_NAME, class_names);
@@ -1422,24 +2511,42 @@ where
         ];
 
         let highlighted_lines = self.highlighted_lines.borrow();
+        let folding = self.base_editor.folding();
         let rendered_lines = highlighted_lines
             .iter()
             .enumerate()
-            .map(|(line_index, line)| {
-                div(
-                    [
-                        class_ns("line"),
-                        // needed to put the height here, since for some reason it add 1px to the
-                        // parent div, not a margin, not border,
-                        style! {height: px(self.ch_height())},
-                    ],
-                    {
-                        [self.view_line_number(line_index + 1)]
-                            .into_iter()
-                            .chain(self.view_highlighted_line(line_index, line))
-                            .collect::<Vec<_>>()
-                    },
-                )
+            .filter(|(line_index, _)| !folding.is_hidden(*line_index))
+            .flat_map(|(line_index, line)| {
+                let row_ranges = self.row_ranges(line_index);
+                let last_row = row_ranges.len() - 1;
+                row_ranges
+                    .into_iter()
+                    .enumerate()
+                    .map(|(row, (start, end))| {
+                        let row_nodes = if last_row == 0 {
+                            self.view_highlighted_line(line_index, 0, line)
+                        } else {
+                            let row_line = slice_highlighted_line(line, start, end);
+                            self.view_highlighted_line(line_index, start, &row_line)
+                        };
+                        div(
+                            [
+                                class_ns("line"),
+                                self.line_classes(line_index),
+                                // needed to put the height here, since for some reason it add 1px to the
+                                // parent div, not a margin, not border,
+                                style! {height: px(self.ch_height())},
+                            ],
+                            self.view_gutter(line_index, row == 0)
+                                .into_iter()
+                                .chain(row_nodes)
+                                .chain(
+                                    (row == last_row).then(|| self.view_fold_ellipsis(line_index)),
+                                )
+                                .collect::<Vec<_>>(),
+                        )
+                    })
+                    .collect::<Vec<_>>()
             });
 
             // using <pre><code> works well when copying in chrome
@@ -1554,31 +2661,66 @@ where
         let text_edit = &self.base_editor.as_ref();
 
         let code_attributes = [class_ns("code")];
+        let folding = self.base_editor.folding();
         let rendered_lines = text_edit
             .lines()
             .into_iter()
             .enumerate()
-            .map(|(line_index, line)| {
-                let line_number = line_index + 1;
-                div(
-                    [
-                        class_ns("line"),
-                        // Important! This is needed to render blank lines with same height as the
-                        // non blank ones
-                        style! {height: px(self.ch_height())},
-                    ],
-                    [
-                        self.view_line_number(line_number),
-                        match self.options.base_options.selection_mode {
-                            SelectionMode::Linear => {
-                                self.view_line_with_linear_selection(line_index, line)
-                            }
-                            SelectionMode::Block => {
-                                self.view_line_with_block_selection(line_index, line)
-                            }
-                        },
-                    ],
-                )
+            .filter(|(line_index, _)| !folding.is_hidden(*line_index))
+            .flat_map(|(line_index, line)| {
+                let row_ranges = self.row_ranges(line_index);
+                let last_row = row_ranges.len() - 1;
+                if last_row == 0 {
+                    return vec![div(
+                        [
+                            class_ns("line"),
+                            self.line_classes(line_index),
+                            // Important! This is needed to render blank lines with same height as the
+                            // non blank ones
+                            style! {height: px(self.ch_height())},
+                        ],
+                        self.view_gutter(line_index, true)
+                            .into_iter()
+                            .chain([
+                                match self.options.base_options.selection_mode {
+                                    SelectionMode::Linear => {
+                                        self.view_line_with_linear_selection(line_index, line)
+                                    }
+                                    SelectionMode::Block => {
+                                        self.view_line_with_block_selection(line_index, line)
+                                    }
+                                },
+                                self.view_fold_ellipsis(line_index),
+                            ])
+                            .collect::<Vec<_>>(),
+                    )];
+                }
+                // the rows of a wrapped line are displayed with the same selection splits
+                // as the highlighted view
+                let chars = &self.base_editor.text_buffer().chars()[line_index];
+                row_ranges
+                    .into_iter()
+                    .enumerate()
+                    .map(|(row, (start, end))| {
+                        let row_chars = chars_within_columns(chars, start, end);
+                        div(
+                            [
+                                class_ns("line"),
+                                self.line_classes(line_index),
+                                style! {height: px(self.ch_height())},
+                            ],
+                            self.view_gutter(line_index, row == 0)
+                                .into_iter()
+                                .chain([self
+                                    .range_selection_splits(line_index, start, &row_chars)
+                                    .view()])
+                                .chain(
+                                    (row == last_row).then(|| self.view_fold_ellipsis(line_index)),
+                                )
+                                .collect::<Vec<_>>(),
+                        )
+                    })
+                    .collect::<Vec<_>>()
             });
 
             // using <pre><code> works well when copying in chrome
@@ -1591,6 +2733,70 @@ where
     }
 }
 
+/// the characters of the line which are within the columns `start..end`
+/// the letter or digit of the physical key, ie: `KeyF` is `f`.
+/// Alt changes the character of the key on mac, ie: option+f types `ƒ`
+fn key_of_code(code: &str, is_shift: bool) -> Option<Key> {
+    let name = code
+        .strip_prefix("Key")
+        .or_else(|| code.strip_prefix("Digit"))?;
+    let mut chars = name.chars();
+    match (chars.next(), chars.next()) {
+        (Some(ch), None) if is_shift => Some(Key::Char(ch.to_ascii_uppercase())),
+        (Some(ch), None) => Some(Key::Char(ch.to_ascii_lowercase())),
+        _ => None,
+    }
+}
+
+fn chars_within_columns(chars: &[Ch], start: usize, end: usize) -> Vec<Ch> {
+    let mut column = 0;
+    chars
+        .iter()
+        .filter(|ch| {
+            let x = column;
+            column += ch.width;
+            x >= start && x < end
+        })
+        .copied()
+        .collect()
+}
+
+/// split the characters which start at the column `start_x` at these columns,
+/// returning the column where each part starts
+fn split_at_columns(chars: &[Ch], start_x: usize, columns: &[usize]) -> Vec<(usize, Vec<Ch>)> {
+    let mut parts: Vec<(usize, Vec<Ch>)> = vec![];
+    let mut x = start_x;
+    for ch in chars {
+        match parts.last_mut() {
+            Some((_, part)) if !columns.contains(&x) => part.push(*ch),
+            _ => parts.push((x, vec![*ch])),
+        }
+        x += ch.width;
+    }
+    parts
+}
+
+/// the highlighted ranges of the line which are within the columns `start..end`
+fn slice_highlighted_line(
+    line: &[(Style, Vec<Ch>)],
+    start: usize,
+    end: usize,
+) -> Vec<(Style, Vec<Ch>)> {
+    let mut range_x = 0;
+    line.iter()
+        .filter_map(|(style, range)| {
+            let range_start_x = range_x;
+            range_x += range.iter().map(|ch| ch.width).sum::<usize>();
+            let chars = chars_within_columns(
+                range,
+                start.saturating_sub(range_start_x),
+                end.saturating_sub(range_start_x),
+            );
+            (!chars.is_empty()).then_some((*style, chars))
+        })
+        .collect()
+}
+
 pub fn view_text_buffer<MSG>(text_buffer: &TextBuffer, options: &Options) -> Node<MSG> {
     let class_ns = |class_names| class_namespaced(COMPONENT_NAME, class_names);
 
@@ -0,0 +1,173 @@
+use super::{Msg, COMPONENT_NAME};
+use sauron::{html::attributes::*, html::events::*, html::units::*, html::*, *};
+use ultron_core::{Binding, CommandRegistry, Key, KeyEvent};
+
+/// The state of the command palette overlay, listing the commands which match the query
+#[derive(Default)]
+pub(super) struct CommandPalette {
+    query: String,
+    /// the index of the highlighted command in the matches
+    selected: usize,
+}
+
+/// what to do after a key is pressed while the command palette is shown
+pub(super) enum PaletteOutcome {
+    /// the query or the highlighted command has changed
+    Handled,
+    Close,
+    /// close the palette and run the command
+    Run(Binding),
+}
+
+impl CommandPalette {
+    pub(super) fn process_key(
+        &mut self,
+        key_event: KeyEvent,
+        registry: &CommandRegistry,
+    ) -> PaletteOutcome {
+        let matches = registry.search(&self.query);
+        match key_event.key {
+            Key::Escape => PaletteOutcome::Close,
+            Key::Enter => match matches.get(self.selected) {
+                Some((entry, _)) => PaletteOutcome::Run(entry.binding.clone()),
+                None => PaletteOutcome::Close,
+            },
+            Key::Up => {
+                self.selected = self
+                    .selected
+                    .checked_sub(1)
+                    .unwrap_or(matches.len().saturating_sub(1));
+                PaletteOutcome::Handled
+            }
+            Key::Down => {
+                self.selected = if self.selected + 1 < matches.len() {
+                    self.selected + 1
+                } else {
+                    0
+                };
+                PaletteOutcome::Handled
+            }
+            Key::Backspace => {
+                self.query.pop();
+                self.selected = 0;
+                PaletteOutcome::Handled
+            }
+            _ => {
+                if let Some(ch) = key_event.typed_char() {
+                    self.query.push(ch);
+                    self.selected = 0;
+                }
+                PaletteOutcome::Handled
+            }
+        }
+    }
+
+    /// the matching commands with their key bindings, the matched characters are highlighted
+    pub(super) fn view(
+        &self,
+        registry: &CommandRegistry,
+        keys_for: impl Fn(&Binding) -> Option<String>,
+    ) -> Node<Msg> {
+        let class_ns = |class_names| class_namespaced(COMPONENT_NAME, class_names);
+        let matches = registry.search(&self.query);
+        div(
+            [class_ns("command_palette")],
+            [
+                div(
+                    [class_ns("command_palette_query")],
+                    [text!("> {}", self.query)],
+                ),
+                ul(
+                    [class_ns("command_palette_list")],
+                    matches.iter().enumerate().map(|(i, (entry, found))| {
+                        let id = entry.id.clone();
+                        li(
+                            [
+                                class_ns("command_palette_item"),
+                                classes_flag_namespaced(
+                                    COMPONENT_NAME,
+                                    [("command_palette_selected", i == self.selected)],
+                                ),
+                                on_click(move |me| {
+                                    me.stop_propagation();
+                                    Msg::RunPaletteCommand(id.clone())
+                                }),
+                            ],
+                            [
+                                span(
+                                    [],
+                                    entry.label().chars().enumerate().map(|(j, ch)| {
+                                        if found.indices.contains(&j) {
+                                            span([class_ns("command_palette_matched")], [text(ch)])
+                                        } else {
+                                            text(ch)
+                                        }
+                                    }),
+                                ),
+                                span(
+                                    [class_ns("command_palette_keys")],
+                                    [text(keys_for(&entry.binding).unwrap_or_default())],
+                                ),
+                            ],
+                        )
+                    }),
+                ),
+            ],
+        )
+    }
+
+    pub(super) fn stylesheet() -> Vec<String> {
+        vec![jss_ns_pretty! {COMPONENT_NAME,
+            ".command_palette": {
+                position: "absolute",
+                top: px(10),
+                left: percent(20),
+                width: percent(60),
+                z_index: 10,
+                background_color: "#eee",
+                border: format!("{} solid #ccc", px(1)),
+                box_shadow: "0 4px 12px rgba(0, 0, 0, 0.25)",
+                user_select: "none",
+                "-webkit-user-select": "none",
+            },
+
+            ".command_palette_query": {
+                padding: px([5, 8]),
+                border_bottom: format!("{} solid #ccc", px(1)),
+                white_space: "pre",
+            },
+
+            ".command_palette_list": {
+                list_style: "none",
+                margin: 0,
+                padding: 0,
+                max_height: px(300),
+                overflow_y: "auto",
+            },
+
+            ".command_palette_item": {
+                display: "flex",
+                justify_content: "space-between",
+                padding: px([3, 8]),
+                cursor: "default",
+            },
+
+            ".command_palette_item:hover": {
+                background_color: "#ddd",
+            },
+
+            ".command_palette_selected": {
+                background_color: "#cde",
+            },
+
+            ".command_palette_matched": {
+                font_weight: "bold",
+            },
+
+            ".command_palette_keys": {
+                opacity: 0.6,
+                padding_left: px(20),
+            },
+        }]
+    }
+}
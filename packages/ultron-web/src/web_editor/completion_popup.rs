@@ -0,0 +1,168 @@
+use super::{Msg, COMPONENT_NAME};
+use sauron::{html::attributes::*, html::events::*, html::units::*, html::*, *};
+use ultron_core::{filter_completions, CompletionItem, Key, KeyEvent, Point2};
+
+/// The state of the completion popup, listing the completions which match the typed word
+pub(super) struct CompletionPopup {
+    /// all the completions, before they are filtered
+    items: Vec<CompletionItem>,
+    matches: Vec<CompletionItem>,
+    /// the start of the word which is completed
+    start: Point2<usize>,
+    /// the index of the highlighted completion in the matches
+    selected: usize,
+}
+
+/// what to do after a key is pressed while the completion popup is shown
+pub(super) enum PopupOutcome {
+    Handled,
+    Close,
+    /// close the popup and insert the completion
+    Accept(CompletionItem),
+    /// the key is processed by the editor, ie: typing the word further
+    Pass,
+}
+
+impl CompletionPopup {
+    pub(super) fn new(items: Vec<CompletionItem>, start: Point2<usize>, typed: &str) -> Self {
+        let mut popup = Self {
+            items,
+            matches: vec![],
+            start,
+            selected: 0,
+        };
+        popup.filter(typed);
+        popup
+    }
+
+    pub(super) fn start(&self) -> Point2<usize> {
+        self.start
+    }
+
+    pub(super) fn filter(&mut self, typed: &str) {
+        self.matches = filter_completions(&self.items, typed);
+        self.selected = 0;
+    }
+
+    pub(super) fn is_empty(&self) -> bool {
+        self.matches.is_empty()
+    }
+
+    pub(super) fn get(&self, index: usize) -> Option<&CompletionItem> {
+        self.matches.get(index)
+    }
+
+    pub(super) fn process_key(&mut self, key_event: KeyEvent) -> PopupOutcome {
+        let is_plain = !key_event.modifiers.ctrl && !key_event.modifiers.alt;
+        match key_event.key {
+            Key::Escape => PopupOutcome::Close,
+            Key::Enter | Key::Tab if is_plain => match self.matches.get(self.selected) {
+                Some(item) => PopupOutcome::Accept(item.clone()),
+                None => PopupOutcome::Close,
+            },
+            Key::Up => {
+                self.selected = self
+                    .selected
+                    .checked_sub(1)
+                    .unwrap_or(self.matches.len().saturating_sub(1));
+                PopupOutcome::Handled
+            }
+            Key::Down => {
+                self.selected = if self.selected + 1 < self.matches.len() {
+                    self.selected + 1
+                } else {
+                    0
+                };
+                PopupOutcome::Handled
+            }
+            _ => PopupOutcome::Pass,
+        }
+    }
+
+    /// the list of the matching completions below the cursor,
+    /// `position` is the client location of the cursor
+    pub(super) fn view(&self, position: Point2<f32>, ch_height: f32) -> Node<Msg> {
+        let class_ns = |class_names| class_namespaced(COMPONENT_NAME, class_names);
+        ul(
+            [
+                class_ns("completion_popup"),
+                style! {
+                    top: px(position.y + ch_height),
+                    left: px(position.x),
+                },
+            ],
+            self.matches.iter().enumerate().map(|(i, item)| {
+                li(
+                    [
+                        class_ns("completion_item"),
+                        classes_flag_namespaced(
+                            COMPONENT_NAME,
+                            [("completion_selected", i == self.selected)],
+                        ),
+                        on_click(move |me| {
+                            me.stop_propagation();
+                            Msg::AcceptCompletion(i)
+                        }),
+                    ],
+                    [
+                        span(
+                            [class_ns(&format!("completion_{}", item.kind.name()))],
+                            [text(item.kind.name())],
+                        ),
+                        span([class_ns("completion_label")], [text(&item.label)]),
+                        span(
+                            [class_ns("completion_detail")],
+                            [text(item.detail.as_deref().unwrap_or_default())],
+                        ),
+                    ],
+                )
+            }),
+        )
+    }
+
+    pub(super) fn stylesheet() -> Vec<String> {
+        vec![jss_ns_pretty! {COMPONENT_NAME,
+            ".completion_popup": {
+                position: "absolute",
+                z_index: 10,
+                list_style: "none",
+                margin: 0,
+                padding: 0,
+                min_width: px(200),
+                max_height: px(200),
+                overflow_y: "auto",
+                background_color: "#eee",
+                border: format!("{} solid #ccc", px(1)),
+                box_shadow: "0 4px 12px rgba(0, 0, 0, 0.25)",
+                user_select: "none",
+                "-webkit-user-select": "none",
+            },
+
+            ".completion_item": {
+                display: "flex",
+                padding: px([1, 8]),
+                cursor: "default",
+                white_space: "pre",
+            },
+
+            ".completion_item:hover": {
+                background_color: "#ddd",
+            },
+
+            ".completion_selected": {
+                background_color: "#cde",
+            },
+
+            ".completion_item > span:first-child": {
+                width: px(70),
+                opacity: 0.6,
+                font_size: percent(80),
+            },
+
+            ".completion_detail": {
+                opacity: 0.6,
+                padding_left: px(20),
+            },
+        }]
+    }
+}
@@ -6,7 +6,7 @@ where
     XMSG: 'static,
 {
     fn observed_attributes() -> Vec<&'static str> {
-        vec!["value", "syntax", "theme"]
+        vec!["value", "syntax", "theme", "keymap"]
     }
 
     /// this is called when the attributes in the mount is changed
@@ -37,6 +37,12 @@ where
                     program.dispatch(Msg::ChangeTheme(new_value));
                 }
             }
+            "keymap" => {
+                if let Some(new_value) = new_value {
+                    log::info!("keymap profile is changed: {new_value}");
+                    program.dispatch(Msg::ChangeKeymapProfile(new_value));
+                }
+            }
             _ => (),
         }
     }
@@ -138,6 +144,11 @@ pub mod attributes {
     pub fn theme<MSG, V: Into<Value>>(value: V) -> Attribute<MSG> {
         attr("theme", value)
     }
+
+    /// the keymap profile: `standard`, `vim`, `emacs` or `kakoune`
+    pub fn keymap<MSG, V: Into<Value>>(value: V) -> Attribute<MSG> {
+        attr("keymap", value)
+    }
 }
 
 pub fn ultron_editor<MSG>(
@@ -0,0 +1,62 @@
+use super::COMPONENT_NAME;
+use sauron::{html::attributes::*, html::units::*, html::*, *};
+use ultron_core::{Diagnostics, Point2};
+
+/// The labels of the diagnostics under the mouse, shown while hovering over their range
+pub(super) struct DiagnosticPopup {
+    /// the hovered location
+    location: Point2<usize>,
+    labels: Vec<String>,
+}
+
+impl DiagnosticPopup {
+    /// the popup of the diagnostics at this location, if there are any
+    pub(super) fn at(diagnostics: &Diagnostics, location: Point2<usize>) -> Option<Self> {
+        let labels: Vec<String> = diagnostics
+            .at(location)
+            .map(|diagnostic| diagnostic.label())
+            .collect();
+        (!labels.is_empty()).then_some(Self { location, labels })
+    }
+
+    pub(super) fn location(&self) -> Point2<usize> {
+        self.location
+    }
+
+    pub(super) fn view<MSG>(&self, position: Point2<f32>, ch_height: f32) -> Node<MSG> {
+        let class_ns = |class_names| class_namespaced(COMPONENT_NAME, class_names);
+        div(
+            [
+                class_ns("diagnostic_popup"),
+                style! {
+                    top: px(position.y + ch_height),
+                    left: px(position.x),
+                },
+            ],
+            self.labels
+                .iter()
+                .map(|label| div([class_ns("diagnostic_label")], [text(label)])),
+        )
+    }
+
+    pub(super) fn stylesheet() -> Vec<String> {
+        vec![jss_ns_pretty! {COMPONENT_NAME,
+            ".diagnostic_popup": {
+                position: "absolute",
+                z_index: 10,
+                max_width: px(500),
+                background_color: "#eee",
+                border: format!("{} solid #ccc", px(1)),
+                box_shadow: "0 4px 12px rgba(0, 0, 0, 0.25)",
+                pointer_events: "none",
+                user_select: "none",
+                "-webkit-user-select": "none",
+            },
+
+            ".diagnostic_label": {
+                padding: px([1, 8]),
+                white_space: "pre-wrap",
+            },
+        }]
+    }
+}
@@ -0,0 +1,94 @@
+use super::COMPONENT_NAME;
+use sauron::{html::attributes::*, html::units::*, html::*, *};
+use ultron_core::{GoToTarget, Key, KeyEvent};
+
+/// The state of the go to line prompt, ie: `12`, `12:5`, `+3` or `-3`
+#[derive(Default)]
+pub(super) struct GoToLinePrompt {
+    input: String,
+    /// the input could not be parsed when enter was pressed
+    is_invalid: bool,
+}
+
+/// what to do after a key is pressed while the go to line prompt is shown
+pub(super) enum PromptOutcome {
+    Handled,
+    Close,
+    /// close the prompt and move the cursor to the target
+    GoTo(GoToTarget),
+}
+
+impl GoToLinePrompt {
+    pub(super) fn process_key(&mut self, key_event: KeyEvent) -> PromptOutcome {
+        match key_event.key {
+            Key::Escape => PromptOutcome::Close,
+            Key::Enter => match self.input.parse() {
+                Ok(target) => PromptOutcome::GoTo(target),
+                Err(_) => {
+                    self.is_invalid = true;
+                    PromptOutcome::Handled
+                }
+            },
+            Key::Backspace => {
+                self.input.pop();
+                self.is_invalid = false;
+                PromptOutcome::Handled
+            }
+            _ => {
+                if let Some(ch) = key_event.typed_char() {
+                    self.input.push(ch);
+                    self.is_invalid = false;
+                }
+                PromptOutcome::Handled
+            }
+        }
+    }
+
+    pub(super) fn view<MSG>(&self, cursor_line: usize, total_lines: usize) -> Node<MSG> {
+        let class_ns = |class_names| class_namespaced(COMPONENT_NAME, class_names);
+        div(
+            [
+                class_ns("go_to_line"),
+                classes_flag_namespaced(COMPONENT_NAME, [("go_to_line_invalid", self.is_invalid)]),
+            ],
+            [
+                div([], [text!("Go to line: {}", self.input)]),
+                div(
+                    [class_ns("go_to_line_hint")],
+                    [text!(
+                        "line {} of {}, type line, line:col, +N or -N",
+                        cursor_line + 1,
+                        total_lines
+                    )],
+                ),
+            ],
+        )
+    }
+
+    pub(super) fn stylesheet() -> Vec<String> {
+        vec![jss_ns_pretty! {COMPONENT_NAME,
+            ".go_to_line": {
+                position: "absolute",
+                top: px(10),
+                left: percent(30),
+                width: percent(40),
+                z_index: 10,
+                padding: px([5, 8]),
+                background_color: "#eee",
+                border: format!("{} solid #ccc", px(1)),
+                box_shadow: "0 4px 12px rgba(0, 0, 0, 0.25)",
+                white_space: "pre",
+                user_select: "none",
+                "-webkit-user-select": "none",
+            },
+
+            ".go_to_line_invalid": {
+                border_color: "#d33",
+            },
+
+            ".go_to_line_hint": {
+                opacity: 0.6,
+            },
+        }]
+    }
+}
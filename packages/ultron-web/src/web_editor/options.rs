@@ -1,6 +1,42 @@
 pub use crate::font_loader::FontSettings;
 use ultron_core::BaseOptions;
 
+/// where the long lines are wrapped when soft wrap is enabled
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WrapWidth {
+    /// wrap at the width of the editor
+    Viewport,
+    /// wrap at this fixed column
+    Column(usize),
+}
+
+/// how the key presses are processed
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum KeymapProfile {
+    /// the key bindings of the keymap, the characters of the unbound keys are inserted
+    #[default]
+    Standard,
+    /// the vim modal editing
+    Vim,
+    /// the emacs key bindings with the mark, the kill ring and the incremental search
+    Emacs,
+    /// the selection first modal editing of kakoune, with multiple selections
+    Kakoune,
+}
+
+impl KeymapProfile {
+    /// the profile from its name, ie: the `keymap` attribute of the custom element
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "standard" | "default" => Some(Self::Standard),
+            "vim" => Some(Self::Vim),
+            "emacs" => Some(Self::Emacs),
+            "kakoune" | "helix" => Some(Self::Kakoune),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Options {
     pub base_options: BaseOptions,
@@ -10,6 +46,12 @@ pub struct Options {
     pub use_paging_optimization: bool,
     /// show line numbers
     pub show_line_numbers: bool,
+    /// show the fold markers in the gutter, clicking on it folds or unfolds the range
+    pub show_fold_markers: bool,
+    /// show the column of the gutter icons, ie: breakpoints, when there is any
+    pub show_gutter_icons: bool,
+    /// wrap the long lines into multiple rows instead of scrolling horizontally
+    pub soft_wrap: Option<WrapWidth>,
     /// show the status line
     pub show_status_line: bool,
     /// show virtual cursor
@@ -42,6 +84,12 @@ pub struct Options {
     pub enable_keypresses: bool,
     /// clicking on the editor will set the cursor location at the click location
     pub enable_click: bool,
+    /// the key bindings added over the standard keymap, see `Keymap::load_config`
+    pub keymap_config: Option<String>,
+    /// the editing profile used to process the key presses
+    pub keymap_profile: KeymapProfile,
+    /// format the content with the formatter before the `@save` host action is emitted
+    pub format_on_save: bool,
     /// the calculated width of the character `0` in px
     /// this is affected by font sized and font used
     pub ch_width: Option<f32>,
@@ -56,6 +104,9 @@ impl Default for Options {
             font_settings: None,
             use_paging_optimization: true,
             show_line_numbers: true,
+            show_fold_markers: true,
+            show_gutter_icons: true,
+            soft_wrap: None,
             show_status_line: true,
             show_cursor: true,
             use_background: true,
@@ -70,6 +121,9 @@ impl Default for Options {
             enable_context_menu: false,
             enable_keypresses: true,
             enable_click: true,
+            keymap_config: None,
+            keymap_profile: KeymapProfile::Standard,
+            format_on_save: false,
             ch_width: None,
             ch_height: None,
         }